@@ -18,12 +18,13 @@ where
 /// Metrics related test aids
 pub mod metrics {
     use metrics::{
-        Counter, CounterFn, Gauge, Histogram, Key, KeyName, Label, Recorder, SharedString, Unit,
+        Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Label, Recorder,
+        SharedString, Unit,
     };
     use std::borrow::Cow;
     use std::collections::HashMap;
     use std::sync::atomic::{AtomicU64, Ordering};
-    use std::sync::{Arc, RwLock};
+    use std::sync::{Arc, Mutex, RwLock};
 
     /// # Purpose
     ///
@@ -60,16 +61,21 @@ pub mod metrics {
     #[derive(Debug, Default)]
     pub struct FakeRecorder(FakeRecorderHandle);
 
-    /// Handle to the [`FakeRecorder`], which allows to get the current value of counters.
+    /// Handle to the [`FakeRecorder`], which allows to get the current value of counters and the
+    /// values recorded by histograms.
     #[derive(Clone, Debug, Default)]
     pub struct FakeRecorderHandle {
         counters: Arc<RwLock<HashMap<Key, Arc<FakeCounterFn>>>>,
+        histograms: Arc<RwLock<HashMap<Key, Arc<FakeHistogramFn>>>>,
         methods: Option<&'static [&'static str]>,
     }
 
     #[derive(Debug, Default)]
     struct FakeCounterFn(AtomicU64);
 
+    #[derive(Debug, Default)]
+    struct FakeHistogramFn(Mutex<Vec<f64>>);
+
     impl Recorder for FakeRecorder {
         fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
         fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
@@ -106,8 +112,19 @@ pub mod metrics {
         fn register_gauge(&self, _: &Key) -> Gauge {
             unimplemented!()
         }
-        fn register_histogram(&self, _: &Key) -> Histogram {
-            unimplemented!()
+        /// Registers a histogram if the method is on the `self::methods` list and returns it.
+        ///
+        /// # Warning
+        ///
+        /// Returns `Histogram::noop()` in other cases.
+        fn register_histogram(&self, key: &Key) -> Histogram {
+            if self.is_key_used(key) {
+                let mut write_guard = self.0.histograms.write().unwrap();
+                let histogram = write_guard.entry(key.clone()).or_default();
+                Histogram::from_arc(histogram.clone())
+            } else {
+                Histogram::noop()
+            }
         }
     }
 
@@ -118,6 +135,7 @@ pub mod metrics {
         pub fn new_for(methods: &'static [&'static str]) -> Self {
             Self(FakeRecorderHandle {
                 counters: Arc::default(),
+                histograms: Arc::default(),
                 methods: Some(methods),
             })
         }
@@ -182,6 +200,31 @@ pub mod metrics {
                 .0
                 .load(Ordering::Relaxed)
         }
+
+        /// Returns the values recorded by a histogram.
+        ///
+        /// Panics if `histogram_name` with these `labels` was not registered via
+        /// [`metrics::register_histogram`].
+        pub fn get_histogram_values_by_label<const N: usize>(
+            &self,
+            histogram_name: &'static str,
+            labels: [(&'static str, &'static str); N],
+        ) -> Vec<f64> {
+            let read_guard = self.histograms.read().unwrap();
+            read_guard
+                .get(&Key::from_parts(
+                    histogram_name,
+                    labels
+                        .iter()
+                        .map(|&(key, val)| Label::new(key, val))
+                        .collect::<Vec<_>>(),
+                ))
+                .expect("Unregistered histogram name")
+                .0
+                .lock()
+                .unwrap()
+                .clone()
+        }
     }
 
     impl CounterFn for FakeCounterFn {
@@ -192,4 +235,10 @@ pub mod metrics {
             unimplemented!()
         }
     }
+
+    impl HistogramFn for FakeHistogramFn {
+        fn record(&self, value: f64) {
+            self.0.lock().unwrap().push(value);
+        }
+    }
 }
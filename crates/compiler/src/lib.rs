@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 
 use anyhow::Context;
-use pathfinder_common::StarknetVersion;
+use pathfinder_common::{CasmHash, StarknetVersion};
 
 pub const COMPILER_VERSION: &str = env!("SIERRA_CASM_COMPILER_VERSION");
 
@@ -48,6 +48,12 @@ pub fn compile_to_casm_with_latest_compiler(sierra_definition: &[u8]) -> anyhow:
     result.unwrap_or_else(|e| Err(panic_error(e)))
 }
 
+/// Computes the compiled class hash of a CASM class definition.
+pub fn casm_class_hash(casm_definition: &[u8]) -> anyhow::Result<CasmHash> {
+    let result = std::panic::catch_unwind(|| v2::compiled_class_hash(casm_definition));
+    result.unwrap_or_else(|e| Err(panic_error(e)))
+}
+
 fn panic_error(e: Box<dyn std::any::Any>) -> anyhow::Error {
     match e.downcast_ref::<&str>() {
         Some(e) => anyhow::anyhow!("Compiler panicked: {}", e),
@@ -238,6 +244,18 @@ mod v2 {
 
         Ok(casm_definition)
     }
+
+    pub(super) fn compiled_class_hash(
+        casm_definition: &[u8],
+    ) -> anyhow::Result<pathfinder_common::CasmHash> {
+        let casm_class = serde_json::from_slice::<CasmContractClass>(casm_definition)
+            .context("Parsing CASM class")?;
+        let hash = casm_class.compiled_class_hash().to_be_bytes();
+        let hash = pathfinder_crypto::Felt::from_be_bytes(hash)
+            .context("Compiled class hash out of range")?;
+
+        Ok(pathfinder_common::CasmHash(hash))
+    }
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
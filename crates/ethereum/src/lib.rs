@@ -1,6 +1,18 @@
-use pathfinder_common::{BlockHash, BlockNumber, EthereumChain, StateCommitment};
+use anyhow::Context;
+use pathfinder_common::{
+    BlockHash, BlockNumber, ContractAddress, EntryPoint, EthereumAddress, EthereumChain,
+    L1ToL2MessageNonce, L1ToL2MessagePayloadElem, L2ToL1MessagePayloadElem, StateCommitment,
+};
 use pathfinder_crypto::Felt;
 use primitive_types::{H160, H256, U256};
+use std::sync::{Arc, Mutex};
+
+mod multi;
+mod proof;
+mod websocket;
+
+pub use multi::{EndpointSelection, MultiEthereumClient};
+use websocket::WebsocketTransport;
 
 pub mod core_addr {
     use const_decoder::Decoder;
@@ -23,16 +35,177 @@ pub struct EthereumStateUpdate {
     pub block_hash: BlockHash,
 }
 
+/// A state update logged by the core contract's `LogStateUpdate` event, together with the L1
+/// block and transaction which settled it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateUpdateLog {
+    pub update: EthereumStateUpdate,
+    pub l1_block_number: u64,
+    pub l1_block_hash: H256,
+    pub l1_transaction_hash: H256,
+}
+
+/// Identifies an L1 block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct L1BlockId {
+    pub number: u64,
+    pub hash: H256,
+}
+
+/// An error response to an Ethereum JSON-RPC request, as opposed to a failure to reach the
+/// endpoint. Sending the same request again is unlikely to help.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Ethereum JSON-RPC error {code}: {message}")]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl JsonRpcError {
+    pub(crate) fn from_response(error: &serde_json::Value) -> Self {
+        Self {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"].as_str().unwrap_or_default().to_owned(),
+        }
+    }
+}
+
+/// The L1 block tag whose state the node follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum L1Finality {
+    Latest,
+    Safe,
+    #[default]
+    Finalized,
+}
+
+impl L1Finality {
+    fn as_tag(&self) -> &'static str {
+        match self {
+            L1Finality::Latest => "latest",
+            L1Finality::Safe => "safe",
+            L1Finality::Finalized => "finalized",
+        }
+    }
+}
+
+/// A message sent from L1 to L2, as logged by the core contract's `LogMessageToL2` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L1ToL2MessageLog {
+    pub l1_block_number: u64,
+    pub l1_transaction_hash: H256,
+    pub from_address: EthereumAddress,
+    pub to_address: ContractAddress,
+    pub selector: EntryPoint,
+    pub payload: Vec<L1ToL2MessagePayloadElem>,
+    pub nonce: L1ToL2MessageNonce,
+}
+
+impl L1ToL2MessageLog {
+    /// The hash the core contract uses to track the message, which is also the message hash of
+    /// the resulting `L1_HANDLER` transaction.
+    pub fn message_hash(&self) -> H256 {
+        let mut from_address = [0u8; 32];
+        from_address[12..].copy_from_slice(self.from_address.0.as_bytes());
+
+        let mut data = Vec::with_capacity(32 * (5 + self.payload.len()));
+        data.extend_from_slice(&from_address);
+        data.extend_from_slice(self.to_address.0.as_be_bytes());
+        data.extend_from_slice(self.nonce.0.as_be_bytes());
+        data.extend_from_slice(self.selector.0.as_be_bytes());
+        // Pad the length to a 32 byte word.
+        data.extend_from_slice(&[0u8; 24]);
+        data.extend_from_slice(&(self.payload.len() as u64).to_be_bytes());
+        for elem in &self.payload {
+            data.extend_from_slice(elem.0.as_be_bytes());
+        }
+
+        keccak(&data)
+    }
+}
+
+/// A message sent from L2 to L1 which was consumed on L1, as logged by the core contract's
+/// `ConsumedMessageToL1` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumedL2ToL1MessageLog {
+    pub l1_block_number: u64,
+    pub l1_transaction_hash: H256,
+    pub from_address: ContractAddress,
+    pub to_address: EthereumAddress,
+    pub payload: Vec<L2ToL1MessagePayloadElem>,
+}
+
+impl ConsumedL2ToL1MessageLog {
+    /// The hash the core contract uses to track the message.
+    pub fn message_hash(&self) -> H256 {
+        let mut to_address = [0u8; 32];
+        to_address[12..].copy_from_slice(self.to_address.0.as_bytes());
+
+        let mut data = Vec::with_capacity(32 * (3 + self.payload.len()));
+        data.extend_from_slice(self.from_address.0.as_be_bytes());
+        data.extend_from_slice(&to_address);
+        // Pad the length to a 32 byte word.
+        data.extend_from_slice(&[0u8; 24]);
+        data.extend_from_slice(&(self.payload.len() as u64).to_be_bytes());
+        for elem in &self.payload {
+            data.extend_from_slice(elem.0.as_be_bytes());
+        }
+
+        keccak(&data)
+    }
+}
+
+/// A message log emitted by the Starknet core contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageLog {
+    L1ToL2(L1ToL2MessageLog),
+    ConsumedL2ToL1(ConsumedL2ToL1MessageLog),
+}
+
 #[async_trait::async_trait]
 pub trait EthereumApi {
+    /// Waits until the L1 state may have changed, or for at most `timeout`.
+    async fn wait_for_update(&self, timeout: std::time::Duration) {
+        tokio::time::sleep(timeout).await
+    }
     async fn get_starknet_state(&self, address: &H160) -> anyhow::Result<EthereumStateUpdate>;
     async fn get_chain(&self) -> anyhow::Result<EthereumChain>;
+    async fn get_finalized_block_number(&self) -> anyhow::Result<u64>;
+    /// Returns the core contract's state as of the given L1 block.
+    async fn get_starknet_state_at(
+        &self,
+        address: &H160,
+        block_hash: H256,
+    ) -> anyhow::Result<EthereumStateUpdate>;
+    /// Returns the L1 block currently tagged with `finality`.
+    async fn get_block(&self, finality: L1Finality) -> anyhow::Result<L1BlockId>;
+    /// Returns the hash of the canonical L1 block with the given number, if it exists.
+    async fn get_block_hash(&self, number: u64) -> anyhow::Result<Option<H256>>;
+    /// Returns the state update logs emitted by the core contract at `address` in the given
+    /// (inclusive) range of L1 blocks.
+    async fn get_state_update_logs(
+        &self,
+        address: &H160,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<StateUpdateLog>>;
+    /// Returns the message logs emitted by the core contract at `address` in the given
+    /// (inclusive) range of L1 blocks.
+    async fn get_message_logs(
+        &self,
+        address: &H160,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<MessageLog>>;
 }
 
 #[derive(Clone, Debug)]
 pub struct EthereumClient {
     http: reqwest::Client,
     url: reqwest::Url,
+    websocket: Option<WebsocketTransport>,
+    /// The latest verified L1 block, if the Starknet state is verified using storage proofs.
+    verified: Option<Arc<Mutex<L1BlockId>>>,
 }
 
 const HTTP_OK: u16 = 200;
@@ -48,6 +221,164 @@ impl EthereumClient {
         Ok(Self {
             http: reqwest::ClientBuilder::new().build()?,
             url,
+            websocket: None,
+            verified: None,
+        })
+    }
+
+    /// Sends requests over a websocket connection to `url` instead, falling back to HTTP while it
+    /// is unavailable. New heads and the logs of the core contract at `core_address` end
+    /// [waiting for updates](EthereumApi::wait_for_update) immediately.
+    pub fn with_websocket(mut self, url: reqwest::Url, core_address: H160) -> Self {
+        self.websocket = Some(WebsocketTransport::spawn(url, core_address));
+        self
+    }
+
+    /// Verifies the Starknet state using storage proofs instead of trusting `eth_call`, see
+    /// [proof].
+    ///
+    /// The L1 blocks whose state is read must descend from the `checkpoint` block, which must be
+    /// a trusted finalized block. The checkpoint is advanced to every verified block, so these
+    /// must be finalized too.
+    pub async fn with_verification(mut self, checkpoint: H256) -> anyhow::Result<Self> {
+        let header = self
+            .get_header(checkpoint)
+            .await
+            .context("Fetching checkpoint block")?;
+        self.verified = Some(Arc::new(Mutex::new(L1BlockId {
+            number: header.number,
+            hash: header.hash,
+        })));
+        Ok(self)
+    }
+
+    /// Fetches the header of the block with the given hash, and checks that it hashes to it.
+    async fn get_header(&self, hash: H256) -> anyhow::Result<proof::Header> {
+        let block = self
+            .call_ethereum(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getBlockByHash",
+                "params": [
+                    format!("0x{}", hex::encode(hash.as_bytes())),
+                    false
+                ],
+                "id": 0
+            }))
+            .await?;
+        anyhow::ensure!(!block.is_null(), "L1 block {hash:?} is missing");
+
+        let header = proof::parse_header(&block)?;
+        anyhow::ensure!(
+            header.hash == hash,
+            "Received L1 block {:?} instead of {hash:?}",
+            header.hash
+        );
+        Ok(header)
+    }
+
+    /// Checks that the block is on the same chain as the latest verified block, by following
+    /// parent hashes from the later of the two, and advances the latest verified block.
+    async fn verify_chain(
+        &self,
+        verified: &Mutex<L1BlockId>,
+        header: &proof::Header,
+    ) -> anyhow::Result<()> {
+        let anchor = *verified.lock().unwrap();
+
+        let (mut descendant, ancestor) = if header.number >= anchor.number {
+            (header.clone(), anchor)
+        } else {
+            let anchor = self.get_header(anchor.hash).await?;
+            let ancestor = L1BlockId {
+                number: header.number,
+                hash: header.hash,
+            };
+            (anchor, ancestor)
+        };
+
+        while descendant.number > ancestor.number {
+            let parent = self.get_header(descendant.parent_hash).await?;
+            anyhow::ensure!(
+                parent.number + 1 == descendant.number,
+                "L1 block {:?} has an invalid parent",
+                descendant.hash
+            );
+            descendant = parent;
+        }
+        anyhow::ensure!(
+            descendant.hash == ancestor.hash,
+            "L1 block {:?} is not on the verified chain",
+            header.hash
+        );
+
+        let mut verified = verified.lock().unwrap();
+        if header.number > verified.number {
+            *verified = L1BlockId {
+                number: header.number,
+                hash: header.hash,
+            };
+        }
+        Ok(())
+    }
+
+    /// Reads the Starknet state from the core contract's storage, and verifies it using
+    /// `eth_getProof` against the header of the verified L1 block.
+    async fn get_proven_starknet_state(
+        &self,
+        verified: &Mutex<L1BlockId>,
+        address: &H160,
+        block_hash: H256,
+    ) -> anyhow::Result<EthereumStateUpdate> {
+        let header = self.get_header(block_hash).await?;
+        self.verify_chain(verified, &header).await?;
+
+        let slots = proof::state_slots();
+        let response = self
+            .call_ethereum(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getProof",
+                "params": [
+                    format!("0x{}", hex::encode(address.as_bytes())),
+                    slots.map(|slot| format!("0x{}", hex::encode(slot.as_bytes()))),
+                    {"blockHash": format!("0x{}", hex::encode(block_hash.as_bytes()))}
+                ],
+                "id": 0
+            }))
+            .await?;
+
+        let storage_root = proof::verify_account(
+            header.state_root,
+            *address,
+            &get_proof_nodes(&response["accountProof"])?,
+        )?;
+
+        let storage_proofs = response["storageProof"]
+            .as_array()
+            .context("Storage proofs are not an array")?;
+        anyhow::ensure!(
+            storage_proofs.len() == slots.len(),
+            "Expected {} storage proofs",
+            slots.len()
+        );
+        let mut values = [U256::zero(); 3];
+        for ((value, slot), storage_proof) in values.iter_mut().zip(slots).zip(storage_proofs) {
+            *value = proof::verify_storage(
+                storage_root,
+                slot,
+                &get_proof_nodes(&storage_proof["proof"])?,
+            )?;
+        }
+        let [state_root, block_number, block_hash] = values;
+
+        let to_h256 = |value: U256| {
+            let mut bytes = H256::zero();
+            value.to_big_endian(bytes.as_bytes_mut());
+            bytes
+        };
+        Ok(EthereumStateUpdate {
+            state_root: get_felt(to_h256(state_root)).map(StateCommitment)?,
+            block_number: get_number(block_number)?,
+            block_hash: get_felt(to_h256(block_hash)).map(BlockHash)?,
         })
     }
 
@@ -89,6 +420,15 @@ impl EthereumClient {
     }
 
     async fn call_ethereum(&self, value: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        if let Some(websocket) = &self.websocket {
+            match websocket.call(value.clone()).await {
+                Ok(result) => return Ok(result),
+                // The node answered, so HTTP would not do any better.
+                Err(e) if e.is::<JsonRpcError>() => return Err(e),
+                Err(e) => tracing::debug!(error=?e, "Ethereum websocket call failed, using HTTP"),
+            }
+        }
+
         let res = self.http.post(self.url.clone()).json(&value).send().await?;
 
         let status = res.status();
@@ -99,15 +439,40 @@ impl EthereumClient {
         }
 
         let response: serde_json::Value = res.json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(JsonRpcError::from_response(error).into());
+        }
         Ok(response["result"].clone())
     }
 }
 
 #[async_trait::async_trait]
 impl EthereumApi for EthereumClient {
+    async fn wait_for_update(&self, timeout: std::time::Duration) {
+        match &self.websocket {
+            Some(websocket) => websocket.wait_for_notification(timeout).await,
+            None => tokio::time::sleep(timeout).await,
+        }
+    }
+
     async fn get_starknet_state(&self, address: &H160) -> anyhow::Result<EthereumStateUpdate> {
         let hash = self.get_finalized_block_hash().await?;
-        let hash = format!("0x{}", hex::encode(hash.as_bytes()));
+        self.get_starknet_state_at(address, hash).await
+    }
+
+    async fn get_starknet_state_at(
+        &self,
+        address: &H160,
+        block_hash: H256,
+    ) -> anyhow::Result<EthereumStateUpdate> {
+        if let Some(verified) = &self.verified {
+            return self
+                .get_proven_starknet_state(verified, address, block_hash)
+                .await
+                .context("Verifying L1 state");
+        }
+
+        let hash = format!("0x{}", hex::encode(block_hash.as_bytes()));
         let addr = format!("0x{}", hex::encode(address.as_bytes()));
         Ok(EthereumStateUpdate {
             state_root: self
@@ -147,6 +512,267 @@ impl EthereumApi for EthereumClient {
             x => EthereumChain::Other(x),
         })
     }
+
+    async fn get_finalized_block_number(&self) -> anyhow::Result<u64> {
+        self.call_ethereum(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getBlockByNumber",
+            "params": [
+                "finalized",
+                false
+            ],
+            "id": 0
+        }))
+        .await
+        .and_then(|value| get_u256(&value["number"]))
+        .map(|number| number.as_u64())
+    }
+
+    async fn get_block(&self, finality: L1Finality) -> anyhow::Result<L1BlockId> {
+        let block = self
+            .call_ethereum(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getBlockByNumber",
+                "params": [
+                    finality.as_tag(),
+                    false
+                ],
+                "id": 0
+            }))
+            .await?;
+
+        Ok(L1BlockId {
+            number: get_u256(&block["number"])?.as_u64(),
+            hash: get_h256(&block["hash"])?,
+        })
+    }
+
+    async fn get_block_hash(&self, number: u64) -> anyhow::Result<Option<H256>> {
+        let block = self
+            .call_ethereum(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getBlockByNumber",
+                "params": [
+                    format!("0x{number:x}"),
+                    false
+                ],
+                "id": 0
+            }))
+            .await?;
+
+        if block.is_null() {
+            return Ok(None);
+        }
+        get_h256(&block["hash"]).map(Some)
+    }
+
+    async fn get_state_update_logs(
+        &self,
+        address: &H160,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<StateUpdateLog>> {
+        let state_update = format!("0x{}", hex::encode(keccak(LOG_STATE_UPDATE.as_bytes())));
+
+        let logs = self
+            .call_ethereum(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getLogs",
+                "params": [
+                    {
+                        "address": format!("0x{}", hex::encode(address.as_bytes())),
+                        "fromBlock": format!("0x{from_block:x}"),
+                        "toBlock": format!("0x{to_block:x}"),
+                        "topics": [state_update]
+                    }
+                ],
+                "id": 0
+            }))
+            .await?;
+
+        let logs = logs.as_array().context("Logs are not an array")?;
+        let mut updates = Vec::with_capacity(logs.len());
+        for log in logs {
+            if log["removed"].as_bool() == Some(true) {
+                continue;
+            }
+            updates.push(parse_state_update_log(log).context("Parsing state update log")?);
+        }
+
+        // The logs are not proven themselves, but the state each L1 block ends with must match
+        // the proven state of that block, which is the last update logged in it.
+        if let Some(verified) = &self.verified {
+            for (i, log) in updates.iter().enumerate() {
+                if updates
+                    .get(i + 1)
+                    .is_some_and(|next| next.l1_block_hash == log.l1_block_hash)
+                {
+                    continue;
+                }
+
+                let proven = self
+                    .get_proven_starknet_state(verified, address, log.l1_block_hash)
+                    .await
+                    .context("Verifying L1 state")?;
+                anyhow::ensure!(
+                    proven == log.update,
+                    "State update log in L1 block {:?} does not match the proven L1 state",
+                    log.l1_block_hash
+                );
+            }
+        }
+
+        Ok(updates)
+    }
+
+    async fn get_message_logs(
+        &self,
+        address: &H160,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<MessageLog>> {
+        let to_l2 = format!("0x{}", hex::encode(keccak(LOG_MESSAGE_TO_L2.as_bytes())));
+        let consumed = format!(
+            "0x{}",
+            hex::encode(keccak(CONSUMED_MESSAGE_TO_L1.as_bytes()))
+        );
+
+        let logs = self
+            .call_ethereum(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getLogs",
+                "params": [
+                    {
+                        "address": format!("0x{}", hex::encode(address.as_bytes())),
+                        "fromBlock": format!("0x{from_block:x}"),
+                        "toBlock": format!("0x{to_block:x}"),
+                        "topics": [[to_l2, consumed]]
+                    }
+                ],
+                "id": 0
+            }))
+            .await?;
+
+        let logs = logs.as_array().context("Logs are not an array")?;
+        let mut messages = Vec::with_capacity(logs.len());
+        for log in logs {
+            if log["removed"].as_bool() == Some(true) {
+                continue;
+            }
+            messages.push(parse_message_log(log).context("Parsing message log")?);
+        }
+
+        Ok(messages)
+    }
+}
+
+/// Signature of the core contract's event for state updates.
+const LOG_STATE_UPDATE: &str = "LogStateUpdate(uint256,int256,uint256)";
+/// Signature of the core contract's event for messages sent from L1 to L2.
+const LOG_MESSAGE_TO_L2: &str = "LogMessageToL2(address,uint256,uint256,uint256[],uint256,uint256)";
+/// Signature of the core contract's event for messages from L2 consumed on L1.
+const CONSUMED_MESSAGE_TO_L1: &str = "ConsumedMessageToL1(uint256,address,uint256[])";
+
+fn parse_message_log(log: &serde_json::Value) -> anyhow::Result<MessageLog> {
+    let l1_block_number = get_u256(&log["blockNumber"])?.as_u64();
+    let l1_transaction_hash = get_h256(&log["transactionHash"])?;
+
+    let topics = log["topics"]
+        .as_array()
+        .context("Topics are not an array")?
+        .iter()
+        .map(get_h256)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let data = log["data"].as_str().context("Data is not a string")?;
+    let data = hex::decode(data.strip_prefix("0x").unwrap_or(data)).context("Decoding data")?;
+    let words = data.chunks(32).collect::<Vec<_>>();
+    anyhow::ensure!(
+        words.last().map_or(true, |word| word.len() == 32),
+        "Data is not a sequence of words"
+    );
+
+    let signature = topics.first().context("Missing event signature")?;
+    if *signature == keccak(LOG_MESSAGE_TO_L2.as_bytes()) {
+        anyhow::ensure!(topics.len() == 4, "Expected 4 topics");
+        let nonce = words.get(1).context("Missing nonce")?;
+        let payload = decode_array(&words, 0)?
+            .into_iter()
+            .map(L1ToL2MessagePayloadElem)
+            .collect();
+
+        Ok(MessageLog::L1ToL2(L1ToL2MessageLog {
+            l1_block_number,
+            l1_transaction_hash,
+            from_address: EthereumAddress(H160::from_slice(&topics[1].as_bytes()[12..])),
+            to_address: ContractAddress(get_felt(topics[2])?),
+            selector: EntryPoint(get_felt(topics[3])?),
+            payload,
+            nonce: L1ToL2MessageNonce(Felt::from_be_slice(nonce)?),
+        }))
+    } else if *signature == keccak(CONSUMED_MESSAGE_TO_L1.as_bytes()) {
+        anyhow::ensure!(topics.len() == 3, "Expected 3 topics");
+        let payload = decode_array(&words, 0)?
+            .into_iter()
+            .map(L2ToL1MessagePayloadElem)
+            .collect();
+
+        Ok(MessageLog::ConsumedL2ToL1(ConsumedL2ToL1MessageLog {
+            l1_block_number,
+            l1_transaction_hash,
+            from_address: ContractAddress(get_felt(topics[1])?),
+            to_address: EthereumAddress(H160::from_slice(&topics[2].as_bytes()[12..])),
+            payload,
+        }))
+    } else {
+        anyhow::bail!("Unexpected event signature {signature:?}");
+    }
+}
+
+fn parse_state_update_log(log: &serde_json::Value) -> anyhow::Result<StateUpdateLog> {
+    let data = log["data"].as_str().context("Data is not a string")?;
+    let data = hex::decode(data.strip_prefix("0x").unwrap_or(data)).context("Decoding data")?;
+    anyhow::ensure!(data.len() == 3 * 32, "Expected 3 data words");
+    let word = |i: usize| H256::from_slice(&data[i * 32..(i + 1) * 32]);
+
+    Ok(StateUpdateLog {
+        update: EthereumStateUpdate {
+            state_root: StateCommitment(get_felt(word(0))?),
+            block_number: get_number(U256::from_big_endian(word(1).as_bytes()))?,
+            block_hash: BlockHash(get_felt(word(2))?),
+        },
+        l1_block_number: get_u256(&log["blockNumber"])?.as_u64(),
+        l1_block_hash: get_h256(&log["blockHash"])?,
+        l1_transaction_hash: get_h256(&log["transactionHash"])?,
+    })
+}
+
+/// Decodes the ABI encoded `uint256[]` whose offset is stored in the `head`'th word.
+fn decode_array(words: &[&[u8]], head: usize) -> anyhow::Result<Vec<Felt>> {
+    let offset = words.get(head).context("Missing array offset")?;
+    let offset = U256::from_big_endian(offset);
+    anyhow::ensure!(
+        offset % 32 == U256::zero() && offset < U256::from(words.len() * 32),
+        "Invalid array offset"
+    );
+    let start = offset.as_usize() / 32;
+
+    let length = U256::from_big_endian(words[start]);
+    anyhow::ensure!(
+        length < U256::from(words.len() - start),
+        "Invalid array length"
+    );
+    let length = length.as_usize();
+
+    words[start + 1..start + 1 + length]
+        .iter()
+        .map(|word| Felt::from_be_slice(word).map_err(Into::into))
+        .collect()
+}
+
+fn keccak(data: &[u8]) -> H256 {
+    let mut output = [0u8; 32];
+    keccak_hash::keccak_256(data, &mut output);
+    H256(output)
 }
 
 fn encode_ethereum_call_data(signature: &[u8]) -> String {
@@ -173,6 +799,18 @@ fn get_u256(value: &serde_json::Value) -> anyhow::Result<U256> {
         .ok_or(anyhow::anyhow!("Failed to fetch U256"))
 }
 
+fn get_proof_nodes(value: &serde_json::Value) -> anyhow::Result<Vec<Vec<u8>>> {
+    value
+        .as_array()
+        .context("Proof is not an array")?
+        .iter()
+        .map(|node| {
+            let node = node.as_str().context("Proof node is not a string")?;
+            hex::decode(node.strip_prefix("0x").unwrap_or(node)).context("Decoding proof node")
+        })
+        .collect()
+}
+
 fn get_felt(value: H256) -> anyhow::Result<Felt> {
     let felt = Felt::from_be_slice(value.as_bytes())?;
     Ok(felt)
@@ -311,6 +949,168 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn error_responses_are_json_rpc_errors() -> anyhow::Result<()> {
+        let server = MockServer::start_async().await;
+
+        server.mock(|when, then| {
+            when.path("/").method(POST);
+            then.status(200)
+                .header("Content-type", "application/json")
+                .body(r#"{"jsonrpc":"2.0","id":0,"error":{"code":-32005,"message":"query returned more than 10000 results"}}"#);
+        });
+
+        let url = Url::parse(&server.url("/"))?;
+        let eth = EthereumClient::new(url)?;
+        let error = eth
+            .get_state_update_logs(&H160::from_slice(&core_addr::MAINNET), 0, 100_000)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<JsonRpcError>(),
+            Some(&JsonRpcError {
+                code: -32005,
+                message: "query returned more than 10000 results".to_owned(),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_message_to_l2_log() -> anyhow::Result<()> {
+        use pathfinder_common::macro_prelude::*;
+
+        // Message of a mainnet L1 handler transaction.
+        let word = |hex: &str| lpad64(hex).trim_start_matches("0x").to_owned();
+        let data = [
+            "0x60",
+            "0x17824b",
+            "0x0",
+            "0x3",
+            "0x2c63ec1313901744d1321b93bda51418cc18998a1562d368960711367f7530f",
+            "0x11e14e1039c000",
+            "0x0",
+        ]
+        .map(word)
+        .concat();
+        let log = serde_json::json!({
+            "blockNumber": "0x1048e0e",
+            "transactionHash": "0x9921984fd976f261e0d70618b51e3db3724b9f4d28d0534c3483dd2162f13fff",
+            "topics": [
+                format!("0x{}", hex::encode(keccak(LOG_MESSAGE_TO_L2.as_bytes()))),
+                lpad64("0xae0ee0a63a2ce6baeeffe56e7714fb4efe48d419"),
+                lpad64("0x73314940630fd6dcda0d772d4c972c4e0a9946bef9dabf4ef84eda8ef542b82"),
+                lpad64("0x2d757788a8d8d6f21d1cd40bce38a8222d70654214e96ff95d8086e684fbee5"),
+            ],
+            "data": format!("0x{data}"),
+            "removed": false,
+        });
+
+        let MessageLog::L1ToL2(message) = parse_message_log(&log)? else {
+            panic!("Expected an L1 to L2 message");
+        };
+        assert_eq!(message.l1_block_number, 0x1048e0e);
+        assert_eq!(
+            message.to_address,
+            contract_address!("0x73314940630fd6dcda0d772d4c972c4e0a9946bef9dabf4ef84eda8ef542b82")
+        );
+        assert_eq!(message.nonce, L1ToL2MessageNonce(Felt::from_u64(0x17824b)));
+        assert_eq!(message.payload.len(), 3);
+
+        // Taken from starkscan: https://starkscan.co/tx/0x063f36452a4255a9d3f06def95a08bbc295f0de0515adefbf04ee795ed4c3f12
+        let expected =
+            H256::from_str("573aeff3cf703775e8a76a27adee9e80f2ce558a6a38ec87e0249a8b175e5c1a")?;
+        assert_eq!(message.message_hash(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_consumed_message_to_l1_log() -> anyhow::Result<()> {
+        let word = |hex: &str| lpad64(hex).trim_start_matches("0x").to_owned();
+        let data = ["0x20", "0x2", "0xa", "0xb"].map(word).concat();
+        let log = serde_json::json!({
+            "blockNumber": "0x10",
+            "transactionHash": "0x9921984fd976f261e0d70618b51e3db3724b9f4d28d0534c3483dd2162f13fff",
+            "topics": [
+                format!("0x{}", hex::encode(keccak(CONSUMED_MESSAGE_TO_L1.as_bytes()))),
+                lpad64("0x1234"),
+                lpad64("0xae0ee0a63a2ce6baeeffe56e7714fb4efe48d419"),
+            ],
+            "data": format!("0x{data}"),
+        });
+
+        let MessageLog::ConsumedL2ToL1(message) = parse_message_log(&log)? else {
+            panic!("Expected a consumed L2 to L1 message");
+        };
+        assert_eq!(
+            message.payload,
+            vec![
+                L2ToL1MessagePayloadElem(Felt::from_u64(0xa)),
+                L2ToL1MessagePayloadElem(Felt::from_u64(0xb))
+            ]
+        );
+        assert_eq!(
+            message.to_address,
+            EthereumAddress(H160::from_str("ae0ee0a63a2ce6baeeffe56e7714fb4efe48d419")?)
+        );
+
+        let expected = keccak(&hex::decode(
+            [
+                "0x1234",
+                "0xae0ee0a63a2ce6baeeffe56e7714fb4efe48d419",
+                "0x2",
+                "0xa",
+                "0xb",
+            ]
+            .map(word)
+            .concat(),
+        )?);
+        assert_eq!(message.message_hash(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_state_update_log() -> anyhow::Result<()> {
+        use pathfinder_common::macro_prelude::*;
+
+        let word = |hex: &str| lpad64(hex).trim_start_matches("0x").to_owned();
+        let data = ["0x1234", "0x5", "0xabcd"].map(word).concat();
+        let log = serde_json::json!({
+            "blockNumber": "0x10",
+            "blockHash": "0xb4fbadb5c33cbc4e6bb1e34a39bb3a9aa87a8f4d0d7e5a4e3e3cf6e29f9f3e4d",
+            "transactionHash": "0x9921984fd976f261e0d70618b51e3db3724b9f4d28d0534c3483dd2162f13fff",
+            "topics": [
+                "0xd342ddf7a308dec111745b00315c14b7efb2bdae570a6856e088ed0c65a3576c"
+            ],
+            "data": format!("0x{data}"),
+        });
+        assert_eq!(
+            format!("0x{}", hex::encode(keccak(LOG_STATE_UPDATE.as_bytes()))),
+            log["topics"][0].as_str().unwrap()
+        );
+
+        let update = parse_state_update_log(&log)?;
+        assert_eq!(
+            update,
+            StateUpdateLog {
+                update: EthereumStateUpdate {
+                    state_root: state_commitment!("0x1234"),
+                    block_number: BlockNumber::new_or_panic(5),
+                    block_hash: block_hash!("0xabcd"),
+                },
+                l1_block_number: 0x10,
+                l1_block_hash: H256::from_str(
+                    "b4fbadb5c33cbc4e6bb1e34a39bb3a9aa87a8f4d0d7e5a4e3e3cf6e29f9f3e4d"
+                )?,
+                l1_transaction_hash: H256::from_str(
+                    "9921984fd976f261e0d70618b51e3db3724b9f4d28d0534c3483dd2162f13fff"
+                )?,
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_h256() {
         assert!(H256::from_str(
@@ -0,0 +1,450 @@
+//! An [EthereumApi] which spreads requests over multiple Ethereum endpoints.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use pathfinder_common::EthereumChain;
+use primitive_types::{H160, H256};
+
+use crate::{
+    EthereumApi, EthereumClient, EthereumStateUpdate, L1BlockId, L1Finality, MessageLog,
+    StateUpdateLog,
+};
+
+/// How long a failing endpoint is avoided after its first failure. Doubles with each consecutive
+/// failure, up to [MAX_BACKOFF].
+const MIN_BACKOFF: Duration = Duration::from_secs(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// The order in which endpoints are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EndpointSelection {
+    /// Always prefer the first healthy endpoint, in the configured order.
+    #[default]
+    Failover,
+    /// Rotate through the healthy endpoints.
+    RoundRobin,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Health {
+    consecutive_failures: u32,
+    avoid_until: Option<Instant>,
+}
+
+impl Health {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.avoid_until.map_or(true, |until| until <= now)
+    }
+}
+
+/// Sends each request to one healthy endpoint, trying the others if it fails.
+///
+/// With a quorum of N, the Starknet state, state update logs and the L1 blocks used to detect
+/// reorgs are instead requested from all endpoints, and only accepted if at least N of them agree.
+/// This protects the L1 accepted status from a single lying or lagging endpoint.
+#[derive(Clone, Debug)]
+pub struct MultiEthereumClient<C = EthereumClient> {
+    clients: Arc<[C]>,
+    selection: EndpointSelection,
+    quorum: Option<usize>,
+    health: Arc<Mutex<Vec<Health>>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl<C> MultiEthereumClient<C> {
+    pub fn new(
+        clients: Vec<C>,
+        selection: EndpointSelection,
+        quorum: Option<usize>,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            !clients.is_empty(),
+            "At least one Ethereum endpoint is required"
+        );
+        if let Some(quorum) = quorum {
+            anyhow::ensure!(
+                quorum > 0 && quorum <= clients.len(),
+                "Quorum must be between 1 and the number of Ethereum endpoints ({})",
+                clients.len()
+            );
+        }
+
+        Ok(Self {
+            health: Arc::new(Mutex::new(vec![Health::default(); clients.len()])),
+            clients: clients.into(),
+            selection,
+            quorum,
+            next: Default::default(),
+        })
+    }
+
+    /// The endpoints in the order they should be tried: healthy endpoints according to the
+    /// [EndpointSelection], followed by the unhealthy ones as a last resort.
+    fn order(&self) -> Vec<usize> {
+        let n = self.clients.len();
+        let start = match self.selection {
+            EndpointSelection::Failover => 0,
+            EndpointSelection::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % n,
+        };
+
+        let now = Instant::now();
+        let health = self.health.lock().unwrap();
+        let (mut healthy, unhealthy): (Vec<_>, Vec<_>) = (0..n)
+            .map(|i| (start + i) % n)
+            .partition(|&i| health[i].is_healthy(now));
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    fn record(&self, index: usize, result: &anyhow::Result<impl Sized>) {
+        let mut health = self.health.lock().unwrap();
+        let health = &mut health[index];
+
+        match result {
+            Ok(_) => {
+                if health.consecutive_failures > 0 {
+                    tracing::info!(endpoint=%index, "Ethereum endpoint recovered");
+                }
+                *health = Health::default();
+            }
+            Err(e) => {
+                health.consecutive_failures += 1;
+                let backoff = MIN_BACKOFF
+                    .saturating_mul(1 << (health.consecutive_failures - 1).min(16))
+                    .min(MAX_BACKOFF);
+                health.avoid_until = Some(Instant::now() + backoff);
+                tracing::warn!(endpoint=%index, error=?e, ?backoff, "Ethereum endpoint failed");
+            }
+        }
+    }
+}
+
+impl<C: EthereumApi + Send + Sync> MultiEthereumClient<C> {
+    /// Returns the first successful result, trying the endpoints in [order](Self::order).
+    async fn any<T, F>(&self, request: F) -> anyhow::Result<T>
+    where
+        F: for<'c> Fn(&'c C) -> BoxFuture<'c, anyhow::Result<T>>,
+    {
+        let mut last_error = None;
+        for index in self.order() {
+            let result = request(&self.clients[index]).await;
+            self.record(index, &result);
+            match result {
+                Ok(value) => return Ok(value),
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(last_error.expect("There is at least one endpoint"))
+    }
+
+    /// Returns the result at least `quorum` endpoints agree on, or the first successful result
+    /// if no quorum is configured.
+    async fn agreed<T, F>(&self, request: F) -> anyhow::Result<T>
+    where
+        T: PartialEq,
+        F: for<'c> Fn(&'c C) -> BoxFuture<'c, anyhow::Result<T>>,
+    {
+        let Some(quorum) = self.quorum else {
+            return self.any(request).await;
+        };
+
+        let results = futures::future::join_all(self.clients.iter().map(&request)).await;
+
+        let mut votes: Vec<(T, usize)> = Vec::new();
+        let mut last_error = None;
+        for (index, result) in results.into_iter().enumerate() {
+            self.record(index, &result);
+            match result {
+                Ok(value) => match votes.iter_mut().find(|(voted, _)| *voted == value) {
+                    Some((_, count)) => *count += 1,
+                    None => votes.push((value, 1)),
+                },
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        let best = votes.into_iter().max_by_key(|(_, count)| *count);
+        match (best, last_error) {
+            (Some((value, count)), _) if count >= quorum => Ok(value),
+            // Keep the cause if every endpoint failed.
+            (None, Some(e)) => Err(e),
+            (best, _) => anyhow::bail!(
+                "Only {} of {} Ethereum endpoints agree, but a quorum of {quorum} is required",
+                best.map_or(0, |(_, count)| count),
+                self.clients.len()
+            ),
+        }
+    }
+}
+
+impl MultiEthereumClient<EthereumClient> {
+    /// Uses a websocket connection to `url` for the first endpoint, see
+    /// [EthereumClient::with_websocket].
+    pub fn with_websocket(self, url: reqwest::Url, core_address: H160) -> Self {
+        let mut clients = self.clients.to_vec();
+        let first = clients.remove(0).with_websocket(url, core_address);
+        clients.insert(0, first);
+
+        Self {
+            clients: clients.into(),
+            ..self
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: EthereumApi + Send + Sync> EthereumApi for MultiEthereumClient<C> {
+    /// Waits until any of the endpoints sees an update.
+    async fn wait_for_update(&self, timeout: Duration) {
+        futures::future::select_all(self.clients.iter().map(|c| c.wait_for_update(timeout))).await;
+    }
+
+    async fn get_starknet_state(&self, address: &H160) -> anyhow::Result<EthereumStateUpdate> {
+        let address = *address;
+        self.agreed(move |c| Box::pin(async move { c.get_starknet_state(&address).await }))
+            .await
+    }
+
+    async fn get_chain(&self) -> anyhow::Result<EthereumChain> {
+        self.any(|c| c.get_chain()).await
+    }
+
+    async fn get_finalized_block_number(&self) -> anyhow::Result<u64> {
+        self.any(|c| c.get_finalized_block_number()).await
+    }
+
+    async fn get_starknet_state_at(
+        &self,
+        address: &H160,
+        block_hash: H256,
+    ) -> anyhow::Result<EthereumStateUpdate> {
+        let address = *address;
+        self.agreed(move |c| {
+            Box::pin(async move { c.get_starknet_state_at(&address, block_hash).await })
+        })
+        .await
+    }
+
+    async fn get_block(&self, finality: L1Finality) -> anyhow::Result<L1BlockId> {
+        self.agreed(move |c| c.get_block(finality)).await
+    }
+
+    async fn get_block_hash(&self, number: u64) -> anyhow::Result<Option<H256>> {
+        self.agreed(move |c| c.get_block_hash(number)).await
+    }
+
+    async fn get_state_update_logs(
+        &self,
+        address: &H160,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<StateUpdateLog>> {
+        let address = *address;
+        self.agreed(move |c| {
+            Box::pin(async move {
+                c.get_state_update_logs(&address, from_block, to_block)
+                    .await
+            })
+        })
+        .await
+    }
+
+    async fn get_message_logs(
+        &self,
+        address: &H160,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<Vec<MessageLog>> {
+        let address = *address;
+        self.any(move |c| {
+            Box::pin(async move { c.get_message_logs(&address, from_block, to_block).await })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::BlockNumber;
+
+    use super::*;
+
+    /// Returns a fixed Starknet state and L1 block, or fails.
+    struct Endpoint(Option<u64>);
+
+    #[async_trait::async_trait]
+    impl EthereumApi for Endpoint {
+        /// Endpoints which are up see an update right away.
+        async fn wait_for_update(&self, timeout: Duration) {
+            if self.0.is_none() {
+                tokio::time::sleep(timeout).await
+            }
+        }
+
+        async fn get_starknet_state(&self, _: &H160) -> anyhow::Result<EthereumStateUpdate> {
+            let block = self.0.ok_or_else(|| anyhow::anyhow!("Endpoint is down"))?;
+            Ok(EthereumStateUpdate {
+                block_number: BlockNumber::new_or_panic(block),
+                ..Default::default()
+            })
+        }
+
+        async fn get_chain(&self) -> anyhow::Result<EthereumChain> {
+            unimplemented!()
+        }
+
+        async fn get_finalized_block_number(&self) -> anyhow::Result<u64> {
+            self.0.ok_or_else(|| anyhow::anyhow!("Endpoint is down"))
+        }
+
+        async fn get_starknet_state_at(
+            &self,
+            _: &H160,
+            _: H256,
+        ) -> anyhow::Result<EthereumStateUpdate> {
+            unimplemented!()
+        }
+
+        async fn get_block(&self, _: L1Finality) -> anyhow::Result<L1BlockId> {
+            let number = self.0.ok_or_else(|| anyhow::anyhow!("Endpoint is down"))?;
+            Ok(L1BlockId {
+                number,
+                hash: H256::from_low_u64_be(number),
+            })
+        }
+
+        async fn get_block_hash(&self, number: u64) -> anyhow::Result<Option<H256>> {
+            let tip = self.0.ok_or_else(|| anyhow::anyhow!("Endpoint is down"))?;
+            Ok((number <= tip).then(|| H256::from_low_u64_be(number + tip)))
+        }
+
+        async fn get_state_update_logs(
+            &self,
+            _: &H160,
+            _: u64,
+            _: u64,
+        ) -> anyhow::Result<Vec<StateUpdateLog>> {
+            unimplemented!()
+        }
+
+        async fn get_message_logs(
+            &self,
+            _: &H160,
+            _: u64,
+            _: u64,
+        ) -> anyhow::Result<Vec<MessageLog>> {
+            unimplemented!()
+        }
+    }
+
+    fn client(
+        endpoints: &[Option<u64>],
+        selection: EndpointSelection,
+        quorum: Option<usize>,
+    ) -> MultiEthereumClient<Endpoint> {
+        let endpoints = endpoints.iter().copied().map(Endpoint).collect();
+        MultiEthereumClient::new(endpoints, selection, quorum).unwrap()
+    }
+
+    #[tokio::test]
+    async fn failover_skips_failing_endpoints() {
+        let client = client(&[None, Some(2), Some(3)], EndpointSelection::Failover, None);
+
+        assert_eq!(client.get_finalized_block_number().await.unwrap(), 2);
+        // The failing endpoint is avoided until its backoff expires.
+        assert_eq!(client.order(), vec![1, 2, 0]);
+        assert_eq!(client.get_finalized_block_number().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn round_robin_rotates() {
+        let client = client(&[Some(1), Some(2)], EndpointSelection::RoundRobin, None);
+
+        assert_eq!(client.get_finalized_block_number().await.unwrap(), 1);
+        assert_eq!(client.get_finalized_block_number().await.unwrap(), 2);
+        assert_eq!(client.get_finalized_block_number().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn all_endpoints_failing() {
+        let client = client(&[None, None], EndpointSelection::Failover, None);
+
+        client.get_finalized_block_number().await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn quorum_accepts_agreed_state() {
+        let client = client(
+            &[Some(5), Some(6), Some(5), None],
+            EndpointSelection::Failover,
+            Some(2),
+        );
+
+        let state = client.get_starknet_state(&H160::zero()).await.unwrap();
+        assert_eq!(state.block_number, BlockNumber::new_or_panic(5));
+    }
+
+    #[tokio::test]
+    async fn quorum_rejects_disagreement() {
+        let client = client(
+            &[Some(5), Some(6), Some(7)],
+            EndpointSelection::Failover,
+            Some(2),
+        );
+
+        client.get_starknet_state(&H160::zero()).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn quorum_agrees_on_blocks() {
+        let agreeing = client(
+            &[Some(5), Some(6), Some(5)],
+            EndpointSelection::Failover,
+            Some(2),
+        );
+
+        let tip = agreeing.get_block(L1Finality::Finalized).await.unwrap();
+        assert_eq!(tip.number, 5);
+        assert_eq!(
+            agreeing.get_block_hash(3).await.unwrap(),
+            Some(H256::from_low_u64_be(8))
+        );
+
+        // The endpoints disagree on the hash of block 3, so a reorg cannot be decided on.
+        let disagreeing = client(
+            &[Some(5), Some(6), Some(7)],
+            EndpointSelection::Failover,
+            Some(2),
+        );
+        disagreeing.get_block_hash(3).await.unwrap_err();
+        disagreeing
+            .get_block(L1Finality::Finalized)
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn any_endpoint_ends_waiting_for_updates() {
+        let client = client(&[None, Some(1)], EndpointSelection::Failover, None);
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            client.wait_for_update(Duration::from_secs(60)),
+        )
+        .await
+        .expect("The second endpoint should end the wait");
+    }
+
+    #[test]
+    fn quorum_must_be_reachable() {
+        let endpoints = vec![Endpoint(None), Endpoint(None)];
+        MultiEthereumClient::new(endpoints, EndpointSelection::Failover, Some(3)).unwrap_err();
+
+        let endpoints = vec![Endpoint(None), Endpoint(None)];
+        MultiEthereumClient::new(endpoints, EndpointSelection::Failover, Some(0)).unwrap_err();
+    }
+}
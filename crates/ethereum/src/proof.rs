@@ -0,0 +1,490 @@
+//! Verification of L1 state using Ethereum storage proofs.
+//!
+//! The core contract's state is read from its storage slots using `eth_getProof`, and the account
+//! and storage proofs are verified against the state root of the L1 block header. Headers are
+//! verified by recomputing their hash, and by following their parent hashes to a trusted
+//! checkpoint block.
+
+use anyhow::Context;
+use primitive_types::{H160, H256, U256};
+
+use crate::keccak;
+
+/// The tag whose hash is the storage slot of the core contract's `StarknetState.State` struct,
+/// which holds the state root, block number and block hash in consecutive slots.
+const STATE_STRUCT_TAG: &str = "STARKNET_1.0_INIT_STARKNET_STATE_STRUCT";
+
+/// The root of an empty Merkle-Patricia trie: the hash of the empty string's encoding.
+const EMPTY_TRIE_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// The storage slots of the core contract's state root, block number and block hash.
+pub(crate) fn state_slots() -> [H256; 3] {
+    let base = U256::from_big_endian(keccak(STATE_STRUCT_TAG.as_bytes()).as_bytes());
+    [0u64, 1, 2].map(|offset| {
+        let mut slot = H256::zero();
+        (base + offset).to_big_endian(slot.as_bytes_mut());
+        slot
+    })
+}
+
+/// The fields of an L1 block header needed to verify proofs against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Header {
+    pub number: u64,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub state_root: H256,
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Data,
+    Quantity,
+}
+
+/// The header fields in the order they are hashed. Fields from [FIRST_FORK_FIELD] onwards were
+/// added by later forks, and are only present in blocks after them.
+const HEADER_FIELDS: [(&str, Field); 21] = [
+    ("parentHash", Field::Data),
+    ("sha3Uncles", Field::Data),
+    ("miner", Field::Data),
+    ("stateRoot", Field::Data),
+    ("transactionsRoot", Field::Data),
+    ("receiptsRoot", Field::Data),
+    ("logsBloom", Field::Data),
+    ("difficulty", Field::Quantity),
+    ("number", Field::Quantity),
+    ("gasLimit", Field::Quantity),
+    ("gasUsed", Field::Quantity),
+    ("timestamp", Field::Quantity),
+    ("extraData", Field::Data),
+    ("mixHash", Field::Data),
+    ("nonce", Field::Data),
+    ("baseFeePerGas", Field::Quantity),
+    ("withdrawalsRoot", Field::Data),
+    ("blobGasUsed", Field::Quantity),
+    ("excessBlobGas", Field::Quantity),
+    ("parentBeaconBlockRoot", Field::Data),
+    ("requestsHash", Field::Data),
+];
+const FIRST_FORK_FIELD: usize = 15;
+
+/// Parses a block returned by `eth_getBlockBy*`, and checks that its hash matches its header.
+pub(crate) fn parse_header(block: &serde_json::Value) -> anyhow::Result<Header> {
+    let mut fields = Vec::with_capacity(HEADER_FIELDS.len());
+    for (index, (name, field)) in HEADER_FIELDS.into_iter().enumerate() {
+        let value = &block[name];
+        if value.is_null() {
+            anyhow::ensure!(index >= FIRST_FORK_FIELD, "Header field {name} is missing");
+            break;
+        }
+
+        let value = value
+            .as_str()
+            .with_context(|| format!("Header field {name} is not a string"))?;
+        let bytes = match field {
+            Field::Data => decode_hex(value),
+            Field::Quantity => decode_quantity(value),
+        }
+        .with_context(|| format!("Parsing header field {name}"))?;
+        fields.push(rlp::encode_bytes(&bytes));
+    }
+
+    let hash = keccak(&rlp::encode_list(&fields));
+    let reported = block["hash"].as_str().context("Block hash is missing")?;
+    anyhow::ensure!(
+        decode_hex(reported)? == hash.as_bytes(),
+        "Block hash {reported} does not match its header"
+    );
+
+    let h256 = |name: &str| -> anyhow::Result<H256> {
+        let bytes = decode_hex(block[name].as_str().unwrap_or_default())?;
+        anyhow::ensure!(bytes.len() == 32, "Header field {name} is not 32 bytes");
+        Ok(H256::from_slice(&bytes))
+    };
+    let number = decode_quantity(block["number"].as_str().unwrap_or_default())?;
+    anyhow::ensure!(number.len() <= 8, "Block number does not fit in a u64");
+
+    Ok(Header {
+        number: U256::from_big_endian(&number).as_u64(),
+        hash,
+        parent_hash: h256("parentHash")?,
+        state_root: h256("stateRoot")?,
+    })
+}
+
+/// Verifies the account proof of `address`, and returns the account's storage root.
+pub(crate) fn verify_account(
+    state_root: H256,
+    address: H160,
+    proof: &[Vec<u8>],
+) -> anyhow::Result<H256> {
+    let account = verify_proof(state_root, keccak(address.as_bytes()), proof)
+        .context("Verifying account proof")?
+        .context("Account does not exist")?;
+
+    // An account is encoded as [nonce, balance, storage root, code hash].
+    let rlp::Item::List { items, .. } = rlp::decode(&account)? else {
+        anyhow::bail!("Account is not a list");
+    };
+    anyhow::ensure!(items.len() == 4, "Account has {} fields", items.len());
+    let storage_root = items[2].bytes()?;
+    anyhow::ensure!(storage_root.len() == 32, "Storage root is not 32 bytes");
+
+    Ok(H256::from_slice(storage_root))
+}
+
+/// Verifies the storage proof of `slot`, and returns its value.
+pub(crate) fn verify_storage(
+    storage_root: H256,
+    slot: H256,
+    proof: &[Vec<u8>],
+) -> anyhow::Result<U256> {
+    let Some(value) = verify_proof(storage_root, keccak(slot.as_bytes()), proof)
+        .context("Verifying storage proof")?
+    else {
+        // Zero values are not stored.
+        return Ok(U256::zero());
+    };
+
+    let value = rlp::decode(&value)?.bytes()?;
+    anyhow::ensure!(value.len() <= 32, "Storage value is longer than 32 bytes");
+
+    Ok(U256::from_big_endian(value))
+}
+
+enum NodeRef<'a> {
+    Hash(H256),
+    /// Nodes whose encoding is shorter than 32 bytes are embedded in their parent.
+    Inline(&'a [u8]),
+}
+
+/// Verifies a Merkle-Patricia proof for `key`, and returns the value at the key, or [None] if
+/// the proof shows that the key is absent.
+fn verify_proof(root: H256, key: H256, proof: &[Vec<u8>]) -> anyhow::Result<Option<Vec<u8>>> {
+    if root == EMPTY_TRIE_ROOT {
+        return Ok(None);
+    }
+
+    let nibbles = key
+        .as_bytes()
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect::<Vec<_>>();
+    let mut path = &nibbles[..];
+    let mut proof = proof.iter();
+    let mut next = NodeRef::Hash(root);
+
+    loop {
+        let node = match next {
+            NodeRef::Hash(hash) => {
+                let node = proof.next().context("Proof is incomplete")?;
+                anyhow::ensure!(keccak(node) == hash, "Proof node does not match its hash");
+                node.as_slice()
+            }
+            NodeRef::Inline(node) => node,
+        };
+
+        let rlp::Item::List { items, .. } = rlp::decode(node)? else {
+            anyhow::bail!("Proof node is not a list");
+        };
+        let child = match items.len() {
+            // Branch node.
+            17 => match path.split_first() {
+                Some((&nibble, rest)) => {
+                    path = rest;
+                    &items[nibble as usize]
+                }
+                None => {
+                    let value = items[16].bytes()?;
+                    return Ok((!value.is_empty()).then(|| value.to_vec()));
+                }
+            },
+            // Leaf or extension node.
+            2 => {
+                let (is_leaf, node_path) = decode_node_path(items[0].bytes()?)?;
+                if is_leaf {
+                    if path != node_path.as_slice() {
+                        return Ok(None);
+                    }
+                    return Ok(Some(items[1].bytes()?.to_vec()));
+                }
+                if !path.starts_with(&node_path) {
+                    return Ok(None);
+                }
+                path = &path[node_path.len()..];
+                &items[1]
+            }
+            len => anyhow::bail!("Proof node has {len} items"),
+        };
+
+        next = match child {
+            rlp::Item::Bytes(hash) if hash.is_empty() => return Ok(None),
+            rlp::Item::Bytes(hash) if hash.len() == 32 => NodeRef::Hash(H256::from_slice(hash)),
+            rlp::Item::List { raw, .. } => NodeRef::Inline(raw),
+            rlp::Item::Bytes(_) => anyhow::bail!("Invalid child reference"),
+        };
+    }
+}
+
+/// Decodes the hex-prefix encoded path of a leaf or extension node.
+fn decode_node_path(encoded: &[u8]) -> anyhow::Result<(bool, Vec<u8>)> {
+    let (&first, rest) = encoded.split_first().context("Node path is empty")?;
+    let flags = first >> 4;
+    anyhow::ensure!(flags <= 3, "Invalid node path flags {flags}");
+
+    let mut nibbles = Vec::with_capacity(2 * encoded.len());
+    // An odd number of nibbles starts in the first byte.
+    if flags & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+
+    Ok((flags & 2 == 2, nibbles))
+}
+
+fn decode_hex(value: &str) -> anyhow::Result<Vec<u8>> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(value).with_context(|| format!("Invalid hex data {value}"))
+}
+
+/// Decodes a hex quantity into its minimal big-endian bytes, as encoded in headers.
+fn decode_quantity(value: &str) -> anyhow::Result<Vec<u8>> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    let value =
+        U256::from_str_radix(value, 16).with_context(|| format!("Invalid quantity {value}"))?;
+
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    let leading_zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+
+    Ok(bytes[leading_zeros..].to_vec())
+}
+
+/// The subset of Ethereum's RLP encoding needed to hash headers and to walk proofs.
+mod rlp {
+    use anyhow::Context;
+
+    pub enum Item<'a> {
+        Bytes(&'a [u8]),
+        List {
+            /// The item's complete encoding.
+            raw: &'a [u8],
+            items: Vec<Item<'a>>,
+        },
+    }
+
+    impl<'a> Item<'a> {
+        pub fn bytes(&self) -> anyhow::Result<&'a [u8]> {
+            match self {
+                Item::Bytes(bytes) => Ok(bytes),
+                Item::List { .. } => anyhow::bail!("Expected bytes, found a list"),
+            }
+        }
+    }
+
+    /// Decodes `data`, which must hold exactly one item.
+    pub fn decode(data: &[u8]) -> anyhow::Result<Item<'_>> {
+        let (item, rest) = decode_item(data)?;
+        anyhow::ensure!(rest.is_empty(), "Trailing bytes after item");
+        Ok(item)
+    }
+
+    fn decode_item(data: &[u8]) -> anyhow::Result<(Item<'_>, &[u8])> {
+        let (&prefix, rest) = data.split_first().context("Item is empty")?;
+
+        let (is_list, header_len, payload_len) = match prefix {
+            0x00..=0x7f => return Ok((Item::Bytes(&data[..1]), rest)),
+            0x80..=0xb7 => (false, 1, usize::from(prefix - 0x80)),
+            0xb8..=0xbf => (false, 1 + usize::from(prefix - 0xb7), 0),
+            0xc0..=0xf7 => (true, 1, usize::from(prefix - 0xc0)),
+            0xf8..=0xff => (true, 1 + usize::from(prefix - 0xf7), 0),
+        };
+        let payload_len = if header_len > 1 {
+            let len_bytes = rest
+                .get(..header_len - 1)
+                .context("Item length is truncated")?;
+            anyhow::ensure!(len_bytes.len() <= 8, "Item length is too long");
+            len_bytes
+                .iter()
+                .fold(0usize, |len, byte| (len << 8) | usize::from(*byte))
+        } else {
+            payload_len
+        };
+
+        let end = header_len
+            .checked_add(payload_len)
+            .context("Item length overflows")?;
+        anyhow::ensure!(end <= data.len(), "Item is truncated");
+        let (raw, rest) = data.split_at(end);
+        let payload = &raw[header_len..];
+
+        if !is_list {
+            return Ok((Item::Bytes(payload), rest));
+        }
+
+        let mut items = Vec::new();
+        let mut remaining = payload;
+        while !remaining.is_empty() {
+            let (item, next) = decode_item(remaining)?;
+            items.push(item);
+            remaining = next;
+        }
+
+        Ok((Item::List { raw, items }, rest))
+    }
+
+    pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+        match bytes {
+            [byte] if *byte < 0x80 => vec![*byte],
+            _ => {
+                let mut encoded = encode_header(0x80, bytes.len());
+                encoded.extend_from_slice(bytes);
+                encoded
+            }
+        }
+    }
+
+    /// Encodes a list of already encoded items.
+    pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        let mut encoded = encode_header(0xc0, payload.len());
+        encoded.extend(payload);
+        encoded
+    }
+
+    fn encode_header(offset: u8, len: usize) -> Vec<u8> {
+        if len < 56 {
+            return vec![offset + len as u8];
+        }
+
+        let len = len.to_be_bytes();
+        let leading_zeros = len.iter().take_while(|byte| **byte == 0).count();
+        let len = &len[leading_zeros..];
+
+        let mut header = vec![offset + 55 + len.len() as u8];
+        header.extend_from_slice(len);
+        header
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a node path with the hex-prefix encoding.
+    fn node_path(is_leaf: bool, nibbles: &[u8]) -> Vec<u8> {
+        let flags = (if is_leaf { 2 } else { 0 }) + (nibbles.len() % 2) as u8;
+        let mut padded = vec![flags];
+        if nibbles.len() % 2 == 0 {
+            padded.push(0);
+        }
+        padded.extend_from_slice(nibbles);
+        padded
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect()
+    }
+
+    fn nibbles(key: H256) -> Vec<u8> {
+        key.as_bytes()
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0x0f])
+            .collect()
+    }
+
+    #[test]
+    fn mainnet_genesis_header() {
+        let zero32 = format!("0x{}", "00".repeat(32));
+        let block = serde_json::json!({
+            "hash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+            "parentHash": zero32,
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "miner": format!("0x{}", "00".repeat(20)),
+            "stateRoot": "0xd7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544",
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "difficulty": "0x400000000",
+            "number": "0x0",
+            "gasLimit": "0x1388",
+            "gasUsed": "0x0",
+            "timestamp": "0x0",
+            "extraData": "0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+            "mixHash": zero32,
+            "nonce": "0x0000000000000042",
+        });
+
+        let header = parse_header(&block).unwrap();
+        assert_eq!(header.number, 0);
+        assert_eq!(header.parent_hash, H256::zero());
+
+        let mut tampered = block;
+        tampered["gasLimit"] = "0x1389".into();
+        parse_header(&tampered).unwrap_err();
+    }
+
+    #[test]
+    fn empty_trie_root() {
+        assert_eq!(keccak(&rlp::encode_bytes(&[])), EMPTY_TRIE_ROOT);
+    }
+
+    #[test]
+    fn storage_proof() {
+        let slot = state_slots()[0];
+        let key = nibbles(keccak(slot.as_bytes()));
+        let value = rlp::encode_bytes(&rlp::encode_bytes(&[0x12, 0x34]));
+
+        // A branch with the slot's leaf at its first nibble, and another leaf next to it.
+        let leaf = rlp::encode_list(&[rlp::encode_bytes(&node_path(true, &key[1..])), value]);
+        let mut other_key = key.clone();
+        other_key[0] = (other_key[0] + 1) % 16;
+        let other_leaf = rlp::encode_list(&[
+            rlp::encode_bytes(&node_path(true, &other_key[1..])),
+            rlp::encode_bytes(&rlp::encode_bytes(&[0x01])),
+        ]);
+
+        let mut children = vec![rlp::encode_bytes(&[]); 17];
+        children[usize::from(key[0])] = rlp::encode_bytes(keccak(&leaf).as_bytes());
+        children[usize::from(other_key[0])] = rlp::encode_bytes(keccak(&other_leaf).as_bytes());
+        let branch = rlp::encode_list(&children);
+        let root = keccak(&branch);
+
+        let value = verify_storage(root, slot, &[branch.clone(), leaf.clone()]).unwrap();
+        assert_eq!(value, U256::from(0x1234));
+
+        // Tampered proofs are rejected.
+        let mut tampered = leaf;
+        *tampered.last_mut().unwrap() ^= 1;
+        verify_storage(root, slot, &[branch, tampered]).unwrap_err();
+    }
+
+    #[test]
+    fn account_proof() {
+        let address = H160::from_low_u64_be(0xc0de);
+        let storage_root = H256::from_low_u64_be(0x1234);
+        let account = rlp::encode_list(&[
+            rlp::encode_bytes(&[1]),
+            rlp::encode_bytes(&[]),
+            rlp::encode_bytes(storage_root.as_bytes()),
+            rlp::encode_bytes(keccak(&[]).as_bytes()),
+        ]);
+        let key = nibbles(keccak(address.as_bytes()));
+        let leaf = rlp::encode_list(&[
+            rlp::encode_bytes(&node_path(true, &key)),
+            rlp::encode_bytes(&account),
+        ]);
+        let root = keccak(&leaf);
+
+        assert_eq!(
+            verify_account(root, address, &[leaf.clone()]).unwrap(),
+            storage_root
+        );
+
+        let other = H160::from_low_u64_be(0xbeef);
+        verify_account(root, other, &[leaf]).unwrap_err();
+    }
+}
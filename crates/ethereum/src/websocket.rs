@@ -0,0 +1,407 @@
+//! JSON-RPC over a websocket connection to an Ethereum node.
+//!
+//! The connection subscribes to new heads and to the core contract's logs using `eth_subscribe`,
+//! so that waiting for L1 updates can end as soon as the node sees them. It is re-established
+//! automatically, and requests fail immediately while it is down so that callers can fall back
+//! to HTTP.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use futures::{SinkExt, StreamExt};
+use primitive_types::H160;
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request as HandshakeRequest;
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, AUTHORIZATION};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::JsonRpcError;
+
+/// How long to wait for the response to a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+type Response = oneshot::Sender<anyhow::Result<serde_json::Value>>;
+
+struct Request {
+    /// The JSON-RPC request, whose `id` is set by the connection.
+    body: serde_json::Value,
+    response: Response,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct WebsocketTransport {
+    requests: mpsc::Sender<Request>,
+    /// Counts the subscription notifications received. The receiver is shared by all clones, so
+    /// that a notification which arrives while nobody is waiting ends the next wait.
+    notifications: Arc<Mutex<watch::Receiver<u64>>>,
+}
+
+impl WebsocketTransport {
+    /// Spawns the task which maintains the connection to `url`, and subscribes to the logs of
+    /// the core contract at `core_address`. The credentials of the URL are sent using basic
+    /// authentication.
+    pub fn spawn(url: reqwest::Url, core_address: H160) -> Self {
+        let (requests_tx, requests_rx) = mpsc::channel(32);
+        let (notifications_tx, notifications_rx) = watch::channel(0);
+
+        tokio::spawn(run(url, core_address, requests_rx, notifications_tx));
+
+        Self {
+            requests: requests_tx,
+            notifications: Arc::new(Mutex::new(notifications_rx)),
+        }
+    }
+
+    /// Sends the JSON-RPC request, and returns the `result` of its response. An error response
+    /// is returned as a [JsonRpcError].
+    pub async fn call(&self, body: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let (tx, rx) = oneshot::channel();
+        self.requests
+            .send(Request { body, response: tx })
+            .await
+            .context("Websocket task has stopped")?;
+
+        tokio::time::timeout(REQUEST_TIMEOUT, rx)
+            .await
+            .context("Request timed out")?
+            .context("Connection was lost")?
+    }
+
+    /// Waits until a subscription notification arrives which was not waited for yet, or for at
+    /// most `timeout`.
+    pub async fn wait_for_notification(&self, timeout: Duration) {
+        _ = tokio::time::timeout(timeout, async {
+            self.notifications.lock().await.changed().await
+        })
+        .await;
+    }
+}
+
+async fn run(
+    url: reqwest::Url,
+    core_address: H160,
+    mut requests: mpsc::Receiver<Request>,
+    notifications: watch::Sender<u64>,
+) {
+    let mut delay = MIN_RECONNECT_DELAY;
+
+    loop {
+        let connection = match handshake_request(&url) {
+            Ok(request) => tokio_tungstenite::connect_async(request)
+                .await
+                .map_err(anyhow::Error::from),
+            Err(e) => Err(e),
+        };
+        match connection {
+            Ok((stream, _)) => {
+                tracing::info!("Connected to Ethereum websocket");
+                delay = MIN_RECONNECT_DELAY;
+
+                match serve(stream, core_address, &mut requests, &notifications).await {
+                    // All transports have been dropped.
+                    Ok(()) => return,
+                    Err(e) => tracing::warn!(error=?e, "Ethereum websocket connection lost"),
+                }
+            }
+            Err(e) => tracing::warn!(error=?e, "Failed to connect to Ethereum websocket"),
+        }
+
+        // Fail requests while disconnected, so that they fall back to HTTP immediately.
+        let reconnect = tokio::time::sleep(delay);
+        tokio::pin!(reconnect);
+        loop {
+            tokio::select! {
+                _ = &mut reconnect => break,
+                request = requests.recv() => match request {
+                    Some(request) => {
+                        _ = request
+                            .response
+                            .send(Err(anyhow::anyhow!("Websocket is disconnected")));
+                    }
+                    None => return,
+                },
+            }
+        }
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+/// The handshake request for `url`, which authenticates using the URL's credentials instead of
+/// sending them as part of the URL.
+fn handshake_request(url: &reqwest::Url) -> anyhow::Result<HandshakeRequest> {
+    let mut public_url = url.clone();
+    // Only fails for URLs which cannot have credentials anyway.
+    _ = public_url.set_username("");
+    _ = public_url.set_password(None);
+
+    let mut request = public_url
+        .as_str()
+        .into_client_request()
+        .context("Creating handshake request")?;
+    if !url.username().is_empty() || url.password().is_some() {
+        let credentials = format!("{}:{}", url.username(), url.password().unwrap_or_default());
+        let authorization = format!("Basic {}", base64::encode(credentials));
+        request.headers_mut().insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&authorization).context("Creating authorization header")?,
+        );
+    }
+
+    Ok(request)
+}
+
+/// Serves requests over the connection until it fails, or until `requests` is closed.
+async fn serve<S>(
+    stream: tokio_tungstenite::WebSocketStream<S>,
+    core_address: H160,
+    requests: &mut mpsc::Receiver<Request>,
+    notifications: &watch::Sender<u64>,
+) -> anyhow::Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut sink, mut stream) = stream.split();
+    let mut next_id = 0u64;
+    let mut pending = HashMap::<u64, Response>::new();
+    // The subscription requests by id, whose responses have no pending request.
+    let mut subscriptions = HashMap::<u64, &'static str>::new();
+
+    let subscription_params = [
+        ("newHeads", serde_json::json!(["newHeads"])),
+        (
+            "logs",
+            serde_json::json!([
+                "logs",
+                {"address": format!("0x{}", hex::encode(core_address.as_bytes()))}
+            ]),
+        ),
+    ];
+    for (name, params) in subscription_params {
+        let subscribe = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_subscribe",
+            "params": params,
+            "id": next_id,
+        });
+        subscriptions.insert(next_id, name);
+        next_id += 1;
+        sink.send(Message::Text(subscribe.to_string()))
+            .await
+            .context("Subscribing")?;
+    }
+
+    loop {
+        tokio::select! {
+            request = requests.recv() => {
+                let Some(Request { mut body, response }) = request else {
+                    return Ok(());
+                };
+                body["id"] = next_id.into();
+
+                sink.send(Message::Text(body.to_string()))
+                    .await
+                    .context("Sending request")?;
+                track(&mut pending, next_id, response);
+                next_id += 1;
+            }
+            message = stream.next() => {
+                let message = message
+                    .context("Connection closed")?
+                    .context("Receiving message")?;
+                match message {
+                    Message::Text(text) => {
+                        handle_message(&text, &mut pending, &subscriptions, notifications)?
+                    }
+                    Message::Ping(data) => sink.send(Message::Pong(data)).await.context("Sending pong")?,
+                    Message::Close(frame) => anyhow::bail!("Connection closed: {frame:?}"),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Tracks the response of a sent request, and forgets the requests whose callers stopped waiting,
+/// for example because they timed out.
+fn track(pending: &mut HashMap<u64, Response>, id: u64, response: Response) {
+    pending.retain(|_, response| !response.is_closed());
+    pending.insert(id, response);
+}
+
+/// Routes a response to its pending request, reports failed subscriptions, and counts
+/// subscription notifications.
+fn handle_message(
+    text: &str,
+    pending: &mut HashMap<u64, Response>,
+    subscriptions: &HashMap<u64, &'static str>,
+    notifications: &watch::Sender<u64>,
+) -> anyhow::Result<()> {
+    let message: serde_json::Value = serde_json::from_str(text).context("Parsing message")?;
+
+    if message["method"] == "eth_subscription" {
+        notifications.send_modify(|count| *count += 1);
+        return Ok(());
+    }
+
+    let Some(id) = message["id"].as_u64() else {
+        return Ok(());
+    };
+    let error = message.get("error").map(JsonRpcError::from_response);
+
+    if let Some(subscription) = subscriptions.get(&id) {
+        if let Some(error) = error {
+            tracing::warn!(%subscription, %error, "Ethereum websocket subscription failed, relying on polling instead");
+        }
+        return Ok(());
+    }
+
+    // Requests which timed out may no longer be pending.
+    if let Some(response) = pending.remove(&id) {
+        let result = match error {
+            Some(error) => Err(error.into()),
+            None => Ok(message["result"].clone()),
+        };
+        _ = response.send(result);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscriptions() -> HashMap<u64, &'static str> {
+        HashMap::from([(0, "newHeads"), (1, "logs")])
+    }
+
+    #[test]
+    fn responses_are_routed_by_id() {
+        let (notifications, _rx) = watch::channel(0);
+        let mut pending = HashMap::new();
+        let (tx1, mut rx1) = oneshot::channel();
+        let (tx2, mut rx2) = oneshot::channel();
+        pending.insert(3, tx1);
+        pending.insert(4, tx2);
+
+        handle_message(
+            r#"{"jsonrpc":"2.0","id":4,"result":"0x10"}"#,
+            &mut pending,
+            &subscriptions(),
+            &notifications,
+        )
+        .unwrap();
+        handle_message(
+            r#"{"jsonrpc":"2.0","id":3,"error":{"code":-32000,"message":"oops"}}"#,
+            &mut pending,
+            &subscriptions(),
+            &notifications,
+        )
+        .unwrap();
+
+        assert_eq!(rx2.try_recv().unwrap().unwrap(), serde_json::json!("0x10"));
+        let error = rx1.try_recv().unwrap().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<JsonRpcError>(),
+            Some(&JsonRpcError {
+                code: -32000,
+                message: "oops".to_owned(),
+            })
+        );
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn notifications_are_counted() {
+        let (notifications, rx) = watch::channel(0);
+        let mut pending = HashMap::new();
+
+        let notification = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"subscription":"0x1","result":{}}}"#;
+        handle_message(notification, &mut pending, &subscriptions(), &notifications).unwrap();
+        handle_message(notification, &mut pending, &subscriptions(), &notifications).unwrap();
+        // Responses to the subscriptions themselves have no pending request.
+        handle_message(
+            r#"{"jsonrpc":"2.0","id":0,"result":"0x1"}"#,
+            &mut pending,
+            &subscriptions(),
+            &notifications,
+        )
+        .unwrap();
+        handle_message(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"not supported"}}"#,
+            &mut pending,
+            &subscriptions(),
+            &notifications,
+        )
+        .unwrap();
+
+        assert_eq!(*rx.borrow(), 2);
+    }
+
+    #[test]
+    fn abandoned_requests_are_forgotten() {
+        let mut pending = HashMap::new();
+        let (tx1, rx1) = oneshot::channel();
+        let (tx2, _rx2) = oneshot::channel();
+        let (tx3, _rx3) = oneshot::channel();
+
+        track(&mut pending, 1, tx1);
+        track(&mut pending, 2, tx2);
+        // The caller timed out.
+        drop(rx1);
+        track(&mut pending, 3, tx3);
+
+        let mut ids = pending.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn notifications_between_waits_are_not_lost() {
+        let (requests, _requests_rx) = mpsc::channel(1);
+        let (notifications, notifications_rx) = watch::channel(0);
+        let transport = WebsocketTransport {
+            requests,
+            notifications: Arc::new(Mutex::new(notifications_rx)),
+        };
+
+        // Arrives before anyone waits, and is noticed by the next wait of any clone.
+        notifications.send_modify(|count| *count += 1);
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            transport
+                .clone()
+                .wait_for_notification(Duration::from_secs(60)),
+        )
+        .await
+        .expect("Notification should end the wait");
+
+        // It only ends one wait.
+        let waited = std::time::Instant::now();
+        transport
+            .wait_for_notification(Duration::from_millis(100))
+            .await;
+        assert!(waited.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn credentials_are_sent_using_basic_authentication() {
+        let url = reqwest::Url::parse("wss://:secret@example.com/ws").unwrap();
+        let request = handshake_request(&url).unwrap();
+
+        assert_eq!(request.uri().to_string(), "wss://example.com/ws");
+        assert_eq!(
+            request.headers()[AUTHORIZATION],
+            format!("Basic {}", base64::encode(":secret"))
+        );
+
+        let url = reqwest::Url::parse("ws://localhost:8546").unwrap();
+        let request = handshake_request(&url).unwrap();
+        assert!(request.headers().get(AUTHORIZATION).is_none());
+    }
+}
@@ -0,0 +1,164 @@
+use blockifier::execution::contract_class::ContractClass;
+use blockifier::state::state_api::StateReader;
+use pathfinder_common::{ClassHash, ContractAddress, StorageAddress};
+use starknet_api::state::StorageKey;
+
+use super::felt::IntoFelt;
+use super::types::AccessList;
+
+/// Records the parts of the state read through it.
+///
+/// Reads which are served from the cache of a
+/// [CachedState](blockifier::state::cached_state::CachedState) never reach the reader, so to
+/// record everything a transaction reads it must be executed on a fresh cached state.
+pub(super) struct RecordingStateReader<S: StateReader> {
+    state: S,
+    access_list: AccessList,
+}
+
+impl<S: StateReader> RecordingStateReader<S> {
+    pub(super) fn new(state: S) -> Self {
+        Self {
+            state,
+            access_list: Default::default(),
+        }
+    }
+
+    /// Returns the reads recorded so far and starts recording anew.
+    pub(super) fn take_access_list(&mut self) -> AccessList {
+        std::mem::take(&mut self.access_list)
+    }
+}
+
+fn contract_address(contract_address: starknet_api::core::ContractAddress) -> ContractAddress {
+    ContractAddress::new_or_panic(contract_address.0.key().into_felt())
+}
+
+impl<S: StateReader> StateReader for RecordingStateReader<S> {
+    fn get_storage_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+        key: StorageKey,
+    ) -> blockifier::state::state_api::StateResult<starknet_api::hash::StarkFelt> {
+        let value = self.state.get_storage_at(contract_address, key)?;
+
+        self.access_list
+            .storage_reads
+            .entry(self::contract_address(contract_address))
+            .or_default()
+            .insert(StorageAddress::new_or_panic(key.0.key().into_felt()));
+
+        Ok(value)
+    }
+
+    fn get_nonce_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+    ) -> blockifier::state::state_api::StateResult<starknet_api::core::Nonce> {
+        let nonce = self.state.get_nonce_at(contract_address)?;
+
+        self.access_list
+            .nonces
+            .insert(self::contract_address(contract_address));
+
+        Ok(nonce)
+    }
+
+    fn get_class_hash_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+    ) -> blockifier::state::state_api::StateResult<starknet_api::core::ClassHash> {
+        let class_hash = self.state.get_class_hash_at(contract_address)?;
+
+        self.access_list
+            .contracts
+            .insert(self::contract_address(contract_address));
+
+        Ok(class_hash)
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &starknet_api::core::ClassHash,
+    ) -> blockifier::state::state_api::StateResult<ContractClass> {
+        // Recorded even if the class is not declared, as the transaction depends on that too.
+        self.access_list
+            .classes
+            .insert(ClassHash(class_hash.0.into_felt()));
+
+        self.state.get_compiled_contract_class(class_hash)
+    }
+
+    fn get_compiled_class_hash(
+        &mut self,
+        class_hash: starknet_api::core::ClassHash,
+    ) -> blockifier::state::state_api::StateResult<starknet_api::core::CompiledClassHash> {
+        // Recorded before the read, as declaring a class expects it to fail for undeclared classes.
+        self.access_list
+            .compiled_class_hashes
+            .insert(ClassHash(class_hash.0.into_felt()));
+
+        self.state.get_compiled_class_hash(class_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pathfinder_common::{class_hash, contract_address, storage_address};
+
+    use crate::test_utils::DummyStateReader;
+
+    fn address(value: u8) -> starknet_api::core::ContractAddress {
+        starknet_api::core::ContractAddress(
+            starknet_api::core::PatriciaKey::try_from(starknet_api::hash::StarkFelt::from(value))
+                .unwrap(),
+        )
+    }
+
+    fn key(value: u8) -> StorageKey {
+        StorageKey(
+            starknet_api::core::PatriciaKey::try_from(starknet_api::hash::StarkFelt::from(value))
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn reads_are_recorded() {
+        let mut uut = RecordingStateReader::new(DummyStateReader {});
+
+        uut.get_storage_at(address(1), key(2)).unwrap();
+        uut.get_storage_at(address(1), key(3)).unwrap();
+        uut.get_storage_at(address(1), key(2)).unwrap();
+        uut.get_storage_at(address(4), key(5)).unwrap();
+        uut.get_nonce_at(address(6)).unwrap();
+        uut.get_class_hash_at(address(7)).unwrap();
+        uut.get_compiled_class_hash(starknet_api::core::ClassHash(
+            starknet_api::hash::StarkFelt::from(8u8),
+        ))
+        .unwrap();
+
+        let access_list = uut.take_access_list();
+        assert_eq!(
+            access_list,
+            AccessList {
+                storage_reads: [
+                    (
+                        contract_address!("0x1"),
+                        [storage_address!("0x2"), storage_address!("0x3")].into(),
+                    ),
+                    (contract_address!("0x4"), [storage_address!("0x5")].into()),
+                ]
+                .into(),
+                nonces: [contract_address!("0x6")].into(),
+                contracts: [contract_address!("0x7")].into(),
+                compiled_class_hashes: [class_hash!("0x8")].into(),
+                ..Default::default()
+            }
+        );
+
+        // Recording starts anew.
+        assert_eq!(uut.take_access_list(), AccessList::default());
+    }
+}
@@ -1,7 +1,9 @@
 use std::{collections::HashMap, sync::Arc};
 
 use blockifier::block_context::BlockContext;
-use pathfinder_common::{contract_address, ContractAddress};
+use pathfinder_common::{
+    contract_address, BlockNumber, BlockTimestamp, ContractAddress, GasPrice, SequencerAddress,
+};
 use starknet_api::core::PatriciaKey;
 
 use super::execution_state::ExecutionState;
@@ -14,6 +16,20 @@ pub const ETH_FEE_TOKEN_ADDRESS: ContractAddress =
 pub const STRK_FEE_TOKEN_ADDRESS: ContractAddress =
     contract_address!("0x04718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d");
 
+/// Replaces properties of the block being executed in, without affecting which state is read.
+///
+/// This includes the system contract update made when executing on the parent state, which stores
+/// the hash of the block 10 blocks before the actual block number rather than the overridden one,
+/// since that hash is part of the block's state.
+#[derive(Debug, Clone, Default)]
+pub struct BlockOverride {
+    pub number: Option<BlockNumber>,
+    pub timestamp: Option<BlockTimestamp>,
+    pub eth_l1_gas_price: Option<GasPrice>,
+    pub strk_l1_gas_price: Option<GasPrice>,
+    pub sequencer_address: Option<SequencerAddress>,
+}
+
 pub(super) fn construct_block_context(
     execution_state: &ExecutionState<'_>,
 ) -> anyhow::Result<BlockContext> {
@@ -35,14 +51,26 @@ pub(super) fn construct_block_context(
         .collect();
     let chain_id = String::from_utf8(chain_id)?;
 
+    let header = &execution_state.header;
+    let block_override = &execution_state.block_override;
+    let block_number = block_override.number.unwrap_or(header.number);
+    let block_timestamp = block_override.timestamp.unwrap_or(header.timestamp);
+    let sequencer_address = block_override
+        .sequencer_address
+        .unwrap_or(header.sequencer_address);
+    let eth_l1_gas_price = block_override
+        .eth_l1_gas_price
+        .unwrap_or(header.eth_l1_gas_price);
+    let strk_l1_gas_price = block_override
+        .strk_l1_gas_price
+        .unwrap_or(header.strk_l1_gas_price);
+
     Ok(BlockContext {
         chain_id: starknet_api::core::ChainId(chain_id),
-        block_number: starknet_api::block::BlockNumber(execution_state.header.number.get()),
-        block_timestamp: starknet_api::block::BlockTimestamp(
-            execution_state.header.timestamp.get(),
-        ),
+        block_number: starknet_api::block::BlockNumber(block_number.get()),
+        block_timestamp: starknet_api::block::BlockTimestamp(block_timestamp.get()),
         sequencer_address: starknet_api::core::ContractAddress(
-            PatriciaKey::try_from(execution_state.header.sequencer_address.0.into_starkfelt())
+            PatriciaKey::try_from(sequencer_address.0.into_starkfelt())
                 .expect("Sequencer address overflow"),
         ),
         fee_token_addresses: blockifier::block_context::FeeTokenAddresses {
@@ -51,8 +79,8 @@ pub(super) fn construct_block_context(
         },
         vm_resource_fee_cost: Arc::new(default_resource_fee_costs()),
         gas_prices: blockifier::block_context::GasPrices {
-            eth_l1_gas_price: execution_state.header.eth_l1_gas_price.0,
-            strk_l1_gas_price: execution_state.header.strk_l1_gas_price.0,
+            eth_l1_gas_price: eth_l1_gas_price.0,
+            strk_l1_gas_price: strk_l1_gas_price.0,
         },
         invoke_tx_max_n_steps: 3_000_000,
         validate_max_n_steps: 1_000_000,
@@ -90,3 +118,51 @@ fn default_resource_fee_costs() -> HashMap<String, f64> {
         (KECCAK_BUILTIN_NAME.to_string(), 2048.0 * N_STEPS_FEE_WEIGHT),
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_common::{BlockHeader, ChainId};
+
+    use super::*;
+    use crate::felt::IntoFelt;
+
+    #[test]
+    fn block_override() {
+        let storage = pathfinder_storage::Storage::in_memory().unwrap();
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+
+        let header = BlockHeader::builder()
+            .with_number(BlockNumber::new_or_panic(5))
+            .with_timestamp(BlockTimestamp::new_or_panic(100))
+            .with_eth_l1_gas_price(GasPrice(1))
+            .with_strk_l1_gas_price(GasPrice(2))
+            .with_sequencer_address(sequencer_address!("0x5e0"))
+            .finalize_with_hash(block_hash!("0xb5"));
+
+        let state = ExecutionState::simulation(&tx, ChainId::MAINNET, header.clone(), None);
+        let context = construct_block_context(&state).unwrap();
+        assert_eq!(context.block_number.0, 5);
+        assert_eq!(context.block_timestamp.0, 100);
+        assert_eq!(context.gas_prices.eth_l1_gas_price, 1);
+        assert_eq!(context.gas_prices.strk_l1_gas_price, 2);
+
+        // Properties which are not overridden are taken from the header.
+        let state = ExecutionState::simulation(&tx, ChainId::MAINNET, header, None)
+            .with_block_override(BlockOverride {
+                timestamp: Some(BlockTimestamp::new_or_panic(3700)),
+                eth_l1_gas_price: Some(GasPrice(10)),
+                ..Default::default()
+            });
+        let context = construct_block_context(&state).unwrap();
+        assert_eq!(context.block_number.0, 5);
+        assert_eq!(context.block_timestamp.0, 3700);
+        assert_eq!(context.gas_prices.eth_l1_gas_price, 10);
+        assert_eq!(context.gas_prices.strk_l1_gas_price, 2);
+        assert_eq!(
+            context.sequencer_address.0.key().into_felt(),
+            felt!("0x5e0")
+        );
+    }
+}
@@ -1,24 +1,106 @@
 use std::sync::Arc;
 
 use blockifier::{
+    execution::call_info::CallInfo,
     execution::entry_point::{CallEntryPoint, EntryPointExecutionContext, ExecutionResources},
     transaction::objects::{AccountTransactionContext, DeprecatedAccountTransactionContext},
 };
 use pathfinder_common::{CallParam, CallResultValue, ContractAddress, EntryPoint};
+use pathfinder_crypto::Felt;
 use starknet_api::core::PatriciaKey;
 
 use super::{
     error::CallError,
     execution_state::ExecutionState,
     felt::{IntoFelt, IntoStarkFelt},
+    types::CallTrace,
 };
 
 pub fn call(
-    mut execution_state: ExecutionState<'_>,
+    execution_state: ExecutionState<'_>,
     contract_address: ContractAddress,
     entry_point_selector: EntryPoint,
     calldata: Vec<CallParam>,
 ) -> Result<Vec<CallResultValue>, CallError> {
+    let call_info = execute(
+        execution_state,
+        contract_address,
+        entry_point_selector,
+        calldata,
+    )?;
+
+    let result = call_info
+        .execution
+        .retdata
+        .0
+        .iter()
+        .map(|f| CallResultValue(f.into_felt()))
+        .collect();
+
+    Ok(result)
+}
+
+/// Executes the call like [call], but returns the whole invocation tree including inner calls,
+/// events and messages.
+///
+/// If a Cairo 1 call fails, the tree up to the failure is returned along with the revert reason.
+/// Other failures, such as those of Cairo 0 calls, leave no tree behind and are returned as errors.
+pub fn trace_call(
+    execution_state: ExecutionState<'_>,
+    contract_address: ContractAddress,
+    entry_point_selector: EntryPoint,
+    calldata: Vec<CallParam>,
+) -> Result<CallTrace, CallError> {
+    let call_info = execute(
+        execution_state,
+        contract_address,
+        entry_point_selector,
+        calldata,
+    )?;
+
+    Ok(call_trace(call_info))
+}
+
+fn call_trace(call_info: CallInfo) -> CallTrace {
+    let revert_reason = call_info.execution.failed.then(|| {
+        let panic_data = call_info
+            .execution
+            .retdata
+            .0
+            .iter()
+            .map(|felt| format_panic_felt(felt.into_felt()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("Execution failed. Failure reason: {panic_data}.")
+    });
+
+    CallTrace {
+        invocation: call_info.into(),
+        revert_reason,
+    }
+}
+
+/// Formats a felt of Cairo 1 panic data, which is usually a short string.
+fn format_panic_felt(felt: Felt) -> String {
+    let bytes = felt.to_be_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+
+    match std::str::from_utf8(&bytes[start..]) {
+        Ok(string)
+            if !string.is_empty() && string.chars().all(|c| c.is_ascii_graphic() || c == ' ') =>
+        {
+            format!("{felt} ('{string}')")
+        }
+        _ => felt.to_string(),
+    }
+}
+
+fn execute(
+    mut execution_state: ExecutionState<'_>,
+    contract_address: ContractAddress,
+    entry_point_selector: EntryPoint,
+    calldata: Vec<CallParam>,
+) -> Result<CallInfo, CallError> {
     let (mut state, block_context) = execution_state.starknet_state()?;
 
     let contract_address = starknet_api::core::ContractAddress(PatriciaKey::try_from(
@@ -50,13 +132,59 @@ pub fn call(
 
     let call_info = call_entry_point.execute(&mut state, &mut resources, &mut context)?;
 
-    let result = call_info
-        .execution
-        .retdata
-        .0
-        .iter()
-        .map(|f| CallResultValue(f.into_felt()))
-        .collect();
+    Ok(call_info)
+}
 
-    Ok(result)
+#[cfg(test)]
+mod tests {
+    use blockifier::execution::call_info::{CallExecution, Retdata};
+    use pathfinder_common::macro_prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn failed_call_keeps_partial_tree() {
+        let inner = CallInfo {
+            execution: CallExecution {
+                retdata: Retdata(vec![felt!("0x1").into_starkfelt()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let failed = CallInfo {
+            execution: CallExecution {
+                retdata: Retdata(vec![
+                    Felt::from_be_slice(b"Not enough funds")
+                        .unwrap()
+                        .into_starkfelt(),
+                    felt!("0x2").into_starkfelt(),
+                ]),
+                failed: true,
+                ..Default::default()
+            },
+            inner_calls: vec![inner],
+            ..Default::default()
+        };
+
+        let trace = call_trace(failed);
+        assert_eq!(trace.invocation.internal_calls.len(), 1);
+        assert_eq!(
+            trace.invocation.internal_calls[0].result,
+            vec![felt!("0x1")]
+        );
+        assert_eq!(
+            trace.revert_reason.unwrap(),
+            format!(
+                "Execution failed. Failure reason: {} ('Not enough funds'), {}.",
+                Felt::from_be_slice(b"Not enough funds").unwrap(),
+                felt!("0x2")
+            )
+        );
+    }
+
+    #[test]
+    fn successful_call_has_no_revert_reason() {
+        let trace = call_trace(CallInfo::default());
+        assert!(trace.revert_reason.is_none());
+    }
 }
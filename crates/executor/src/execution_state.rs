@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
+use super::block_context::BlockOverride;
 use super::pending::PendingStateReader;
+use super::state_override::{OverrideStateReader, StateOverride};
 use super::state_reader::PathfinderStateReader;
 use crate::IntoStarkFelt;
 use anyhow::Context;
@@ -11,7 +13,14 @@ use blockifier::{
         state_api::State,
     },
 };
-use pathfinder_common::{BlockHeader, ChainId, StateUpdate};
+use pathfinder_common::{
+    BlockHeader, ChainId, ContractAddress, StateUpdate, StorageAddress, StorageValue,
+};
+use pathfinder_crypto::Felt;
+
+/// The reader of the state transactions are executed on.
+pub(super) type StarknetStateReader<'tx> =
+    OverrideStateReader<PendingStateReader<PathfinderStateReader<'tx>>>;
 
 pub struct ExecutionState<'tx> {
     transaction: &'tx pathfinder_storage::Transaction<'tx>,
@@ -19,17 +28,43 @@ pub struct ExecutionState<'tx> {
     pub header: BlockHeader,
     execute_on_parent_state: bool,
     pending_state: Option<Arc<StateUpdate>>,
+    state_override: Arc<StateOverride>,
+    block_override: BlockOverride,
+    access_lists: bool,
 }
 
 impl<'tx> ExecutionState<'tx> {
     pub(super) fn starknet_state(
         &mut self,
-    ) -> anyhow::Result<(
-        CachedState<PendingStateReader<PathfinderStateReader<'_>>>,
-        BlockContext,
-    )> {
+    ) -> anyhow::Result<(CachedState<StarknetStateReader<'tx>>, BlockContext)> {
         let block_context = super::block_context::construct_block_context(self)?;
 
+        let mut cached_state = CachedState::new(
+            self.state_reader(self.state_override.clone()),
+            GlobalContractCache::default(),
+        );
+
+        if let Some((contract_address, key, value)) = self.system_contract_update()? {
+            cached_state.set_storage_at(
+                starknet_api::core::ContractAddress(starknet_api::core::PatriciaKey::try_from(
+                    contract_address.0.into_starkfelt(),
+                )?),
+                starknet_api::state::StorageKey(starknet_api::core::PatriciaKey::try_from(
+                    key.0.into_starkfelt(),
+                )?),
+                value.0.into_starkfelt(),
+            )
+        }
+
+        Ok((cached_state, block_context))
+    }
+
+    /// Returns a reader of the state with `state_override` applied. Unlike [Self::starknet_state]
+    /// this does not include the [system contract update](Self::system_contract_update).
+    pub(super) fn state_reader(
+        &self,
+        state_override: Arc<StateOverride>,
+    ) -> StarknetStateReader<'tx> {
         let block_number = if self.execute_on_parent_state {
             self.header.number.parent()
         } else {
@@ -42,35 +77,36 @@ impl<'tx> ExecutionState<'tx> {
             self.pending_state.is_some(),
         );
         let pending_state_reader = PendingStateReader::new(raw_reader, self.pending_state.clone());
-        let mut cached_state =
-            CachedState::new(pending_state_reader, GlobalContractCache::default());
+        OverrideStateReader::new(pending_state_reader, state_override)
+    }
 
-        // Perform system contract updates if we are executing ontop of a parent block.
-        // Currently this is only the block hash from 10 blocks ago.
-        if self.execute_on_parent_state && self.header.number.get() >= 10 {
-            let block_number_whose_hash_becomes_available =
-                pathfinder_common::BlockNumber::new_or_panic(self.header.number.get() - 10);
-            let (_, block_hash) = self
-                .transaction
-                .block_id(block_number_whose_hash_becomes_available.into())?
-                .context("Getting historical block hash")?;
+    /// The system contract update to perform if we are executing ontop of a parent block.
+    /// Currently this is only the block hash from 10 blocks ago.
+    ///
+    /// This uses the block's actual number even if it is overridden, see [BlockOverride].
+    pub fn system_contract_update(
+        &self,
+    ) -> anyhow::Result<Option<(ContractAddress, StorageAddress, StorageValue)>> {
+        if !self.execute_on_parent_state || self.header.number.get() < 10 {
+            return Ok(None);
+        }
 
-            tracing::trace!(%block_number_whose_hash_becomes_available, %block_hash, "Setting historical block hash");
+        let block_number_whose_hash_becomes_available =
+            pathfinder_common::BlockNumber::new_or_panic(self.header.number.get() - 10);
+        let (_, block_hash) = self
+            .transaction
+            .block_id(block_number_whose_hash_becomes_available.into())?
+            .context("Getting historical block hash")?;
 
-            cached_state.set_storage_at(
-                starknet_api::core::ContractAddress(starknet_api::core::PatriciaKey::try_from(
-                    starknet_api::hash::StarkFelt::from(1u8),
-                )?),
-                starknet_api::state::StorageKey(starknet_api::core::PatriciaKey::try_from(
-                    starknet_api::hash::StarkFelt::from(
-                        block_number_whose_hash_becomes_available.get(),
-                    ),
-                )?),
-                block_hash.0.into_starkfelt(),
-            )
-        }
+        tracing::trace!(%block_number_whose_hash_becomes_available, %block_hash, "Setting historical block hash");
 
-        Ok((cached_state, block_context))
+        Ok(Some((
+            ContractAddress::ONE,
+            StorageAddress::new_or_panic(Felt::from_u64(
+                block_number_whose_hash_becomes_available.get(),
+            )),
+            StorageValue(block_hash.0),
+        )))
     }
 
     pub fn trace(
@@ -85,6 +121,9 @@ impl<'tx> ExecutionState<'tx> {
             header,
             pending_state,
             execute_on_parent_state: true,
+            state_override: Default::default(),
+            block_override: Default::default(),
+            access_lists: false,
         }
     }
 
@@ -100,6 +139,43 @@ impl<'tx> ExecutionState<'tx> {
             header,
             pending_state,
             execute_on_parent_state: false,
+            state_override: Default::default(),
+            block_override: Default::default(),
+            access_lists: false,
+        }
+    }
+
+    /// Executes on top of `state_override` instead of the plain block state.
+    pub fn with_state_override(self, state_override: StateOverride) -> Self {
+        Self {
+            state_override: Arc::new(state_override),
+            ..self
+        }
+    }
+
+    /// Executes as if in a block with the overridden properties, on the same state.
+    pub fn with_block_override(self, block_override: BlockOverride) -> Self {
+        Self {
+            block_override,
+            ..self
+        }
+    }
+
+    /// Records the parts of the state each simulated or traced transaction accesses.
+    ///
+    /// This is slower, since transactions can no longer share cached state.
+    pub fn with_access_lists(self) -> Self {
+        Self {
+            access_lists: true,
+            ..self
         }
     }
+
+    pub(super) fn access_lists(&self) -> bool {
+        self.access_lists
+    }
+
+    pub(super) fn state_override(&self) -> &StateOverride {
+        &self.state_override
+    }
 }
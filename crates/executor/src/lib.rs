@@ -1,3 +1,4 @@
+pub(crate) mod access_list;
 pub(crate) mod block_context;
 pub(crate) mod call;
 pub(crate) mod class;
@@ -8,18 +9,22 @@ pub(crate) mod felt;
 pub(crate) mod lru_cache;
 pub(crate) mod pending;
 pub(crate) mod simulate;
+pub(crate) mod state_override;
 pub(crate) mod state_reader;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub(crate) mod transaction;
 pub mod types;
 
-pub use block_context::ETH_FEE_TOKEN_ADDRESS;
-pub use call::call;
+pub use block_context::{BlockOverride, ETH_FEE_TOKEN_ADDRESS, STRK_FEE_TOKEN_ADDRESS};
+pub use call::{call, trace_call};
 pub use class::{parse_casm_definition, parse_deprecated_class_definition};
 pub use error::{CallError, TransactionExecutionError};
 pub use estimate::estimate;
 pub use execution_state::ExecutionState;
 pub use felt::{IntoFelt, IntoStarkFelt};
 pub use simulate::{simulate, trace_all, trace_one};
+pub use state_override::{ContractOverride, StateOverride};
 
 // re-export blockifier transaction type since it's exposed on our API
 pub use blockifier::transaction::account_transaction::AccountTransaction;
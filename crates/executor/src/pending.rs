@@ -110,54 +110,7 @@ mod tests {
         class_hash, contract_address, contract_nonce, storage_address, storage_value, StateUpdate,
     };
 
-    struct DummyStateReader {}
-
-    impl StateReader for DummyStateReader {
-        fn get_storage_at(
-            &mut self,
-            _contract_address: starknet_api::core::ContractAddress,
-            _key: starknet_api::state::StorageKey,
-        ) -> blockifier::state::state_api::StateResult<starknet_api::hash::StarkFelt> {
-            Ok(starknet_api::hash::StarkFelt::from(u32::MAX))
-        }
-
-        fn get_nonce_at(
-            &mut self,
-            _contract_address: starknet_api::core::ContractAddress,
-        ) -> blockifier::state::state_api::StateResult<starknet_api::core::Nonce> {
-            Ok(starknet_api::core::Nonce(
-                starknet_api::hash::StarkFelt::from(u32::MAX),
-            ))
-        }
-
-        fn get_class_hash_at(
-            &mut self,
-            _contract_address: starknet_api::core::ContractAddress,
-        ) -> blockifier::state::state_api::StateResult<starknet_api::core::ClassHash> {
-            Ok(starknet_api::core::ClassHash(
-                starknet_api::hash::StarkFelt::from(u32::MAX),
-            ))
-        }
-
-        fn get_compiled_contract_class(
-            &mut self,
-            _class_hash: &starknet_api::core::ClassHash,
-        ) -> blockifier::state::state_api::StateResult<
-            blockifier::execution::contract_class::ContractClass,
-        > {
-            unimplemented!()
-        }
-
-        fn get_compiled_class_hash(
-            &mut self,
-            _class_hash: starknet_api::core::ClassHash,
-        ) -> blockifier::state::state_api::StateResult<starknet_api::core::CompiledClassHash>
-        {
-            Ok(starknet_api::core::CompiledClassHash(
-                starknet_api::hash::StarkFelt::from(u32::MAX),
-            ))
-        }
-    }
+    use crate::test_utils::DummyStateReader;
 
     #[test]
     fn test_pending_nonce() {
@@ -1,7 +1,16 @@
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
 use blockifier::{
-    state::{cached_state::CachedState, errors::StateError, state_api::State},
+    block_context::BlockContext,
+    execution::contract_class::ContractClass,
+    state::{
+        cached_state::{CachedState, GlobalContractCache},
+        errors::StateError,
+        state_api::{State, StateReader},
+    },
+    transaction::errors::TransactionExecutionError as BlockifierTransactionExecutionError,
+    transaction::objects::TransactionExecutionInfo,
     transaction::transaction_execution::Transaction,
     transaction::transactions::ExecutableTransaction,
 };
@@ -12,21 +21,197 @@ use pathfinder_common::{
 use primitive_types::U256;
 
 use crate::{
+    state_override::StateOverride,
     transaction::transaction_hash,
     types::{
-        DeclareTransactionTrace, DeclaredSierraClass, DeployAccountTransactionTrace,
+        AccessList, DeclareTransactionTrace, DeclaredSierraClass, DeployAccountTransactionTrace,
         DeployedContract, ExecuteInvocation, InvokeTransactionTrace, L1HandlerTransactionTrace,
         PriceUnit, ReplacedClass, StateDiff, StorageDiff,
     },
-    IntoFelt,
+    IntoFelt, IntoStarkFelt,
 };
 
 use super::{
+    access_list::RecordingStateReader,
     error::TransactionExecutionError,
-    execution_state::ExecutionState,
+    execution_state::{ExecutionState, StarknetStateReader},
     types::{FeeEstimate, TransactionSimulation, TransactionTrace},
 };
 
+/// The state transactions are executed on, one after the other.
+#[allow(clippy::large_enum_variant)]
+enum BlockState<'a, 'tx> {
+    Cached(CachedState<StarknetStateReader<'tx>>),
+    /// Executes each transaction on a fresh cached state on top of the writes of the previous
+    /// transactions, so that all of its reads reach the [RecordingStateReader].
+    Recording {
+        execution_state: &'a ExecutionState<'tx>,
+        /// Updated in place once the transaction's state, which shares it, is dropped.
+        writes: Arc<StateOverride>,
+        /// Shared by the transaction states. Classes are never moved into it, as that would hide
+        /// class reads from the [RecordingStateReader]; compiled classes are cached by the
+        /// underlying reader instead.
+        contract_cache: GlobalContractCache,
+    },
+}
+
+struct ExecutedTransaction {
+    result: Result<TransactionExecutionInfo, BlockifierTransactionExecutionError>,
+    state_diff: StateDiff,
+    access_list: Option<AccessList>,
+}
+
+impl<'a, 'tx> BlockState<'a, 'tx> {
+    fn new(execution_state: &'a mut ExecutionState<'tx>) -> anyhow::Result<(Self, BlockContext)> {
+        if !execution_state.access_lists() {
+            let (state, block_context) = execution_state.starknet_state()?;
+            return Ok((Self::Cached(state), block_context));
+        }
+
+        let block_context = super::block_context::construct_block_context(execution_state)?;
+
+        let mut writes = execution_state.state_override().clone();
+        if let Some((contract_address, key, value)) = execution_state.system_contract_update()? {
+            writes
+                .contracts
+                .entry(contract_address)
+                .or_default()
+                .storage
+                .insert(key, value);
+        }
+
+        Ok((
+            Self::Recording {
+                execution_state,
+                writes: Arc::new(writes),
+                contract_cache: GlobalContractCache::default(),
+            },
+            block_context,
+        ))
+    }
+
+    fn execute(
+        &mut self,
+        transaction: Transaction,
+        block_context: &BlockContext,
+        charge_fee: bool,
+        validate: bool,
+    ) -> Result<ExecutedTransaction, StateError> {
+        let declared_deprecated_class_hash = transaction_declared_deprecated_class(&transaction);
+
+        match self {
+            Self::Cached(state) => {
+                let mut tx_state = CachedState::<_>::create_transactional(state);
+                let result =
+                    transaction.execute(&mut tx_state, block_context, charge_fee, validate);
+                let state_diff = to_state_diff(&mut tx_state, declared_deprecated_class_hash)?;
+                tx_state.commit();
+
+                Ok(ExecutedTransaction {
+                    result,
+                    state_diff,
+                    access_list: None,
+                })
+            }
+            Self::Recording {
+                execution_state,
+                writes,
+                contract_cache,
+            } => {
+                let reader =
+                    RecordingStateReader::new(execution_state.state_reader(writes.clone()));
+                let mut tx_state = CachedState::new(reader, contract_cache.clone());
+                let result =
+                    transaction.execute(&mut tx_state, block_context, charge_fee, validate);
+                let mut access_list = tx_state.state.take_access_list();
+                let state_diff = to_state_diff(&mut tx_state, declared_deprecated_class_hash)?;
+
+                for (contract_address, diffs) in &state_diff.storage_diffs {
+                    access_list
+                        .storage_writes
+                        .entry(*contract_address)
+                        .or_default()
+                        .extend(diffs.iter().map(|diff| diff.key));
+                }
+                access_list.nonces.extend(state_diff.nonces.keys().copied());
+
+                let declared_classes = declared_classes(&mut tx_state, &state_diff)?;
+                drop(tx_state);
+                add_writes(Arc::make_mut(writes), &state_diff, declared_classes);
+
+                Ok(ExecutedTransaction {
+                    result,
+                    state_diff,
+                    access_list: Some(access_list),
+                })
+            }
+        }
+    }
+}
+
+/// Returns the compiled definitions of the classes declared by a transaction.
+fn declared_classes<S: StateReader>(
+    state: &mut CachedState<S>,
+    state_diff: &StateDiff,
+) -> Result<Vec<(ClassHash, ContractClass)>, StateError> {
+    state_diff
+        .declared_classes
+        .iter()
+        .map(|class| ClassHash(class.class_hash.0))
+        .chain(state_diff.deprecated_declared_classes.iter().copied())
+        .map(|class_hash| {
+            let class = state.get_compiled_contract_class(&starknet_api::core::ClassHash(
+                class_hash.0.into_starkfelt(),
+            ))?;
+            Ok((class_hash, class))
+        })
+        .collect()
+}
+
+/// Adds the writes of a transaction to `writes`, for the following transactions to execute on.
+fn add_writes(
+    writes: &mut StateOverride,
+    state_diff: &StateDiff,
+    declared_classes: Vec<(ClassHash, ContractClass)>,
+) {
+    for (contract_address, diffs) in &state_diff.storage_diffs {
+        writes
+            .contracts
+            .entry(*contract_address)
+            .or_default()
+            .storage
+            .extend(diffs.iter().map(|diff| (diff.key, diff.value)));
+    }
+
+    for (contract_address, nonce) in &state_diff.nonces {
+        writes.contracts.entry(*contract_address).or_default().nonce = Some(*nonce);
+    }
+
+    let deployed = state_diff
+        .deployed_contracts
+        .iter()
+        .map(|contract| (contract.address, contract.class_hash));
+    let replaced = state_diff
+        .replaced_classes
+        .iter()
+        .map(|contract| (contract.contract_address, contract.class_hash));
+    for (contract_address, class_hash) in deployed.chain(replaced) {
+        writes
+            .contracts
+            .entry(contract_address)
+            .or_default()
+            .class_hash = Some(class_hash);
+    }
+
+    for class in &state_diff.declared_classes {
+        writes
+            .compiled_class_hashes
+            .insert(ClassHash(class.class_hash.0), class.compiled_class_hash);
+    }
+
+    writes.classes.extend(declared_classes);
+}
+
 pub fn simulate(
     mut execution_state: ExecutionState<'_>,
     transactions: Vec<Transaction>,
@@ -35,15 +220,13 @@ pub fn simulate(
 ) -> Result<Vec<TransactionSimulation>, TransactionExecutionError> {
     let block_number = execution_state.header.number;
 
-    let (mut state, block_context) = execution_state.starknet_state()?;
+    let (mut state, block_context) = BlockState::new(&mut execution_state)?;
 
     let mut simulations = Vec::with_capacity(transactions.len());
     for (transaction_idx, transaction) in transactions.into_iter().enumerate() {
         let _span = tracing::debug_span!("simulate", transaction_hash=%super::transaction::transaction_hash(&transaction), %block_number, %transaction_idx).entered();
 
         let transaction_type = transaction_type(&transaction);
-        let transaction_declared_deprecated_class_hash =
-            transaction_declared_deprecated_class(&transaction);
         let fee_type = &super::transaction::fee_type(&transaction);
         let gas_price: U256 = block_context.gas_prices.get_by_fee_type(fee_type).into();
         let unit = match fee_type {
@@ -51,28 +234,24 @@ pub fn simulate(
             blockifier::transaction::objects::FeeType::Eth => PriceUnit::Wei,
         };
 
-        let mut tx_state = CachedState::<_>::create_transactional(&mut state);
-        let tx_info = transaction
-            .execute(
-                &mut tx_state,
-                &block_context,
-                !skip_fee_charge,
-                !skip_validate,
-            )
-            .and_then(|mut tx_info| {
-                // skipping fee charge in .execute() means that the fee isn't calculated, do that explicitly
-                // some other cases, like having max_fee=0 also lead to not calculating fees
-                if tx_info.actual_fee.0 == 0 {
-                    tx_info.actual_fee = blockifier::fee::fee_utils::calculate_tx_fee(
-                        &tx_info.actual_resources,
-                        &block_context,
-                        fee_type,
-                    )?
-                };
-                Ok(tx_info)
-            });
-        let state_diff = to_state_diff(&mut tx_state, transaction_declared_deprecated_class_hash)?;
-        tx_state.commit();
+        let executed = state.execute(
+            transaction,
+            &block_context,
+            !skip_fee_charge,
+            !skip_validate,
+        )?;
+        let tx_info = executed.result.and_then(|mut tx_info| {
+            // skipping fee charge in .execute() means that the fee isn't calculated, do that explicitly
+            // some other cases, like having max_fee=0 also lead to not calculating fees
+            if tx_info.actual_fee.0 == 0 {
+                tx_info.actual_fee = blockifier::fee::fee_utils::calculate_tx_fee(
+                    &tx_info.actual_resources,
+                    &block_context,
+                    fee_type,
+                )?
+            };
+            Ok(tx_info)
+        });
 
         match tx_info {
             Ok(tx_info) => {
@@ -89,7 +268,12 @@ pub fn simulate(
                         overall_fee: tx_info.actual_fee.0.into(),
                         unit,
                     },
-                    trace: to_trace(transaction_type, tx_info, state_diff),
+                    trace: to_trace(
+                        transaction_type,
+                        tx_info,
+                        executed.state_diff,
+                        executed.access_list,
+                    ),
                 });
             }
             Err(error) => {
@@ -108,26 +292,23 @@ pub fn trace_one(
     charge_fee: bool,
     validate: bool,
 ) -> Result<TransactionTrace, TransactionExecutionError> {
-    let (mut state, block_context) = execution_state.starknet_state()?;
+    let (mut state, block_context) = BlockState::new(&mut execution_state)?;
 
     for (transaction_idx, tx) in transactions.into_iter().enumerate() {
         let _span = tracing::debug_span!("simulate", transaction_hash=%super::transaction::transaction_hash(&tx), %transaction_idx).entered();
 
         let hash = transaction_hash(&tx);
         let tx_type = transaction_type(&tx);
-        let tx_declared_deprecated_class_hash = transaction_declared_deprecated_class(&tx);
 
-        let mut tx_state = CachedState::<_>::create_transactional(&mut state);
-        let tx_info = tx
-            .execute(&mut tx_state, &block_context, charge_fee, validate)
+        let executed = state.execute(tx, &block_context, charge_fee, validate)?;
+        let tx_info = executed
+            .result
             .map_err(|e| TransactionExecutionError::ExecutionError {
                 transaction_index: transaction_idx,
                 error: e.to_string(),
             })?;
-        let state_diff = to_state_diff(&mut tx_state, tx_declared_deprecated_class_hash)?;
-        tx_state.commit();
 
-        let trace = to_trace(tx_type, tx_info, state_diff);
+        let trace = to_trace(tx_type, tx_info, executed.state_diff, executed.access_list);
         if hash == target_transaction_hash {
             return Ok(trace);
         }
@@ -145,7 +326,7 @@ pub fn trace_all(
     charge_fee: bool,
     validate: bool,
 ) -> Result<Vec<(TransactionHash, TransactionTrace)>, TransactionExecutionError> {
-    let (mut state, block_context) = execution_state.starknet_state()?;
+    let (mut state, block_context) = BlockState::new(&mut execution_state)?;
 
     let mut ret = Vec::with_capacity(transactions.len());
     for (transaction_idx, tx) in transactions.into_iter().enumerate() {
@@ -153,19 +334,16 @@ pub fn trace_all(
 
         let hash = transaction_hash(&tx);
         let tx_type = transaction_type(&tx);
-        let tx_declared_deprecated_class_hash = transaction_declared_deprecated_class(&tx);
 
-        let mut tx_state = CachedState::<_>::create_transactional(&mut state);
-        let tx_info = tx
-            .execute(&mut tx_state, &block_context, charge_fee, validate)
+        let executed = state.execute(tx, &block_context, charge_fee, validate)?;
+        let tx_info = executed
+            .result
             .map_err(|e| TransactionExecutionError::ExecutionError {
                 transaction_index: transaction_idx,
                 error: e.to_string(),
             })?;
-        let state_diff = to_state_diff(&mut tx_state, tx_declared_deprecated_class_hash)?;
-        tx_state.commit();
 
-        let trace = to_trace(tx_type, tx_info, state_diff);
+        let trace = to_trace(tx_type, tx_info, executed.state_diff, executed.access_list);
         ret.push((hash, trace));
     }
 
@@ -290,8 +468,9 @@ fn to_state_diff<S: blockifier::state::state_api::StateReader>(
 
 fn to_trace(
     transaction_type: TransactionType,
-    execution_info: blockifier::transaction::objects::TransactionExecutionInfo,
+    execution_info: TransactionExecutionInfo,
     state_diff: StateDiff,
+    access_list: Option<AccessList>,
 ) -> TransactionTrace {
     let validate_invocation = execution_info.validate_call_info.map(Into::into);
     let maybe_function_invocation = execution_info.execute_call_info.map(Into::into);
@@ -302,6 +481,7 @@ fn to_trace(
             validate_invocation,
             fee_transfer_invocation,
             state_diff,
+            access_list,
         }),
         TransactionType::DeployAccount => {
             TransactionTrace::DeployAccount(DeployAccountTransactionTrace {
@@ -309,6 +489,7 @@ fn to_trace(
                 constructor_invocation: maybe_function_invocation,
                 fee_transfer_invocation,
                 state_diff,
+                access_list,
             })
         }
         TransactionType::Invoke => TransactionTrace::Invoke(InvokeTransactionTrace {
@@ -320,10 +501,12 @@ fn to_trace(
             },
             fee_transfer_invocation,
             state_diff,
+            access_list,
         }),
         TransactionType::L1Handler => TransactionTrace::L1Handler(L1HandlerTransactionTrace {
             function_invocation: maybe_function_invocation,
             state_diff,
+            access_list,
         }),
     }
 }
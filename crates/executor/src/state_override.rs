@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use blockifier::execution::contract_class::ContractClass;
+use blockifier::state::{errors::StateError, state_api::StateReader};
+
+use pathfinder_common::{
+    CasmHash, ClassHash, ContractAddress, ContractNonce, StorageAddress, StorageValue,
+};
+use starknet_api::state::StorageKey;
+use starknet_api::StarknetApiError;
+
+use super::felt::{IntoFelt, IntoStarkFelt};
+
+/// State which takes precedence over the state being executed on.
+#[derive(Debug, Clone, Default)]
+pub struct StateOverride {
+    pub contracts: HashMap<ContractAddress, ContractOverride>,
+    /// Compiled definitions of classes which are not declared.
+    pub classes: HashMap<ClassHash, ContractClass>,
+    /// Compiled class hashes of Sierra classes which are not declared.
+    pub compiled_class_hashes: HashMap<ClassHash, CasmHash>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ContractOverride {
+    pub storage: HashMap<StorageAddress, StorageValue>,
+    pub nonce: Option<ContractNonce>,
+    pub class_hash: Option<ClassHash>,
+}
+
+impl StateOverride {
+    fn contract(
+        &self,
+        contract_address: starknet_api::core::ContractAddress,
+    ) -> Option<&ContractOverride> {
+        self.contracts.get(&ContractAddress::new_or_panic(
+            contract_address.0.key().into_felt(),
+        ))
+    }
+}
+
+pub(super) struct OverrideStateReader<S: StateReader> {
+    state: S,
+    state_override: Arc<StateOverride>,
+}
+
+impl<S: StateReader> OverrideStateReader<S> {
+    pub(super) fn new(state: S, state_override: Arc<StateOverride>) -> Self {
+        Self {
+            state,
+            state_override,
+        }
+    }
+}
+
+impl<S: StateReader> StateReader for OverrideStateReader<S> {
+    fn get_storage_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+        key: StorageKey,
+    ) -> blockifier::state::state_api::StateResult<starknet_api::hash::StarkFelt> {
+        let storage_key = StorageAddress::new(key.0.key().into_felt()).ok_or_else(|| {
+            StateError::StarknetApiError(StarknetApiError::OutOfRange {
+                string: "Storage key out of range".to_owned(),
+            })
+        })?;
+
+        match self
+            .state_override
+            .contract(contract_address)
+            .and_then(|contract| contract.storage.get(&storage_key))
+        {
+            Some(value) => Ok(value.0.into_starkfelt()),
+            None => self.state.get_storage_at(contract_address, key),
+        }
+    }
+
+    fn get_nonce_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+    ) -> blockifier::state::state_api::StateResult<starknet_api::core::Nonce> {
+        match self
+            .state_override
+            .contract(contract_address)
+            .and_then(|contract| contract.nonce)
+        {
+            Some(nonce) => Ok(starknet_api::core::Nonce(nonce.0.into_starkfelt())),
+            None => self.state.get_nonce_at(contract_address),
+        }
+    }
+
+    fn get_class_hash_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+    ) -> blockifier::state::state_api::StateResult<starknet_api::core::ClassHash> {
+        match self
+            .state_override
+            .contract(contract_address)
+            .and_then(|contract| contract.class_hash)
+        {
+            Some(class_hash) => Ok(starknet_api::core::ClassHash(class_hash.0.into_starkfelt())),
+            None => self.state.get_class_hash_at(contract_address),
+        }
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &starknet_api::core::ClassHash,
+    ) -> blockifier::state::state_api::StateResult<ContractClass> {
+        match self
+            .state_override
+            .classes
+            .get(&ClassHash(class_hash.0.into_felt()))
+        {
+            Some(class) => Ok(class.clone()),
+            None => self.state.get_compiled_contract_class(class_hash),
+        }
+    }
+
+    fn get_compiled_class_hash(
+        &mut self,
+        class_hash: starknet_api::core::ClassHash,
+    ) -> blockifier::state::state_api::StateResult<starknet_api::core::CompiledClassHash> {
+        match self
+            .state_override
+            .compiled_class_hashes
+            .get(&ClassHash(class_hash.0.into_felt()))
+        {
+            Some(casm_hash) => Ok(starknet_api::core::CompiledClassHash(
+                casm_hash.0.into_starkfelt(),
+            )),
+            None => self.state.get_compiled_class_hash(class_hash),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pathfinder_common::{
+        class_hash, contract_address, contract_nonce, storage_address, storage_value,
+    };
+
+    use crate::test_utils::DummyStateReader;
+
+    fn address(value: u8) -> starknet_api::core::ContractAddress {
+        starknet_api::core::ContractAddress(
+            starknet_api::core::PatriciaKey::try_from(starknet_api::hash::StarkFelt::from(value))
+                .unwrap(),
+        )
+    }
+
+    fn uut() -> OverrideStateReader<DummyStateReader> {
+        let state_override = StateOverride {
+            contracts: [(
+                contract_address!("0x2"),
+                ContractOverride {
+                    storage: [(storage_address!("0x3"), storage_value!("0x4"))].into(),
+                    nonce: Some(contract_nonce!("0x5")),
+                    class_hash: Some(class_hash!("0x6")),
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+
+        OverrideStateReader::new(DummyStateReader {}, Arc::new(state_override))
+    }
+
+    #[test]
+    fn storage() {
+        let mut uut = uut();
+        let key = |value: u8| {
+            StorageKey(
+                starknet_api::core::PatriciaKey::try_from(starknet_api::hash::StarkFelt::from(
+                    value,
+                ))
+                .unwrap(),
+            )
+        };
+
+        // Overridden slot.
+        let storage = uut.get_storage_at(address(2), key(3)).unwrap();
+        assert_eq!(storage, starknet_api::hash::StarkFelt::from(4u8));
+
+        // Other slots of the overridden contract are untouched.
+        let storage = uut.get_storage_at(address(2), key(4)).unwrap();
+        assert_eq!(storage, starknet_api::hash::StarkFelt::from(u32::MAX));
+
+        // Contract without overrides.
+        let storage = uut.get_storage_at(address(1), key(3)).unwrap();
+        assert_eq!(storage, starknet_api::hash::StarkFelt::from(u32::MAX));
+    }
+
+    #[test]
+    fn nonce_and_class_hash() {
+        let mut uut = uut();
+
+        assert_eq!(
+            uut.get_nonce_at(address(2)).unwrap(),
+            starknet_api::core::Nonce(starknet_api::hash::StarkFelt::from(5u8))
+        );
+        assert_eq!(
+            uut.get_class_hash_at(address(2)).unwrap(),
+            starknet_api::core::ClassHash(starknet_api::hash::StarkFelt::from(6u8))
+        );
+
+        assert_eq!(
+            uut.get_nonce_at(address(1)).unwrap(),
+            starknet_api::core::Nonce(starknet_api::hash::StarkFelt::from(u32::MAX))
+        );
+        assert_eq!(
+            uut.get_class_hash_at(address(1)).unwrap(),
+            starknet_api::core::ClassHash(starknet_api::hash::StarkFelt::from(u32::MAX))
+        );
+    }
+}
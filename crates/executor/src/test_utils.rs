@@ -0,0 +1,46 @@
+use blockifier::execution::contract_class::ContractClass;
+use blockifier::state::state_api::{StateReader, StateResult};
+use starknet_api::hash::StarkFelt;
+
+/// A state in which every storage slot, nonce, class hash and compiled class hash is `u32::MAX`.
+pub(crate) struct DummyStateReader {}
+
+impl StateReader for DummyStateReader {
+    fn get_storage_at(
+        &mut self,
+        _contract_address: starknet_api::core::ContractAddress,
+        _key: starknet_api::state::StorageKey,
+    ) -> StateResult<StarkFelt> {
+        Ok(StarkFelt::from(u32::MAX))
+    }
+
+    fn get_nonce_at(
+        &mut self,
+        _contract_address: starknet_api::core::ContractAddress,
+    ) -> StateResult<starknet_api::core::Nonce> {
+        Ok(starknet_api::core::Nonce(StarkFelt::from(u32::MAX)))
+    }
+
+    fn get_class_hash_at(
+        &mut self,
+        _contract_address: starknet_api::core::ContractAddress,
+    ) -> StateResult<starknet_api::core::ClassHash> {
+        Ok(starknet_api::core::ClassHash(StarkFelt::from(u32::MAX)))
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        _class_hash: &starknet_api::core::ClassHash,
+    ) -> StateResult<ContractClass> {
+        unimplemented!()
+    }
+
+    fn get_compiled_class_hash(
+        &mut self,
+        _class_hash: starknet_api::core::ClassHash,
+    ) -> StateResult<starknet_api::core::CompiledClassHash> {
+        Ok(starknet_api::core::CompiledClassHash(StarkFelt::from(
+            u32::MAX,
+        )))
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use blockifier::execution::call_info::OrderedL2ToL1Message;
 use pathfinder_common::{
@@ -50,6 +50,16 @@ pub enum TransactionTrace {
 }
 
 impl TransactionTrace {
+    /// The parts of the state the transaction accessed, if access lists were recorded.
+    pub fn access_list(&self) -> Option<&AccessList> {
+        match self {
+            TransactionTrace::Declare(trace) => trace.access_list.as_ref(),
+            TransactionTrace::DeployAccount(trace) => trace.access_list.as_ref(),
+            TransactionTrace::Invoke(trace) => trace.access_list.as_ref(),
+            TransactionTrace::L1Handler(trace) => trace.access_list.as_ref(),
+        }
+    }
+
     fn revert_reason(&self) -> Option<&str> {
         match self {
             TransactionTrace::Invoke(InvokeTransactionTrace {
@@ -66,6 +76,7 @@ pub struct DeclareTransactionTrace {
     pub validate_invocation: Option<FunctionInvocation>,
     pub fee_transfer_invocation: Option<FunctionInvocation>,
     pub state_diff: StateDiff,
+    pub access_list: Option<AccessList>,
 }
 
 #[derive(Debug)]
@@ -74,6 +85,7 @@ pub struct DeployAccountTransactionTrace {
     pub constructor_invocation: Option<FunctionInvocation>,
     pub fee_transfer_invocation: Option<FunctionInvocation>,
     pub state_diff: StateDiff,
+    pub access_list: Option<AccessList>,
 }
 
 #[derive(Debug)]
@@ -88,12 +100,14 @@ pub struct InvokeTransactionTrace {
     pub execute_invocation: ExecuteInvocation,
     pub fee_transfer_invocation: Option<FunctionInvocation>,
     pub state_diff: StateDiff,
+    pub access_list: Option<AccessList>,
 }
 
 #[derive(Debug)]
 pub struct L1HandlerTransactionTrace {
     pub function_invocation: Option<FunctionInvocation>,
     pub state_diff: StateDiff,
+    pub access_list: Option<AccessList>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -109,6 +123,14 @@ pub struct Event {
     pub keys: Vec<Felt>,
 }
 
+/// The invocation tree of a call. If the call failed, the tree ends where it failed.
+#[derive(Debug)]
+pub struct CallTrace {
+    pub invocation: FunctionInvocation,
+    /// Why the call failed, if it did.
+    pub revert_reason: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct FunctionInvocation {
     pub calldata: Vec<Felt>,
@@ -143,6 +165,44 @@ pub struct StateDiff {
     pub replaced_classes: Vec<ReplacedClass>,
 }
 
+/// The parts of the state read or written by a transaction.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AccessList {
+    pub storage_reads: BTreeMap<ContractAddress, BTreeSet<StorageAddress>>,
+    pub storage_writes: BTreeMap<ContractAddress, BTreeSet<StorageAddress>>,
+    /// Classes whose definitions were loaded.
+    pub classes: BTreeSet<ClassHash>,
+    /// Classes whose compiled class hashes were read.
+    pub compiled_class_hashes: BTreeSet<ClassHash>,
+    /// Contracts whose nonces were read or written.
+    pub nonces: BTreeSet<ContractAddress>,
+    /// Contracts whose class hashes were read.
+    pub contracts: BTreeSet<ContractAddress>,
+}
+
+impl AccessList {
+    /// Adds the accesses of `other`.
+    pub fn extend(&mut self, other: AccessList) {
+        for (contract_address, keys) in other.storage_reads {
+            self.storage_reads
+                .entry(contract_address)
+                .or_default()
+                .extend(keys);
+        }
+        for (contract_address, keys) in other.storage_writes {
+            self.storage_writes
+                .entry(contract_address)
+                .or_default()
+                .extend(keys);
+        }
+        self.classes.extend(other.classes);
+        self.compiled_class_hashes
+            .extend(other.compiled_class_hashes);
+        self.nonces.extend(other.nonces);
+        self.contracts.extend(other.contracts);
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct StorageDiff {
     pub key: StorageAddress,
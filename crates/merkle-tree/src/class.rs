@@ -1,14 +1,13 @@
-use std::collections::HashMap;
-
 use anyhow::Context;
 use pathfinder_common::{
     BlockNumber, ClassCommitment, ClassCommitmentLeafHash, ClassHash, SierraHash,
 };
 use pathfinder_crypto::Felt;
-use pathfinder_storage::{Node, Transaction};
+use pathfinder_storage::Transaction;
 
-use crate::tree::MerkleTree;
+use crate::tree::{MerkleTree, TrieUpdate};
 use pathfinder_common::hash::PoseidonHash;
+use pathfinder_common::trie::TrieNode;
 
 /// A [Patricia Merkle tree](MerkleTree) used to calculate commitments to Starknet's Sierra classes.
 ///
@@ -50,6 +49,28 @@ impl<'tx> ClassCommitmentTree<'tx> {
         self
     }
 
+    /// Generates a proof for the given `class`. See [`MerkleTree::get_proof`].
+    pub fn get_proof(
+        tx: &'tx Transaction<'tx>,
+        block: BlockNumber,
+        class: SierraHash,
+    ) -> anyhow::Result<Vec<TrieNode>> {
+        let root = tx
+            .class_root_index(block)
+            .context("Querying class root index")?;
+
+        let Some(root) = root else {
+            return Ok(Vec::new());
+        };
+
+        let storage = ClassStorage {
+            tx,
+            block: Some(block),
+        };
+
+        MerkleTree::<PoseidonHash, 251>::get_proof(root, &storage, class.view_bits())
+    }
+
     /// Adds a leaf node for a Sierra -> CASM commitment.
     ///
     /// Note that the leaf value is _not_ the Cairo hash, but a hashed value based on that.
@@ -61,12 +82,13 @@ impl<'tx> ClassCommitmentTree<'tx> {
     }
 
     /// Commits the changes and calculates the new node hashes. Returns the new commitment and
-    /// any potentially newly created nodes.
-    pub fn commit(self) -> anyhow::Result<(ClassCommitment, HashMap<Felt, Node>)> {
+    /// the [changes](TrieUpdate) to the tree: any potentially newly created nodes and the stored
+    /// nodes which were replaced.
+    pub fn commit(self) -> anyhow::Result<(ClassCommitment, TrieUpdate)> {
         let update = self.tree.commit(&self.storage)?;
 
         let commitment = ClassCommitment(update.root);
-        Ok((commitment, update.nodes))
+        Ok((commitment, update))
     }
 }
 
@@ -5,7 +5,7 @@
 
 use crate::{
     merkle_node::InternalNode,
-    tree::{MerkleTree, Visit},
+    tree::{MerkleTree, TrieUpdate, Visit},
 };
 use anyhow::Context;
 use bitvec::{prelude::Msb0, slice::BitSlice};
@@ -16,8 +16,7 @@ use pathfinder_common::{
     StorageCommitment, StorageValue,
 };
 use pathfinder_crypto::Felt;
-use pathfinder_storage::{Node, Transaction};
-use std::collections::HashMap;
+use pathfinder_storage::Transaction;
 use std::ops::ControlFlow;
 
 /// A [Patricia Merkle tree](MerkleTree) used to calculate commitments to a Starknet contract's storage.
@@ -99,11 +98,12 @@ impl<'tx> ContractsStorageTree<'tx> {
     }
 
     /// Commits the changes and calculates the new node hashes. Returns the new commitment and
-    /// any potentially newly created nodes.
-    pub fn commit(self) -> anyhow::Result<(ContractRoot, HashMap<Felt, Node>)> {
+    /// the [changes](TrieUpdate) to the tree: any potentially newly created nodes and the stored
+    /// nodes which were replaced.
+    pub fn commit(self) -> anyhow::Result<(ContractRoot, TrieUpdate)> {
         let update = self.tree.commit(&self.storage)?;
         let commitment = ContractRoot(update.root);
-        Ok((commitment, update.nodes))
+        Ok((commitment, update))
     }
 
     /// See [`MerkleTree::dfs`]
@@ -166,11 +166,12 @@ impl<'tx> StorageCommitmentTree<'tx> {
     }
 
     /// Commits the changes and calculates the new node hashes. Returns the new commitment and
-    /// any potentially newly created nodes.
-    pub fn commit(self) -> anyhow::Result<(StorageCommitment, HashMap<Felt, Node>)> {
+    /// the [changes](TrieUpdate) to the tree: any potentially newly created nodes and the stored
+    /// nodes which were replaced.
+    pub fn commit(self) -> anyhow::Result<(StorageCommitment, TrieUpdate)> {
         let update = self.tree.commit(&self.storage)?;
         let commitment = StorageCommitment(update.root);
-        Ok((commitment, update.nodes))
+        Ok((commitment, update))
     }
 
     /// Generates a proof for the given `key`. See [`MerkleTree::get_proof`].
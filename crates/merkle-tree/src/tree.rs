@@ -65,6 +65,8 @@ pub struct MerkleTree<H: FeltHash, const HEIGHT: usize> {
     /// If enables, node hashes are verified as they are resolved. This allows
     /// testing for database corruption.
     verify_hashes: bool,
+    /// Indices of the stored nodes which are no longer part of this tree.
+    nodes_removed: Vec<u64>,
 }
 
 /// The result of committing a [MerkleTree]. Contains the new root and any
@@ -74,6 +76,8 @@ pub struct TrieUpdate {
     /// New nodes added. Note that these may contain false positives if the
     /// mutations resulted in removing and then re-adding the same nodes within the tree.
     pub nodes: HashMap<Felt, Node>,
+    /// Indices of the previously stored nodes which are no longer reachable from the new root.
+    pub nodes_removed: Vec<u64>,
 }
 
 impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
@@ -84,6 +88,7 @@ impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
             _hasher: std::marker::PhantomData,
             verify_hashes: false,
             leaves: Default::default(),
+            nodes_removed: Default::default(),
         }
     }
 
@@ -98,6 +103,7 @@ impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
             _hasher: std::marker::PhantomData,
             verify_hashes: false,
             leaves: Default::default(),
+            nodes_removed: Default::default(),
         }
     }
 
@@ -110,9 +116,13 @@ impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
         // Go through tree, collect mutated nodes and calculate their hashes.
         let mut added = HashMap::new();
 
+        let mut removed_root = None;
+
         let root = if let Some(root) = self.root.as_ref() {
             match &mut *root.borrow_mut() {
                 InternalNode::Unresolved(idx) => {
+                    // The resolved root gets stored again as a new node.
+                    removed_root = Some(*idx);
                     let mut root = self.resolve(storage, *idx, 0).context("Resolving root")?;
                     self.commit_subtree(&mut root, &mut added, storage, BitVec::new())?
                 }
@@ -123,7 +133,14 @@ impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
             Felt::ZERO
         };
 
-        Ok(TrieUpdate { root, nodes: added })
+        let mut nodes_removed = std::mem::take(&mut self.nodes_removed);
+        nodes_removed.extend(removed_root);
+
+        Ok(TrieUpdate {
+            root,
+            nodes: added,
+            nodes_removed,
+        })
     }
 
     /// Persists any changes in this subtree to storage.
@@ -438,7 +455,11 @@ impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
 
     /// Returns the value stored at key, or `None` if it does not exist.
     #[cfg(test)]
-    fn get(&self, storage: &impl Storage, key: BitVec<u8, Msb0>) -> anyhow::Result<Option<Felt>> {
+    fn get(
+        &mut self,
+        storage: &impl Storage,
+        key: BitVec<u8, Msb0>,
+    ) -> anyhow::Result<Option<Felt>> {
         let node = self.traverse(storage, &key)?;
         let node = node.last();
 
@@ -571,8 +592,11 @@ impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
     /// The final node can __not__ be a [Binary](InternalNode::Binary) node since it would always be possible to continue
     /// on towards the destination. Nor can it be an [Unresolved](InternalNode::Unresolved) node since this would be
     /// resolved to check if we can travel further.
+    ///
+    /// Stored nodes along the path are resolved in place, and will therefore be stored again as
+    /// new nodes once committed.
     fn traverse(
-        &self,
+        &mut self,
         storage: &impl Storage,
         dst: &BitSlice<u8, Msb0>,
     ) -> anyhow::Result<Vec<Rc<RefCell<InternalNode>>>> {
@@ -591,6 +615,7 @@ impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
                 Unresolved(idx) => {
                     let node = self.resolve(storage, idx, height)?;
                     current.swap(&RefCell::new(node));
+                    self.nodes_removed.push(idx);
                     current
                 }
                 Binary(binary) => {
@@ -665,17 +690,20 @@ impl<H: FeltHash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
     ///
     /// This can occur when mutating the tree (e.g. deleting a child of a binary node), and is an illegal state
     /// (since edge nodes __must be__ maximal subtrees).
-    fn merge_edges(&self, storage: &impl Storage, parent: &mut EdgeNode) -> anyhow::Result<()> {
-        let resolved_child = match &*parent.child.borrow() {
-            InternalNode::Unresolved(hash) => {
-                self.resolve(storage, *hash, parent.height + parent.path.len())?
-            }
-            other => other.clone(),
+    fn merge_edges(&mut self, storage: &impl Storage, parent: &mut EdgeNode) -> anyhow::Result<()> {
+        let (resolved_child, stored_index) = match &*parent.child.borrow() {
+            InternalNode::Unresolved(hash) => (
+                self.resolve(storage, *hash, parent.height + parent.path.len())?,
+                Some(*hash),
+            ),
+            other => (other.clone(), None),
         };
 
         if let Some(child_edge) = resolved_child.as_edge().cloned() {
             parent.path.extend_from_bitslice(&child_edge.path);
             parent.child = child_edge.child;
+            // The child is merged into its parent and is no longer a node of its own.
+            self.nodes_removed.extend(stored_index);
         }
 
         Ok(())
@@ -801,6 +829,7 @@ pub enum Visit {
 mod tests {
     use pathfinder_common::hash::PedersenHash;
     use pathfinder_storage::StoredNode;
+    use std::collections::HashSet;
 
     use super::*;
     use bitvec::prelude::*;
@@ -835,14 +864,18 @@ mod tests {
         tree: MerkleTree<H, HEIGHT>,
         storage: &mut TestStorage,
     ) -> (Felt, u64) {
-        use pathfinder_storage::Child;
-
         for (key, value) in &tree.leaves {
             let key = Felt::from_bits(key).unwrap();
             storage.leaves.insert(key, *value);
         }
 
         let update = tree.commit(storage).unwrap();
+        persist(update, storage)
+    }
+
+    /// Persists the nodes of a [TrieUpdate] to storage.
+    fn persist(update: TrieUpdate, storage: &mut TestStorage) -> (Felt, u64) {
+        use pathfinder_storage::Child;
 
         let mut indices = HashMap::new();
         let mut idx = storage.nodes.len();
@@ -896,7 +929,7 @@ mod tests {
 
     #[test]
     fn get_empty() {
-        let uut = TestTree::empty();
+        let mut uut = TestTree::empty();
         let storage = TestStorage::default();
 
         let key = felt!("0x99cadc82").view_bits().to_bitvec();
@@ -1220,7 +1253,7 @@ mod tests {
 
             let root = commit_and_persist(uut, &mut storage);
 
-            let uut = TestTree::new(root.1);
+            let mut uut = TestTree::new(root.1);
 
             assert_eq!(uut.get(&storage, key0).unwrap(), Some(val0));
             assert_eq!(uut.get(&storage, key1).unwrap(), Some(val1));
@@ -1300,17 +1333,17 @@ mod tests {
             uut.set(&storage, key2.clone(), val2).unwrap();
             let root2 = commit_and_persist(uut, &mut storage);
 
-            let uut = TestTree::new(root0.1);
+            let mut uut = TestTree::new(root0.1);
             assert_eq!(uut.get(&storage, key0.clone()).unwrap(), Some(val0));
             assert_eq!(uut.get(&storage, key1.clone()).unwrap(), None);
             assert_eq!(uut.get(&storage, key2.clone()).unwrap(), None);
 
-            let uut = TestTree::new(root1.1);
+            let mut uut = TestTree::new(root1.1);
             assert_eq!(uut.get(&storage, key0.clone()).unwrap(), Some(val0));
             assert_eq!(uut.get(&storage, key1.clone()).unwrap(), Some(val1));
             assert_eq!(uut.get(&storage, key2.clone()).unwrap(), None);
 
-            let uut = TestTree::new(root2.1);
+            let mut uut = TestTree::new(root2.1);
             assert_eq!(uut.get(&storage, key0).unwrap(), Some(val0));
             assert_eq!(uut.get(&storage, key1).unwrap(), Some(val1));
             assert_eq!(uut.get(&storage, key2).unwrap(), Some(val2));
@@ -1339,17 +1372,17 @@ mod tests {
             uut.set(&storage, key2.clone(), val2).unwrap();
             let root2 = commit_and_persist(uut, &mut storage);
 
-            let uut = TestTree::new(root0.1);
+            let mut uut = TestTree::new(root0.1);
             assert_eq!(uut.get(&storage, key0.clone()).unwrap(), Some(val0));
             assert_eq!(uut.get(&storage, key1.clone()).unwrap(), None);
             assert_eq!(uut.get(&storage, key2.clone()).unwrap(), None);
 
-            let uut = TestTree::new(root1.1);
+            let mut uut = TestTree::new(root1.1);
             assert_eq!(uut.get(&storage, key0.clone()).unwrap(), Some(val0));
             assert_eq!(uut.get(&storage, key1.clone()).unwrap(), Some(val1));
             assert_eq!(uut.get(&storage, key2.clone()).unwrap(), None);
 
-            let uut = TestTree::new(root2.1);
+            let mut uut = TestTree::new(root2.1);
             assert_eq!(uut.get(&storage, key0).unwrap(), Some(val0));
             assert_eq!(uut.get(&storage, key1).unwrap(), None);
             assert_eq!(uut.get(&storage, key2).unwrap(), Some(val2));
@@ -1377,6 +1410,93 @@ mod tests {
         }
     }
 
+    mod nodes_removed {
+        use super::*;
+
+        /// Returns the indices of all stored nodes reachable from the given root.
+        fn reachable(storage: &TestStorage, root: u64) -> HashSet<u64> {
+            let mut reachable = HashSet::new();
+            let mut to_visit = vec![root];
+
+            while let Some(idx) = to_visit.pop() {
+                reachable.insert(idx);
+                match &storage.nodes.get(&idx).unwrap().1 {
+                    StoredNode::Binary { left, right } => to_visit.extend([*left, *right]),
+                    StoredNode::Edge { child, .. } => to_visit.push(*child),
+                    StoredNode::LeafBinary | StoredNode::LeafEdge { .. } => {}
+                }
+            }
+
+            reachable
+        }
+
+        fn setup() -> (TestStorage, u64, [BitVec<u8, Msb0>; 3]) {
+            let mut uut = TestTree::empty();
+            let mut storage = TestStorage::default();
+
+            let keys = [
+                felt!("0x99cadc82").view_bits().to_bitvec(),
+                felt!("0x99cadc83").view_bits().to_bitvec(),
+                felt!("0x1234").view_bits().to_bitvec(),
+            ];
+            for (i, key) in keys.iter().enumerate() {
+                uut.set(&storage, key.clone(), Felt::from_u64(i as u64 + 1))
+                    .unwrap();
+            }
+
+            let (_, root) = commit_and_persist(uut, &mut storage);
+
+            (storage, root, keys)
+        }
+
+        #[test]
+        fn empty_for_new_tree() {
+            let mut uut = TestTree::empty();
+            let storage = TestStorage::default();
+            uut.set(&storage, felt!("0x1").view_bits().to_bitvec(), felt!("0x2"))
+                .unwrap();
+
+            let update = uut.commit(&storage).unwrap();
+            assert!(update.nodes_removed.is_empty());
+        }
+
+        #[test]
+        fn are_no_longer_reachable() {
+            let (mut storage, root0, keys) = setup();
+            let old_nodes = reachable(&storage, root0);
+
+            let mut uut = TestTree::new(root0);
+            uut.set(&storage, keys[0].clone(), felt!("0x99")).unwrap();
+            uut.set(&storage, keys[1].clone(), Felt::ZERO).unwrap();
+
+            let update = uut.commit(&storage).unwrap();
+            let removed = update.nodes_removed.clone();
+            let (_, root1) = persist(update, &mut storage);
+
+            let new_nodes = reachable(&storage, root1);
+            assert!(removed.contains(&root0));
+            for idx in &removed {
+                assert!(old_nodes.contains(idx));
+                assert!(!new_nodes.contains(idx));
+            }
+
+            // Every old node is either still in use or was removed.
+            for idx in old_nodes {
+                assert!(new_nodes.contains(&idx) || removed.contains(&idx));
+            }
+        }
+
+        #[test]
+        fn unchanged_tree_replaces_only_the_root() {
+            let (storage, root, _) = setup();
+
+            let uut = TestTree::new(root);
+            let update = uut.commit(&storage).unwrap();
+
+            assert_eq!(update.nodes_removed, vec![root]);
+        }
+    }
+
     mod real_world {
         use super::*;
         use pathfinder_common::felt;
@@ -18,7 +18,11 @@ use pathfinder_common::consts::VERGEN_GIT_DESCRIBE;
 #[command(
     about = "A Starknet node implemented by Equilibrium Labs. Submit bug reports and issues at https://github.com/eqlabs/pathfinder."
 )]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(
         long,
         value_name = "DIR", 
@@ -41,14 +45,77 @@ struct Cli {
         long = "ethereum.url",
         long_help = r"This should point to the HTTP RPC endpoint of your Ethereum entry-point, typically a local Ethereum client or a hosted gateway service such as Infura or Cloudflare.
 
+A comma separated list of endpoints can be given, in which case requests fail over to the next healthy endpoint. See also ethereum.selection and ethereum.quorum.
+
 Examples:
     infura: https://goerli.infura.io/v3/<PROJECT_ID>
-    geth:   https://localhost:8545",
-        value_name = "HTTP(s) URL",
+    geth:   https://localhost:8545
+    a list: https://localhost:8545,https://goerli.infura.io/v3/<PROJECT_ID>",
+        value_name = "HTTP(s) URL LIST",
         value_hint = clap::ValueHint::Url,
+        value_delimiter = ',',
         env = "PATHFINDER_ETHEREUM_API_URL", 
+        required = true,
+    )]
+    ethereum_url: Vec<Url>,
+
+    #[arg(
+        long = "ethereum.selection",
+        long_help = r"How requests are spread over multiple ethereum.url endpoints. `failover` prefers the endpoints in the given order, while `round-robin` rotates through them. Either way, failing endpoints are avoided for a while.",
+        default_value = "failover",
+        env = "PATHFINDER_ETHEREUM_SELECTION"
+    )]
+    ethereum_selection: EthereumSelection,
+
+    #[arg(
+        long = "ethereum.quorum",
+        long_help = r"The number of ethereum.url endpoints which must agree on the Starknet state, and on the Ethereum blocks used to follow it and detect reorgs, before it is accepted. Without a quorum, these are accepted from any single endpoint.",
+        value_name = "N",
+        env = "PATHFINDER_ETHEREUM_QUORUM"
+    )]
+    ethereum_quorum: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "ethereum.websocket-url",
+        long_help = r"The optional websocket RPC endpoint of your Ethereum entry-point, typically a local Ethereum client.
+
+When set, Ethereum requests are sent over this connection, and L1 sync reacts to new Ethereum blocks and Starknet core contract logs as soon as they are published instead of polling. The connection is re-established automatically, and requests fall back to the HTTP endpoint while it is down. The ethereum.password is used for this endpoint too.
+
+Example:
+    geth:   ws://localhost:8546",
+        value_name = "WS URL",
+        value_hint = clap::ValueHint::Url,
+        env = "PATHFINDER_ETHEREUM_WEBSOCKET_URL"
+    )]
+    ethereum_websocket_url: Option<Url>,
+
+    #[arg(
+        long = "ethereum.finality",
+        long_help = r"The Ethereum block up to which Starknet state updates are followed. Following `latest` or `safe` blocks reports L1 acceptance sooner, at the cost of reverting it when Ethereum reorganizes.",
+        default_value = "finalized",
+        env = "PATHFINDER_ETHEREUM_FINALITY"
+    )]
+    ethereum_finality: EthereumFinality,
+
+    #[arg(
+        long = "ethereum.verify-checkpoint",
+        long_help = r"Verifies the Starknet state read from Ethereum using storage proofs, instead of trusting the Ethereum endpoint.
+
+The value is the hash of a trusted, finalized Ethereum block. Every Ethereum block whose state is read must descend from it, which is checked by recomputing each block hash and following parent hashes back to it, so it should be recent. Ethereum consensus is not verified, so blocks after the checkpoint are only known to descend from it. Requires ethereum.finality to be `finalized`.",
+        value_name = "BLOCK HASH",
+        env = "PATHFINDER_ETHEREUM_VERIFY_CHECKPOINT"
     )]
-    ethereum_url: Url,
+    ethereum_verify_checkpoint: Option<primitive_types::H256>,
+
+    #[arg(
+        long = "ethereum.index-messages-from",
+        long_help = r"The Ethereum block from which messages between L1 and L2 are indexed for pathfinder_getMessagesStatus.
+
+Messages are always indexed from the first finalized Ethereum block seen onwards. When set, the message logs of the earlier blocks down to this one are backfilled in the background. The Starknet core contract's deployment block covers all messages.",
+        value_name = "BLOCK NUMBER",
+        env = "PATHFINDER_ETHEREUM_INDEX_MESSAGES_FROM"
+    )]
+    ethereum_index_messages_from: Option<u64>,
 
     #[arg(
         long = "http-rpc",
@@ -98,6 +165,44 @@ Examples:
     )]
     monitor_address: Option<SocketAddr>,
 
+    #[arg(
+        long = "monitor.watch-addresses",
+        long_help = r"Comma separated list of contract addresses whose ETH and STRK fee token balances and nonces are exported as the `watched_fee_token_balance` and `watched_nonce` metrics after every synced block.
+
+Example:
+    0x1234,0x5678",
+        value_name = "ADDRESS LIST",
+        value_delimiter = ',',
+        value_parser = parse_contract_address,
+        env = "PATHFINDER_MONITOR_WATCH_ADDRESSES"
+    )]
+    monitor_watch_addresses: Vec<pathfinder_common::ContractAddress>,
+
+    #[arg(
+        long = "monitor.ready-max-blocks-behind",
+        long_help = "The `/ready` endpoint reports the node as not ready while its latest block is more than this many blocks behind the gateway's latest block",
+        value_name = "BLOCKS",
+        env = "PATHFINDER_MONITOR_READY_MAX_BLOCKS_BEHIND"
+    )]
+    monitor_ready_max_blocks_behind: Option<u64>,
+
+    #[arg(
+        long = "monitor.ready-max-head-age",
+        long_help = "The `/ready` endpoint reports the node as not ready if its latest block has not changed for this many seconds",
+        value_name = "SECONDS",
+        env = "PATHFINDER_MONITOR_READY_MAX_HEAD_AGE"
+    )]
+    monitor_ready_max_head_age: Option<std::num::NonZeroU64>,
+
+    #[arg(
+        long = "monitor.ready-require-rpc",
+        long_help = "The `/ready` endpoint reports the node as not ready unless the RPC server is serving",
+        env = "PATHFINDER_MONITOR_READY_REQUIRE_RPC",
+        default_value = "false",
+        action=ArgAction::Set
+    )]
+    monitor_ready_require_rpc: bool,
+
     #[clap(flatten)]
     network: NetworkCli,
 
@@ -180,6 +285,60 @@ This should only be enabled for debugging purposes as it adds substantial proces
     )]
     rpc_batch_concurrency_limit: NonZeroUsize,
 
+    #[arg(
+        long = "rpc.slow-request-threshold",
+        long_help = "RPC requests taking longer than this many milliseconds are logged as a warning, \
+            including their full params.",
+        env = "PATHFINDER_RPC_SLOW_REQUEST_THRESHOLD",
+        value_name = "MILLISECONDS"
+    )]
+    rpc_slow_request_threshold: Option<std::num::NonZeroU64>,
+
+    #[arg(
+        long = "rpc.rate-limit",
+        long_help = "The number of requests per second each client may make, excluding expensive \
+            methods. Requests above this limit are rejected with a JSON-RPC error. \
+            Clients are identified by their IP address, or by their API key if \
+            `--rpc.rate-limit-api-key-header` is set.",
+        env = "PATHFINDER_RPC_RATE_LIMIT",
+        value_name = "REQUESTS PER SECOND"
+    )]
+    rpc_rate_limit: Option<std::num::NonZeroU32>,
+
+    #[arg(
+        long = "rpc.rate-limit-expensive",
+        long_help = "The number of requests per second each client may make to expensive methods, \
+            which are the starknet_* and pathfinder_* methods that execute or trace transactions \
+            and starknet_getEvents.",
+        env = "PATHFINDER_RPC_RATE_LIMIT_EXPENSIVE",
+        value_name = "REQUESTS PER SECOND"
+    )]
+    rpc_rate_limit_expensive: Option<std::num::NonZeroU32>,
+
+    #[arg(
+        long = "rpc.rate-limit-api-key-header",
+        long_help = "Rate limit clients by the value of this header instead of their IP address. \
+            Clients which don't send the header are limited by IP address. \
+            Since clients choose their own key, this should only be used behind a proxy which \
+            authenticates the keys.",
+        env = "PATHFINDER_RPC_RATE_LIMIT_API_KEY_HEADER",
+        value_name = "HEADER"
+    )]
+    rpc_rate_limit_api_key_header: Option<http::HeaderName>,
+
+    #[arg(
+        long = "rpc.rate-limit-trust-forwarded-for",
+        long_help = "Rate limit clients by the last IP address of the X-Forwarded-For header \
+            instead of the address they connect from. \
+            Since clients can send the header themselves, this should only be enabled behind a \
+            proxy which appends the client's address to it.",
+        default_value = "false",
+        action = clap::ArgAction::Set,
+        env = "PATHFINDER_RPC_RATE_LIMIT_TRUST_FORWARDED_FOR",
+        value_name = "BOOL"
+    )]
+    rpc_rate_limit_trust_forwarded_for: bool,
+
     #[arg(
         long = "sync.enable",
         long_help = "Enable syncing the chain",
@@ -205,6 +364,132 @@ This should only be enabled for debugging purposes as it adds substantial proces
         env = "PATHFINDER_GATEWAY_API_KEY"
     )]
     gateway_api_key: Option<String>,
+
+    #[arg(
+        long = "storage.prune-state-tries",
+        long_help = r"Enable pruning of historical state trie data, keeping only the tries of the latest N blocks.
+
+Storage proofs for older blocks are no longer available once pruned. Only trie data replaced while this option is enabled is tracked for pruning. N must be at least 1000, the deepest reorg which is handled.",
+        value_name = "N_BLOCKS",
+        env = "PATHFINDER_STORAGE_PRUNE_STATE_TRIES"
+    )]
+    prune_state_tries: Option<std::num::NonZeroU64>,
+
+    #[arg(
+        long = "storage.prune-transactions",
+        long_help = r"Enable pruning of historical transactions, receipts and events, keeping only those of the latest N blocks.
+
+Block headers and state are kept. RPC methods return a DATA_PRUNED error for transaction data of older blocks. N must be at least 1000, the deepest reorg which is handled.",
+        value_name = "N_BLOCKS",
+        env = "PATHFINDER_STORAGE_PRUNE_TRANSACTIONS"
+    )]
+    prune_transactions: Option<std::num::NonZeroU64>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Export or import an offline database snapshot.
+    #[command(subcommand)]
+    Snapshot(SnapshotCommand),
+    /// Verifies the integrity of a database without syncing, and reports the first
+    /// inconsistent block.
+    VerifyDb(VerifyDbCommand),
+    /// Removes all blocks after the given block from a database, so that syncing resumes from
+    /// the block after it.
+    Rollback(RollbackCommand),
+}
+
+#[derive(clap::Args)]
+pub struct VerifyDbCommand {
+    #[arg(
+        long,
+        long_help = "The database file to verify. It is opened read-only, and must already be migrated to the latest schema version by running pathfinder.",
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub database: PathBuf,
+    #[arg(
+        long = "from-block",
+        long_help = "The block to start verifying from",
+        value_name = "BLOCK_NUMBER",
+        default_value = "0"
+    )]
+    pub from_block: u64,
+    #[arg(
+        long = "chain-id",
+        long_help = "The Starknet chain ID (e.g. SN_GOERLI) of a custom network's database. Known networks are detected from the database's genesis block.",
+        value_name = "CHAIN ID"
+    )]
+    pub chain_id: Option<String>,
+}
+
+#[derive(clap::Args)]
+pub struct RollbackCommand {
+    #[arg(
+        long,
+        long_help = "The database file to roll back",
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub database: PathBuf,
+    #[arg(
+        long = "to-block",
+        long_help = "The block to roll back to. This block is kept and becomes the new head of the database.",
+        value_name = "BLOCK_NUMBER"
+    )]
+    pub to_block: u64,
+}
+
+#[derive(clap::Subcommand)]
+pub enum SnapshotCommand {
+    /// Writes a compressed and checksummed snapshot of the database, split into chunks.
+    Export {
+        #[arg(
+            long,
+            long_help = "The database file to export",
+            value_name = "FILE",
+            value_hint = clap::ValueHint::FilePath
+        )]
+        database: PathBuf,
+        #[arg(
+            long,
+            long_help = "The directory to write the snapshot to",
+            value_name = "DIR",
+            value_hint = clap::ValueHint::DirPath
+        )]
+        output: PathBuf,
+        #[arg(
+            long,
+            long_help = "The block at which to take the snapshot. Defaults to the latest block.",
+            value_name = "BLOCK_NUMBER"
+        )]
+        block: Option<u64>,
+        #[arg(
+            long = "chunk-size",
+            long_help = "The uncompressed size of each snapshot chunk, in MiB",
+            value_name = "MiB",
+            default_value = "256"
+        )]
+        chunk_size: std::num::NonZeroU64,
+    },
+    /// Verifies a snapshot and imports it as a new database. An interrupted import resumes
+    /// from the last verified chunk.
+    Import {
+        #[arg(
+            long,
+            long_help = "The snapshot directory to import",
+            value_name = "DIR",
+            value_hint = clap::ValueHint::DirPath
+        )]
+        input: PathBuf,
+        #[arg(
+            long,
+            long_help = "The database file to create",
+            value_name = "FILE",
+            value_hint = clap::ValueHint::FilePath
+        )]
+        database: PathBuf,
+    },
 }
 
 #[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
@@ -227,6 +512,19 @@ impl Color {
     }
 }
 
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum EthereumFinality {
+    Latest,
+    Safe,
+    Finalized,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum EthereumSelection {
+    Failover,
+    RoundRobin,
+}
+
 #[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
 pub enum RpcVersion {
     V04,
@@ -415,6 +713,12 @@ fn parse_cors(inputs: Vec<String>) -> Result<Option<AllowedOrigins>, RpcCorsDoma
     )))
 }
 
+fn parse_contract_address(input: &str) -> Result<pathfinder_common::ContractAddress, String> {
+    pathfinder_crypto::Felt::from_hex_str(input)
+        .map(pathfinder_common::ContractAddress)
+        .map_err(|e| format!("Invalid contract address {input}: {e}"))
+}
+
 pub fn parse_cors_or_exit(input: Vec<String>) -> Option<AllowedOrigins> {
     use clap::error::ErrorKind;
 
@@ -448,6 +752,8 @@ pub struct Config {
     pub rpc_root_version: RpcVersion,
     pub websocket: WebsocketConfig,
     pub monitor_address: Option<SocketAddr>,
+    pub monitor_watch_addresses: Vec<pathfinder_common::ContractAddress>,
+    pub readiness_policy: pathfinder_lib::monitoring::ReadinessPolicy,
     pub network: Option<NetworkConfig>,
     pub execution_concurrency: Option<std::num::NonZeroU32>,
     pub sqlite_wal: JournalMode,
@@ -458,14 +764,24 @@ pub struct Config {
     pub debug: DebugConfig,
     pub verify_tree_hashes: bool,
     pub rpc_batch_concurrency_limit: NonZeroUsize,
+    pub rpc_slow_request_threshold: Option<std::time::Duration>,
+    pub rpc_rate_limits: pathfinder_rpc::middleware::rate_limit::RateLimitConfig,
     pub is_sync_enabled: bool,
     pub is_rpc_enabled: bool,
     pub gateway_api_key: Option<String>,
+    pub prune_state_tries: Option<std::num::NonZeroU64>,
+    pub prune_transactions: Option<std::num::NonZeroU64>,
 }
 
 pub struct Ethereum {
-    pub url: Url,
+    pub urls: Vec<Url>,
     pub password: Option<String>,
+    pub selection: EthereumSelection,
+    pub quorum: Option<std::num::NonZeroUsize>,
+    pub websocket_url: Option<Url>,
+    pub finality: EthereumFinality,
+    pub verify_checkpoint: Option<primitive_types::H256>,
+    pub index_messages_from: Option<u64>,
 }
 
 pub enum NetworkConfig {
@@ -598,23 +914,57 @@ impl DebugConfig {
     }
 }
 
-impl Config {
+/// What pathfinder was invoked to do.
+pub enum Invocation {
+    /// Run the node.
+    Node(Box<Config>),
+    Snapshot(SnapshotCommand),
+    VerifyDb(VerifyDbCommand),
+    Rollback(RollbackCommand),
+}
+
+impl Invocation {
     pub fn parse() -> Self {
         let cli = Cli::parse();
 
+        match cli.command {
+            Some(Command::Snapshot(command)) => Self::Snapshot(command),
+            Some(Command::VerifyDb(command)) => Self::VerifyDb(command),
+            Some(Command::Rollback(command)) => Self::Rollback(command),
+            None => Self::Node(Box::new(Config::from_cli(cli))),
+        }
+    }
+}
+
+impl Config {
+    fn from_cli(cli: Cli) -> Self {
         let network = NetworkConfig::from_components(cli.network);
 
         Config {
             data_directory: cli.data_directory,
             ethereum: Ethereum {
                 password: cli.ethereum_password,
-                url: cli.ethereum_url,
+                urls: cli.ethereum_url,
+                selection: cli.ethereum_selection,
+                quorum: cli.ethereum_quorum,
+                websocket_url: cli.ethereum_websocket_url,
+                finality: cli.ethereum_finality,
+                verify_checkpoint: cli.ethereum_verify_checkpoint,
+                index_messages_from: cli.ethereum_index_messages_from,
             },
             rpc_address: cli.rpc_address,
             rpc_cors_domains: parse_cors_or_exit(cli.rpc_cors_domains),
             rpc_root_version: cli.rpc_root_version,
             websocket: cli.websocket,
             monitor_address: cli.monitor_address,
+            monitor_watch_addresses: cli.monitor_watch_addresses,
+            readiness_policy: pathfinder_lib::monitoring::ReadinessPolicy {
+                max_blocks_behind: cli.monitor_ready_max_blocks_behind,
+                max_head_age: cli
+                    .monitor_ready_max_head_age
+                    .map(|secs| std::time::Duration::from_secs(secs.get())),
+                require_rpc: cli.monitor_ready_require_rpc,
+            },
             network,
             execution_concurrency: cli.execution_concurrency,
             sqlite_wal: match cli.sqlite_wal {
@@ -628,9 +978,20 @@ impl Config {
             debug: DebugConfig::parse(cli.debug),
             verify_tree_hashes: cli.verify_tree_node_data,
             rpc_batch_concurrency_limit: cli.rpc_batch_concurrency_limit,
+            rpc_slow_request_threshold: cli
+                .rpc_slow_request_threshold
+                .map(|ms| std::time::Duration::from_millis(ms.get())),
+            rpc_rate_limits: pathfinder_rpc::middleware::rate_limit::RateLimitConfig {
+                requests_per_second: cli.rpc_rate_limit,
+                expensive_requests_per_second: cli.rpc_rate_limit_expensive,
+                api_key_header: cli.rpc_rate_limit_api_key_header,
+                trust_forwarded_for: cli.rpc_rate_limit_trust_forwarded_for,
+            },
             is_sync_enabled: cli.is_sync_enabled,
             is_rpc_enabled: cli.is_rpc_enabled,
             gateway_api_key: cli.gateway_api_key,
+            prune_state_tries: cli.prune_state_tries,
+            prune_transactions: cli.prune_transactions,
         }
     }
 }
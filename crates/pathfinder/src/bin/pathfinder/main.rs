@@ -5,15 +5,15 @@ use metrics_exporter_prometheus::PrometheusBuilder;
 use mimalloc::MiMalloc;
 
 use pathfinder_common::{consts::VERGEN_GIT_DESCRIBE, BlockNumber, Chain, ChainId, EthereumChain};
-use pathfinder_ethereum::{EthereumApi, EthereumClient};
+use pathfinder_ethereum::{EndpointSelection, EthereumApi, EthereumClient, MultiEthereumClient};
 use pathfinder_lib::state::SyncContext;
 use pathfinder_lib::{
-    monitoring::{self},
+    monitoring::{self, status::NodeStatus},
     state,
 };
 use pathfinder_rpc::context::WebsocketContext;
 use pathfinder_rpc::SyncState;
-use pathfinder_storage::Storage;
+use pathfinder_storage::{Storage, TriePruneMode};
 use primitive_types::H160;
 use starknet_gateway_client::GatewayApi;
 use std::net::SocketAddr;
@@ -25,11 +25,19 @@ use tracing::info;
 use crate::config::NetworkConfig;
 
 mod config;
+mod rollback;
+mod snapshot;
 mod update;
+mod verify_db;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+/// How often the state tries are pruned when [TriePruneMode::Prune] is enabled.
+const TRIE_PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// How often transaction data is pruned when `--storage.prune-transactions` is set.
+const TRANSACTION_PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
 fn main() -> anyhow::Result<()> {
     tokio::runtime::Builder::new_multi_thread()
         .enable_all()
@@ -45,7 +53,21 @@ async fn async_main() -> anyhow::Result<()> {
         std::env::set_var("RUST_LOG", "pathfinder=info");
     }
 
-    let config = config::Config::parse();
+    let config = match config::Invocation::parse() {
+        config::Invocation::Node(config) => *config,
+        config::Invocation::Snapshot(command) => {
+            setup_tracing(config::Color::Auto, false);
+            return tokio::task::block_in_place(|| snapshot::run(command));
+        }
+        config::Invocation::VerifyDb(command) => {
+            setup_tracing(config::Color::Auto, false);
+            return tokio::task::block_in_place(|| verify_db::run(command));
+        }
+        config::Invocation::Rollback(command) => {
+            setup_tracing(config::Color::Auto, false);
+            return tokio::task::block_in_place(|| rollback::run(command));
+        }
+    };
 
     setup_tracing(config.color, config.debug.pretty_log);
 
@@ -66,10 +88,27 @@ async fn async_main() -> anyhow::Result<()> {
 
     // A readiness flag which is used to indicate that pathfinder is ready via monitoring.
     let readiness = Arc::new(AtomicBool::new(false));
+    // Sync progress which is served via monitoring.
+    let node_status = Arc::new(NodeStatus::default());
 
-    let ethereum = EthereumContext::setup(config.ethereum.url, config.ethereum.password)
-        .await
-        .context("Creating Ethereum context")?;
+    anyhow::ensure!(
+        config.ethereum.verify_checkpoint.is_none()
+            || config.ethereum.finality == config::EthereumFinality::Finalized,
+        "Verifying the Ethereum state requires following finalized Ethereum blocks"
+    );
+
+    let ethereum = EthereumContext::setup(
+        config.ethereum.urls,
+        config.ethereum.password.clone(),
+        config.ethereum.verify_checkpoint,
+        match config.ethereum.selection {
+            config::EthereumSelection::Failover => EndpointSelection::Failover,
+            config::EthereumSelection::RoundRobin => EndpointSelection::RoundRobin,
+        },
+        config.ethereum.quorum.map(|quorum| quorum.get()),
+    )
+    .await
+    .context("Creating Ethereum context")?;
 
     // Use the default starknet network if none was configured.
     let network = match config.network {
@@ -89,9 +128,15 @@ async fn async_main() -> anyhow::Result<()> {
             NetworkConfig::SepoliaIntegration => "integration-sepolia",
             NetworkConfig::Custom { .. } => "custom",
         };
-        spawn_monitoring(network_label, address, readiness.clone())
-            .await
-            .context("Starting monitoring task")?;
+        spawn_monitoring(
+            network_label,
+            address,
+            readiness.clone(),
+            node_status.clone(),
+            config.readiness_policy,
+        )
+        .await
+        .context("Starting monitoring task")?;
     }
 
     let pathfinder_context = PathfinderContext::configure_and_proxy_check(
@@ -105,8 +150,15 @@ async fn async_main() -> anyhow::Result<()> {
     verify_networks(pathfinder_context.network, ethereum.chain)?;
 
     // Setup and verify database
-    let storage_manager =
-        Storage::migrate(pathfinder_context.database.clone(), config.sqlite_wal).unwrap();
+    let trie_prune_mode = match config.prune_state_tries {
+        Some(num_blocks_kept) => TriePruneMode::Prune {
+            num_blocks_kept: num_blocks_kept.get(),
+        },
+        None => TriePruneMode::Archive,
+    };
+    let storage_manager = Storage::migrate(pathfinder_context.database.clone(), config.sqlite_wal)
+        .unwrap()
+        .with_trie_prune_mode(trie_prune_mode);
     let sync_storage = storage_manager
         // 5 is enough for normal sync operations, and then `available_parallelism` for
         // the rayon thread pool workers to use.
@@ -148,6 +200,16 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
         .context(
             r"Creating database connection pool for p2p
 
+Hint: This is usually caused by exceeding the file descriptor limit of your system.
+      Try increasing the file limit to using `ulimit` or similar tooling.",
+        )?;
+
+    let prune_storage = storage_manager
+        // One connection each for trie and transaction pruning.
+        .create_pool(NonZeroU32::new(2).unwrap())
+        .context(
+            r"Creating database connection pool for pruning
+
 Hint: This is usually caused by exceeding the file descriptor limit of your system.
       Try increasing the file limit to using `ulimit` or similar tooling.",
         )?;
@@ -184,6 +246,17 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
         context
     };
 
+    let context = match config.rpc_slow_request_threshold {
+        Some(threshold) => context.with_slow_request_threshold(threshold),
+        None => context,
+    };
+
+    let context = if config.rpc_rate_limits.is_enabled() {
+        context.with_rate_limits(config.rpc_rate_limits)
+    } else {
+        context
+    };
+
     let default_version = match config.rpc_root_version {
         config::RpcVersion::V04 => pathfinder_rpc::DefaultVersion::V04,
         config::RpcVersion::V05 => pathfinder_rpc::DefaultVersion::V05,
@@ -204,12 +277,31 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
     )
     .await?;
 
+    let ethereum_client = match config.ethereum.websocket_url {
+        Some(mut url) => {
+            if let Some(password) = &config.ethereum.password {
+                url.set_password(Some(password))
+                    .map_err(|_| anyhow::anyhow!("Setting Ethereum websocket password failed"))?;
+            }
+            ethereum
+                .client
+                .with_websocket(url, pathfinder_context.l1_core_address)
+        }
+        None => ethereum.client,
+    };
+
     let sync_context = SyncContext {
         storage: sync_storage,
-        ethereum: ethereum.client,
+        ethereum: ethereum_client,
         chain: pathfinder_context.network,
         chain_id: pathfinder_context.network_id,
         core_address: pathfinder_context.l1_core_address,
+        l1_finality: match config.ethereum.finality {
+            config::EthereumFinality::Latest => pathfinder_ethereum::L1Finality::Latest,
+            config::EthereumFinality::Safe => pathfinder_ethereum::L1Finality::Safe,
+            config::EthereumFinality::Finalized => pathfinder_ethereum::L1Finality::Finalized,
+        },
+        index_messages_from: config.ethereum.index_messages_from,
         sequencer,
         state: sync_state.clone(),
         head_poll_interval: config.poll_interval,
@@ -217,9 +309,11 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
         // Currently p2p does not perform block hash and state commitment verification if p2p header lacks state commitment
         block_validation_mode: state::l2::BlockValidationMode::Strict,
         websocket_txs: rpc_server.get_topic_broadcasters().cloned(),
-        block_cache_size: 1_000,
+        block_cache_size: state::MAX_REORG_DEPTH as usize,
         restart_delay: config.debug.restart_delay,
         verify_tree_hashes: config.verify_tree_hashes,
+        watch_addresses: config.monitor_watch_addresses,
+        node_status: node_status.clone(),
     };
 
     let sync_handle = if config.is_sync_enabled {
@@ -234,13 +328,45 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
             .spawn()
             .context("Starting the RPC server")?;
         info!("📡 HTTP-RPC server started on: {}", local_addr);
-        rpc_handle
+        node_status.set_rpc_serving(true);
+
+        // Readiness requires RPC to be serving, so this must be cleared once the server stops.
+        let node_status = node_status.clone();
+        tokio::spawn(async move {
+            let result = rpc_handle.await;
+            node_status.set_rpc_serving(false);
+            result
+        })
     } else {
         tokio::spawn(std::future::pending())
     };
 
     let update_handle = tokio::spawn(update::poll_github_for_releases());
 
+    let prune_handle = match trie_prune_mode {
+        TriePruneMode::Prune { num_blocks_kept } => {
+            info!(%num_blocks_kept, "State trie pruning enabled");
+            tokio::spawn(state::prune_tries(
+                prune_storage.clone(),
+                num_blocks_kept,
+                TRIE_PRUNE_INTERVAL,
+            ))
+        }
+        TriePruneMode::Archive => tokio::spawn(std::future::pending()),
+    };
+
+    let transaction_prune_handle = match config.prune_transactions {
+        Some(num_blocks_kept) => {
+            info!(%num_blocks_kept, "Transaction pruning enabled");
+            tokio::spawn(state::prune_transactions(
+                prune_storage,
+                num_blocks_kept.get(),
+                TRANSACTION_PRUNE_INTERVAL,
+            ))
+        }
+        None => tokio::spawn(std::future::pending()),
+    };
+
     // We are now ready.
     readiness.store(true, std::sync::atomic::Ordering::Relaxed);
 
@@ -270,6 +396,18 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
                 Err(err) => tracing::error!(error=%err, "P2P process ended unexpectedly"),
             }
         }
+        result = prune_handle => {
+            match result {
+                Ok(task_result) => tracing::error!("Trie pruning process ended unexpected with: {:?}", task_result),
+                Err(err) => tracing::error!(error=%err, "Trie pruning process ended unexpectedly"),
+            }
+        }
+        result = transaction_prune_handle => {
+            match result {
+                Ok(task_result) => tracing::error!("Transaction pruning process ended unexpected with: {:?}", task_result),
+                Err(err) => tracing::error!(error=%err, "Transaction pruning process ended unexpectedly"),
+            }
+        }
     }
 
     anyhow::bail!("Unexpected shutdown");
@@ -411,6 +549,8 @@ async fn spawn_monitoring(
     network: &str,
     address: SocketAddr,
     readiness: Arc<AtomicBool>,
+    node_status: Arc<NodeStatus>,
+    readiness_policy: monitoring::ReadinessPolicy,
 ) -> anyhow::Result<tokio::task::JoinHandle<()>> {
     let prometheus_handle = PrometheusBuilder::new()
         .add_global_label("network", network)
@@ -419,30 +559,77 @@ async fn spawn_monitoring(
 
     metrics::gauge!("pathfinder_build_info", 1.0, "version" => VERGEN_GIT_DESCRIBE);
 
-    let handle = monitoring::spawn_server(address, readiness, prometheus_handle).await;
+    let handle = monitoring::spawn_server(
+        address,
+        readiness,
+        prometheus_handle,
+        node_status,
+        readiness_policy,
+    )
+    .await;
     Ok(handle)
 }
 
 /// Convenience bundle for an Ethereum transport and chain.
 struct EthereumContext {
-    client: EthereumClient,
+    client: MultiEthereumClient,
     chain: EthereumChain,
 }
 
 impl EthereumContext {
     /// Configure an [EthereumContext]'s transport and read the chain ID using it.
-    async fn setup(url: reqwest::Url, password: Option<String>) -> anyhow::Result<Self> {
-        let client = if let Some(password) = password.as_ref() {
-            EthereumClient::with_password(url, password).context("Creating Ethereum client")?
-        } else {
-            EthereumClient::new(url).context("Creating Ethereum client")?
-        };
+    ///
+    /// Every endpoint must be on the same chain. If a `checkpoint` is given, every endpoint
+    /// verifies the Starknet state against it.
+    async fn setup(
+        urls: Vec<reqwest::Url>,
+        password: Option<String>,
+        checkpoint: Option<primitive_types::H256>,
+        selection: EndpointSelection,
+        quorum: Option<usize>,
+    ) -> anyhow::Result<Self> {
+        let mut clients = Vec::with_capacity(urls.len());
+        let mut chain = None;
+
+        for url in urls {
+            let client = if let Some(password) = password.as_ref() {
+                EthereumClient::with_password(url.clone(), password)
+                    .context("Creating Ethereum client")?
+            } else {
+                EthereumClient::new(url.clone()).context("Creating Ethereum client")?
+            };
 
-        let chain = client.get_chain().await.context(
-            r"Determining Ethereum chain.
+            let endpoint_chain = client.get_chain().await.with_context(|| {
+                format!(
+                    r"Determining Ethereum chain of {}.
                             
 Hint: Make sure the provided ethereum.url and ethereum.password are good.",
-        )?;
+                    url.host_str().unwrap_or_default()
+                )
+            })?;
+
+            match chain {
+                None => chain = Some(endpoint_chain),
+                Some(chain) => anyhow::ensure!(
+                    chain == endpoint_chain,
+                    "Ethereum endpoints are on different chains: {chain:?} and {endpoint_chain:?}"
+                ),
+            }
+
+            let client = match checkpoint {
+                Some(checkpoint) => client
+                    .with_verification(checkpoint)
+                    .await
+                    .context("Verifying Ethereum checkpoint")?,
+                None => client,
+            };
+
+            clients.push(client);
+        }
+
+        let chain = chain.context("At least one Ethereum endpoint is required")?;
+        let client = MultiEthereumClient::new(clients, selection, quorum)
+            .context("Configuring Ethereum endpoints")?;
 
         Ok(Self { client, chain })
     }
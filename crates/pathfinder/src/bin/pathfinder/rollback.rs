@@ -0,0 +1,78 @@
+//! Offline database rollback to an earlier block.
+
+use std::num::NonZeroU32;
+
+use anyhow::Context;
+use pathfinder_common::BlockNumber;
+use pathfinder_storage::{BlockId, JournalMode, Storage, TransactionBehavior};
+
+use crate::config::RollbackCommand;
+
+/// Removes all blocks after the requested block in a single database transaction.
+pub fn run(command: RollbackCommand) -> anyhow::Result<()> {
+    let target = BlockNumber::new(command.to_block).context("Block number is out of range")?;
+
+    let storage = Storage::migrate(command.database.clone(), JournalMode::WAL)
+        .context("Migrating database")?
+        .create_pool(NonZeroU32::new(1).unwrap())
+        .context("Creating database connection pool")?;
+    let mut db = storage
+        .connection()
+        .context("Opening database connection")?;
+    let tx = db
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .context("Creating database transaction")?;
+
+    let (latest, _) = tx
+        .block_id(BlockId::Latest)
+        .context("Querying latest block")?
+        .context("Database is empty")?;
+    anyhow::ensure!(
+        target <= latest,
+        "Requested block {target} is newer than the database head {latest}"
+    );
+    if target == latest {
+        tracing::info!(block=%latest, "Database head is already at the requested block");
+        return Ok(());
+    }
+
+    if let Some(horizon) = tx
+        .trie_prune_horizon()
+        .context("Querying trie prune horizon")?
+    {
+        anyhow::ensure!(
+            target >= horizon,
+            "The state tries of block {target} have been pruned, the oldest block available is {horizon}"
+        );
+    }
+
+    let target_header = tx
+        .block_header(target.into())
+        .context("Querying target block header")?
+        .context("Target block is missing")?;
+
+    tracing::info!(from=%latest, to=%target, database=%command.database.display(), "Rolling back database");
+    tx.purge_blocks_from(target + 1)
+        .context("Removing newer blocks")?;
+
+    // Syncing resumes from the new head's tries, so make sure these are intact before committing.
+    let state_commitment = tx
+        .state_commitment_from_tries(target)
+        .context("Calculating state commitment")?;
+    anyhow::ensure!(
+        state_commitment == target_header.state_commitment,
+        "State commitment {} of block {target} does not match the stored state tries' {state_commitment}",
+        target_header.state_commitment
+    );
+
+    tx.commit().context("Committing database transaction")?;
+
+    tracing::info!(
+        previous_head=%latest,
+        block_number=%target,
+        block_hash=%target_header.hash,
+        "Database rolled back"
+    );
+
+    Ok(())
+}
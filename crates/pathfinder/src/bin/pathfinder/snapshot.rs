@@ -0,0 +1,50 @@
+//! Offline database snapshot export and import.
+
+use anyhow::Context;
+use pathfinder_common::BlockNumber;
+use pathfinder_storage::snapshot;
+
+use crate::config::SnapshotCommand;
+
+/// Runs the snapshot command to completion.
+pub fn run(command: SnapshotCommand) -> anyhow::Result<()> {
+    match command {
+        SnapshotCommand::Export {
+            database,
+            output,
+            block,
+            chunk_size,
+        } => {
+            let block = block
+                .map(|block| BlockNumber::new(block).context("Block number is out of range"))
+                .transpose()?;
+            let chunk_size = chunk_size
+                .get()
+                .checked_mul(1024 * 1024)
+                .context("Chunk size is too large")?;
+
+            let manifest = snapshot::export(&database, &output, block, chunk_size)
+                .context("Exporting snapshot")?;
+
+            tracing::info!(
+                block_number=%manifest.block_number,
+                block_hash=%manifest.block_hash,
+                chunks=%manifest.chunks.len(),
+                output=%output.display(),
+                "Snapshot exported"
+            );
+        }
+        SnapshotCommand::Import { input, database } => {
+            let manifest = snapshot::import(&input, &database).context("Importing snapshot")?;
+
+            tracing::info!(
+                block_number=%manifest.block_number,
+                block_hash=%manifest.block_hash,
+                database=%database.display(),
+                "Snapshot imported"
+            );
+        }
+    }
+
+    Ok(())
+}
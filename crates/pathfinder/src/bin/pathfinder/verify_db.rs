@@ -0,0 +1,90 @@
+//! Offline database integrity verification.
+
+use std::num::NonZeroU32;
+
+use anyhow::Context;
+use pathfinder_common::{BlockNumber, Chain, ChainId};
+use pathfinder_crypto::Felt;
+use pathfinder_lib::state::verify::verify_database;
+use pathfinder_storage::{BlockId, Storage};
+
+use crate::config::VerifyDbCommand;
+
+/// Verifies the database and fails with the first inconsistent block.
+pub fn run(command: VerifyDbCommand) -> anyhow::Result<()> {
+    let from = BlockNumber::new(command.from_block).context("Block number is out of range")?;
+
+    let storage = Storage::open_read_only(command.database.clone())
+        .context("Opening database")?
+        .create_pool(NonZeroU32::new(1).unwrap())
+        .context("Creating database connection pool")?;
+
+    let (chain, chain_id) = detect_chain(&storage, command.chain_id)?;
+    tracing::info!(database=%command.database.display(), %chain, "Verifying database");
+
+    let inconsistency = verify_database(&storage, chain, chain_id, from, |block| {
+        if block.get() % 1000 == 0 {
+            tracing::info!(%block, "Verified");
+        }
+    })?;
+
+    match inconsistency {
+        None => {
+            tracing::info!("Database is consistent");
+            Ok(())
+        }
+        Some(inconsistency) => {
+            for problem in &inconsistency.problems {
+                tracing::error!(block=%inconsistency.block, %problem, "Inconsistency found");
+            }
+            Err(anyhow::anyhow!(inconsistency))
+        }
+    }
+}
+
+/// Identifies the chain from the database's genesis block.
+fn detect_chain(storage: &Storage, chain_id: Option<String>) -> anyhow::Result<(Chain, ChainId)> {
+    use pathfinder_common::consts::{
+        GOERLI_INTEGRATION_GENESIS_HASH, GOERLI_TESTNET_GENESIS_HASH, MAINNET_GENESIS_HASH,
+        SEPOLIA_INTEGRATION_GENESIS_HASH, SEPOLIA_TESTNET_GENESIS_HASH,
+    };
+
+    let genesis = storage
+        .connection()?
+        .transaction()?
+        .block_id(BlockId::Number(BlockNumber::GENESIS))
+        .context("Querying genesis block")?
+        .map(|(_, hash)| hash);
+
+    let known = match genesis {
+        Some(MAINNET_GENESIS_HASH) => Some((Chain::Mainnet, ChainId::MAINNET)),
+        Some(GOERLI_TESTNET_GENESIS_HASH) => Some((Chain::GoerliTestnet, ChainId::GOERLI_TESTNET)),
+        Some(GOERLI_INTEGRATION_GENESIS_HASH) => {
+            Some((Chain::GoerliIntegration, ChainId::GOERLI_INTEGRATION))
+        }
+        Some(SEPOLIA_TESTNET_GENESIS_HASH) => {
+            Some((Chain::SepoliaTestnet, ChainId::SEPOLIA_TESTNET))
+        }
+        Some(SEPOLIA_INTEGRATION_GENESIS_HASH) => {
+            Some((Chain::SepoliaIntegration, ChainId::SEPOLIA_INTEGRATION))
+        }
+        _ => None,
+    };
+
+    match (known, chain_id) {
+        (Some(known), None) => Ok(known),
+        (Some((chain, _)), Some(_)) => {
+            anyhow::bail!(
+                "--chain-id may only be used with a custom network, but this is a {chain} database"
+            )
+        }
+        (None, Some(chain_id)) => {
+            let chain_id =
+                ChainId(Felt::from_be_slice(chain_id.as_bytes()).context("Parsing chain ID")?);
+            Ok((Chain::Custom, chain_id))
+        }
+        (None, None) => anyhow::bail!(
+            "The database is not from a known network, please specify its chain ID using --chain-id"
+        ),
+    }
+}
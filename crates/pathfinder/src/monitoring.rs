@@ -3,13 +3,54 @@ use std::sync::atomic::AtomicBool;
 use metrics_exporter_prometheus::PrometheusHandle;
 use warp::Filter;
 
+pub mod status;
+pub mod watchlist;
+
+use status::{NodeStatus, Status};
+
+/// The conditions under which the node reports itself as ready, in addition to having started.
+#[derive(Debug, Default, Clone)]
+pub struct ReadinessPolicy {
+    /// The maximum number of blocks the local head may be behind the gateway head.
+    pub max_blocks_behind: Option<u64>,
+    /// The maximum time since the local head last changed.
+    pub max_head_age: Option<std::time::Duration>,
+    /// Whether the RPC server must be serving.
+    pub require_rpc: bool,
+}
+
+impl ReadinessPolicy {
+    fn is_satisfied(&self, status: &Status) -> bool {
+        let behind = match self.max_blocks_behind {
+            Some(max) => status.blocks_behind.is_some_and(|behind| behind <= max),
+            None => true,
+        };
+        let fresh = match self.max_head_age {
+            Some(max) => status
+                .local_head_updated
+                .is_some_and(|updated| updated.elapsed() <= max),
+            None => true,
+        };
+        let rpc = !self.require_rpc || status.rpc_serving;
+
+        behind && fresh && rpc
+    }
+}
+
 /// Spawns a server which hosts a `/health` endpoint.
 pub async fn spawn_server(
     addr: impl Into<std::net::SocketAddr> + 'static,
     readiness: std::sync::Arc<AtomicBool>,
     prometheus_handle: PrometheusHandle,
+    node_status: std::sync::Arc<NodeStatus>,
+    readiness_policy: ReadinessPolicy,
 ) -> tokio::task::JoinHandle<()> {
-    let server = warp::serve(routes(readiness, prometheus_handle));
+    let server = warp::serve(routes(
+        readiness,
+        prometheus_handle,
+        node_status,
+        readiness_policy,
+    ));
     let server = server.bind(addr);
 
     tokio::spawn(server)
@@ -18,10 +59,17 @@ pub async fn spawn_server(
 fn routes(
     readiness: std::sync::Arc<AtomicBool>,
     prometheus_handle: PrometheusHandle,
+    node_status: std::sync::Arc<NodeStatus>,
+    readiness_policy: ReadinessPolicy,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     health_route()
-        .or(ready_route(readiness))
+        .or(ready_route(
+            readiness,
+            node_status.clone(),
+            readiness_policy,
+        ))
         .or(metrics_route(prometheus_handle))
+        .or(status_route(node_status))
 }
 
 /// Always returns `Ok(200)` at `/health`.
@@ -29,19 +77,20 @@ fn health_route() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Re
     warp::get().and(warp::path!("health")).map(warp::reply)
 }
 
-/// Returns `Ok` if `readiness == true`, or `SERVICE_UNAVAILABLE` otherwise.
+/// Returns `Ok` if `readiness == true` and the [ReadinessPolicy] is satisfied, or
+/// `SERVICE_UNAVAILABLE` otherwise.
 fn ready_route(
     readiness: std::sync::Arc<AtomicBool>,
+    node_status: std::sync::Arc<NodeStatus>,
+    policy: ReadinessPolicy,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-    warp::get()
-        .and(warp::path!("ready"))
-        .map(move || -> std::sync::Arc<AtomicBool> { readiness.clone() })
-        .and_then(|readiness: std::sync::Arc<AtomicBool>| async move {
-            match readiness.load(std::sync::atomic::Ordering::Relaxed) {
-                true => Ok::<_, std::convert::Infallible>(warp::http::StatusCode::OK),
-                false => Ok(warp::http::StatusCode::SERVICE_UNAVAILABLE),
-            }
-        })
+    warp::get().and(warp::path!("ready")).map(move || {
+        let started = readiness.load(std::sync::atomic::Ordering::Relaxed);
+        match started && policy.is_satisfied(&node_status.snapshot()) {
+            true => warp::http::StatusCode::OK,
+            false => warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        }
+    })
 }
 
 /// Returns Prometheus merics snapshot at `/metrics`.
@@ -56,6 +105,15 @@ fn metrics_route(
         })
 }
 
+/// Returns the node's sync progress as JSON at `/status`.
+fn status_route(
+    node_status: std::sync::Arc<NodeStatus>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("status"))
+        .map(move || warp::reply::json(&node_status.snapshot()))
+}
+
 #[cfg(test)]
 mod tests {
     use metrics_exporter_prometheus::PrometheusBuilder;
@@ -67,7 +125,7 @@ mod tests {
         let recorder = PrometheusBuilder::new().build_recorder();
         let handle = recorder.handle();
         let readiness = Arc::new(AtomicBool::new(false));
-        let filter = super::routes(readiness, handle);
+        let filter = super::routes(readiness, handle, Default::default(), Default::default());
         let response = warp::test::request().path("/health").reply(&filter).await;
 
         assert_eq!(response.status(), http::StatusCode::OK);
@@ -78,7 +136,12 @@ mod tests {
         let recorder = PrometheusBuilder::new().build_recorder();
         let handle = recorder.handle();
         let readiness = Arc::new(AtomicBool::new(false));
-        let filter = super::routes(readiness.clone(), handle);
+        let filter = super::routes(
+            readiness.clone(),
+            handle,
+            Default::default(),
+            Default::default(),
+        );
         let response = warp::test::request().path("/ready").reply(&filter).await;
         assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
 
@@ -87,6 +150,60 @@ mod tests {
         assert_eq!(response.status(), http::StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn ready_policy() {
+        use pathfinder_common::BlockNumber;
+
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let readiness = Arc::new(AtomicBool::new(true));
+        let node_status = Arc::new(super::NodeStatus::default());
+        let policy = super::ReadinessPolicy {
+            max_blocks_behind: Some(2),
+            max_head_age: Some(std::time::Duration::from_secs(60)),
+            require_rpc: true,
+        };
+        let filter = super::routes(readiness, handle, node_status.clone(), policy);
+
+        // Nothing is known yet.
+        let response = warp::test::request().path("/ready").reply(&filter).await;
+        assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+        node_status.set_gateway_head(BlockNumber::new_or_panic(10));
+        node_status.set_local_head(Some(BlockNumber::new_or_panic(5)));
+        node_status.set_rpc_serving(true);
+        let response = warp::test::request().path("/ready").reply(&filter).await;
+        assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+        node_status.set_local_head(Some(BlockNumber::new_or_panic(8)));
+        let response = warp::test::request().path("/ready").reply(&filter).await;
+        assert_eq!(response.status(), http::StatusCode::OK);
+
+        node_status.set_rpc_serving(false);
+        let response = warp::test::request().path("/ready").reply(&filter).await;
+        assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn status() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let readiness = Arc::new(AtomicBool::new(false));
+        let node_status = Arc::new(super::NodeStatus::default());
+        node_status.set_gateway_head(pathfinder_common::BlockNumber::new_or_panic(5));
+        node_status.set_local_head(Some(pathfinder_common::BlockNumber::new_or_panic(3)));
+
+        let filter = super::routes(readiness, handle, node_status, Default::default());
+        let response = warp::test::request().path("/status").reply(&filter).await;
+
+        assert_eq!(response.status(), http::StatusCode::OK);
+        let status: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(status["local_head"], 3);
+        assert_eq!(status["gateway_head"], 5);
+        assert_eq!(status["blocks_behind"], 2);
+        assert_eq!(status["reorg_count"], 0);
+    }
+
     #[tokio::test]
     async fn metrics() {
         use pathfinder_common::test_utils::metrics::ScopedRecorderGuard;
@@ -102,7 +219,12 @@ mod tests {
         counter.increment(123);
 
         let readiness = Arc::new(AtomicBool::new(false));
-        let filter = super::routes(readiness.clone(), handle);
+        let filter = super::routes(
+            readiness.clone(),
+            handle,
+            Default::default(),
+            Default::default(),
+        );
         let response = warp::test::request().path("/metrics").reply(&filter).await;
 
         assert_eq!(response.status(), http::StatusCode::OK);
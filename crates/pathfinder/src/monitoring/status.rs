@@ -0,0 +1,185 @@
+//! Sync progress of the node, served as JSON at `/status` and exported as metrics.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use pathfinder_common::BlockNumber;
+
+/// Shared between the sync tasks, which record their progress, and the monitoring server.
+#[derive(Debug, Default)]
+pub struct NodeStatus(Mutex<Status>);
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize)]
+pub struct Status {
+    /// The latest block stored locally.
+    pub local_head: Option<BlockNumber>,
+    /// The latest block known to the gateway.
+    pub gateway_head: Option<BlockNumber>,
+    /// How many blocks the local head is behind the gateway head.
+    pub blocks_behind: Option<u64>,
+    /// The latest block accepted on L1.
+    pub l1_accepted: Option<BlockNumber>,
+    /// Seconds since the timestamp of the latest pending block.
+    pub pending_block_age: Option<u64>,
+    pub reorg_count: u64,
+    /// The number of blocks reverted by the latest reorg.
+    pub last_reorg_depth: Option<u64>,
+    pub last_block_timings: Option<BlockTimings>,
+    pub rpc_serving: bool,
+    /// When the local head last changed.
+    #[serde(skip)]
+    pub local_head_updated: Option<Instant>,
+    /// The timestamp of the latest pending block, from which [Status::pending_block_age] is
+    /// computed.
+    #[serde(skip)]
+    pub pending_block_timestamp: Option<u64>,
+}
+
+/// How long the stages of syncing the latest block took, in seconds.
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize)]
+pub struct BlockTimings {
+    /// Downloading the block, its state diff and signature.
+    pub download: f64,
+    /// Downloading the classes declared in the block.
+    pub class_download: f64,
+    /// Updating the state tries and verifying the state commitment.
+    pub verify: f64,
+    /// Storing and committing the block.
+    pub commit: f64,
+}
+
+impl NodeStatus {
+    pub fn snapshot(&self) -> Status {
+        let mut status = self.0.lock().unwrap().clone();
+        status.pending_block_age = status.pending_block_timestamp.map(|timestamp| {
+            let now = time::OffsetDateTime::now_utc().unix_timestamp() as u64;
+            now.saturating_sub(timestamp)
+        });
+        status
+    }
+
+    pub fn set_local_head(&self, head: Option<BlockNumber>) {
+        let mut status = self.0.lock().unwrap();
+        status.local_head = head;
+        status.local_head_updated = Some(Instant::now());
+        status.update_blocks_behind();
+    }
+
+    pub fn set_gateway_head(&self, head: BlockNumber) {
+        let mut status = self.0.lock().unwrap();
+        status.gateway_head = Some(head);
+        status.update_blocks_behind();
+    }
+
+    pub fn set_l1_accepted(&self, block: Option<BlockNumber>) {
+        self.0.lock().unwrap().l1_accepted = block;
+
+        if let Some(block) = block {
+            metrics::gauge!("l1_accepted_block", block.get() as f64);
+        }
+    }
+
+    pub fn set_rpc_serving(&self, serving: bool) {
+        self.0.lock().unwrap().rpc_serving = serving;
+    }
+
+    pub fn set_pending_block_timestamp(&self, timestamp: u64) {
+        self.0.lock().unwrap().pending_block_timestamp = Some(timestamp);
+
+        metrics::gauge!("pending_block_timestamp", timestamp as f64);
+    }
+
+    pub fn record_reorg(&self, depth: u64) {
+        let mut status = self.0.lock().unwrap();
+        status.reorg_count += 1;
+        status.last_reorg_depth = Some(depth);
+
+        metrics::increment_counter!("reorg_count");
+        metrics::gauge!("reorg_depth", depth as f64);
+    }
+
+    pub fn record_block_timings(
+        &self,
+        download: Duration,
+        class_download: Duration,
+        verify: Duration,
+        commit: Duration,
+    ) {
+        let timings = BlockTimings {
+            download: download.as_secs_f64(),
+            class_download: class_download.as_secs_f64(),
+            verify: verify.as_secs_f64(),
+            commit: commit.as_secs_f64(),
+        };
+
+        metrics::histogram!("block_download_seconds", timings.download);
+        metrics::histogram!("class_download_seconds", timings.class_download);
+        metrics::histogram!("block_verify_seconds", timings.verify);
+        metrics::histogram!("block_commit_seconds", timings.commit);
+
+        self.0.lock().unwrap().last_block_timings = Some(timings);
+    }
+}
+
+impl Status {
+    fn update_blocks_behind(&mut self) {
+        self.blocks_behind = self.gateway_head.map(|gateway| {
+            let local = self
+                .local_head
+                .map(|local| local.get() + 1)
+                .unwrap_or_default();
+            (gateway.get() + 1).saturating_sub(local)
+        });
+
+        if let Some(behind) = self.blocks_behind {
+            metrics::gauge!("blocks_behind", behind as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_behind() {
+        let status = NodeStatus::default();
+        assert_eq!(status.snapshot().blocks_behind, None);
+
+        status.set_gateway_head(BlockNumber::new_or_panic(10));
+        assert_eq!(status.snapshot().blocks_behind, Some(11));
+
+        status.set_local_head(Some(BlockNumber::new_or_panic(7)));
+        assert_eq!(status.snapshot().blocks_behind, Some(3));
+
+        // The gateway head may lag behind after a reorg.
+        status.set_local_head(Some(BlockNumber::new_or_panic(12)));
+        assert_eq!(status.snapshot().blocks_behind, Some(0));
+    }
+
+    #[test]
+    fn reorgs() {
+        let status = NodeStatus::default();
+        status.record_reorg(3);
+        status.record_reorg(1);
+
+        let snapshot = status.snapshot();
+        assert_eq!(snapshot.reorg_count, 2);
+        assert_eq!(snapshot.last_reorg_depth, Some(1));
+    }
+
+    #[test]
+    fn pending_block_age() {
+        let status = NodeStatus::default();
+        assert_eq!(status.snapshot().pending_block_age, None);
+
+        let now = time::OffsetDateTime::now_utc().unix_timestamp() as u64;
+        status.set_pending_block_timestamp(now - 30);
+        let age = status.snapshot().pending_block_age.unwrap();
+        assert!((30..35).contains(&age), "age was {age}");
+
+        // Blocks from the future are not negative in age.
+        status.set_pending_block_timestamp(now + 60);
+        assert_eq!(status.snapshot().pending_block_age, Some(0));
+    }
+}
@@ -0,0 +1,132 @@
+//! Fee token balances and nonces of watched contracts, exported as Prometheus gauges.
+
+use anyhow::Context;
+use pathfinder_common::{BlockNumber, ContractAddress, StorageAddress, StorageValue};
+use pathfinder_executor::{ETH_FEE_TOKEN_ADDRESS, STRK_FEE_TOKEN_ADDRESS};
+use pathfinder_storage::{BlockId, Transaction};
+
+const FEE_TOKENS: [(&str, ContractAddress); 2] = [
+    ("ETH", ETH_FEE_TOKEN_ADDRESS),
+    ("STRK", STRK_FEE_TOKEN_ADDRESS),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+struct Watched {
+    /// Balances per fee token symbol.
+    balances: Vec<(&'static str, f64)>,
+    nonce: f64,
+}
+
+/// Updates the `watched_fee_token_balance` and `watched_nonce` gauges of every contract in
+/// `addresses` to their value at `block`.
+pub fn update(
+    tx: &Transaction<'_>,
+    block: BlockNumber,
+    addresses: &[ContractAddress],
+) -> anyhow::Result<()> {
+    for address in addresses {
+        let watched = read(tx, block, *address)
+            .with_context(|| format!("Reading watched contract {}", address.0))?;
+        let label = address.0.to_string();
+
+        for (token, balance) in watched.balances {
+            metrics::gauge!("watched_fee_token_balance", balance, "address" => label.clone(), "token" => token);
+        }
+        metrics::gauge!("watched_nonce", watched.nonce, "address" => label);
+    }
+
+    Ok(())
+}
+
+fn read(
+    tx: &Transaction<'_>,
+    block: BlockNumber,
+    address: ContractAddress,
+) -> anyhow::Result<Watched> {
+    let block = BlockId::Number(block);
+
+    // Balances are u256 values stored as two felts, the low 128 bits followed by the high ones.
+    let low_key = StorageAddress::from_map_name_and_key(b"ERC20_balances", address.0);
+    let high_key = StorageAddress(low_key.0 + pathfinder_crypto::Felt::from_u64(1));
+
+    let mut balances = Vec::with_capacity(FEE_TOKENS.len());
+    for (token, token_address) in FEE_TOKENS {
+        let low = tx
+            .storage_value(block, token_address, low_key)
+            .context("Querying balance")?
+            .unwrap_or_default();
+        let high = tx
+            .storage_value(block, token_address, high_key)
+            .context("Querying balance")?
+            .unwrap_or_default();
+
+        balances.push((token, to_f64(low) + to_f64(high) * 2f64.powi(128)));
+    }
+
+    let nonce = tx
+        .contract_nonce(address, block)
+        .context("Querying nonce")?
+        .map(|nonce| to_f64(StorageValue(nonce.0)))
+        .unwrap_or_default();
+
+    Ok(Watched { balances, nonce })
+}
+
+fn to_f64(value: StorageValue) -> f64 {
+    value
+        .0
+        .as_be_bytes()
+        .iter()
+        .fold(0.0, |acc, byte| acc * 256.0 + f64::from(*byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_common::{BlockHeader, StateUpdate};
+    use pathfinder_storage::Storage;
+
+    use super::*;
+
+    #[test]
+    fn balances_and_nonce() {
+        let storage = Storage::in_memory().unwrap();
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+
+        let account = contract_address!("0xc01");
+        let low_key = StorageAddress::from_map_name_and_key(b"ERC20_balances", account.0);
+        let high_key = StorageAddress(low_key.0 + pathfinder_crypto::Felt::from_u64(1));
+
+        let header = BlockHeader::builder()
+            .with_number(BlockNumber::GENESIS)
+            .finalize_with_hash(block_hash!("0xb00"));
+        tx.insert_block_header(&header).unwrap();
+        let state_update = StateUpdate::default()
+            .with_block_hash(header.hash)
+            .with_contract_nonce(account, contract_nonce!("0x5"))
+            .with_storage_update(ETH_FEE_TOKEN_ADDRESS, low_key, storage_value!("0x100"))
+            .with_storage_update(STRK_FEE_TOKEN_ADDRESS, low_key, storage_value!("0x1"))
+            .with_storage_update(STRK_FEE_TOKEN_ADDRESS, high_key, storage_value!("0x2"));
+        tx.insert_state_update(BlockNumber::GENESIS, &state_update)
+            .unwrap();
+
+        let watched = read(&tx, BlockNumber::GENESIS, account).unwrap();
+        assert_eq!(
+            watched,
+            Watched {
+                balances: vec![("ETH", 256.0), ("STRK", 1.0 + 2.0 * 2f64.powi(128))],
+                nonce: 5.0,
+            }
+        );
+
+        let unknown = read(&tx, BlockNumber::GENESIS, contract_address!("0xc02")).unwrap();
+        assert_eq!(
+            unknown,
+            Watched {
+                balances: vec![("ETH", 0.0), ("STRK", 0.0)],
+                nonce: 0.0,
+            }
+        );
+    }
+}
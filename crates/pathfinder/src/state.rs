@@ -1,4 +1,7 @@
 pub mod block_hash;
+mod prune;
 mod sync;
+pub mod verify;
 
-pub use sync::{l1, l2, sync, SyncContext};
+pub use prune::{prune_transactions, prune_tries};
+pub use sync::{l1, l2, sync, SyncContext, MAX_REORG_DEPTH};
@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use pathfinder_common::BlockNumber;
+use pathfinder_storage::{Storage, Transaction, TransactionBehavior};
+
+use super::sync::MAX_REORG_DEPTH;
+
+/// Periodically prunes the state tries so that only the tries of the latest `num_blocks_kept`
+/// blocks remain.
+///
+/// The trie nodes which are pruned must have been tracked during sync, which requires the
+/// [Storage] to use [TriePruneMode::Prune](pathfinder_storage::TriePruneMode::Prune).
+/// `num_blocks_kept` must be at least [MAX_REORG_DEPTH], so that reorgs never revert to a block
+/// whose tries are pruned.
+pub async fn prune_tries(
+    storage: Storage,
+    num_blocks_kept: u64,
+    interval: Duration,
+) -> anyhow::Result<()> {
+    ensure_reorgs_are_kept(num_blocks_kept)?;
+
+    prune_periodically(storage, interval, "state tries", move |storage| {
+        prune_tries_once(storage, num_blocks_kept)
+    })
+    .await
+}
+
+/// Periodically prunes the transactions, receipts and events so that only those of the latest
+/// `num_blocks_kept` blocks remain. `num_blocks_kept` must be at least [MAX_REORG_DEPTH].
+pub async fn prune_transactions(
+    storage: Storage,
+    num_blocks_kept: u64,
+    interval: Duration,
+) -> anyhow::Result<()> {
+    ensure_reorgs_are_kept(num_blocks_kept)?;
+
+    prune_periodically(storage, interval, "transactions", move |storage| {
+        prune_transactions_once(storage, num_blocks_kept)
+    })
+    .await
+}
+
+fn ensure_reorgs_are_kept(num_blocks_kept: u64) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        num_blocks_kept >= MAX_REORG_DEPTH,
+        "At least {MAX_REORG_DEPTH} blocks must be kept, the deepest reorg which is handled, but \
+         only {num_blocks_kept} are"
+    );
+    Ok(())
+}
+
+async fn prune_periodically(
+    storage: Storage,
+    interval: Duration,
+    what: &'static str,
+    prune_once: impl Fn(&Storage) -> anyhow::Result<Option<BlockNumber>> + Clone + Send + 'static,
+) -> anyhow::Result<()> {
+    loop {
+        let storage = storage.clone();
+        let prune_once = prune_once.clone();
+        let result = tokio::task::spawn_blocking(move || prune_once(&storage))
+            .await
+            .with_context(|| format!("Joining {what} pruning task"))?;
+
+        match result {
+            Ok(Some(keep_from)) => {
+                tracing::debug!(%keep_from, "Pruned {what}");
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!(error=?e, "Failed to prune {what}");
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Prunes the state tries of all blocks older than the latest `num_blocks_kept` blocks.
+///
+/// Returns the oldest block whose tries are kept, or [None] if there is nothing to prune yet.
+fn prune_tries_once(
+    storage: &Storage,
+    num_blocks_kept: u64,
+) -> anyhow::Result<Option<BlockNumber>> {
+    let mut db = storage
+        .connection()
+        .context("Creating database connection")?;
+    let tx = db
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .context("Creating database transaction")?;
+
+    let horizon = tx
+        .trie_prune_horizon()
+        .context("Querying trie prune horizon")?;
+    let Some(keep_from) = keep_from(&tx, num_blocks_kept, horizon)? else {
+        return Ok(None);
+    };
+
+    tx.prune_tries(keep_from).context("Pruning tries")?;
+    tx.commit().context("Committing database transaction")?;
+
+    Ok(Some(keep_from))
+}
+
+/// Prunes the transactions, receipts and events of all blocks older than the latest
+/// `num_blocks_kept` blocks.
+///
+/// Returns the oldest block whose transaction data is kept, or [None] if there is nothing to
+/// prune yet.
+fn prune_transactions_once(
+    storage: &Storage,
+    num_blocks_kept: u64,
+) -> anyhow::Result<Option<BlockNumber>> {
+    let mut db = storage
+        .connection()
+        .context("Creating database connection")?;
+    let tx = db
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .context("Creating database transaction")?;
+
+    let horizon = tx
+        .transaction_prune_horizon()
+        .context("Querying transaction prune horizon")?;
+    let Some(keep_from) = keep_from(&tx, num_blocks_kept, horizon)? else {
+        return Ok(None);
+    };
+
+    tx.prune_transactions(keep_from)
+        .context("Pruning transactions")?;
+    tx.commit().context("Committing database transaction")?;
+
+    Ok(Some(keep_from))
+}
+
+/// The oldest of the latest `num_blocks_kept` blocks, or [None] if it is not newer than the
+/// current `horizon`.
+fn keep_from(
+    tx: &Transaction<'_>,
+    num_blocks_kept: u64,
+    horizon: Option<BlockNumber>,
+) -> anyhow::Result<Option<BlockNumber>> {
+    let Some((latest, _)) = tx
+        .block_id(pathfinder_storage::BlockId::Latest)
+        .context("Querying latest block")?
+    else {
+        return Ok(None);
+    };
+
+    let Some(keep_from) = (latest.get() + 1).checked_sub(num_blocks_kept) else {
+        return Ok(None);
+    };
+    let keep_from = BlockNumber::new_or_panic(keep_from);
+
+    if horizon.is_some_and(|horizon| horizon >= keep_from) {
+        return Ok(None);
+    }
+
+    Ok(Some(keep_from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pathfinder_common::{BlockHash, BlockHeader};
+    use pathfinder_storage::TriePruneMode;
+
+    fn storage_with_blocks(count: u64) -> Storage {
+        let storage =
+            Storage::in_memory_with_trie_prune_mode(TriePruneMode::Prune { num_blocks_kept: 2 })
+                .unwrap();
+
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        for i in 0..count {
+            let header = BlockHeader::builder()
+                .with_number(BlockNumber::new_or_panic(i))
+                .finalize_with_hash(BlockHash(pathfinder_crypto::Felt::from_u64(i + 1)));
+            tx.insert_block_header(&header).unwrap();
+        }
+        tx.commit().unwrap();
+
+        storage
+    }
+
+    #[test]
+    fn nothing_to_prune_within_retained_blocks() {
+        let storage = storage_with_blocks(2);
+        assert_eq!(prune_tries_once(&storage, 2).unwrap(), None);
+
+        let storage = storage_with_blocks(0);
+        assert_eq!(prune_tries_once(&storage, 2).unwrap(), None);
+    }
+
+    #[test]
+    fn keeps_latest_blocks() {
+        let storage = storage_with_blocks(5);
+        assert_eq!(
+            prune_tries_once(&storage, 2).unwrap(),
+            Some(BlockNumber::new_or_panic(3))
+        );
+        // Already pruned up to the same block.
+        assert_eq!(prune_tries_once(&storage, 2).unwrap(), None);
+
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        assert_eq!(
+            tx.trie_prune_horizon().unwrap(),
+            Some(BlockNumber::new_or_panic(3))
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_at_least_the_deepest_reorg() {
+        let interval = Duration::from_secs(1);
+        let result = prune_tries(storage_with_blocks(0), MAX_REORG_DEPTH - 1, interval).await;
+        assert!(result.is_err());
+        let result =
+            prune_transactions(storage_with_blocks(0), MAX_REORG_DEPTH - 1, interval).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn transactions_keep_latest_blocks() {
+        let storage = storage_with_blocks(5);
+        assert_eq!(prune_transactions_once(&storage, 5).unwrap(), None);
+        assert_eq!(
+            prune_transactions_once(&storage, 3).unwrap(),
+            Some(BlockNumber::new_or_panic(2))
+        );
+        assert_eq!(prune_transactions_once(&storage, 3).unwrap(), None);
+
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        assert_eq!(
+            tx.transaction_prune_horizon().unwrap(),
+            Some(BlockNumber::new_or_panic(2))
+        );
+        // The two horizons are independent.
+        assert_eq!(tx.trie_prune_horizon().unwrap(), None);
+    }
+}
@@ -6,38 +6,55 @@ mod pending;
 use anyhow::Context;
 use pathfinder_common::{
     BlockCommitmentSignature, BlockHash, BlockHeader, BlockNumber, CasmHash, Chain, ChainId,
-    ClassCommitment, ClassHash, EventCommitment, GasPrice, SequencerAddress, SierraHash,
-    StateCommitment, StateUpdate, StorageCommitment, TransactionCommitment,
+    ClassCommitment, ClassHash, ContractAddress, EventCommitment, GasPrice, SequencerAddress,
+    SierraHash, StateCommitment, StateUpdate, StorageCommitment, TransactionCommitment,
 };
 use pathfinder_crypto::Felt;
-use pathfinder_ethereum::{EthereumApi, EthereumStateUpdate};
+use pathfinder_ethereum::{
+    EthereumApi, EthereumStateUpdate, L1BlockId, L1Finality, StateUpdateLog,
+};
 use pathfinder_merkle_tree::contract_state::update_contract_state;
 use pathfinder_merkle_tree::{ClassCommitmentTree, StorageCommitmentTree};
 use pathfinder_rpc::PendingData;
 use pathfinder_rpc::{
     v02::types::syncing::{self, NumberedBlock, Syncing},
-    SyncState, TopicBroadcasters,
+    EmittedEvent, L1Reorg, NewHead, PendingTransaction, Reorg, SyncState, TopicBroadcasters,
+    TransactionStatus, TransactionStatusUpdate,
 };
-use pathfinder_storage::{Connection, Storage, Transaction, TransactionBehavior};
+use pathfinder_storage::{Connection, L1Message, Storage, Transaction, TransactionBehavior};
 use primitive_types::H160;
 use starknet_gateway_client::{GatewayApi, GossipApi};
 use starknet_gateway_types::reply::Block;
 use starknet_gateway_types::reply::PendingBlock;
 
 use std::future::Future;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 use tokio::sync::mpsc::{self, Receiver};
 
+use crate::monitoring::status::NodeStatus;
 use crate::state::l1::L1SyncContext;
 use crate::state::l2::{BlockChain, L2SyncContext};
 
 use tokio::sync::watch::Sender as WatchSender;
 
+/// The number of latest blocks which are tracked to detect reorgs, which bounds how deep a reorg
+/// can be.
+pub const MAX_REORG_DEPTH: u64 = 1_000;
+
 #[derive(Debug)]
 pub enum SyncEvent {
     L1Update(EthereumStateUpdate),
+    /// State updates logged in L1 blocks up to and including `head`.
+    L1StateUpdates {
+        logs: Vec<StateUpdateLog>,
+        head: L1BlockId,
+    },
+    /// L1 blocks after the contained block are no longer canonical. [None] if none of the L1
+    /// blocks with stored state updates are canonical.
+    L1Reorg(Option<L1BlockId>),
     /// New L2 [block update](StateUpdate) found.
     Block(
         (Box<Block>, (TransactionCommitment, EventCommitment)),
@@ -63,6 +80,12 @@ pub enum SyncEvent {
     },
     /// A new L2 pending update was polled.
     Pending(Box<(PendingBlock, StateUpdate)>),
+    /// Messages found in the L1 `blocks`, which extend the indexed L1 blocks either upwards or
+    /// downwards.
+    L1Messages {
+        messages: Vec<L1Message>,
+        blocks: RangeInclusive<u64>,
+    },
 }
 
 pub struct SyncContext<G, E> {
@@ -71,6 +94,9 @@ pub struct SyncContext<G, E> {
     pub chain: Chain,
     pub chain_id: ChainId,
     pub core_address: H160,
+    pub l1_finality: L1Finality,
+    /// The L1 block down to which message logs are backfilled.
+    pub index_messages_from: Option<u64>,
     pub sequencer: G,
     pub state: Arc<SyncState>,
     pub head_poll_interval: Duration,
@@ -80,6 +106,9 @@ pub struct SyncContext<G, E> {
     pub block_cache_size: usize,
     pub restart_delay: Duration,
     pub verify_tree_hashes: bool,
+    /// Contracts whose fee token balances and nonces are exported as metrics.
+    pub watch_addresses: Vec<ContractAddress>,
+    pub node_status: Arc<NodeStatus>,
 }
 
 impl<G, E> From<&SyncContext<G, E>> for L1SyncContext<E>
@@ -90,8 +119,12 @@ where
         Self {
             ethereum: value.ethereum.clone(),
             chain: value.chain,
+            chain_id: value.chain_id,
             core_address: value.core_address,
             poll_interval: value.head_poll_interval,
+            storage: value.storage.clone(),
+            finality: value.l1_finality,
+            index_messages_from: value.index_messages_from,
         }
     }
 }
@@ -140,6 +173,8 @@ where
         chain: _,
         chain_id: _,
         core_address: _,
+        l1_finality: _,
+        index_messages_from: _,
         sequencer,
         state,
         head_poll_interval,
@@ -149,6 +184,8 @@ where
         block_cache_size,
         restart_delay,
         verify_tree_hashes: _,
+        watch_addresses: _,
+        node_status: _,
     } = context;
 
     let mut db_conn = storage
@@ -177,6 +214,7 @@ where
     ));
     let _status_sync = tokio::spawn(update_sync_status_latest(
         Arc::clone(&state),
+        Arc::clone(&context.node_status),
         sequencer.clone(),
         starting_block_hash,
         starting_block_num,
@@ -190,7 +228,7 @@ where
     let latest_blocks = latest_n_blocks(&mut db_conn, block_cache_size)
         .await
         .context("Fetching latest blocks from storage")?;
-    let block_chain = BlockChain::with_capacity(1_000, latest_blocks);
+    let block_chain = BlockChain::with_capacity(MAX_REORG_DEPTH as usize, latest_blocks);
 
     // Start L2 producer task. Clone the event sender so that the channel remains open
     // even if the producer task fails.
@@ -207,6 +245,8 @@ where
         pending_data,
         verify_tree_hashes: context.verify_tree_hashes,
         websocket_txs,
+        watch_addresses: context.watch_addresses,
+        node_status: context.node_status,
     };
     let mut consumer_handle = tokio::spawn(consumer(event_receiver, consumer_context));
 
@@ -254,7 +294,7 @@ where
                 .map(|block| (block.number, block.hash, block.state_commitment));
 
                 let latest_blocks = latest_n_blocks(&mut db_conn, block_cache_size).await.context("Fetching latest blocks from storage")?;
-                let block_chain = BlockChain::with_capacity(1_000, latest_blocks);
+                let block_chain = BlockChain::with_capacity(MAX_REORG_DEPTH as usize, latest_blocks);
                 let fut = l2_sync(event_sender.clone(), l2_context.clone(), l2_head, block_chain);
 
                 l2_handle = tokio::spawn(async move {
@@ -326,6 +366,8 @@ struct ConsumerContext {
     pub pending_data: WatchSender<PendingData>,
     pub verify_tree_hashes: bool,
     pub websocket_txs: Option<TopicBroadcasters>,
+    pub watch_addresses: Vec<ContractAddress>,
+    pub node_status: Arc<NodeStatus>,
 }
 
 async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) -> anyhow::Result<()> {
@@ -335,9 +377,13 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
         pending_data,
         verify_tree_hashes,
         mut websocket_txs,
+        watch_addresses,
+        node_status,
     } = context;
 
     let mut last_block_start = std::time::Instant::now();
+    // Parent hash and transaction count of the last pending block.
+    let mut last_pending: Option<(BlockHash, usize)> = None;
     let mut block_time_avg = std::time::Duration::ZERO;
     const BLOCK_TIME_WEIGHT: f32 = 0.05;
 
@@ -358,14 +404,39 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
         anyhow::Ok(latest)
     })
     .context("Fetching latest block time")?;
+    node_status.set_local_head(
+        next_number
+            .get()
+            .checked_sub(1)
+            .map(BlockNumber::new_or_panic),
+    );
+    node_status.set_l1_accepted(l1_accepted(&mut db_conn).await?);
 
     while let Some(event) = events.recv().await {
         use SyncEvent::*;
         match event {
             L1Update(update) => {
-                l1_update(&mut db_conn, &update).await?;
+                l1_update(&mut db_conn, &update, &websocket_txs).await?;
+                node_status.set_l1_accepted(l1_accepted(&mut db_conn).await?);
                 tracing::info!("L1 sync updated to block {}", update.block_number);
             }
+            L1StateUpdates { logs, head } => {
+                l1_state_updates(&mut db_conn, &logs, head, &websocket_txs).await?;
+                node_status.set_l1_accepted(l1_accepted(&mut db_conn).await?);
+                if let Some(last) = logs.last() {
+                    tracing::info!("L1 sync updated to block {}", last.update.block_number);
+                }
+            }
+            L1Reorg(fork) => {
+                l1_reorg(&mut db_conn, fork, &websocket_txs).await?;
+                node_status.set_l1_accepted(l1_accepted(&mut db_conn).await?);
+                tracing::info!(fork=?fork.map(|fork| fork.number), "L1 reorg handled");
+            }
+            L1Messages { messages, blocks } => {
+                let count = messages.len();
+                l1_messages_update(&mut db_conn, &messages, &blocks).await?;
+                tracing::debug!(%count, ?blocks, "L1 messages indexed");
+            }
             Block((block, (tx_comm, ev_comm)), state_update, signature, timings) => {
                 if block.block_number < next_number {
                     tracing::debug!("Ignoring duplicate block {}", block.block_number);
@@ -381,7 +452,7 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
                     .map(|x| x.1.storage.len())
                     .sum();
                 let update_t = std::time::Instant::now();
-                l2_update(
+                let (verify_t, commit_t, accepted_on_l1) = l2_update(
                     &mut db_conn,
                     *block,
                     tx_comm,
@@ -394,6 +465,28 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
                 )
                 .await
                 .with_context(|| format!("Update L2 state to {block_number}"))?;
+                node_status.set_local_head(Some(block_number));
+                if accepted_on_l1 {
+                    node_status.set_l1_accepted(Some(block_number));
+                }
+                node_status.record_block_timings(
+                    timings.block_download
+                        + timings.state_diff_download
+                        + timings.signature_download,
+                    timings.class_declaration,
+                    verify_t,
+                    commit_t,
+                );
+                if !watch_addresses.is_empty() {
+                    if let Err(e) = tokio::task::block_in_place(|| {
+                        let tx = db_conn
+                            .transaction()
+                            .context("Creating database transaction")?;
+                        crate::monitoring::watchlist::update(&tx, block_number, &watch_addresses)
+                    }) {
+                        tracing::warn!(error=?e, "Failed to update watched contract metrics");
+                    }
+                }
                 let block_time = last_block_start.elapsed();
                 let update_t = update_t.elapsed();
                 last_block_start = std::time::Instant::now();
@@ -461,16 +554,38 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
                 }
             }
             Reorg(reorg_tail) => {
-                l2_reorg(&mut db_conn, reorg_tail)
+                let reorg = l2_reorg(&mut db_conn, reorg_tail)
                     .await
                     .with_context(|| format!("Reorg L2 state to {reorg_tail:?}"))?;
 
+                if let Some(sender) = &websocket_txs {
+                    // Subscribers to blocks and events receive the reorg in order with the
+                    // items of their own topic.
+                    for result in [
+                        sender.new_head.send_reorg_if_receiving(&reorg),
+                        sender.events.send_reorg_if_receiving(&reorg),
+                    ] {
+                        if let Err(e) = result {
+                            tracing::error!(error=?e, "Failed to send reorg over websocket broadcaster.");
+                        }
+                    }
+                    if let Err(e) = sender.reorg.send_if_receiving(reorg) {
+                        tracing::error!(error=?e, "Failed to send reorg over websocket broadcaster.");
+                    }
+                    sender
+                        .transaction_status
+                        .send_if_receiving(TransactionStatusUpdate::Reorg(reorg_tail));
+                }
+
+                node_status.record_reorg(next_number.get().saturating_sub(reorg_tail.get()));
                 next_number = reorg_tail;
 
                 let new_head = match reorg_tail {
                     BlockNumber::GENESIS => None,
                     other => Some(other - 1),
                 };
+                node_status.set_local_head(new_head);
+                node_status.set_l1_accepted(l1_accepted(&mut db_conn).await?);
                 match new_head {
                     Some(head) => {
                         tracing::info!("L2 reorg occurred, new L2 head is block {}", head)
@@ -518,6 +633,8 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
                 tracing::debug!(sierra=%sierra_hash, casm=%casm_hash, "Inserted new Sierra class");
             }
             Pending(pending) => {
+                node_status.set_pending_block_timestamp(pending.0.timestamp.get());
+
                 let (number, hash) = tokio::task::block_in_place(|| {
                     let tx = db_conn
                         .transaction()
@@ -532,6 +649,45 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
                 .context("Fetching latest block hash")?;
 
                 if pending.0.parent_hash == hash {
+                    // The pending block only ever grows for the same parent, so only the
+                    // receipts past the previously seen ones are new.
+                    let already_seen = match last_pending {
+                        Some((parent_hash, count)) if parent_hash == hash => count,
+                        _ => 0,
+                    };
+                    last_pending = Some((hash, pending.0.transaction_receipts.len()));
+
+                    if let Some(sender) = &websocket_txs {
+                        let statuses = pending
+                            .0
+                            .transaction_receipts
+                            .iter()
+                            .skip(already_seen)
+                            .map(|receipt| (receipt.transaction_hash, l2_status(receipt)))
+                            .collect::<Vec<_>>();
+                        if !statuses.is_empty() {
+                            sender
+                                .transaction_status
+                                .send_if_receiving(TransactionStatusUpdate::Pending(statuses));
+                        }
+
+                        let transactions = pending
+                            .0
+                            .transactions
+                            .iter()
+                            .skip(already_seen)
+                            .cloned()
+                            .map(PendingTransaction::from)
+                            .collect::<Vec<_>>();
+                        if !transactions.is_empty() {
+                            if let Err(e) =
+                                sender.pending_transactions.send_if_receiving(transactions)
+                            {
+                                tracing::error!(error=?e, "Failed to send pending transactions over websocket broadcaster, closing subscriptions.");
+                            }
+                        }
+                    }
+
                     let data = PendingData {
                         block: pending.0.into(),
                         state_update: pending.1.into(),
@@ -587,6 +743,7 @@ async fn latest_n_blocks(
 /// propagates latest head after every change or otherwise every 2 minutes.
 async fn update_sync_status_latest(
     state: Arc<SyncState>,
+    node_status: Arc<NodeStatus>,
     sequencer: impl GatewayApi + GossipApi,
     starting_block_hash: BlockHash,
     starting_block_num: BlockNumber,
@@ -599,6 +756,7 @@ async fn update_sync_status_latest(
         match sequencer.head().await {
             Ok((block_number, block_hash)) => {
                 let latest = NumberedBlock::from((block_hash, block_number));
+                node_status.set_gateway_head(block_number);
 
                 match &mut *state.status.write().await {
                     sync_status @ Syncing::False(_) => {
@@ -657,9 +815,45 @@ async fn propagate_head(
     *last_propagated = Instant::now();
 }
 
+async fn l1_messages_update(
+    connection: &mut Connection,
+    messages: &[L1Message],
+    blocks: &RangeInclusive<u64>,
+) -> anyhow::Result<()> {
+    tokio::task::block_in_place(move || {
+        let transaction = connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .context("Create database transaction")?;
+
+        transaction
+            .insert_l1_messages(messages)
+            .context("Insert L1 messages")?;
+
+        let head = transaction
+            .l1_messages_head()
+            .context("Querying L1 messages head")?;
+        if head.map_or(true, |head| head < *blocks.end()) {
+            transaction
+                .update_l1_messages_head(*blocks.end())
+                .context("Updating L1 messages head")?;
+        }
+        let tail = transaction
+            .l1_messages_tail()
+            .context("Querying L1 messages tail")?;
+        if tail.map_or(true, |tail| tail > *blocks.start()) {
+            transaction
+                .update_l1_messages_tail(*blocks.start())
+                .context("Updating L1 messages tail")?;
+        }
+
+        transaction.commit().context("Commit database transaction")
+    })
+}
+
 async fn l1_update(
     connection: &mut Connection,
     update: &EthereumStateUpdate,
+    websocket_txs: &Option<TopicBroadcasters>,
 ) -> anyhow::Result<()> {
     tokio::task::block_in_place(move || {
         let transaction = connection
@@ -670,29 +864,143 @@ async fn l1_update(
             .upsert_l1_state(update)
             .context("Insert update")?;
 
-        let l2_hash = transaction
-            .block_id(update.block_number.into())
-            .context("Fetching block hash")?
-            .map(|(_, hash)| hash);
-
-        if let Some(l2_hash) = l2_hash {
-            if l2_hash == update.block_hash {
-                transaction
-                    .update_l1_l2_pointer(Some(update.block_number))
-                    .context("Updating L1-L2 pointer")?;
-                tracing::info!(block=?update.block_number, "Updated L1/L2 match");
-            } else {
-                tracing::warn!(block_number=?update.block_number, L1=?update.block_hash, L2=?l2_hash, "L1/L2 block hash mismatch");
-                if let Some(matching_block_number) = transaction.l1_l2_pointer()? {
-                    tracing::warn!(block_number=?matching_block_number, "Most recent L1/L2 block hash match")
-                }
-            }
+        match_l1_l2(&transaction, update, websocket_txs)?;
+
+        transaction.commit().context("Commit database transaction")
+    })
+}
+
+async fn l1_state_updates(
+    connection: &mut Connection,
+    logs: &[StateUpdateLog],
+    head: L1BlockId,
+    websocket_txs: &Option<TopicBroadcasters>,
+) -> anyhow::Result<()> {
+    tokio::task::block_in_place(move || {
+        let transaction = connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .context("Create database transaction")?;
+
+        for log in logs {
+            transaction
+                .insert_l1_state_log(log)
+                .context("Insert update")?;
+
+            match_l1_l2(&transaction, &log.update, websocket_txs)?;
         }
 
+        transaction
+            .update_l1_state_head(Some(head))
+            .context("Updating L1 state head")?;
+
         transaction.commit().context("Commit database transaction")
     })
 }
 
+/// Removes the L1 state updates logged after the `fork` L1 block, and reverts the L1-L2 pointer
+/// to the latest remaining L1 state update.
+async fn l1_reorg(
+    connection: &mut Connection,
+    fork: Option<L1BlockId>,
+    websocket_txs: &Option<TopicBroadcasters>,
+) -> anyhow::Result<()> {
+    tokio::task::block_in_place(move || {
+        let transaction = connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .context("Create database transaction")?;
+
+        transaction
+            .purge_l1_state_after(fork.map(|fork| fork.number).unwrap_or_default())
+            .context("Removing reorged L1 state updates")?;
+        transaction
+            .update_l1_state_head(fork)
+            .context("Updating L1 state head")?;
+
+        let latest = transaction
+            .latest_l1_state()
+            .context("Querying latest L1 state")?;
+        let pointer = transaction
+            .l1_l2_pointer()
+            .context("Querying L1-L2 pointer")?;
+
+        let reverted = match (&latest, pointer) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(latest), Some(pointer)) => latest.block_number < pointer,
+        };
+        if reverted {
+            let l2_hash = match &latest {
+                Some(latest) => transaction
+                    .block_id(latest.block_number.into())
+                    .context("Fetching block hash")?
+                    .map(|(_, hash)| hash),
+                None => None,
+            };
+            let pointer = latest
+                .filter(|latest| Some(latest.block_hash) == l2_hash)
+                .map(|latest| latest.block_number);
+
+            transaction
+                .update_l1_l2_pointer(pointer)
+                .context("Updating L1-L2 pointer")?;
+            tracing::info!(block=?pointer, "Reverted L1/L2 match");
+        }
+        let l1_accepted = transaction
+            .l1_l2_pointer()
+            .context("Querying L1-L2 pointer")?;
+
+        transaction
+            .commit()
+            .context("Commit database transaction")?;
+
+        if let Some(sender) = websocket_txs {
+            let reorg = L1Reorg {
+                fork_l1_block_number: fork.map(|fork| fork.number),
+                l1_accepted_block_number: l1_accepted,
+            };
+            if let Err(e) = sender.l1_reorg.send_if_receiving(reorg) {
+                tracing::error!(error=?e, "Failed to send L1 reorg over websocket broadcaster.");
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Advances the L1-L2 pointer if the L1 state update matches the L2 block.
+fn match_l1_l2(
+    transaction: &Transaction<'_>,
+    update: &EthereumStateUpdate,
+    websocket_txs: &Option<TopicBroadcasters>,
+) -> anyhow::Result<()> {
+    let l2_hash = transaction
+        .block_id(update.block_number.into())
+        .context("Fetching block hash")?
+        .map(|(_, hash)| hash);
+
+    if let Some(l2_hash) = l2_hash {
+        if l2_hash == update.block_hash {
+            transaction
+                .update_l1_l2_pointer(Some(update.block_number))
+                .context("Updating L1-L2 pointer")?;
+            tracing::info!(block=?update.block_number, "Updated L1/L2 match");
+
+            if let Some(sender) = websocket_txs {
+                sender
+                    .transaction_status
+                    .send_if_receiving(TransactionStatusUpdate::L1Accepted(update.block_number));
+            }
+        } else {
+            tracing::warn!(block_number=?update.block_number, L1=?update.block_hash, L2=?l2_hash, "L1/L2 block hash mismatch");
+            if let Some(matching_block_number) = transaction.l1_l2_pointer()? {
+                tracing::warn!(block_number=?matching_block_number, "Most recent L1/L2 block hash match")
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Returns the new [StateCommitment] after the update.
 #[allow(clippy::too_many_arguments)]
 async fn l2_update(
@@ -707,8 +1015,9 @@ async fn l2_update(
     // parallel contract state updates
     storage: Storage,
     websocket_txs: &mut Option<TopicBroadcasters>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<(Duration, Duration, bool)> {
     tokio::task::block_in_place(move || {
+        let verify_t = Instant::now();
         let transaction = connection
             .transaction_with_behavior(TransactionBehavior::Immediate)
             .context("Create database transaction")?;
@@ -737,6 +1046,8 @@ async fn l2_update(
                 || state_commitment == block.state_commitment,
             "State root mismatch"
         );
+        let verify_t = verify_t.elapsed();
+        let commit_t = Instant::now();
 
         let transaction_count = block.transactions.len();
         let event_count = block
@@ -807,6 +1118,7 @@ async fn l2_update(
             .map(|head| head + 1)
             .unwrap_or(BlockNumber::GENESIS);
 
+        let mut l1_accepted = false;
         if expected_next == header.number {
             if let Some(l1_state) = transaction
                 .l1_state_at_number(header.number)
@@ -816,6 +1128,7 @@ async fn l2_update(
                     transaction
                         .update_l1_l2_pointer(Some(header.number))
                         .context("Update L1-L2 head")?;
+                    l1_accepted = true;
                 }
             }
         }
@@ -823,72 +1136,120 @@ async fn l2_update(
         transaction
             .commit()
             .context("Commit database transaction")?;
+        let commit_t = commit_t.elapsed();
 
         if let Some(sender) = websocket_txs {
+            let (block_hash, block_number) = (header.hash, header.number);
+
             if let Err(e) = sender.new_head.send_if_receiving(header.into()) {
                 tracing::error!(error=?e, "Failed to send header over websocket broadcaster.");
                 // Disable websocket entirely so that the closed channel doesn't spam this error. It
                 // is unlikely that any error here wouldn't simply repeat indefinitely.
                 *websocket_txs = None;
+            } else if sender.events.is_receiving() {
+                let events = transaction_data
+                    .iter()
+                    .flat_map(|(_, receipt)| {
+                        receipt.events.iter().map(|event| EmittedEvent {
+                            from_address: event.from_address,
+                            keys: event.keys.clone(),
+                            data: event.data.clone(),
+                            block_hash,
+                            block_number,
+                            transaction_hash: receipt.transaction_hash,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                if let Err(e) = sender.events.send_if_receiving(events) {
+                    tracing::error!(error=?e, "Failed to send events over websocket broadcaster.");
+                    *websocket_txs = None;
+                }
             }
         }
 
-        Ok(())
-    })?;
+        if let Some(sender) = websocket_txs {
+            let statuses = transaction_data
+                .iter()
+                .map(|(_, receipt)| (receipt.transaction_hash, l2_status(receipt)))
+                .collect();
+            sender
+                .transaction_status
+                .send_if_receiving(TransactionStatusUpdate::Block(block.block_number, statuses));
+
+            if l1_accepted {
+                sender
+                    .transaction_status
+                    .send_if_receiving(TransactionStatusUpdate::L1Accepted(block.block_number));
+            }
+        }
 
-    Ok(())
+        Ok((verify_t, commit_t, l1_accepted))
+    })
 }
 
-async fn l2_reorg(connection: &mut Connection, reorg_tail: BlockNumber) -> anyhow::Result<()> {
+/// The latest L1 accepted block, as reported by [NodeStatus].
+async fn l1_accepted(connection: &mut Connection) -> anyhow::Result<Option<BlockNumber>> {
+    tokio::task::block_in_place(|| {
+        connection
+            .transaction()
+            .context("Creating database transaction")?
+            .l1_l2_pointer()
+            .context("Querying L1-L2 head")
+    })
+}
+
+/// The status of a transaction which is part of an L2 block, ignoring L1 acceptance.
+fn l2_status(receipt: &starknet_gateway_types::reply::transaction::Receipt) -> TransactionStatus {
+    use starknet_gateway_types::reply::transaction::ExecutionStatus;
+
+    match receipt.execution_status {
+        ExecutionStatus::Succeeded => TransactionStatus::AcceptedOnL2,
+        ExecutionStatus::Reverted => TransactionStatus::Reverted,
+    }
+}
+
+/// Purges all blocks from `reorg_tail` onwards and returns a description of the orphaned blocks.
+async fn l2_reorg(connection: &mut Connection, reorg_tail: BlockNumber) -> anyhow::Result<Reorg> {
     tokio::task::block_in_place(move || {
         let transaction = connection
             .transaction_with_behavior(TransactionBehavior::Immediate)
             .context("Create database transaction")?;
 
-        let mut head = transaction
+        let (last_block_number, last_block_hash) = transaction
             .block_id(pathfinder_storage::BlockId::Latest)
             .context("Querying latest block number")?
-            .context("Latest block number is none during reorg")?
-            .0;
-
-        // Purge each block one at a time.
-        //
-        // This is done 1-by-1 to allow sending the reorg'd block data
-        // to websocket subscriptions while keeping a constant memory footprint.
-        //
-        // This is acceptable performance because reorgs are rare and need not be
-        // 100% optimal. However a large reorg could cause a massive memory spike
-        // which is not acceptable.
-        while head >= reorg_tail {
-            transaction
-                .purge_block(head)
-                .with_context(|| format!("Purging block {head} from database"))?;
+            .context("Latest block number is none during reorg")?;
 
-            // No further blocks to purge if we just purged genesis.
-            if head == BlockNumber::GENESIS {
-                break;
-            }
+        let first_block_hash = transaction
+            .block_id(reorg_tail.into())
+            .context("Querying reorg tail block hash")?
+            .context("Reorg tail block is missing")?
+            .1;
 
-            head -= 1;
-        }
+        transaction
+            .purge_blocks_from(reorg_tail)
+            .context("Purging reorg'd blocks from database")?;
 
-        // Track combined L1 and L2 state.
-        let l1_l2_head = transaction.l1_l2_pointer().context("Query L1-L2 head")?;
-        if let Some(l1_l2_head) = l1_l2_head {
-            if reorg_tail == BlockNumber::GENESIS {
-                // If we purged genesis then unset the L1 L2 pointer as well since there
-                // are now no blocks remaining.
-                transaction
-                    .update_l1_l2_pointer(None)
-                    .context("Unsetting L1-L2 head")?;
-            } else if l1_l2_head >= reorg_tail {
-                transaction
-                    .update_l1_l2_pointer(Some(reorg_tail - 1))
-                    .context("Updating L1-L2 head")?;
-            }
-        }
+        let new_head = transaction
+            .block_id(pathfinder_storage::BlockId::Latest)
+            .context("Querying new head")?
+            .map(|(block_number, block_hash)| NewHead {
+                block_number,
+                block_hash,
+            });
 
-        transaction.commit().context("Commit database transaction")
+        transaction
+            .commit()
+            .context("Commit database transaction")?;
+
+        Ok(Reorg {
+            first_block_number: reorg_tail,
+            first_block_hash,
+            last_block_number,
+            last_block_hash,
+            new_head,
+        })
     })
 }
 
@@ -980,13 +1341,13 @@ fn update_starknet_state(
     }
 
     // Apply storage commitment tree changes.
-    let (storage_commitment, nodes) = storage_commitment_tree
+    let (storage_commitment, update) = storage_commitment_tree
         .commit()
         .context("Apply storage commitment tree updates")?;
 
     let root_idx = if !storage_commitment.0.is_zero() {
         let root_idx = transaction
-            .insert_storage_trie(storage_commitment, &nodes)
+            .insert_storage_trie(storage_commitment, &update.nodes)
             .context("Persisting storage trie")?;
 
         Some(root_idx)
@@ -997,6 +1358,9 @@ fn update_starknet_state(
     transaction
         .insert_storage_root(block, root_idx)
         .context("Inserting storage root index")?;
+    transaction
+        .insert_storage_trie_removals(block, &update.nodes_removed)
+        .context("Inserting storage trie removals")?;
 
     // Add new Sierra classes to class commitment tree.
     let mut class_commitment_tree = match block.parent() {
@@ -1019,13 +1383,13 @@ fn update_starknet_state(
     }
 
     // Apply all class commitment tree changes.
-    let (class_commitment, nodes) = class_commitment_tree
+    let (class_commitment, update) = class_commitment_tree
         .commit()
         .context("Apply class commitment tree updates")?;
 
     let class_root_idx = if !class_commitment.0.is_zero() {
         let class_root_idx = transaction
-            .insert_class_trie(class_commitment, &nodes)
+            .insert_class_trie(class_commitment, &update.nodes)
             .context("Persisting class trie")?;
 
         Some(class_root_idx)
@@ -1036,6 +1400,9 @@ fn update_starknet_state(
     transaction
         .insert_class_root(block, class_root_idx)
         .context("Inserting class root index")?;
+    transaction
+        .insert_class_trie_removals(block, &update.nodes_removed)
+        .context("Inserting class trie removals")?;
 
     Ok((storage_commitment, class_commitment))
 }
@@ -1151,6 +1518,8 @@ mod tests {
             pending_data: tx,
             verify_tree_hashes: false,
             websocket_txs: None,
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
         };
 
         consumer(event_rx, context).await.unwrap();
@@ -1196,6 +1565,8 @@ mod tests {
             pending_data: tx,
             verify_tree_hashes: false,
             websocket_txs: None,
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
         };
 
         consumer(event_rx, context).await.unwrap();
@@ -1215,6 +1586,195 @@ mod tests {
         assert!(!block_2_exists);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reorg_is_broadcast() {
+        let storage = Storage::in_memory().unwrap();
+
+        let (event_tx, event_rx) = tokio::sync::mpsc::channel(100);
+
+        // Send block updates, followed by a reorg of the last block.
+        for (a, b, c, d) in generate_block_data() {
+            event_tx.send(SyncEvent::Block(a, b, c, d)).await.unwrap();
+        }
+        event_tx
+            .send(SyncEvent::Reorg(BlockNumber::new_or_panic(2)))
+            .await
+            .unwrap();
+        drop(event_tx);
+
+        let websocket_txs = pathfinder_rpc::TopicBroadcasters::default();
+        let mut reorgs = websocket_txs.reorg.subscribe();
+        let mut heads = websocket_txs.new_head.subscribe();
+
+        let (tx, _rx) = tokio::sync::watch::channel(Default::default());
+        let context = ConsumerContext {
+            storage,
+            state: Arc::new(SyncState::default()),
+            pending_data: tx,
+            verify_tree_hashes: false,
+            websocket_txs: Some(websocket_txs),
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
+        };
+
+        consumer(event_rx, context).await.unwrap();
+
+        let expected = pathfinder_rpc::Reorg {
+            first_block_number: BlockNumber::new_or_panic(2),
+            first_block_hash: BlockHash(Felt::from_be_slice(b"2 block hash").unwrap()),
+            last_block_number: BlockNumber::new_or_panic(2),
+            last_block_hash: BlockHash(Felt::from_be_slice(b"2 block hash").unwrap()),
+            new_head: Some(pathfinder_rpc::NewHead {
+                block_number: BlockNumber::new_or_panic(1),
+                block_hash: BlockHash(Felt::from_be_slice(b"1 block hash").unwrap()),
+            }),
+        };
+        let expected = Arc::new(serde_json::to_value(expected).unwrap());
+        let reorg = reorgs.try_recv().unwrap();
+        assert_eq!(reorg, pathfinder_rpc::TopicMessage::Item(expected.clone()));
+
+        // New head subscribers receive the reorg after the headers it removes.
+        let mut received = std::iter::from_fn(|| heads.try_recv().ok()).collect::<Vec<_>>();
+        assert_eq!(
+            received.pop(),
+            Some(pathfinder_rpc::TopicMessage::Reorg(expected))
+        );
+        assert!(received
+            .iter()
+            .all(|head| matches!(head, pathfinder_rpc::TopicMessage::Item(_))));
+        assert!(!received.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn l1_reorg_is_broadcast() {
+        use pathfinder_ethereum::{EthereumStateUpdate, L1BlockId, StateUpdateLog};
+        use primitive_types::H256;
+
+        let storage = Storage::in_memory().unwrap();
+
+        let (event_tx, event_rx) = tokio::sync::mpsc::channel(100);
+
+        let l1_block = |number| L1BlockId {
+            number,
+            hash: H256::from_low_u64_be(number),
+        };
+        let state_update_log = |block_number, block_hash, l1_block_number| StateUpdateLog {
+            update: EthereumStateUpdate {
+                block_number: BlockNumber::new_or_panic(block_number),
+                block_hash,
+                ..Default::default()
+            },
+            l1_block_number,
+            l1_block_hash: H256::from_low_u64_be(l1_block_number),
+            l1_transaction_hash: H256::from_low_u64_be(l1_block_number),
+        };
+
+        // Blocks 0 and 1 are accepted on L1, until the L1 block settling block 1 is reorged.
+        for (a, b, c, d) in generate_block_data() {
+            event_tx.send(SyncEvent::Block(a, b, c, d)).await.unwrap();
+        }
+        event_tx
+            .send(SyncEvent::L1StateUpdates {
+                logs: vec![
+                    state_update_log(0, block_hash_bytes!(b"genesis block hash"), 10),
+                    state_update_log(1, block_hash_bytes!(b"1 block hash"), 20),
+                ],
+                head: l1_block(20),
+            })
+            .await
+            .unwrap();
+        event_tx
+            .send(SyncEvent::L1Reorg(Some(l1_block(10))))
+            .await
+            .unwrap();
+        drop(event_tx);
+
+        let websocket_txs = pathfinder_rpc::TopicBroadcasters::default();
+        let mut l1_reorgs = websocket_txs.l1_reorg.subscribe();
+
+        let (tx, _rx) = tokio::sync::watch::channel(Default::default());
+        let context = ConsumerContext {
+            storage,
+            state: Arc::new(SyncState::default()),
+            pending_data: tx,
+            verify_tree_hashes: false,
+            websocket_txs: Some(websocket_txs),
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
+        };
+
+        consumer(event_rx, context).await.unwrap();
+
+        let expected = pathfinder_rpc::L1Reorg {
+            fork_l1_block_number: Some(10),
+            l1_accepted_block_number: Some(BlockNumber::GENESIS),
+        };
+        let expected = Arc::new(serde_json::to_value(expected).unwrap());
+        assert_eq!(
+            l1_reorgs.try_recv().unwrap(),
+            pathfinder_rpc::TopicMessage::Item(expected)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn synced_chain_is_verified_as_consistent() {
+        use crate::state::block_hash::{
+            calculate_event_commitment, calculate_transaction_commitment,
+            TransactionCommitmentFinalHashType,
+        };
+        use pathfinder_common::{Chain, ChainId};
+        use starknet_gateway_test_fixtures::{v0_11_0, v0_9_0};
+
+        let storage = Storage::in_memory().unwrap();
+
+        let block: Block = serde_json::from_str(v0_9_0::block::GENESIS).unwrap();
+        let state_update: reply::StateUpdate =
+            serde_json::from_str(v0_11_0::state_update::GENESIS).unwrap();
+        let final_hash_type =
+            TransactionCommitmentFinalHashType::for_version(&block.starknet_version).unwrap();
+        let transaction_commitment =
+            calculate_transaction_commitment(&block.transactions, final_hash_type).unwrap();
+        let event_commitment = calculate_event_commitment(&block.transaction_receipts).unwrap();
+        let signature = BlockCommitmentSignature {
+            r: block_commitment_signature_elem!("0x1001"),
+            s: block_commitment_signature_elem!("0x1002"),
+        };
+
+        let (event_tx, event_rx) = tokio::sync::mpsc::channel(1);
+        event_tx
+            .send(SyncEvent::Block(
+                (Box::new(block), (transaction_commitment, event_commitment)),
+                Box::new(state_update.into()),
+                Box::new(signature),
+                l2::Timings::default(),
+            ))
+            .await
+            .unwrap();
+        drop(event_tx);
+
+        let (tx, _rx) = tokio::sync::watch::channel(Default::default());
+        let context = ConsumerContext {
+            storage: storage.clone(),
+            state: Arc::new(SyncState::default()),
+            pending_data: tx,
+            verify_tree_hashes: false,
+            websocket_txs: None,
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
+        };
+        consumer(event_rx, context).await.unwrap();
+
+        let inconsistency = crate::state::verify::verify_database(
+            &storage,
+            Chain::GoerliTestnet,
+            ChainId::GOERLI_TESTNET,
+            BlockNumber::GENESIS,
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(inconsistency, None);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn blocks_are_not_skipped_after_a_reorg() {
         // A bug caused reorg'd block numbers to be skipped. This
@@ -1253,6 +1813,8 @@ mod tests {
             pending_data: tx,
             verify_tree_hashes: false,
             websocket_txs: None,
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
         };
 
         consumer(event_rx, context).await.unwrap();
@@ -1297,6 +1859,8 @@ mod tests {
             pending_data: tx,
             verify_tree_hashes: false,
             websocket_txs: None,
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
         };
 
         consumer(event_rx, context).await.unwrap();
@@ -1333,6 +1897,8 @@ mod tests {
             pending_data: tx,
             verify_tree_hashes: false,
             websocket_txs: None,
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
         };
 
         consumer(event_rx, context).await.unwrap();
@@ -1372,6 +1938,8 @@ mod tests {
             pending_data: tx,
             verify_tree_hashes: false,
             websocket_txs: None,
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
         };
 
         consumer(event_rx, context).await.unwrap();
@@ -1408,6 +1976,8 @@ mod tests {
             pending_data: tx,
             verify_tree_hashes: false,
             websocket_txs: None,
+            watch_addresses: Vec::new(),
+            node_status: Default::default(),
         };
 
         consumer(event_rx, context).await.unwrap();
@@ -1,24 +1,58 @@
-use std::{num::NonZeroU64, time::Duration};
+use std::{num::NonZeroU64, ops::RangeInclusive, time::Duration};
 
-use pathfinder_common::Chain;
-use pathfinder_ethereum::{EthereumApi, EthereumStateUpdate};
+use anyhow::Context;
+use pathfinder_common::{
+    CallParam, Chain, ChainId, TransactionHash, TransactionNonce, TransactionVersion,
+};
+use pathfinder_crypto::Felt;
+use pathfinder_ethereum::{
+    EthereumApi, JsonRpcError, L1BlockId, L1Finality, L1ToL2MessageLog, MessageLog,
+};
 use pathfinder_retry::Retry;
+use pathfinder_storage::{L1Message, L1MessageDirection, Storage};
 use primitive_types::H160;
+use starknet_gateway_types::reply::transaction::{L1HandlerTransaction, Transaction};
+use starknet_gateway_types::transaction_hash::compute_transaction_hash;
 use tokio::sync::mpsc;
 
 use crate::state::sync::SyncEvent;
 
+/// The maximum number of L1 blocks whose message logs are requested at once. Batches shrink if
+/// the Ethereum endpoint rejects them, see [shrink_batch].
+const MESSAGE_LOGS_BATCH_SIZE: u64 = 1_000;
+/// The maximum number of batches of earlier L1 blocks whose message logs are backfilled per poll,
+/// so that backfilling does not hold up following new L1 blocks.
+const MESSAGE_BACKFILL_BATCHES: u64 = 10;
+/// The maximum number of L1 blocks whose state update logs are requested at once. Batches shrink
+/// if the Ethereum endpoint rejects them, see [shrink_batch].
+const STATE_UPDATE_LOGS_BATCH_SIZE: u64 = 10_000;
+
 #[derive(Clone)]
 pub struct L1SyncContext<EthereumClient> {
     pub ethereum: EthereumClient,
     pub chain: Chain,
+    pub chain_id: ChainId,
     /// The Starknet core contract address on Ethereum
     pub core_address: H160,
     pub poll_interval: Duration,
+    pub storage: Storage,
+    /// The L1 block tag up to which state updates are followed.
+    pub finality: L1Finality,
+    /// The L1 block down to which message logs are backfilled.
+    pub index_messages_from: Option<u64>,
 }
 
-/// Syncs L1 state update logs. Emits [Ethereum state update](EthereumStateUpdate)
-/// which should be handled to update storage and respond to queries.
+/// Syncs L1 state update logs. Emits [SyncEvent::L1StateUpdates] for the state updates logged
+/// by the core contract up to the L1 block tagged with the configured [L1Finality], and
+/// [SyncEvent::L1Reorg] if previously emitted L1 blocks are no longer canonical.
+///
+/// The first time this runs, the core contract's current state is emitted as an
+/// [Ethereum state update](pathfinder_ethereum::EthereumStateUpdate) instead, and logs are
+/// followed from there on.
+///
+/// Also indexes the message logs of finalized L1 blocks, which are emitted as
+/// [SyncEvent::L1Messages]. Indexing starts from the first finalized block seen, and the earlier
+/// blocks down to `index_messages_from` are backfilled a few batches per poll.
 pub async fn sync<T>(
     tx_event: mpsc::Sender<SyncEvent>,
     context: L1SyncContext<T>,
@@ -29,27 +63,724 @@ where
     let L1SyncContext {
         ethereum,
         chain: _,
+        chain_id,
         core_address,
         poll_interval,
+        storage,
+        finality,
+        index_messages_from,
     } = context;
 
-    let mut previous = EthereumStateUpdate::default();
+    let (mut state_head, mut messages_indexed) = tokio::task::block_in_place(|| {
+        let mut db = storage
+            .connection()
+            .context("Creating database connection")?;
+        let tx = db.transaction().context("Creating database transaction")?;
+
+        let state_head = tx.l1_state_head().context("Querying L1 state head")?;
+        let messages_head = tx.l1_messages_head().context("Querying L1 messages head")?;
+        let messages_tail = tx.l1_messages_tail().context("Querying L1 messages tail")?;
+        let messages_indexed = messages_head.map(|head| messages_tail.unwrap_or(head)..=head);
+
+        anyhow::Ok((state_head, messages_indexed))
+    })?;
 
     loop {
-        let state_update = Retry::exponential(
-            || async { ethereum.get_starknet_state(&core_address).await },
+        state_head = Retry::exponential(
+            || {
+                follow_state_updates(
+                    &ethereum,
+                    &tx_event,
+                    &storage,
+                    core_address,
+                    finality,
+                    state_head,
+                )
+            },
             NonZeroU64::new(1).unwrap(),
         )
         .factor(NonZeroU64::new(2).unwrap())
         .max_delay(poll_interval / 2)
-        .when(|_| true)
+        // Error responses are not transient, so L1 sync fails and is restarted after a delay
+        // instead of repeating the same request forever.
+        .when(|e| !e.is::<JsonRpcError>())
         .await?;
 
-        if previous != state_update {
-            previous = state_update.clone();
-            tx_event.send(SyncEvent::L1Update(state_update)).await?;
+        // Message indexing is best effort, and must not hold up the L1 state updates.
+        if let Err(e) = sync_messages(
+            &ethereum,
+            &tx_event,
+            core_address,
+            chain_id,
+            &mut messages_indexed,
+            index_messages_from,
+        )
+        .await
+        {
+            tracing::warn!(error=?e, "Failed to index L1 messages");
+        }
+
+        ethereum.wait_for_update(poll_interval).await;
+    }
+}
+
+/// Emits the state update logs of the L1 blocks after `head` up to the L1 block tagged with
+/// `finality`, and returns the new head.
+async fn follow_state_updates<T: EthereumApi>(
+    ethereum: &T,
+    tx_event: &mpsc::Sender<SyncEvent>,
+    storage: &Storage,
+    core_address: H160,
+    finality: L1Finality,
+    head: Option<L1BlockId>,
+) -> anyhow::Result<Option<L1BlockId>> {
+    let tip = ethereum
+        .get_block(finality)
+        .await
+        .context("Fetching L1 block")?;
+
+    let Some(mut head) = head else {
+        // Nothing has been followed yet, so start from the current state onwards.
+        let state = ethereum
+            .get_starknet_state_at(&core_address, tip.hash)
+            .await
+            .context("Fetching L1 state")?;
+        tx_event.send(SyncEvent::L1Update(state)).await?;
+        tx_event
+            .send(SyncEvent::L1StateUpdates {
+                logs: Vec::new(),
+                head: tip,
+            })
+            .await?;
+        return Ok(Some(tip));
+    };
+
+    let canonical = ethereum
+        .get_block_hash(head.number)
+        .await
+        .context("Fetching L1 block hash")?;
+    if canonical != Some(head.hash) {
+        let fork = find_fork(ethereum, storage, head.number).await?;
+        tracing::info!(reorged=%head.number, fork=?fork.map(|fork| fork.number), "L1 reorg detected");
+        tx_event.send(SyncEvent::L1Reorg(fork)).await?;
+
+        match fork {
+            Some(fork) => head = fork,
+            // Start over from the current state.
+            None => return Ok(None),
+        }
+    }
+
+    let mut batch_size = STATE_UPDATE_LOGS_BATCH_SIZE;
+    while head.number < tip.number {
+        let to = tip.number.min(head.number + batch_size);
+        // The hash is fetched before the logs so that a reorg in between is detected by the
+        // next poll.
+        let to_hash = if to == tip.number {
+            tip.hash
+        } else {
+            ethereum
+                .get_block_hash(to)
+                .await
+                .context("Fetching L1 block hash")?
+                .context("L1 block is missing")?
+        };
+        let logs = match ethereum
+            .get_state_update_logs(&core_address, head.number + 1, to)
+            .await
+        {
+            Ok(logs) => logs,
+            Err(e) if shrink_batch(&mut batch_size, &e) => continue,
+            Err(e) => {
+                return Err(e.context(format!(
+                    "Fetching state update logs of L1 blocks {}-{to}",
+                    head.number + 1
+                )))
+            }
+        };
+
+        head = L1BlockId {
+            number: to,
+            hash: to_hash,
+        };
+        tx_event
+            .send(SyncEvent::L1StateUpdates { logs, head })
+            .await?;
+    }
+
+    Ok(Some(head))
+}
+
+/// Finds the latest L1 block before `reorged` which logged a stored state update and is still
+/// canonical.
+async fn find_fork<T: EthereumApi>(
+    ethereum: &T,
+    storage: &Storage,
+    reorged: u64,
+) -> anyhow::Result<Option<L1BlockId>> {
+    let mut db = storage
+        .connection()
+        .context("Creating database connection")?;
+
+    let mut before = reorged;
+    loop {
+        let candidate = tokio::task::block_in_place(|| {
+            db.transaction()
+                .context("Creating database transaction")?
+                .l1_state_block_before(before)
+                .context("Querying L1 state updates")
+        })?;
+        let Some(candidate) = candidate else {
+            return Ok(None);
+        };
+
+        let canonical = ethereum
+            .get_block_hash(candidate.number)
+            .await
+            .context("Fetching L1 block hash")?;
+        if canonical == Some(candidate.hash) {
+            return Ok(Some(candidate));
         }
 
-        tokio::time::sleep(poll_interval).await;
+        before = candidate.number;
+    }
+}
+
+/// Emits the message logs of the finalized L1 blocks after the `indexed` ones, and backfills those
+/// of the earlier blocks down to `backfill_to`. `indexed` is extended as blocks are emitted.
+async fn sync_messages<T: EthereumApi>(
+    ethereum: &T,
+    tx_event: &mpsc::Sender<SyncEvent>,
+    core_address: H160,
+    chain_id: ChainId,
+    indexed: &mut Option<RangeInclusive<u64>>,
+    backfill_to: Option<u64>,
+) -> anyhow::Result<()> {
+    let finalized = ethereum
+        .get_finalized_block_number()
+        .await
+        .context("Fetching finalized L1 block")?;
+
+    let (mut tail, mut head) = match indexed.clone() {
+        Some(indexed) => indexed.into_inner(),
+        None => {
+            // Nothing has been indexed yet, so start from the current finalized block.
+            index_messages(
+                ethereum,
+                tx_event,
+                core_address,
+                chain_id,
+                finalized..=finalized,
+            )
+            .await?;
+            *indexed = Some(finalized..=finalized);
+            (finalized, finalized)
+        }
+    };
+
+    let mut batch_size = MESSAGE_LOGS_BATCH_SIZE;
+    while head < finalized {
+        let to = finalized.min(head + batch_size);
+        match index_messages(ethereum, tx_event, core_address, chain_id, head + 1..=to).await {
+            Ok(()) => {}
+            Err(e) if shrink_batch(&mut batch_size, &e) => continue,
+            Err(e) => return Err(e),
+        }
+
+        head = to;
+        *indexed = Some(tail..=head);
+    }
+
+    let Some(backfill_to) = backfill_to else {
+        return Ok(());
+    };
+    let mut batches = 0;
+    while tail > backfill_to && batches < MESSAGE_BACKFILL_BATCHES {
+        let from = backfill_to.max(tail.saturating_sub(batch_size));
+        match index_messages(ethereum, tx_event, core_address, chain_id, from..=tail - 1).await {
+            Ok(()) => {}
+            Err(e) if shrink_batch(&mut batch_size, &e) => continue,
+            Err(e) => return Err(e),
+        }
+
+        batches += 1;
+        tail = from;
+        *indexed = Some(tail..=head);
+    }
+
+    Ok(())
+}
+
+/// Emits the message logs of the given L1 `blocks`.
+async fn index_messages<T: EthereumApi>(
+    ethereum: &T,
+    tx_event: &mpsc::Sender<SyncEvent>,
+    core_address: H160,
+    chain_id: ChainId,
+    blocks: RangeInclusive<u64>,
+) -> anyhow::Result<()> {
+    let logs = ethereum
+        .get_message_logs(&core_address, *blocks.start(), *blocks.end())
+        .await
+        .with_context(|| {
+            format!(
+                "Fetching message logs of L1 blocks {}-{}",
+                blocks.start(),
+                blocks.end()
+            )
+        })?;
+
+    let messages = logs
+        .into_iter()
+        .map(|log| l1_message(log, chain_id))
+        .collect();
+    tx_event
+        .send(SyncEvent::L1Messages { messages, blocks })
+        .await?;
+
+    Ok(())
+}
+
+/// Halves the number of L1 blocks whose logs are requested at once if the Ethereum endpoint
+/// rejected the request, which is usually due to a limit on the range of blocks or on the number
+/// of logs per request. Returns false if the request should not be retried with a smaller batch.
+fn shrink_batch(batch_size: &mut u64, error: &anyhow::Error) -> bool {
+    if *batch_size <= 1 || !error.is::<JsonRpcError>() {
+        return false;
+    }
+
+    *batch_size /= 2;
+    tracing::debug!(error=?error, %batch_size, "Requesting the logs of fewer L1 blocks at once");
+    true
+}
+
+fn l1_message(log: MessageLog, chain_id: ChainId) -> L1Message {
+    match log {
+        MessageLog::L1ToL2(log) => L1Message {
+            l1_transaction_hash: log.l1_transaction_hash,
+            message_hash: log.message_hash(),
+            direction: L1MessageDirection::L1ToL2,
+            l1_block_number: log.l1_block_number,
+            l2_transaction_hash: Some(l1_handler_hash(&log, chain_id)),
+        },
+        MessageLog::ConsumedL2ToL1(log) => L1Message {
+            l1_transaction_hash: log.l1_transaction_hash,
+            message_hash: log.message_hash(),
+            direction: L1MessageDirection::ConsumedL2ToL1,
+            l1_block_number: log.l1_block_number,
+            l2_transaction_hash: None,
+        },
+    }
+}
+
+/// The hash of the `L1_HANDLER` transaction which handles the message on L2.
+fn l1_handler_hash(log: &L1ToL2MessageLog, chain_id: ChainId) -> TransactionHash {
+    // An Ethereum address always fits in a felt.
+    let from_address = Felt::from_be_slice(log.from_address.0.as_bytes()).unwrap();
+    let calldata = std::iter::once(CallParam(from_address))
+        .chain(log.payload.iter().map(|elem| CallParam(elem.0)))
+        .collect();
+
+    let transaction = Transaction::L1Handler(L1HandlerTransaction {
+        contract_address: log.to_address,
+        entry_point_selector: log.selector,
+        nonce: TransactionNonce(log.nonce.0),
+        calldata,
+        transaction_hash: TransactionHash::ZERO,
+        version: TransactionVersion::ZERO,
+    });
+
+    compute_transaction_hash(&transaction, chain_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_common::{BlockNumber, EthereumAddress};
+    use pathfinder_ethereum::{
+        ConsumedL2ToL1MessageLog, EthereumChain, EthereumStateUpdate, StateUpdateLog,
+    };
+    use primitive_types::H256;
+
+    use super::*;
+
+    /// An Ethereum chain whose canonical blocks have the given hashes, serving fixed logs.
+    #[derive(Default)]
+    struct FakeEthereum {
+        /// The canonical block hashes by block number, the last one is the tip.
+        hashes: Vec<H256>,
+        finalized: u64,
+        state_update_logs: Vec<StateUpdateLog>,
+        message_logs: Vec<MessageLog>,
+        /// Requests for the logs of more blocks than this are rejected.
+        max_logs_range: Option<u64>,
+    }
+
+    impl FakeEthereum {
+        fn check_logs_range(&self, from_block: u64, to_block: u64) -> anyhow::Result<()> {
+            match self.max_logs_range {
+                Some(max) if to_block - from_block + 1 > max => Err(JsonRpcError {
+                    code: -32005,
+                    message: "block range too large".to_owned(),
+                }
+                .into()),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl EthereumApi for FakeEthereum {
+        async fn get_starknet_state(&self, _: &H160) -> anyhow::Result<EthereumStateUpdate> {
+            unimplemented!()
+        }
+
+        async fn get_chain(&self) -> anyhow::Result<EthereumChain> {
+            unimplemented!()
+        }
+
+        async fn get_finalized_block_number(&self) -> anyhow::Result<u64> {
+            Ok(self.finalized)
+        }
+
+        async fn get_starknet_state_at(
+            &self,
+            _: &H160,
+            _: H256,
+        ) -> anyhow::Result<EthereumStateUpdate> {
+            Ok(EthereumStateUpdate::default())
+        }
+
+        async fn get_block(&self, _: L1Finality) -> anyhow::Result<L1BlockId> {
+            let hash = *self.hashes.last().unwrap();
+            Ok(L1BlockId {
+                number: self.hashes.len() as u64 - 1,
+                hash,
+            })
+        }
+
+        async fn get_block_hash(&self, number: u64) -> anyhow::Result<Option<H256>> {
+            Ok(self.hashes.get(number as usize).copied())
+        }
+
+        async fn get_state_update_logs(
+            &self,
+            _: &H160,
+            from_block: u64,
+            to_block: u64,
+        ) -> anyhow::Result<Vec<StateUpdateLog>> {
+            self.check_logs_range(from_block, to_block)?;
+            Ok(self
+                .state_update_logs
+                .iter()
+                .filter(|log| (from_block..=to_block).contains(&log.l1_block_number))
+                .cloned()
+                .collect())
+        }
+
+        async fn get_message_logs(
+            &self,
+            _: &H160,
+            from_block: u64,
+            to_block: u64,
+        ) -> anyhow::Result<Vec<MessageLog>> {
+            self.check_logs_range(from_block, to_block)?;
+            Ok(self
+                .message_logs
+                .iter()
+                .filter(|log| {
+                    let MessageLog::ConsumedL2ToL1(log) = log else {
+                        unimplemented!()
+                    };
+                    (from_block..=to_block).contains(&log.l1_block_number)
+                })
+                .cloned()
+                .collect())
+        }
+    }
+
+    /// The hash of L1 block `number`, which differs after a reorg.
+    fn l1_hash(number: u64, reorged: bool) -> H256 {
+        H256::from_low_u64_be(number + if reorged { 1_000_000 } else { 0 })
+    }
+
+    /// The hashes of L1 blocks 0 up to `tip`, where the blocks from `reorged_from` on were
+    /// replaced by a reorg.
+    fn l1_chain(tip: u64, reorged_from: u64) -> Vec<H256> {
+        (0..=tip)
+            .map(|number| l1_hash(number, number >= reorged_from))
+            .collect()
+    }
+
+    fn state_update_log(starknet_block: u64, l1_block: u64, reorged: bool) -> StateUpdateLog {
+        StateUpdateLog {
+            update: EthereumStateUpdate {
+                block_number: BlockNumber::new_or_panic(starknet_block),
+                ..Default::default()
+            },
+            l1_block_number: l1_block,
+            l1_block_hash: l1_hash(l1_block, reorged),
+            l1_transaction_hash: H256::from_low_u64_be(l1_block),
+        }
+    }
+
+    /// Storage with the state update logs of the original chain in L1 blocks 10, 20 and 30.
+    fn storage_with_state_updates() -> Storage {
+        let storage = Storage::in_memory().unwrap();
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        for (starknet_block, l1_block) in [(1, 10), (2, 20), (3, 30)] {
+            tx.insert_l1_state_log(&state_update_log(starknet_block, l1_block, false))
+                .unwrap();
+        }
+        tx.commit().unwrap();
+        storage
+    }
+
+    /// Runs [follow_state_updates] from the original L1 block 30, and returns its result and the
+    /// emitted events.
+    async fn follow(
+        ethereum: &FakeEthereum,
+        storage: &Storage,
+    ) -> (Option<L1BlockId>, Vec<SyncEvent>) {
+        let (tx_event, mut rx_event) = mpsc::channel(100);
+        let head = L1BlockId {
+            number: 30,
+            hash: l1_hash(30, false),
+        };
+        let result = follow_state_updates(
+            ethereum,
+            &tx_event,
+            storage,
+            H160::zero(),
+            L1Finality::Latest,
+            Some(head),
+        )
+        .await
+        .unwrap();
+        drop(tx_event);
+
+        let mut events = Vec::new();
+        while let Some(event) = rx_event.recv().await {
+            events.push(event);
+        }
+        (result, events)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reorg_is_followed_from_the_fork() {
+        let storage = storage_with_state_updates();
+        let ethereum = FakeEthereum {
+            hashes: l1_chain(40, 25),
+            state_update_logs: vec![state_update_log(3, 35, true)],
+            ..Default::default()
+        };
+
+        let (head, events) = follow(&ethereum, &storage).await;
+
+        let fork = L1BlockId {
+            number: 20,
+            hash: l1_hash(20, false),
+        };
+        let tip = L1BlockId {
+            number: 40,
+            hash: l1_hash(40, true),
+        };
+        assert_eq!(head, Some(tip));
+        assert_matches::assert_matches!(
+            events.as_slice(),
+            [
+                SyncEvent::L1Reorg(Some(reorg_fork)),
+                SyncEvent::L1StateUpdates { logs, head },
+            ] if *reorg_fork == fork
+                && *logs == vec![state_update_log(3, 35, true)]
+                && *head == tip
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reorg_of_all_state_updates_starts_over() {
+        let storage = storage_with_state_updates();
+        let ethereum = FakeEthereum {
+            hashes: l1_chain(40, 5),
+            ..Default::default()
+        };
+
+        let (head, events) = follow(&ethereum, &storage).await;
+
+        assert_eq!(head, None);
+        assert_matches::assert_matches!(events.as_slice(), [SyncEvent::L1Reorg(None)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn find_fork_skips_reorged_state_updates() {
+        let storage = storage_with_state_updates();
+
+        let ethereum = FakeEthereum {
+            hashes: l1_chain(40, 15),
+            ..Default::default()
+        };
+        let fork = find_fork(&ethereum, &storage, 30).await.unwrap();
+        assert_eq!(
+            fork,
+            Some(L1BlockId {
+                number: 10,
+                hash: l1_hash(10, false),
+            })
+        );
+
+        // Only state updates before the reorged block are considered.
+        let ethereum = FakeEthereum {
+            hashes: l1_chain(40, 35),
+            ..Default::default()
+        };
+        let fork = find_fork(&ethereum, &storage, 20).await.unwrap();
+        assert_eq!(
+            fork,
+            Some(L1BlockId {
+                number: 10,
+                hash: l1_hash(10, false),
+            })
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rejected_state_update_log_batches_shrink() {
+        let storage = storage_with_state_updates();
+        let ethereum = FakeEthereum {
+            hashes: l1_chain(40, 41),
+            state_update_logs: vec![state_update_log(4, 33, false)],
+            max_logs_range: Some(3),
+            ..Default::default()
+        };
+
+        let (head, events) = follow(&ethereum, &storage).await;
+
+        assert_eq!(head.map(|head| head.number), Some(40));
+        let mut logs = Vec::new();
+        let mut from = 31;
+        for event in events {
+            let SyncEvent::L1StateUpdates { logs: l, head } = event else {
+                panic!("Unexpected event");
+            };
+            assert!(head.number - from < 3);
+            from = head.number + 1;
+            logs.extend(l);
+        }
+        assert_eq!(from, 41);
+        assert_eq!(logs, vec![state_update_log(4, 33, false)]);
+    }
+
+    fn consumed_message(l1_block_number: u64) -> MessageLog {
+        MessageLog::ConsumedL2ToL1(ConsumedL2ToL1MessageLog {
+            l1_block_number,
+            l1_transaction_hash: H256::from_low_u64_be(l1_block_number),
+            from_address: contract_address!("0x1"),
+            to_address: EthereumAddress(H160::from_low_u64_be(2)),
+            payload: vec![],
+        })
+    }
+
+    /// Runs [sync_messages] and returns the emitted L1 block ranges and messages.
+    async fn sync(
+        ethereum: &FakeEthereum,
+        indexed: &mut Option<RangeInclusive<u64>>,
+        backfill_to: Option<u64>,
+    ) -> (Vec<RangeInclusive<u64>>, Vec<u64>) {
+        let (tx_event, mut rx_event) = mpsc::channel(100);
+        sync_messages(
+            ethereum,
+            &tx_event,
+            H160::zero(),
+            ChainId::SEPOLIA_TESTNET,
+            indexed,
+            backfill_to,
+        )
+        .await
+        .unwrap();
+        drop(tx_event);
+
+        let mut ranges = Vec::new();
+        let mut messages = Vec::new();
+        while let Some(event) = rx_event.recv().await {
+            let SyncEvent::L1Messages {
+                messages: m,
+                blocks,
+            } = event
+            else {
+                panic!("Unexpected event");
+            };
+            ranges.push(blocks);
+            messages.extend(m.into_iter().map(|m| m.l1_block_number));
+        }
+        (ranges, messages)
+    }
+
+    #[tokio::test]
+    async fn messages_are_followed_in_batches() {
+        let ethereum = FakeEthereum {
+            finalized: 2500,
+            message_logs: vec![consumed_message(100), consumed_message(2000)],
+            ..Default::default()
+        };
+        let mut indexed = Some(100..=100);
+
+        let (ranges, messages) = sync(&ethereum, &mut indexed, None).await;
+
+        assert_eq!(ranges, vec![101..=1100, 1101..=2100, 2101..=2500]);
+        assert_eq!(messages, vec![2000]);
+        assert_eq!(indexed, Some(100..=2500));
+    }
+
+    #[tokio::test]
+    async fn messages_are_backfilled() {
+        let ethereum = FakeEthereum {
+            finalized: 2500,
+            message_logs: vec![consumed_message(10), consumed_message(2500)],
+            ..Default::default()
+        };
+        let mut indexed = None;
+
+        let (ranges, messages) = sync(&ethereum, &mut indexed, Some(5)).await;
+
+        assert_eq!(ranges, vec![2500..=2500, 1500..=2499, 500..=1499, 5..=499]);
+        assert_eq!(messages, vec![2500, 10]);
+        assert_eq!(indexed, Some(5..=2500));
+
+        // Everything has been backfilled.
+        let (ranges, _) = sync(&ethereum, &mut indexed, Some(5)).await;
+        assert_eq!(ranges, vec![]);
+    }
+
+    #[tokio::test]
+    async fn rejected_message_log_batches_shrink() {
+        let ethereum = FakeEthereum {
+            finalized: 1000,
+            max_logs_range: Some(300),
+            ..Default::default()
+        };
+        let mut indexed = Some(100..=100);
+
+        let (ranges, _) = sync(&ethereum, &mut indexed, None).await;
+
+        assert_eq!(ranges, vec![101..=350, 351..=600, 601..=850, 851..=1000]);
+        assert_eq!(indexed, Some(100..=1000));
+    }
+
+    #[tokio::test]
+    async fn backfill_is_limited_per_poll() {
+        let ethereum = FakeEthereum {
+            finalized: 100_000,
+            ..Default::default()
+        };
+        let mut indexed = Some(50_000..=100_000);
+
+        let (ranges, _) = sync(&ethereum, &mut indexed, Some(0)).await;
+
+        assert_eq!(ranges.len() as u64, MESSAGE_BACKFILL_BATCHES);
+        assert_eq!(
+            indexed,
+            Some(50_000 - MESSAGE_BACKFILL_BATCHES * MESSAGE_LOGS_BATCH_SIZE..=100_000)
+        );
     }
 }
@@ -0,0 +1,347 @@
+//! Offline verification of the data stored in a database.
+
+use anyhow::Context;
+use pathfinder_common::{
+    BlockHash, BlockHeader, BlockNumber, Chain, ChainId, EventCommitment, TransactionCommitment,
+};
+use pathfinder_storage::{BlockId, Storage, Transaction};
+use starknet_gateway_types::class_hash::compute_class_hash;
+use starknet_gateway_types::reply::transaction as gateway;
+use starknet_gateway_types::reply::{Block, Status};
+
+use crate::state::block_hash::{
+    calculate_event_commitment, calculate_transaction_commitment, verify_block_hash,
+    TransactionCommitmentFinalHashType, VerifyResult,
+};
+
+/// Describes the problems found in the first inconsistent block.
+#[derive(Debug, PartialEq)]
+pub struct Inconsistency {
+    pub block: BlockNumber,
+    pub problems: Vec<String>,
+}
+
+impl std::fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Block {} is inconsistent: {}",
+            self.block,
+            self.problems.join("; ")
+        )
+    }
+}
+
+/// Verifies the stored blocks starting at `from`, up to and including the latest block.
+///
+/// For each block this recomputes the block hash, the transaction and event commitments, the
+/// transaction hashes and the state commitment from the stored trie roots, and verifies the
+/// hashes of the classes declared in the block against their stored definitions. Checks which
+/// need pruned transaction data or tries are skipped.
+///
+/// Returns the first inconsistent block, or [None] if all blocks are consistent. `progress` is
+/// called after each verified block.
+pub fn verify_database(
+    storage: &Storage,
+    chain: Chain,
+    chain_id: ChainId,
+    from: BlockNumber,
+    mut progress: impl FnMut(BlockNumber),
+) -> anyhow::Result<Option<Inconsistency>> {
+    let mut db = storage
+        .connection()
+        .context("Opening database connection")?;
+
+    let Some((latest, _)) = db
+        .transaction()?
+        .block_id(BlockId::Latest)
+        .context("Querying latest block")?
+    else {
+        return Ok(None);
+    };
+
+    let mut parent_hash = match from.parent() {
+        Some(parent) => Some(
+            db.transaction()?
+                .block_id(parent.into())
+                .context("Querying parent block")?
+                .context("Parent block is missing")?
+                .1,
+        ),
+        None => None,
+    };
+
+    for number in from.get()..=latest.get() {
+        let number = BlockNumber::new_or_panic(number);
+        let tx = db.transaction().context("Creating database transaction")?;
+
+        let (header, problems) = verify_block(&tx, number, parent_hash, chain, chain_id)
+            .with_context(|| format!("Verifying block {number}"))?;
+        if !problems.is_empty() {
+            return Ok(Some(Inconsistency {
+                block: number,
+                problems,
+            }));
+        }
+
+        parent_hash = header.map(|header| header.hash);
+        progress(number);
+    }
+
+    Ok(None)
+}
+
+/// Verifies a single block, returning its header (if any) and the problems found.
+fn verify_block(
+    tx: &Transaction<'_>,
+    number: BlockNumber,
+    parent_hash: Option<BlockHash>,
+    chain: Chain,
+    chain_id: ChainId,
+) -> anyhow::Result<(Option<BlockHeader>, Vec<String>)> {
+    let Some(header) = tx
+        .block_header(number.into())
+        .context("Querying block header")?
+    else {
+        return Ok((None, vec!["block header is missing".to_owned()]));
+    };
+
+    let mut problems = Vec::new();
+
+    if let Some(parent_hash) = parent_hash {
+        if header.parent_hash != parent_hash {
+            problems.push(format!(
+                "parent hash {} does not match the previous block's hash {parent_hash}",
+                header.parent_hash
+            ));
+        }
+    }
+
+    // The block hash depends on the transactions and receipts, so none of these can be checked
+    // once they are pruned.
+    let transactions_pruned = tx
+        .transaction_prune_horizon()
+        .context("Querying transaction prune horizon")?
+        .is_some_and(|horizon| number < horizon);
+    if !transactions_pruned {
+        let Some(transaction_data) = tx
+            .transaction_data_for_block(number.into())
+            .context("Querying transaction data")?
+        else {
+            problems.push("transaction data is missing".to_owned());
+            return Ok((Some(header), problems));
+        };
+        problems.extend(verify_transaction_data(
+            &header,
+            transaction_data,
+            chain,
+            chain_id,
+        )?);
+    }
+
+    let tries_pruned = tx
+        .trie_prune_horizon()
+        .context("Querying trie prune horizon")?
+        .is_some_and(|horizon| number < horizon);
+    if !tries_pruned {
+        let state_commitment = tx
+            .state_commitment_from_tries(number)
+            .context("Calculating state commitment")?;
+        if state_commitment != header.state_commitment {
+            problems.push(format!(
+                "state commitment {} does not match the stored state tries' {state_commitment}",
+                header.state_commitment
+            ));
+        }
+    }
+
+    let state_update = tx
+        .state_update(number.into())
+        .context("Querying state update")?
+        .context("State update is missing")?;
+    let declared = state_update.declared_cairo_classes.iter().copied().chain(
+        state_update
+            .declared_sierra_classes
+            .keys()
+            .map(|sierra| pathfinder_common::ClassHash(sierra.0)),
+    );
+    for class_hash in declared {
+        let Some(definition) = tx
+            .class_definition(class_hash)
+            .context("Querying class definition")?
+        else {
+            problems.push(format!("class {class_hash} definition is missing"));
+            continue;
+        };
+
+        match compute_class_hash(&definition) {
+            Ok(computed) if computed.hash() == class_hash => {}
+            Ok(computed) => problems.push(format!(
+                "class {class_hash} definition hashes to {}",
+                computed.hash()
+            )),
+            Err(e) => problems.push(format!("class {class_hash} definition is invalid: {e}")),
+        }
+    }
+
+    Ok((Some(header), problems))
+}
+
+/// Verifies the block's transactions and receipts against its header, returning the problems
+/// found.
+fn verify_transaction_data(
+    header: &BlockHeader,
+    transaction_data: Vec<(gateway::Transaction, gateway::Receipt)>,
+    chain: Chain,
+    chain_id: ChainId,
+) -> anyhow::Result<Vec<String>> {
+    let number = header.number;
+    let mut problems = Vec::new();
+
+    let (transactions, receipts): (Vec<_>, Vec<_>) = transaction_data.into_iter().unzip();
+
+    if header.transaction_count != transactions.len() {
+        problems.push(format!(
+            "header transaction count {} does not match the {} stored transactions",
+            header.transaction_count,
+            transactions.len()
+        ));
+    }
+    let event_count = receipts.iter().map(|r| r.events.len()).sum::<usize>();
+    if header.event_count != event_count {
+        problems.push(format!(
+            "header event count {} does not match the {event_count} stored events",
+            header.event_count
+        ));
+    }
+
+    for (i, transaction) in transactions.iter().enumerate() {
+        use starknet_gateway_types::transaction_hash::{verify, VerifyResult};
+
+        if let VerifyResult::Mismatch(calculated) = verify(transaction, chain_id, number) {
+            problems.push(format!(
+                "transaction {i} has hash {} but {calculated} was calculated",
+                transaction.hash()
+            ));
+        }
+    }
+
+    // Commitments are only stored if they could be verified during sync.
+    let final_hash_type =
+        TransactionCommitmentFinalHashType::for_version(&header.starknet_version)?;
+    let transaction_commitment = calculate_transaction_commitment(&transactions, final_hash_type)?;
+    if header.transaction_commitment != TransactionCommitment::ZERO
+        && header.transaction_commitment != transaction_commitment
+    {
+        problems.push(format!(
+            "transaction commitment {} does not match the calculated {transaction_commitment}",
+            header.transaction_commitment
+        ));
+    }
+    let event_commitment = calculate_event_commitment(&receipts)?;
+    if header.event_commitment != EventCommitment::ZERO
+        && header.event_commitment != event_commitment
+    {
+        problems.push(format!(
+            "event commitment {} does not match the calculated {event_commitment}",
+            header.event_commitment
+        ));
+    }
+
+    let block = Block {
+        block_hash: header.hash,
+        block_number: header.number,
+        eth_l1_gas_price: Some(header.eth_l1_gas_price),
+        strk_l1_gas_price: Some(header.strk_l1_gas_price),
+        parent_block_hash: header.parent_hash,
+        sequencer_address: Some(header.sequencer_address),
+        state_commitment: header.state_commitment,
+        status: Status::AcceptedOnL2,
+        timestamp: header.timestamp,
+        transaction_receipts: receipts,
+        transactions,
+        starknet_version: header.starknet_version.clone(),
+    };
+    if verify_block_hash(&block, chain, chain_id, header.hash)? == VerifyResult::Mismatch {
+        problems.push(format!(
+            "block hash {} does not match the calculated hash",
+            header.hash
+        ));
+    }
+
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_database_is_consistent() {
+        let storage = Storage::in_memory().unwrap();
+
+        let result = verify_database(
+            &storage,
+            Chain::Custom,
+            ChainId::SEPOLIA_TESTNET,
+            BlockNumber::GENESIS,
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn reports_first_inconsistent_block() {
+        let storage = Storage::in_memory().unwrap();
+        pathfinder_storage::fake::with_n_blocks(&storage, 3);
+
+        let inconsistency = verify_database(
+            &storage,
+            Chain::Custom,
+            ChainId::SEPOLIA_TESTNET,
+            BlockNumber::new_or_panic(1),
+            |_| {},
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(inconsistency.block, BlockNumber::new_or_panic(1));
+        assert!(!inconsistency.problems.is_empty());
+    }
+
+    #[test]
+    fn skips_pruned_transaction_data() {
+        let storage = Storage::in_memory().unwrap();
+        pathfinder_storage::fake::with_n_blocks(&storage, 3);
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        tx.prune_transactions(BlockNumber::new_or_panic(2)).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.transaction().unwrap();
+        for number in [0, 1] {
+            let (header, problems) = verify_block(
+                &tx,
+                BlockNumber::new_or_panic(number),
+                None,
+                Chain::Custom,
+                ChainId::SEPOLIA_TESTNET,
+            )
+            .unwrap();
+            assert!(header.is_some());
+            assert!(!problems
+                .iter()
+                .any(|problem| problem.contains("transaction") || problem.contains("block hash")));
+        }
+        drop(tx);
+
+        verify_database(
+            &storage,
+            Chain::Custom,
+            ChainId::SEPOLIA_TESTNET,
+            BlockNumber::GENESIS,
+            |_| {},
+        )
+        .unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 use crate::gas_price;
 pub use crate::jsonrpc::websocket::WebsocketContext;
+use crate::middleware::rate_limit::{RateLimitConfig, RateLimiter};
 use crate::pending::PendingData;
 use crate::pending::PendingWatcher;
 use crate::SyncState;
@@ -7,6 +8,7 @@ use pathfinder_common::ChainId;
 use pathfinder_storage::Storage;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
 type SequencerClient = starknet_gateway_client::Client;
 use tokio::sync::watch as tokio_watch;
@@ -22,6 +24,9 @@ pub struct RpcContext {
     pub sequencer: SequencerClient,
     pub websocket: Option<WebsocketContext>,
     pub batch_concurrency_limit: NonZeroUsize,
+    /// Requests taking longer than this are logged along with their params.
+    pub slow_request_threshold: Option<Duration>,
+    pub rate_limiter: Option<RateLimiter>,
 }
 
 impl RpcContext {
@@ -45,6 +50,8 @@ impl RpcContext {
             sequencer,
             websocket: None,
             batch_concurrency_limit,
+            slow_request_threshold: None,
+            rate_limiter: None,
         }
     }
 
@@ -118,4 +125,18 @@ impl RpcContext {
             ..self
         }
     }
+
+    pub fn with_slow_request_threshold(self, threshold: Duration) -> Self {
+        Self {
+            slow_request_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    pub fn with_rate_limits(self, config: RateLimitConfig) -> Self {
+        Self {
+            rate_limiter: Some(RateLimiter::new(config)),
+            ..self
+        }
+    }
 }
@@ -76,6 +76,10 @@ pub enum ApplicationError {
     UnexpectedError { data: String },
     #[error("Too many storage keys requested")]
     ProofLimitExceeded { limit: u32, requested: u32 },
+    #[error("Block pruned")]
+    BlockPruned { oldest_block: u64 },
+    #[error("Data pruned")]
+    DataPruned { oldest_block: u64 },
     #[error("Internal error")]
     GatewayError(starknet_gateway_types::error::StarknetError),
     #[error("Transaction execution error")]
@@ -131,6 +135,8 @@ impl ApplicationError {
             ApplicationError::UnexpectedError { .. } => 63,
             // doc/rpc/pathfinder_rpc_api.json
             ApplicationError::ProofLimitExceeded { .. } => 10000,
+            ApplicationError::BlockPruned { .. } => 10001,
+            ApplicationError::DataPruned { .. } => 10002,
             // https://www.jsonrpc.org/specification#error_object
             ApplicationError::GatewayError(_)
             | ApplicationError::Internal(_)
@@ -206,6 +212,12 @@ impl ApplicationError {
                 "limit": limit,
                 "requested": requested,
             })),
+            ApplicationError::BlockPruned { oldest_block } => Some(json!({
+                "oldest_block": oldest_block,
+            })),
+            ApplicationError::DataPruned { oldest_block } => Some(json!({
+                "oldest_block": oldest_block,
+            })),
             ApplicationError::ValidationFailureV06(error) => Some(json!(error)),
         }
     }
@@ -288,6 +300,13 @@ macro_rules! generate_rpc_error_subset {
         generate_rpc_error_subset!(@from_anyhow, $enum_name);
         generate_rpc_error_subset!(@from_def, $enum_name,);
     };
+    // Entry-point for the errors of methods which read transaction data, which may have been
+    // pruned. Adds a `DataPruned` variant which reads of pruned data are converted into.
+    ($enum_name:ident: $($subset:ident),* ; DataPruned) => {
+        generate_rpc_error_subset!(@enum_def_pruned, $enum_name, $($subset),*);
+        generate_rpc_error_subset!(@from_anyhow_pruned, $enum_name);
+        generate_rpc_error_subset!(@from_def_pruned, $enum_name, $($subset),*);
+    };
     // Main entry-point for the macro
     ($enum_name:ident: $($subset:tt),+) => {
         generate_rpc_error_subset!(@enum_def, $enum_name, $($subset),+);
@@ -305,6 +324,19 @@ macro_rules! generate_rpc_error_subset {
             $($subset),*
         }
     };
+    // Same as `@enum_def`, with an additional `DataPruned` variant.
+    (@enum_def_pruned, $enum_name:ident, $($subset:tt),*) => {
+        #[derive(Debug)]
+        pub enum $enum_name {
+            /// See [`crate::error::ApplicationError::Internal`]
+            Internal(anyhow::Error),
+            /// See [`crate::error::ApplicationError::Custom`]
+            Custom(anyhow::Error),
+            /// See [`crate::error::ApplicationError::DataPruned`]
+            DataPruned { oldest_block: pathfinder_common::BlockNumber },
+            $($subset),*
+        }
+    };
     // Generates From<anyhow::Error>, nothing tricky here.
     (@from_anyhow, $enum_name:ident) => {
         impl From<anyhow::Error> for $enum_name {
@@ -313,6 +345,19 @@ macro_rules! generate_rpc_error_subset {
             }
         }
     };
+    // Generates From<anyhow::Error>, converting the storage's [pathfinder_storage::DataPruned].
+    (@from_anyhow_pruned, $enum_name:ident) => {
+        impl From<anyhow::Error> for $enum_name {
+            fn from(e: anyhow::Error) -> Self {
+                match e.downcast::<pathfinder_storage::DataPruned>() {
+                    Ok(pruned) => Self::DataPruned {
+                        oldest_block: pruned.oldest_block,
+                    },
+                    Err(e) => Self::Internal(e),
+                }
+            }
+        }
+    };
     // Generates From<$enum_name> for RpcError, this macro arm itself is not tricky,
     // however its child calls are.
     //
@@ -334,6 +379,22 @@ macro_rules! generate_rpc_error_subset {
             }
         }
     };
+    // Same as `@from_def`, with the arm of the `DataPruned` variant as the initial arm.
+    (@from_def_pruned, $enum_name:ident, $($variants:ident),*) => {
+        impl From<$enum_name> for crate::error::ApplicationError {
+            fn from(x: $enum_name) -> Self {
+                generate_rpc_error_subset!(
+                    @parse, x, $enum_name,
+                    {
+                        $enum_name::DataPruned { oldest_block } => Self::DataPruned {
+                            oldest_block: oldest_block.get(),
+                        },
+                    },
+                    $($variants),*
+                )
+            }
+        }
+    };
     // Termination case (no further input to munch). We generate the match statement here.
     (@parse, $var:ident, $enum_name:ident, {$($arms:tt)*}, $(,)*) => {
         match $var {
@@ -402,5 +463,21 @@ mod tests {
             assert_matches!(no_blocks, ApplicationError::NoBlocks);
             assert_matches!(contract_error, ApplicationError::ContractError);
         }
+
+        #[test]
+        fn data_pruned() {
+            generate_rpc_error_subset!(Pruned: ContractNotFound; DataPruned);
+
+            let oldest_block = pathfinder_common::BlockNumber::new_or_panic(10);
+            let error = anyhow::Error::from(pathfinder_storage::DataPruned { oldest_block })
+                .context("Reading transaction");
+            let pruned = ApplicationError::from(Pruned::from(error));
+            let internal = ApplicationError::from(Pruned::from(anyhow::anyhow!("Failure")));
+            let contract_not_found = ApplicationError::from(Pruned::ContractNotFound);
+
+            assert_matches!(pruned, ApplicationError::DataPruned { oldest_block: 10 });
+            assert_matches!(internal, ApplicationError::Internal(_));
+            assert_matches!(contract_not_found, ApplicationError::ContractNotFound);
+        }
     }
 }
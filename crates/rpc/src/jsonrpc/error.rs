@@ -15,6 +15,9 @@ pub enum RpcError {
         subscription_id: u32,
         reason: String,
     },
+    LimitExceeded {
+        retry_after: std::time::Duration,
+    },
 }
 
 impl PartialEq for RpcError {
@@ -37,6 +40,7 @@ impl RpcError {
             RpcError::InternalError(_) => -32603,
             RpcError::ApplicationError(err) => err.code(),
             RpcError::WebsocketSubscriptionClosed { .. } => -32099,
+            RpcError::LimitExceeded { .. } => -32005,
         }
     }
 
@@ -49,6 +53,7 @@ impl RpcError {
             RpcError::InternalError(_) => "Internal error".into(),
             RpcError::ApplicationError(e) => e.to_string().into(),
             RpcError::WebsocketSubscriptionClosed { .. } => "Websocket subscription closed".into(),
+            RpcError::LimitExceeded { .. } => "Limit exceeded".into(),
         }
     }
 
@@ -61,6 +66,9 @@ impl RpcError {
                 "id": subscription_id,
                 "reason": reason,
             })),
+            RpcError::LimitExceeded { retry_after } => Some(json!({
+                "retry_after_ms": retry_after.as_millis() as u64,
+            })),
             RpcError::ApplicationError(e) => e.data(),
             RpcError::InternalError(_) => None,
             RpcError::MethodNotFound => None,
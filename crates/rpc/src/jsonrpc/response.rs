@@ -40,6 +40,20 @@ impl<'a> RpcResponse<'a> {
             id,
         }
     }
+
+    /// Logs internal errors.
+    pub(crate) fn log_errors(&self) {
+        match &self.output {
+            Err(RpcError::InternalError(e))
+            | Err(RpcError::ApplicationError(ApplicationError::Internal(e))) => {
+                tracing::warn!(backtrace = ?e, "Internal error");
+            }
+            Err(RpcError::ApplicationError(ApplicationError::Custom(e))) => {
+                tracing::debug!(backtrace = ?e, "Custom error");
+            }
+            _ => {}
+        }
+    }
 }
 
 pub type RpcResult = Result<Value, RpcError>;
@@ -72,17 +86,7 @@ impl Serialize for RpcResponse<'_> {
 
 impl IntoResponse for RpcResponse<'_> {
     fn into_response(self) -> axum::response::Response {
-        // Log internal errors.
-        match &self.output {
-            Err(RpcError::InternalError(e))
-            | Err(RpcError::ApplicationError(ApplicationError::Internal(e))) => {
-                tracing::warn!(backtrace = ?e, "Internal error");
-            }
-            Err(RpcError::ApplicationError(ApplicationError::Custom(e))) => {
-                tracing::debug!(backtrace = ?e, "Custom error");
-            }
-            _ => {}
-        }
+        self.log_errors();
 
         serde_json::to_vec(&self).unwrap().into_response()
     }
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 
 use axum::async_trait;
-use axum::extract::State;
+use axum::extract::{ConnectInfo, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use futures::{Future, FutureExt, StreamExt};
@@ -17,6 +18,7 @@ use crate::context::RpcContext;
 use crate::jsonrpc::error::RpcError;
 use crate::jsonrpc::request::{RawParams, RpcRequest};
 use crate::jsonrpc::response::{RpcResponse, RpcResult};
+use crate::middleware::rate_limit::Client;
 
 #[derive(Clone)]
 pub struct RpcRouter {
@@ -76,14 +78,33 @@ impl RpcRouter {
         RpcRouterBuilder::new(version)
     }
 
-    /// Parses and executes a request. Returns [None] if its a notification.
-    async fn run_request<'a>(&self, request: &'a str) -> Option<RpcResponse<'a>> {
+    /// Parses and executes a request, and serializes its response. Returns [None] if its a
+    /// notification.
+    async fn run_request(&self, request: &str, client: Option<&Client>) -> Option<String> {
+        let (response, method_name) = self.execute_request(request, client).await?;
+        response.log_errors();
+        let serialized = serde_json::to_string(&response).unwrap();
+
+        if let Some(method_name) = method_name {
+            metrics::histogram!("rpc_method_response_bytes", serialized.len() as f64, "method" => method_name, "version" => self.version);
+        }
+
+        Some(serialized)
+    }
+
+    /// Parses and executes a request. Returns [None] if its a notification, and the name of the
+    /// method which was executed otherwise.
+    async fn execute_request<'a>(
+        &self,
+        request: &'a str,
+        client: Option<&Client>,
+    ) -> Option<(RpcResponse<'a>, Option<&'static str>)> {
         tracing::trace!(%request, "Running request");
 
         let request = match serde_json::from_str::<RpcRequest<'_>>(request) {
             Ok(request) => request,
             Err(e) => {
-                return Some(RpcResponse::invalid_request(e.to_string()));
+                return Some((RpcResponse::invalid_request(e.to_string()), None));
             }
         };
 
@@ -95,13 +116,32 @@ impl RpcRouter {
         // Also grab the method_name as it is a static str, which is required by the metrics.
         let Some((&method_name, method)) = self.methods.get_key_value(request.method.as_ref())
         else {
-            return Some(RpcResponse::method_not_found(request.id));
+            return Some((RpcResponse::method_not_found(request.id), None));
         };
 
+        if let (Some(limiter), Some(client)) = (&self.context.rate_limiter, client) {
+            if let Err(retry_after) = limiter.check(client, method_name) {
+                metrics::increment_counter!("rpc_method_calls_limited_total", "method" => method_name, "version" => self.version);
+                let response = RpcResponse {
+                    output: Err(RpcError::LimitExceeded { retry_after }),
+                    id: request.id,
+                };
+                return Some((response, Some(method_name)));
+            }
+        }
+
         metrics::increment_counter!("rpc_method_calls_total", "method" => method_name, "version" => self.version);
 
+        // Only keep hold of the params if they may need to be logged.
+        let params = self
+            .context
+            .slow_request_threshold
+            .and_then(|_| request.params.0);
+
+        let started = std::time::Instant::now();
         let method = method.invoke(self.context.clone(), request.params);
         let result = std::panic::AssertUnwindSafe(method).catch_unwind().await;
+        let elapsed = started.elapsed();
 
         let output = match result {
             Ok(output) => output,
@@ -113,14 +153,25 @@ impl RpcRouter {
             }
         };
 
-        if output.is_err() {
+        metrics::histogram!("rpc_method_calls_duration_seconds", elapsed.as_secs_f64(), "method" => method_name, "version" => self.version);
+
+        if let Err(e) = &output {
             metrics::increment_counter!("rpc_method_calls_failed_total", "method" => method_name, "version" => self.version);
+            metrics::increment_counter!("rpc_method_errors_total", "method" => method_name, "version" => self.version, "code" => e.code().to_string());
         }
 
-        Some(RpcResponse {
+        if let Some(threshold) = self.context.slow_request_threshold {
+            if elapsed > threshold {
+                let params = params.map(|p| p.get()).unwrap_or_default();
+                tracing::warn!(method=%method_name, version=%self.version, %params, ?elapsed, "Slow RPC request");
+            }
+        }
+
+        let response = RpcResponse {
             output,
             id: request.id,
-        })
+        };
+        Some((response, Some(method_name)))
     }
 }
 
@@ -156,9 +207,16 @@ fn is_utf8_encoded_json(headers: http::HeaderMap) -> bool {
 #[axum::debug_handler]
 pub async fn rpc_handler(
     State(state): State<RpcRouter>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: http::HeaderMap,
     body: axum::body::Bytes,
 ) -> impl axum::response::IntoResponse {
+    let client = state
+        .context
+        .rate_limiter
+        .as_ref()
+        .and_then(|limiter| limiter.client(connect_info.map(|info| info.0), &headers));
+
     // Only utf8 json content allowed.
     if !is_utf8_encoded_json(headers) {
         return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
@@ -169,6 +227,7 @@ pub async fn rpc_handler(
     /// instead of dealing with branches / early exits.
     async fn handle(
         state: RpcRouter,
+        client: Option<Client>,
         body: axum::body::Bytes,
     ) -> impl axum::response::IntoResponse {
         // Unfortunately due to this https://github.com/serde-rs/json/issues/497
@@ -183,7 +242,7 @@ pub async fn rpc_handler(
                 }
             };
 
-            match state.run_request(request.get()).await {
+            match state.run_request(request.get(), client.as_ref()).await {
                 Some(response) => response.into_response(),
                 None => ().into_response(),
             }
@@ -207,24 +266,24 @@ pub async fn rpc_handler(
                 requests.into_iter().enumerate(),
                 |(idx, request)| {
                     state
-                        .run_request(request.get())
+                        .run_request(request.get(), client.as_ref())
                         .instrument(tracing::debug_span!("batch", idx))
                 },
             )
             .await
             .flatten()
-            .collect::<Vec<RpcResponse<'_>>>();
+            .collect::<Vec<String>>();
 
             // All requests were notifications.
             if responses.is_empty() {
                 return ().into_response();
             }
 
-            serde_json::to_string(&responses).unwrap().into_response()
+            format!("[{}]", responses.join(",")).into_response()
         }
     }
 
-    let mut response = handle(state, body).await.into_response();
+    let mut response = handle(state, client, body).await.into_response();
 
     use http::header::CONTENT_TYPE;
     static APPLICATION_JSON: HeaderValue = HeaderValue::from_static("application/json");
@@ -579,7 +638,7 @@ mod tests {
                 .with_state(router);
             axum::Server::from_tcp(listener)
                 .unwrap()
-                .serve(router.into_make_service())
+                .serve(router.into_make_service_with_connect_info::<SocketAddr>())
                 .await
         });
 
@@ -838,6 +897,122 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn rate_limited_requests_are_rejected() {
+        use crate::middleware::rate_limit::RateLimitConfig;
+
+        fn always_success() -> &'static str {
+            "Success"
+        }
+
+        let context = RpcContext::for_tests().with_rate_limits(RateLimitConfig {
+            requests_per_second: std::num::NonZeroU32::new(1),
+            ..Default::default()
+        });
+        let router = RpcRouter::builder("vTest")
+            .register("success", always_success)
+            .build(context);
+
+        let response = serve_and_query(
+            router,
+            json!([
+                {"jsonrpc": "2.0", "method": "success", "id": 1},
+                {"jsonrpc": "2.0", "method": "success", "id": 2},
+            ]),
+        )
+        .await;
+
+        // Batched requests may run in any order.
+        let responses = response.as_array().unwrap();
+        let limited = responses
+            .iter()
+            .filter(|response| response["error"]["code"] == json!(-32005))
+            .collect::<Vec<_>>();
+        assert_eq!(limited.len(), 1);
+        assert!(limited[0]["error"]["data"]["retry_after_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn metrics() {
+        use pathfinder_common::test_utils::metrics::{FakeRecorder, ScopedRecorderGuard};
+
+        async fn success(_ctx: RpcContext) -> RpcResult {
+            Ok(json!("Success"))
+        }
+
+        async fn failure(_ctx: RpcContext) -> RpcResult {
+            Err(RpcError::InternalError(anyhow::anyhow!("Failure")))
+        }
+
+        // Only the methods of this test are recorded, as the recorder is global.
+        let recorder = FakeRecorder::new_for(&["metrics_success", "metrics_failure"]);
+        let handle = recorder.handle();
+        let _guard = ScopedRecorderGuard::new(recorder);
+
+        let router = RpcRouter::builder("vTest")
+            .register("metrics_success", success)
+            .register("metrics_failure", failure)
+            .build(RpcContext::for_tests());
+        let url = spawn_server(router).await;
+
+        let client = reqwest::Client::new();
+        let mut bodies = Vec::new();
+        for method in ["metrics_success", "metrics_failure"] {
+            let body = client
+                .post(url.clone())
+                .json(&json!({"jsonrpc": "2.0", "method": method, "id": 1}))
+                .send()
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap();
+            bodies.push(body);
+        }
+
+        for method in ["metrics_success", "metrics_failure"] {
+            let labels = [("method", method), ("version", "vTest")];
+            assert_eq!(
+                handle.get_counter_value_by_label("rpc_method_calls_total", labels),
+                1
+            );
+            assert_eq!(
+                handle
+                    .get_histogram_values_by_label("rpc_method_calls_duration_seconds", labels)
+                    .len(),
+                1
+            );
+        }
+
+        // Response sizes are those of the responses sent.
+        let success_labels = [("method", "metrics_success"), ("version", "vTest")];
+        let failure_labels = [("method", "metrics_failure"), ("version", "vTest")];
+        assert_eq!(
+            handle.get_histogram_values_by_label("rpc_method_response_bytes", success_labels),
+            vec![bodies[0].len() as f64]
+        );
+        assert_eq!(
+            handle.get_histogram_values_by_label("rpc_method_response_bytes", failure_labels),
+            vec![bodies[1].len() as f64]
+        );
+
+        assert_eq!(
+            handle.get_counter_value_by_label("rpc_method_calls_failed_total", failure_labels),
+            1
+        );
+        assert_eq!(
+            handle.get_counter_value_by_label(
+                "rpc_method_errors_total",
+                [
+                    ("method", "metrics_failure"),
+                    ("version", "vTest"),
+                    ("code", "-32603")
+                ]
+            ),
+            1
+        );
+    }
+
     #[tokio::test]
     async fn rejects_non_json_content_header() {
         async fn always_success(_ctx: RpcContext) -> RpcResult {
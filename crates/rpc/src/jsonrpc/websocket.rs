@@ -16,6 +16,54 @@
 //! < {"jsonrpc":"2.0","method":"pathfinder_subscription","result":{"subscription":0,"event":{"class_commitment":"0x4a1c4c3cd477eb052655963781fd7ae0cd647752f01595e4e33fed2ab0eff90","eth_l1_gas_price":1000000015,"event_commitment":"0x79789afccc8f0cac4a3992b2b52cc15f560b4f5a997d883b29d73236b2dfce7","event_count":387,"hash":"0x412edf5929693f8d6bb29512d1a777066dfbf493f3ee64bcb14c64165f5006b","number":908104,"parent_hash":"0x16562de7d258e27809ec6b3d3da5edaedc6526a046442f2f5d72fe7c5dc0a1d","sequencer_address":"0x1176a1bd84444c89232ec27754698e5d2e7e1a7f1539f12027f28b23ec9f3d8","starknet_version":"0.12.3","state_commitment":"0x1d00410c349e70996834a144598bc762602df09cd38a51c25528fb2fd662403","storage_commitment":"0x5129d4a27efa0429975f67440314ab921cc554681ac3ecf476850c1f6b723bf","strk_l1_gas_price":0,"timestamp":1700823087,"transaction_commitment":"0x273bfec6af3c812b59a864e67334132d5bd26c570a9b202e0adce2bb4d6b0cf","transaction_count":36}}}
 //! ```
 //!
+//! Events emitted in newly committed blocks can be subscribed to using the `events` topic,
+//! optionally filtered by `from_address` and `keys` (same semantics as `starknet_getEvents`):
+//! ```
+//! > {"jsonrpc":"2.0", "id": 1, "method": "pathfinder_subscribe", "params": {"kind": "events", "from_address": "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7", "keys": [["0x99cd8bde557814842a3121e8ddfd433a539b8c9f14bf31ebf108d12e6196e9"]]}}
+//! < {"jsonrpc":"2.0","result":1,"id":1}
+//! ```
+//!
+//! The status of a transaction can be followed using the `transactionStatus` topic. The current
+//! status is sent first, followed by each transition until a final status is reached:
+//! ```
+//! > {"jsonrpc":"2.0", "id": 1, "method": "pathfinder_subscribe", "params": {"kind": "transactionStatus", "transaction_hash": "0x6a6fd3bc8cb5e2db7bd3cfb4e1c16b5bf81f37e80d68cd12c6fdaf3e4f37ad"}}
+//! < {"jsonrpc":"2.0","result":2,"id":1}
+//! < {"jsonrpc":"2.0","method":"pathfinder_subscription","result":{"subscription":2,"result":{"transaction_hash":"0x6a6fd3bc8cb5e2db7bd3cfb4e1c16b5bf81f37e80d68cd12c6fdaf3e4f37ad","status":"RECEIVED"}}}
+//! < {"jsonrpc":"2.0","method":"pathfinder_subscription","result":{"subscription":2,"result":{"transaction_hash":"0x6a6fd3bc8cb5e2db7bd3cfb4e1c16b5bf81f37e80d68cd12c6fdaf3e4f37ad","status":"ACCEPTED_ON_L2"}}}
+//! ```
+//!
+//! Transactions newly added to the pending block are streamed by the `pendingTransactions` topic,
+//! optionally filtered by `sender_address`:
+//! ```
+//! > {"jsonrpc":"2.0", "id": 1, "method": "pathfinder_subscribe", "params": {"kind": "pendingTransactions", "sender_address": "0x1176a1bd84444c89232ec27754698e5d2e7e1a7f1539f12027f28b23ec9f3d8"}}
+//! < {"jsonrpc":"2.0","result":3,"id":1}
+//! ```
+//!
+//! Blocks removed from the canonical chain are announced by the `reorg` topic. The same
+//! notification is also pushed to `newHeads` and `events` subscribers, before any of the
+//! replacement blocks:
+//! ```
+//! > {"jsonrpc":"2.0", "id": 1, "method": "pathfinder_subscribe", "params": {"kind": "reorg"}}
+//! < {"jsonrpc":"2.0","result":4,"id":1}
+//! < {"jsonrpc":"2.0","method":"pathfinder_subscription","result":{"subscription":4,"result":{"first_block_number":512,"first_block_hash":"0x1a2b...","last_block_number":513,"last_block_hash":"0x3c4d...","new_head":{"block_number":511,"block_hash":"0x5e6f..."}}}}
+//! ```
+//!
+//! Ethereum reorgs which revert the L1 acceptance of Starknet blocks are announced by the
+//! `l1Reorg` topic:
+//! ```
+//! > {"jsonrpc":"2.0", "id": 1, "method": "pathfinder_subscribe", "params": {"kind": "l1Reorg"}}
+//! < {"jsonrpc":"2.0","result":5,"id":1}
+//! < {"jsonrpc":"2.0","method":"pathfinder_subscription","result":{"subscription":5,"result":{"fork_l1_block_number":19000000,"l1_accepted_block_number":510}}}
+//! ```
+//!
+//! Clients reconnecting after a disconnect can backfill the blocks they missed by passing
+//! `from_block` to the `newHeads` and `events` topics. Blocks in storage are replayed starting
+//! at that block, after which the subscription continues with new blocks:
+//! ```
+//! > {"jsonrpc":"2.0", "id": 1, "method": "pathfinder_subscribe", "params": {"kind": "newHeads", "from_block": 908000}}
+//! < {"jsonrpc":"2.0","result":6,"id":1}
+//! ```
+//!
 //! Subscriptions may lag behind because of a slow network or slow client and result in an error:
 //! ```
 //! > pierre:~/pathfinder$ wscat -c ws://localhost:9545/ws
@@ -1,6 +1,10 @@
 //! See [the parent module documentation](super)
 
 use crate::jsonrpc::{RequestId, RpcError, RpcResponse};
+use crate::pathfinder::methods::TransactionStatus;
+use pathfinder_common::{
+    BlockHash, BlockNumber, ContractAddress, EventData, EventKey, TransactionHash,
+};
 use serde::ser::Error;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -13,6 +17,40 @@ pub(super) struct Kind<'a> {
     pub(super) kind: Cow<'a, str>,
 }
 
+/// Optional parameter of the block based topics, `newHeads` and `events`, which first
+/// replays the items of historical blocks starting at `from_block`.
+#[derive(Deserialize)]
+pub(super) struct ReplayParams {
+    /// Parsed by [Kind], declared so that positional params are supported too.
+    #[serde(rename = "kind")]
+    _kind: serde::de::IgnoredAny,
+    #[serde(default)]
+    pub(super) from_block: Option<BlockNumber>,
+}
+
+/// Optional filter parameters of the `events` topic.
+///
+/// The `keys` filter has the same shape as the one of `starknet_getEvents`.
+#[derive(Default, Deserialize, Serialize)]
+pub(super) struct EventFilterParams {
+    #[serde(default)]
+    pub(super) from_address: Option<ContractAddress>,
+    #[serde(default)]
+    pub(super) keys: Vec<Vec<EventKey>>,
+}
+
+/// Optional filter parameters of the `pendingTransactions` topic.
+#[derive(Default, Deserialize, Serialize)]
+pub(super) struct PendingTransactionsParams {
+    #[serde(default)]
+    pub(super) sender_address: Option<ContractAddress>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(super) struct TransactionStatusParams {
+    pub(super) transaction_hash: TransactionHash,
+}
+
 #[derive(Deserialize, Serialize)]
 pub(super) struct SubscriptionId {
     pub(super) id: u32,
@@ -94,7 +132,14 @@ pub(super) enum ResponseEvent {
     InvalidRequest(String),
     InvalidMethod(OwnedRequestId),
     InvalidParams(OwnedRequestId, String),
+    InternalError(OwnedRequestId),
+    LimitExceeded(OwnedRequestId, std::time::Duration),
     Header(SubscriptionItem<Arc<Value>>),
+    Event(SubscriptionItem<Value>),
+    TransactionStatus(SubscriptionItem<TransactionStatusItem>),
+    PendingTransaction(SubscriptionItem<Value>),
+    Reorg(SubscriptionItem<Arc<Value>>),
+    L1Reorg(SubscriptionItem<Arc<Value>>),
 }
 
 impl ResponseEvent {
@@ -102,11 +147,18 @@ impl ResponseEvent {
         match self {
             ResponseEvent::InvalidRequest(_) => "InvalidRequest",
             ResponseEvent::InvalidMethod(_) => "InvalidMethod",
+            ResponseEvent::LimitExceeded(..) => "LimitExceeded",
             ResponseEvent::Header(_) => "BlockHeader",
+            ResponseEvent::Event(_) => "EmittedEvent",
+            ResponseEvent::TransactionStatus(_) => "TransactionStatus",
+            ResponseEvent::PendingTransaction(_) => "PendingTransaction",
+            ResponseEvent::Reorg(_) => "Reorg",
+            ResponseEvent::L1Reorg(_) => "L1Reorg",
             ResponseEvent::Subscribed { .. } => "Subscribed",
             ResponseEvent::Unsubscribed { .. } => "Unsubscribed",
             ResponseEvent::SubscriptionClosed { .. } => "SubscriptionClosed",
             ResponseEvent::InvalidParams(..) => "InvalidParams",
+            ResponseEvent::InternalError(_) => "InternalError",
         }
     }
 }
@@ -126,7 +178,24 @@ impl Serialize for ResponseEvent {
             ResponseEvent::InvalidParams(id, e) => {
                 RpcResponse::invalid_params(id.into(), e.clone()).serialize(serializer)
             }
+            ResponseEvent::InternalError(id) => RpcResponse {
+                output: Err(RpcError::InternalError(anyhow::anyhow!("Internal error"))),
+                id: id.into(),
+            }
+            .serialize(serializer),
+            ResponseEvent::LimitExceeded(id, retry_after) => RpcResponse {
+                output: Err(RpcError::LimitExceeded {
+                    retry_after: *retry_after,
+                }),
+                id: id.into(),
+            }
+            .serialize(serializer),
             ResponseEvent::Header(header) => header.serialize(serializer),
+            ResponseEvent::Event(event) => event.serialize(serializer),
+            ResponseEvent::TransactionStatus(status) => status.serialize(serializer),
+            ResponseEvent::PendingTransaction(transaction) => transaction.serialize(serializer),
+            ResponseEvent::Reorg(reorg) => reorg.serialize(serializer),
+            ResponseEvent::L1Reorg(reorg) => reorg.serialize(serializer),
             ResponseEvent::Subscribed {
                 subscription_id,
                 request_id,
@@ -224,3 +293,86 @@ impl serde::Serialize for BlockHeader {
         map.end()
     }
 }
+
+/// A transaction which was newly added to the pending block.
+///
+/// Serialized the same way as the transactions of `starknet_getBlockWithTxs`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingTransaction(pub starknet_gateway_types::reply::transaction::Transaction);
+
+impl From<starknet_gateway_types::reply::transaction::Transaction> for PendingTransaction {
+    fn from(value: starknet_gateway_types::reply::transaction::Transaction) -> Self {
+        Self(value)
+    }
+}
+
+impl serde::Serialize for PendingTransaction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        crate::v06::types::TransactionWithHash::from(self.0.clone()).serialize(serializer)
+    }
+}
+
+/// An event emitted in a block which was just committed to storage.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct EmittedEvent {
+    pub from_address: ContractAddress,
+    pub keys: Vec<EventKey>,
+    pub data: Vec<EventData>,
+    pub block_hash: BlockHash,
+    pub block_number: BlockNumber,
+    pub transaction_hash: TransactionHash,
+}
+
+/// The status of a transaction sent to `transactionStatus` subscriptions.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(super) struct TransactionStatusItem {
+    pub(super) transaction_hash: TransactionHash,
+    pub(super) status: TransactionStatus,
+}
+
+/// Transaction status transitions observed by the sync process.
+///
+/// These are consumed by `transactionStatus` subscriptions and are never sent to clients as is.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionStatusUpdate {
+    /// Statuses of the transactions of a block which was just committed to storage.
+    Block(BlockNumber, Vec<(TransactionHash, TransactionStatus)>),
+    /// Statuses of transactions which were newly added to the pending block.
+    Pending(Vec<(TransactionHash, TransactionStatus)>),
+    /// The block with this number, and therefore all of its ancestors, is now accepted on L1.
+    L1Accepted(BlockNumber),
+    /// The blocks from this number onwards were removed from the canonical chain by an L2 reorg.
+    Reorg(BlockNumber),
+}
+
+/// Blocks which were removed from the canonical chain by an L2 reorg.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Reorg {
+    pub first_block_number: BlockNumber,
+    pub first_block_hash: BlockHash,
+    pub last_block_number: BlockNumber,
+    pub last_block_hash: BlockHash,
+    /// The head of the chain after the orphaned blocks were removed, [None] if
+    /// the reorg removed the genesis block.
+    pub new_head: Option<NewHead>,
+}
+
+/// L1 blocks which were removed from the canonical Ethereum chain by a reorg, reverting the L1
+/// acceptance of the Starknet blocks settled in them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct L1Reorg {
+    /// The latest remaining L1 block which logged a state update, [None] if the reorg removed
+    /// all of them.
+    pub fork_l1_block_number: Option<u64>,
+    /// The latest Starknet block which is still accepted on L1.
+    pub l1_accepted_block_number: Option<BlockNumber>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct NewHead {
+    pub block_number: BlockNumber,
+    pub block_hash: BlockHash,
+}
@@ -1,30 +1,51 @@
 //! See [the parent module documentation](super)
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::marker::PhantomData;
+use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::ops::ControlFlow;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
 use crate::jsonrpc::request::RawParams;
 use crate::jsonrpc::{RequestId, RpcRequest};
 use axum::extract::ws::{Message, WebSocket};
-use axum::extract::{State, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, State, WebSocketUpgrade};
 use axum::response::IntoResponse;
+use futures::future::BoxFuture;
 use futures::sink::Buffer;
 use futures::stream::{SplitSink, SplitStream};
-use futures::{SinkExt, StreamExt};
-use serde::Serialize;
+use futures::{FutureExt, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::sync::{broadcast, mpsc};
 use tracing::error;
 
-use crate::jsonrpc::websocket::data::{Kind, ResponseEvent, SubscriptionId, SubscriptionItem};
+use crate::context::RpcContext;
+use crate::jsonrpc::websocket::data::{
+    EmittedEvent, EventFilterParams, Kind, L1Reorg, OwnedRequestId, PendingTransaction,
+    PendingTransactionsParams, Reorg, ReplayParams, ResponseEvent, SubscriptionId,
+    SubscriptionItem, TransactionStatusItem, TransactionStatusParams, TransactionStatusUpdate,
+};
+use crate::middleware::rate_limit::Client;
+use crate::pathfinder::methods::{GetGatewayTransactionInput, TransactionStatus};
 use crate::BlockHeader;
+use anyhow::Context;
+use pathfinder_common::{BlockId, BlockNumber, ContractAddress, EventKey, TransactionHash};
+use pathfinder_storage::Storage;
 
 const SUBSCRIBE_METHOD: &str = "pathfinder_subscribe";
 const UNSUBSCRIBE_METHOD: &str = "pathfinder_unsubscribe";
 const NEW_HEADS_TOPIC: &str = "newHeads";
+const EVENTS_TOPIC: &str = "events";
+const TRANSACTION_STATUS_TOPIC: &str = "transactionStatus";
+const PENDING_TRANSACTIONS_TOPIC: &str = "pendingTransactions";
+const REORG_TOPIC: &str = "reorg";
+const L1_REORG_TOPIC: &str = "l1Reorg";
+/// The number of historical blocks loaded from storage at once when replaying a topic.
+const REPLAY_BATCH_SIZE: usize = 100;
+/// The maximum number of blocks behind the latest block a topic can be replayed from.
+const MAX_REPLAY_BLOCKS: u64 = 1024;
 
 #[derive(Clone)]
 pub struct WebsocketContext {
@@ -55,9 +76,15 @@ impl Default for WebsocketContext {
 
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
-    State(state): State<WebsocketContext>,
+    State(state): State<RpcContext>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: http::HeaderMap,
 ) -> impl IntoResponse {
-    let mut upgrade_response = ws.on_upgrade(|socket| handle_socket(socket, state));
+    let client = state
+        .rate_limiter
+        .as_ref()
+        .and_then(|limiter| limiter.client(connect_info.map(|info| info.0), &headers));
+    let mut upgrade_response = ws.on_upgrade(|socket| handle_socket(socket, state, client));
 
     static APPLICATION_JSON: http::HeaderValue = http::HeaderValue::from_static("application/json");
     upgrade_response
@@ -67,7 +94,8 @@ pub async fn websocket_handler(
     upgrade_response
 }
 
-async fn handle_socket(socket: WebSocket, context: WebsocketContext) {
+async fn handle_socket(socket: WebSocket, rpc_context: RpcContext, client: Option<Client>) {
+    let context = rpc_context.websocket.clone().unwrap_or_default();
     let (ws_sender, ws_receiver) = socket.split();
 
     let (response_sender, response_receiver) = mpsc::channel(10);
@@ -77,7 +105,13 @@ async fn handle_socket(socket: WebSocket, context: WebsocketContext) {
         response_receiver,
         context.socket_buffer_capacity,
     ));
-    tokio::spawn(read(ws_receiver, response_sender, context.broadcasters));
+    tokio::spawn(read(
+        ws_receiver,
+        response_sender,
+        context.broadcasters,
+        rpc_context,
+        client,
+    ));
 }
 
 async fn write(
@@ -122,6 +156,8 @@ async fn read(
     mut receiver: SplitStream<WebSocket>,
     response_sender: mpsc::Sender<ResponseEvent>,
     source: TopicBroadcasters,
+    rpc_context: RpcContext,
+    client: Option<Client>,
 ) {
     let mut subscription_manager = SubscriptionManager::default();
 
@@ -153,14 +189,46 @@ async fn read(
             }
         };
 
+        if let (Some(limiter), Some(client)) = (&rpc_context.rate_limiter, &client) {
+            if let Err(retry_after) = limiter.check(client, &request.method) {
+                let response = ResponseEvent::LimitExceeded(request.id.into(), retry_after);
+                if let Err(e) = response_sender.try_send(response) {
+                    tracing::debug!(reason=%e, "Failed to send response");
+                    break;
+                }
+                continue;
+            }
+        }
+
         // Handle request.
         let response = match request.method.as_ref() {
-            SUBSCRIBE_METHOD => subscription_manager.subscribe(
-                request.id,
-                request.params,
-                response_sender.clone(),
-                source.clone(),
-            ),
+            SUBSCRIBE_METHOD => match subscription_manager
+                .subscribe(
+                    request.id,
+                    request.params,
+                    response_sender.clone(),
+                    source.clone(),
+                    rpc_context.clone(),
+                )
+                .await
+            {
+                Ok(subscription) => {
+                    // Confirm the subscription before starting it, so that the confirmation
+                    // is guaranteed to precede the subscription's first notification.
+                    let response = ResponseEvent::Subscribed {
+                        subscription_id: subscription.id,
+                        request_id: subscription.request_id,
+                    };
+                    if let Err(e) = response_sender.try_send(response) {
+                        tracing::debug!(reason=%e, "Failed to send response");
+                        break;
+                    }
+
+                    subscription_manager.start(subscription.id, subscription.task);
+                    continue;
+                }
+                Err(response) => response,
+            },
             UNSUBSCRIBE_METHOD => {
                 subscription_manager
                     .unsubscribe(request.id, request.params)
@@ -181,6 +249,13 @@ async fn read(
     subscription_manager.abort_all();
 }
 
+/// A subscription which was accepted but not started yet.
+struct NewSubscription {
+    id: u32,
+    request_id: OwnedRequestId,
+    task: BoxFuture<'static, ()>,
+}
+
 /// Manages the subscription for a single connection
 #[derive(Default)]
 struct SubscriptionManager {
@@ -224,49 +299,159 @@ impl SubscriptionManager {
         }
     }
 
-    fn subscribe(
+    async fn subscribe(
         &mut self,
         request_id: RequestId<'_>,
         request_params: RawParams<'_>,
         response_sender: mpsc::Sender<ResponseEvent>,
         websocket_source: TopicBroadcasters,
-    ) -> ResponseEvent {
-        let kind = match request_params.deserialize::<Kind<'_>>() {
-            Ok(x) => x,
-            Err(crate::jsonrpc::RpcError::InvalidParams(e)) => {
-                return ResponseEvent::InvalidParams(request_id.into(), e)
+        rpc_context: RpcContext,
+    ) -> Result<NewSubscription, ResponseEvent> {
+        let kind = deserialize_params::<Kind<'_>>(RawParams(request_params.0), &request_id)?;
+        let replay = deserialize_params::<ReplayParams>(RawParams(request_params.0), &request_id)?;
+
+        let replay_kind = match kind.kind.as_ref() {
+            NEW_HEADS_TOPIC => Some(ReplayKind::Headers),
+            EVENTS_TOPIC => Some(ReplayKind::Events),
+            _ => None,
+        };
+        let replay = match (replay.from_block, replay_kind) {
+            (None, _) => None,
+            (Some(from_block), Some(kind)) => {
+                let latest = latest_block_number(rpc_context.storage.clone())
+                    .await
+                    .map_err(|e| {
+                        tracing::warn!(error=?e, "Querying latest block for replay failed");
+                        ResponseEvent::InternalError(request_id.clone().into())
+                    })?;
+                if let Some(latest) = latest {
+                    if latest.get().saturating_sub(from_block.get()) > MAX_REPLAY_BLOCKS {
+                        return Err(ResponseEvent::InvalidParams(
+                            request_id.into(),
+                            format!(
+                                "from_block must be at most {MAX_REPLAY_BLOCKS} blocks behind the \
+                                 latest block {}",
+                                latest.get()
+                            ),
+                        ));
+                    }
+                }
+
+                Some(Replay::new(rpc_context.storage.clone(), kind, from_block))
             }
-            Err(_) => {
-                return ResponseEvent::InvalidParams(
+            (Some(_), None) => {
+                return Err(ResponseEvent::InvalidParams(
                     request_id.into(),
-                    "Unexpected parsing error".to_owned(),
-                )
+                    "from_block is only supported by the newHeads and events topics".to_owned(),
+                ))
             }
         };
 
         let subscription_id = self.next_id;
         self.next_id += 1;
-        let receiver = websocket_source.new_head.subscribe();
-        let handle = match kind.kind.as_ref() {
-            NEW_HEADS_TOPIC => tokio::spawn(header_subscription(
+        let task = match kind.kind.as_ref() {
+            NEW_HEADS_TOPIC => forward_subscription(
                 response_sender,
-                receiver,
+                TopicReceiver::new(websocket_source.new_head.subscribe(), replay),
+                subscription_id,
+                ResponseEvent::Header,
+                "header",
+            )
+            .boxed(),
+            REORG_TOPIC => forward_subscription(
+                response_sender,
+                TopicReceiver::new(websocket_source.reorg.subscribe(), None),
+                subscription_id,
+                ResponseEvent::Reorg,
+                "reorg",
+            )
+            .boxed(),
+            L1_REORG_TOPIC => forward_subscription(
+                response_sender,
+                TopicReceiver::new(websocket_source.l1_reorg.subscribe(), None),
                 subscription_id,
-            )),
+                ResponseEvent::L1Reorg,
+                "L1 reorg",
+            )
+            .boxed(),
+            EVENTS_TOPIC => {
+                let filter = deserialize_params::<EventFilterParams>(request_params, &request_id)?;
+
+                if filter.keys.len() > pathfinder_storage::EVENT_KEY_FILTER_LIMIT {
+                    return Err(ResponseEvent::InvalidParams(
+                        request_id.into(),
+                        format!(
+                            "Too many keys in filter, the limit is {}",
+                            pathfinder_storage::EVENT_KEY_FILTER_LIMIT
+                        ),
+                    ));
+                }
+
+                let filter = EventFilter::from(filter);
+                batch_subscription(
+                    response_sender,
+                    TopicReceiver::new(websocket_source.events.subscribe(), replay),
+                    subscription_id,
+                    move |event| filter.matches(event),
+                    ResponseEvent::Event,
+                    "event",
+                )
+                .boxed()
+            }
+            PENDING_TRANSACTIONS_TOPIC => {
+                let params =
+                    deserialize_params::<PendingTransactionsParams>(request_params, &request_id)?;
+
+                batch_subscription(
+                    response_sender,
+                    TopicReceiver::new(websocket_source.pending_transactions.subscribe(), None),
+                    subscription_id,
+                    move |transaction| match params.sender_address {
+                        Some(address) => {
+                            ContractAddress::deserialize(&transaction["sender_address"])
+                                .is_ok_and(|sender| sender == address)
+                        }
+                        None => true,
+                    },
+                    ResponseEvent::PendingTransaction,
+                    "transaction",
+                )
+                .boxed()
+            }
+            TRANSACTION_STATUS_TOPIC => {
+                let params =
+                    deserialize_params::<TransactionStatusParams>(request_params, &request_id)?;
+
+                transaction_status_subscription(
+                    response_sender,
+                    websocket_source.transaction_status.subscribe(),
+                    subscription_id,
+                    params.transaction_hash,
+                    websocket_source
+                        .transaction_status_polls
+                        .get(params.transaction_hash),
+                    rpc_context,
+                )
+                .boxed()
+            }
             _ => {
-                return ResponseEvent::InvalidParams(
+                return Err(ResponseEvent::InvalidParams(
                     request_id.into(),
                     "Unknown subscription type".to_owned(),
-                )
+                ))
             }
         };
 
-        self.subscriptions.insert(subscription_id, handle);
-
-        ResponseEvent::Subscribed {
-            subscription_id,
+        Ok(NewSubscription {
+            id: subscription_id,
             request_id: request_id.into(),
-        }
+            task,
+        })
+    }
+
+    fn start(&mut self, subscription_id: u32, task: BoxFuture<'static, ()>) {
+        self.subscriptions
+            .insert(subscription_id, tokio::spawn(task));
     }
 
     fn abort_all(self) {
@@ -276,37 +461,583 @@ impl SubscriptionManager {
     }
 }
 
-async fn header_subscription(
+/// The number of the latest stored block, or [None] if there are no blocks yet.
+async fn latest_block_number(storage: Storage) -> anyhow::Result<Option<BlockNumber>> {
+    tokio::task::spawn_blocking(move || {
+        let mut db = storage
+            .connection()
+            .context("Opening database connection")?;
+        let transaction = db.transaction().context("Creating database transaction")?;
+        let latest = transaction
+            .block_id(pathfinder_storage::BlockId::Latest)
+            .context("Querying latest block")?;
+
+        Ok(latest.map(|(number, _)| number))
+    })
+    .await
+    .context("Joining blocking task")?
+}
+
+/// Deserializes the request params, mapping failures to [ResponseEvent::InvalidParams].
+fn deserialize_params<'a, T: Deserialize<'a>>(
+    params: RawParams<'a>,
+    request_id: &RequestId<'_>,
+) -> Result<T, ResponseEvent> {
+    params.deserialize::<T>().map_err(|e| {
+        let reason = match e {
+            crate::jsonrpc::RpcError::InvalidParams(e) => e,
+            _ => "Unexpected parsing error".to_owned(),
+        };
+        ResponseEvent::InvalidParams(request_id.clone().into(), reason)
+    })
+}
+
+/// Forwards every broadcasted item, as well as the reorg notifications of the topic.
+async fn forward_subscription(
     msg_sender: mpsc::Sender<ResponseEvent>,
-    mut headers: broadcast::Receiver<Arc<Value>>,
+    mut items: TopicReceiver,
     subscription_id: u32,
+    to_response: fn(SubscriptionItem<Arc<Value>>) -> ResponseEvent,
+    item_name: &'static str,
 ) {
-    use broadcast::error::RecvError;
     loop {
-        let response = match headers.recv().await {
-            Ok(header) => ResponseEvent::Header(SubscriptionItem {
+        let response = match items.recv().await {
+            Ok(Received::Item(item)) => to_response(SubscriptionItem {
                 subscription_id,
-                item: header,
+                item,
             }),
-            Err(RecvError::Closed) => break,
-            Err(RecvError::Lagged(amount)) => {
+            Ok(Received::Reorg(reorg)) => ResponseEvent::Reorg(SubscriptionItem {
+                subscription_id,
+                item: reorg,
+            }),
+            Err(TopicError::Closed) => break,
+            Err(error) => {
+                let _ = msg_sender
+                    .send(error.into_response(subscription_id, item_name))
+                    .await;
+                break;
+            }
+        };
+
+        if msg_sender.send(response).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// An item or reorg notification received from a topic.
+enum Received {
+    Item(Arc<Value>),
+    Reorg(Arc<Value>),
+}
+
+/// Failure to receive the next item of a topic.
+enum TopicError {
+    Closed,
+    Lagged(u64),
+    /// Replaying historical blocks from storage failed.
+    Replay(anyhow::Error),
+    /// The broadcaster failed to serialize an item.
+    SerializationFailed,
+}
+
+impl From<broadcast::error::RecvError> for TopicError {
+    fn from(error: broadcast::error::RecvError) -> Self {
+        match error {
+            broadcast::error::RecvError::Closed => TopicError::Closed,
+            broadcast::error::RecvError::Lagged(amount) => TopicError::Lagged(amount),
+        }
+    }
+}
+
+impl TopicError {
+    /// The notification closing the subscription because of this error.
+    fn into_response(self, subscription_id: u32, item_name: &str) -> ResponseEvent {
+        let reason = match self {
+            TopicError::Closed => "Subscription source closed.".to_owned(),
+            TopicError::Lagged(amount) => {
                 tracing::info!(
                     amount,
-                    "Lagging header stream, missed some events, closing subscription"
+                    "Lagging {item_name} stream, missed some items, closing subscription"
                 );
+                format!("Lagging stream, some {item_name}s were skipped. Closing subscription.")
+            }
+            TopicError::Replay(e) => {
+                tracing::debug!(error=?e, "Replaying historical {item_name}s failed");
+                format!("Replaying historical {item_name}s failed. Closing subscription.")
+            }
+            TopicError::SerializationFailed => {
+                format!("Failed to serialize a {item_name}. Closing subscription.")
+            }
+        };
+
+        ResponseEvent::SubscriptionClosed {
+            subscription_id,
+            reason,
+        }
+    }
+}
+
+/// Receives the items of a topic, optionally preceded by a [Replay] of historical blocks.
+///
+/// The live receiver is subscribed before the replay starts, so that blocks committed in
+/// the meantime are not missed. Live items of blocks which were already replayed are skipped,
+/// and a lagging live receiver catches up by replaying the skipped blocks from storage.
+struct TopicReceiver {
+    live: broadcast::Receiver<TopicMessage>,
+    replay: Option<Replay>,
+}
+
+impl TopicReceiver {
+    fn new(live: broadcast::Receiver<TopicMessage>, replay: Option<Replay>) -> Self {
+        Self { live, replay }
+    }
+
+    /// Receives the next item or reorg notification. This is cancel safe.
+    async fn recv(&mut self) -> Result<Received, TopicError> {
+        let Some(replay) = &mut self.replay else {
+            return self.live.recv().await?.into_received();
+        };
+
+        loop {
+            if replay.catching_up {
+                if let Some((_, item)) = replay.backlog.pop_front() {
+                    return Ok(Received::Item(item));
+                }
+
+                if !replay.load().await.map_err(TopicError::Replay)? {
+                    replay.catching_up = false;
+                }
+                continue;
+            }
+
+            match self.live.recv().await {
+                Ok(message) => match message.into_received()? {
+                    Received::Item(item) => match replay.kind.block_number(&item) {
+                        Some(number) if number < replay.next_block => continue,
+                        Some(number) => {
+                            replay.next_block = number + 1;
+                            return Ok(Received::Item(item));
+                        }
+                        None => return Ok(Received::Item(item)),
+                    },
+                    Received::Reorg(reorg) => {
+                        replay.rewind(&reorg);
+                        return Ok(Received::Reorg(reorg));
+                    }
+                },
+                Err(broadcast::error::RecvError::Lagged(amount)) => {
+                    tracing::debug!(amount, "Lagging stream, replaying skipped blocks");
+                    replay.catching_up = true;
+                }
+                Err(broadcast::error::RecvError::Closed) => return Err(TopicError::Closed),
+            }
+        }
+    }
+}
+
+/// The kind of items replayed from storage.
+#[derive(Clone, Copy)]
+enum ReplayKind {
+    /// A [BlockHeader] per block.
+    Headers,
+    /// A batch of all the [EmittedEvent]s of each block.
+    Events,
+}
+
+impl ReplayKind {
+    /// Loads the item of the block in the format used by its topic, [None] if the block
+    /// does not exist.
+    fn load(
+        self,
+        transaction: &pathfinder_storage::Transaction<'_>,
+        block: BlockNumber,
+    ) -> anyhow::Result<Option<Value>> {
+        let Some(header) = transaction
+            .block_header(block.into())
+            .context("Fetching block header")?
+        else {
+            return Ok(None);
+        };
+
+        let item = match self {
+            ReplayKind::Headers => serde_json::to_value(BlockHeader(header))?,
+            ReplayKind::Events => {
+                let transaction_data = transaction
+                    .transaction_data_for_block(BlockId::Number(block))
+                    .context("Fetching transaction data")?
+                    .context("Transaction data missing")?;
+
+                let events = transaction_data
+                    .iter()
+                    .flat_map(|(_, receipt)| {
+                        receipt.events.iter().map(|event| EmittedEvent {
+                            from_address: event.from_address,
+                            keys: event.keys.clone(),
+                            data: event.data.clone(),
+                            block_hash: header.hash,
+                            block_number: header.number,
+                            transaction_hash: receipt.transaction_hash,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                serde_json::to_value(events)?
+            }
+        };
+
+        Ok(Some(item))
+    }
+
+    /// The block of a live item, [None] for empty event batches.
+    fn block_number(self, item: &Value) -> Option<BlockNumber> {
+        let number = match self {
+            ReplayKind::Headers => &item["number"],
+            ReplayKind::Events => &item.get(0)?["block_number"],
+        };
+        BlockNumber::deserialize(number).ok()
+    }
+}
+
+/// Historical blocks to be replayed from storage, starting at `next_block`.
+struct Replay {
+    storage: Storage,
+    kind: ReplayKind,
+    /// The next block to load from storage.
+    next_block: BlockNumber,
+    /// Whether blocks are still being loaded from storage rather than the live receiver.
+    catching_up: bool,
+    /// Loaded blocks which were not sent yet.
+    backlog: VecDeque<(BlockNumber, Arc<Value>)>,
+}
+
+impl Replay {
+    /// Ensures the blocks removed by the reorg are sent again once replaced.
+    fn rewind(&mut self, reorg: &Value) {
+        let Ok(first_block) = BlockNumber::deserialize(&reorg["first_block_number"]) else {
+            return;
+        };
+
+        self.backlog.retain(|(number, _)| *number < first_block);
+        self.next_block = self.next_block.min(first_block);
+    }
+
+    fn new(storage: Storage, kind: ReplayKind, from_block: BlockNumber) -> Self {
+        Self {
+            storage,
+            kind,
+            next_block: from_block,
+            catching_up: true,
+            backlog: VecDeque::new(),
+        }
+    }
+
+    /// Loads the next batch of blocks into the backlog, returns `false` once the head of
+    /// the chain in storage was reached.
+    async fn load(&mut self) -> anyhow::Result<bool> {
+        let storage = self.storage.clone();
+        let kind = self.kind;
+        let from_block = self.next_block;
+
+        let blocks = tokio::task::spawn_blocking(move || {
+            let mut db = storage
+                .connection()
+                .context("Opening database connection")?;
+            let transaction = db.transaction().context("Creating database transaction")?;
+
+            let mut blocks = Vec::new();
+            let mut block = from_block;
+            while blocks.len() < REPLAY_BATCH_SIZE {
+                let Some(item) = kind.load(&transaction, block)? else {
+                    break;
+                };
+                blocks.push((block, Arc::new(item)));
+                block += 1;
+            }
+
+            anyhow::Ok(blocks)
+        })
+        .await
+        .context("Joining blocking task")??;
+
+        // State is only updated once loading completed, keeping `TopicReceiver::recv` cancel safe.
+        let Some((last_block, _)) = blocks.last() else {
+            return Ok(false);
+        };
+        self.next_block = *last_block + 1;
+        self.backlog.extend(blocks);
+
+        Ok(true)
+    }
+}
+
+/// Matches emitted events against the filter of an `events` subscription.
+///
+/// Key matching follows the semantics of `starknet_getEvents`: each position in `keys`
+/// lists the accepted values for that position of the event's keys, and an empty list
+/// accepts any value.
+struct EventFilter {
+    from_address: Option<ContractAddress>,
+    keys: Vec<HashSet<EventKey>>,
+}
+
+impl From<EventFilterParams> for EventFilter {
+    fn from(params: EventFilterParams) -> Self {
+        Self {
+            from_address: params.from_address,
+            keys: params
+                .keys
+                .into_iter()
+                .map(|keys| keys.into_iter().collect())
+                .collect(),
+        }
+    }
+}
+
+impl EventFilter {
+    /// Checks the `from_address` and `keys` fields of a serialized [EmittedEvent].
+    fn matches(&self, event: &Value) -> bool {
+        if let Some(address) = self.from_address {
+            match ContractAddress::deserialize(&event["from_address"]) {
+                Ok(from_address) if from_address == address => {}
+                _ => return false,
+            }
+        }
+
+        if self.keys.iter().all(HashSet::is_empty) {
+            return true;
+        }
+
+        let Ok(keys) = Vec::<EventKey>::deserialize(&event["keys"]) else {
+            return false;
+        };
 
-                // No explicit break here, the loop will be broken by the dropped receiver.
-                ResponseEvent::SubscriptionClosed {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter(|(_, filter)| !filter.is_empty())
+            .all(|(i, filter)| keys.get(i).is_some_and(|key| filter.contains(key)))
+    }
+}
+
+/// Forwards each item of the broadcasted batches which matches the filter.
+///
+/// Batching keeps the topic capacity counted in blocks rather than in individual items.
+async fn batch_subscription(
+    msg_sender: mpsc::Sender<ResponseEvent>,
+    mut batches: TopicReceiver,
+    subscription_id: u32,
+    filter: impl Fn(&Value) -> bool,
+    to_response: fn(SubscriptionItem<Value>) -> ResponseEvent,
+    item_name: &'static str,
+) {
+    loop {
+        let batch = match batches.recv().await {
+            Ok(Received::Item(batch)) => batch,
+            Ok(Received::Reorg(reorg)) => {
+                let response = ResponseEvent::Reorg(SubscriptionItem {
                     subscription_id,
-                    reason: "Lagging stream, some headers were skipped. Closing subscription."
-                        .to_owned(),
+                    item: reorg,
+                });
+                if msg_sender.send(response).await.is_err() {
+                    break;
                 }
+                continue;
+            }
+            Err(TopicError::Closed) => break,
+            Err(error) => {
+                let _ = msg_sender
+                    .send(error.into_response(subscription_id, item_name))
+                    .await;
+                break;
             }
         };
 
-        if msg_sender.send(response).await.is_err() {
+        let Some(items) = batch.as_array() else {
+            continue;
+        };
+
+        for item in items.iter().filter(|item| filter(item)) {
+            let response = to_response(SubscriptionItem {
+                subscription_id,
+                item: item.clone(),
+            });
+
+            if msg_sender.send(response).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Sends the current status of the transaction followed by each of its status transitions.
+///
+/// The subscription ends once the transaction reaches a final status.
+async fn transaction_status_subscription(
+    msg_sender: mpsc::Sender<ResponseEvent>,
+    mut updates: broadcast::Receiver<Arc<TransactionStatusUpdate>>,
+    subscription_id: u32,
+    transaction_hash: TransactionHash,
+    poll: Arc<StatusPoll>,
+    rpc_context: RpcContext,
+) {
+    use broadcast::error::RecvError;
+
+    let query_status = || {
+        crate::pathfinder::methods::get_transaction_status(
+            rpc_context.clone(),
+            GetGatewayTransactionInput { transaction_hash },
+        )
+    };
+
+    let mut current = None;
+    let mut status = query_status().await;
+
+    loop {
+        let new_status = match status {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::debug!(error=?e, %transaction_hash, "Querying transaction status failed");
+                let _ = msg_sender
+                    .send(ResponseEvent::SubscriptionClosed {
+                        subscription_id,
+                        reason: "Querying the transaction status failed. Closing subscription."
+                            .to_owned(),
+                    })
+                    .await;
+                break;
+            }
+        };
+
+        if current != Some(new_status) {
+            current = Some(new_status);
+
+            let response = ResponseEvent::TransactionStatus(SubscriptionItem {
+                subscription_id,
+                item: TransactionStatusItem {
+                    transaction_hash,
+                    status: new_status,
+                },
+            });
+            if msg_sender.send(response).await.is_err() {
+                break;
+            }
+        }
+
+        use TransactionStatus::*;
+        if matches!(new_status, AcceptedOnL1 | Rejected | Reverted | Aborted) {
             break;
         }
+
+        // Wait for the next update relevant to this transaction.
+        status = loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(RecvError::Closed) => return,
+                Err(RecvError::Lagged(amount)) => {
+                    // Some updates were skipped, so the only reliable source is storage.
+                    tracing::debug!(amount, "Lagging transaction status stream");
+                    break query_status().await;
+                }
+            };
+
+            let statuses = match update.as_ref() {
+                TransactionStatusUpdate::Block(_, statuses)
+                | TransactionStatusUpdate::Pending(statuses) => statuses.as_slice(),
+                _ => &[],
+            };
+            if let Some((_, status)) = statuses.iter().find(|(hash, _)| hash == &transaction_hash) {
+                break Ok(*status);
+            }
+
+            let known_to_node = matches!(new_status, AcceptedOnL2);
+            let trigger = match update.as_ref() {
+                // Rejections are only known to the gateway, check for them once per block.
+                &TransactionStatusUpdate::Block(number, _) if !known_to_node => {
+                    PollTrigger::Block(number)
+                }
+                &TransactionStatusUpdate::L1Accepted(number) if known_to_node => {
+                    PollTrigger::L1Accepted(number)
+                }
+                // The block of the transaction may no longer be part of the chain.
+                &TransactionStatusUpdate::Reorg(number) => PollTrigger::Reorg(number),
+                _ => continue,
+            };
+            break poll_status(&poll, trigger, query_status()).await;
+        };
+    }
+}
+
+/// The update which caused a transaction's status to be polled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PollTrigger {
+    Block(BlockNumber),
+    L1Accepted(BlockNumber),
+    Reorg(BlockNumber),
+}
+
+/// The latest status poll of a transaction.
+type StatusPoll = tokio::sync::Mutex<Option<(PollTrigger, TransactionStatus)>>;
+
+/// The status polls of the transactions with `transactionStatus` subscriptions.
+///
+/// Every subscription receives the same updates, so sharing the polls means a transaction is
+/// only queried once per update no matter how many clients subscribed to it.
+#[derive(Debug, Clone, Default)]
+struct TransactionStatusPolls(Arc<std::sync::Mutex<HashMap<TransactionHash, Weak<StatusPoll>>>>);
+
+impl TransactionStatusPolls {
+    /// The poll of the transaction, shared by all of its subscriptions.
+    fn get(&self, transaction_hash: TransactionHash) -> Arc<StatusPoll> {
+        let mut polls = self.0.lock().unwrap();
+        if let Some(poll) = polls.get(&transaction_hash).and_then(Weak::upgrade) {
+            return poll;
+        }
+
+        // Forget the transactions which no longer have subscriptions.
+        polls.retain(|_, poll| poll.strong_count() > 0);
+
+        let poll = Arc::new(StatusPoll::default());
+        polls.insert(transaction_hash, Arc::downgrade(&poll));
+        poll
+    }
+}
+
+/// Returns the status polled for `trigger`, querying it if no other subscription did so yet.
+async fn poll_status<E>(
+    poll: &StatusPoll,
+    trigger: PollTrigger,
+    query: impl std::future::Future<Output = Result<TransactionStatus, E>>,
+) -> Result<TransactionStatus, E> {
+    let mut last = poll.lock().await;
+    if let Some((polled_for, status)) = *last {
+        if polled_for == trigger {
+            return Ok(status);
+        }
+    }
+
+    let status = query.await?;
+    *last = Some((trigger, status));
+    Ok(status)
+}
+
+/// A message broadcast to the subscriptions of a topic.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TopicMessage {
+    Item(Arc<Value>),
+    /// A serialized [Reorg]. Sent on the same channel as the items, so that subscriptions
+    /// receive it after the items it invalidates and before the items replacing them.
+    Reorg(Arc<Value>),
+    /// An item failed to serialize. Subscriptions are closed rather than silently skipping it.
+    SerializationFailed,
+}
+
+impl TopicMessage {
+    fn into_received(self) -> Result<Received, TopicError> {
+        match self {
+            TopicMessage::Item(item) => Ok(Received::Item(item)),
+            TopicMessage::Reorg(reorg) => Ok(Received::Reorg(reorg)),
+            TopicMessage::SerializationFailed => Err(TopicError::SerializationFailed),
+        }
     }
 }
 
@@ -314,7 +1045,7 @@ async fn header_subscription(
 /// Relies on `Arc`s to flatten the cloning costs inherent to Tokio broadcast channels.
 #[derive(Debug, Clone)]
 pub struct JsonBroadcaster<T> {
-    sender: broadcast::Sender<Arc<Value>>,
+    sender: broadcast::Sender<TopicMessage>,
     item_type: PhantomData<T>,
 }
 
@@ -322,28 +1053,74 @@ impl<T> JsonBroadcaster<T>
 where
     T: Serialize,
 {
+    pub fn is_receiving(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    /// Broadcasts the item. If it fails to serialize, the subscriptions are closed and the
+    /// error is returned.
     pub fn send_if_receiving(&self, item: T) -> Result<(), serde_json::Error> {
         if self.sender.receiver_count() > 0 {
             tracing::debug!("Broadcasting");
 
             // This won't cut all of serialization costs but it's a simple compromise.
             // At least things like string encoding will be performed once only.
-            let value = serde_json::to_value(item)?;
-            // Tokio broadcast channels clone the items for each subscriber.
-            // Embed the value in an `Arc` to flatten this cost.
-            let value = Arc::new(value);
+            let (message, result) = match serde_json::to_value(item) {
+                // Tokio broadcast channels clone the items for each subscriber.
+                // Embed the value in an `Arc` to flatten this cost.
+                Ok(value) => (TopicMessage::Item(Arc::new(value)), Ok(())),
+                Err(e) => (TopicMessage::SerializationFailed, Err(e)),
+            };
 
-            if let Err(err) = self.sender.send(value) {
+            if let Err(err) = self.sender.send(message) {
                 tracing::warn!("Broadcasting failed, the buffer might be full: {}", err);
             }
+
+            result
         } else {
             tracing::debug!("No receivers, skipping the broadcast");
+            Ok(())
+        }
+    }
+
+    /// Notifies the subscriptions of a reorg, in order with the items of the topic.
+    pub fn send_reorg_if_receiving(&self, reorg: &Reorg) -> Result<(), serde_json::Error> {
+        if self.sender.receiver_count() > 0 {
+            let reorg = Arc::new(serde_json::to_value(reorg)?);
+            if let Err(err) = self.sender.send(TopicMessage::Reorg(reorg)) {
+                tracing::warn!("Broadcasting failed, the buffer might be full: {}", err);
+            }
         }
 
         Ok(())
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<Arc<Value>> {
+    pub fn subscribe(&self) -> broadcast::Receiver<TopicMessage> {
+        self.sender.subscribe()
+    }
+}
+
+/// A Tokio broadcast sender for items which are consumed by the subscriptions themselves
+/// instead of being forwarded to clients, and are therefore not worth pre-serializing.
+#[derive(Debug, Clone)]
+pub struct Broadcaster<T> {
+    sender: broadcast::Sender<Arc<T>>,
+}
+
+impl<T> Broadcaster<T> {
+    pub fn is_receiving(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    pub fn send_if_receiving(&self, item: T) {
+        if self.is_receiving() {
+            if let Err(err) = self.sender.send(Arc::new(item)) {
+                tracing::warn!("Broadcasting failed, the buffer might be full: {}", err);
+            }
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<T>> {
         self.sender.subscribe()
     }
 }
@@ -351,6 +1128,14 @@ where
 #[derive(Debug, Clone)]
 pub struct TopicBroadcasters {
     pub new_head: JsonBroadcaster<BlockHeader>,
+    /// Broadcasts all events emitted in a block at once.
+    pub events: JsonBroadcaster<Vec<EmittedEvent>>,
+    pub transaction_status: Broadcaster<TransactionStatusUpdate>,
+    /// Broadcasts the transactions newly added to the pending block at once.
+    pub pending_transactions: JsonBroadcaster<Vec<PendingTransaction>>,
+    pub reorg: JsonBroadcaster<Reorg>,
+    pub l1_reorg: JsonBroadcaster<L1Reorg>,
+    transaction_status_polls: TransactionStatusPolls,
 }
 
 impl TopicBroadcasters {
@@ -360,25 +1145,46 @@ impl TopicBroadcasters {
                 sender: broadcast::channel(capacity.get()).0,
                 item_type: PhantomData {},
             },
-        }
-    }
-}
-
-impl Default for TopicBroadcasters {
-    fn default() -> Self {
-        TopicBroadcasters::with_capacity(
-            NonZeroUsize::new(100).expect("Invalid default broadcaster capacity"),
-        )
-    }
-}
+            events: JsonBroadcaster {
+                sender: broadcast::channel(capacity.get()).0,
+                item_type: PhantomData {},
+            },
+            transaction_status: Broadcaster {
+                sender: broadcast::channel(capacity.get()).0,
+            },
+            pending_transactions: JsonBroadcaster {
+                sender: broadcast::channel(capacity.get()).0,
+                item_type: PhantomData {},
+            },
+            reorg: JsonBroadcaster {
+                sender: broadcast::channel(capacity.get()).0,
+                item_type: PhantomData {},
+            },
+            l1_reorg: JsonBroadcaster {
+                sender: broadcast::channel(capacity.get()).0,
+                item_type: PhantomData {},
+            },
+            transaction_status_polls: Default::default(),
+        }
+    }
+}
+
+impl Default for TopicBroadcasters {
+    fn default() -> Self {
+        TopicBroadcasters::with_capacity(
+            NonZeroUsize::new(100).expect("Invalid default broadcaster capacity"),
+        )
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::jsonrpc::websocket::data::successful_response;
+    use crate::jsonrpc::websocket::data::{successful_response, NewHead};
     use crate::jsonrpc::{RpcError, RpcResponse};
     use axum::routing::get;
     use futures::{SinkExt, StreamExt};
+    use pathfinder_common::BlockNumber;
     use serde::Serialize;
     use serde_json::value::RawValue;
     use serde_json::{json, Number, Value};
@@ -474,6 +1280,534 @@ mod tests {
         client.destroy().await;
     }
 
+    #[tokio::test]
+    async fn new_heads_are_replayed_from_block() {
+        let mut client = Client::new().await;
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": NEW_HEADS_TOPIC,
+                    "from_block": 1,
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&successful_response(&0, req_id).unwrap())
+            .await;
+
+        let mut db = client.storage.connection().unwrap();
+        let db_tx = db.transaction().unwrap();
+        for number in [1, 2] {
+            let header = db_tx
+                .block_header(BlockNumber::new_or_panic(number).into())
+                .unwrap()
+                .unwrap();
+            client
+                .expect_response(&SubscriptionItem {
+                    subscription_id: 0,
+                    item: BlockHeader(header),
+                })
+                .await;
+        }
+
+        // The live stream resumes after the replayed blocks, without duplicates.
+        let header = |number| {
+            BlockHeader(pathfinder_common::BlockHeader {
+                number: BlockNumber::new_or_panic(number),
+                ..Default::default()
+            })
+        };
+        client.head_sender.send_if_receiving(header(2)).unwrap();
+        client.head_sender.send_if_receiving(header(3)).unwrap();
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: header(3),
+            })
+            .await;
+        client.expect_no_response().await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn from_block_too_far_behind_is_rejected() {
+        let mut client = Client::new().await;
+
+        let latest = BlockNumber::new_or_panic(MAX_REPLAY_BLOCKS + 2);
+        let mut db = client.storage.connection().unwrap();
+        let db_tx = db.transaction().unwrap();
+        db_tx
+            .insert_block_header(&pathfinder_common::BlockHeader {
+                number: latest,
+                hash: pathfinder_common::block_hash_bytes!(b"latest block hash"),
+                ..Default::default()
+            })
+            .unwrap();
+        db_tx.commit().unwrap();
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": NEW_HEADS_TOPIC,
+                    "from_block": 1,
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&RpcResponse {
+                output: Err(RpcError::InvalidParams(format!(
+                    "from_block must be at most {MAX_REPLAY_BLOCKS} blocks behind the latest \
+                     block {}",
+                    latest.get()
+                ))),
+                id: req_id,
+            })
+            .await;
+
+        // Replaying from the oldest block allowed is accepted.
+        let req_id = RequestId::Number(2);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": NEW_HEADS_TOPIC,
+                    "from_block": 2,
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&successful_response(&0, req_id).unwrap())
+            .await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn from_block_is_rejected_for_pending_topics() {
+        let mut client = Client::new().await;
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": PENDING_TRANSACTIONS_TOPIC,
+                    "from_block": 1,
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&RpcResponse {
+                output: Err(RpcError::InvalidParams(
+                    "from_block is only supported by the newHeads and events topics".to_owned(),
+                )),
+                id: req_id,
+            })
+            .await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn reorgs_are_pushed_to_new_heads_subscribers() {
+        use pathfinder_common::macro_prelude::*;
+
+        let mut client = Client::new().await;
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&Kind {
+                    kind: NEW_HEADS_TOPIC.into(),
+                }))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&successful_response(&0, req_id).unwrap())
+            .await;
+
+        let reorg = Reorg {
+            first_block_number: BlockNumber::new_or_panic(4),
+            first_block_hash: block_hash!("0x4"),
+            last_block_number: BlockNumber::new_or_panic(5),
+            last_block_hash: block_hash!("0x5"),
+            new_head: Some(NewHead {
+                block_number: BlockNumber::new_or_panic(3),
+                block_hash: block_hash!("0x3"),
+            }),
+        };
+        let header = header_sample();
+        client
+            .head_sender
+            .send_if_receiving(header.clone())
+            .unwrap();
+        client.head_sender.send_reorg_if_receiving(&reorg).unwrap();
+        client
+            .head_sender
+            .send_if_receiving(header.clone())
+            .unwrap();
+
+        // The reorg does not overtake the header sent before it.
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: header.clone(),
+            })
+            .await;
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: reorg,
+            })
+            .await;
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: header,
+            })
+            .await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn can_subscribe_to_filtered_events() {
+        use pathfinder_common::macro_prelude::*;
+
+        let mut client = Client::new().await;
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": EVENTS_TOPIC,
+                    "from_address": "0x1",
+                    "keys": [[], ["0xa", "0xb"]],
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&successful_response(&0, req_id).unwrap())
+            .await;
+
+        let event = |address, keys: Vec<EventKey>| EmittedEvent {
+            from_address: address,
+            keys,
+            data: vec![event_data!("0x99")],
+            block_hash: block_hash!("0x123"),
+            block_number: BlockNumber::new_or_panic(5),
+            transaction_hash: transaction_hash!("0x456"),
+        };
+        let matching = event(
+            contract_address!("0x1"),
+            vec![event_key!("0x3"), event_key!("0xb")],
+        );
+        let events = vec![
+            event(
+                contract_address!("0x2"),
+                vec![event_key!("0x3"), event_key!("0xb")],
+            ),
+            event(contract_address!("0x1"), vec![event_key!("0x3")]),
+            event(
+                contract_address!("0x1"),
+                vec![event_key!("0x3"), event_key!("0xc")],
+            ),
+            matching.clone(),
+        ];
+        client.event_sender.send_if_receiving(events).unwrap();
+
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: matching,
+            })
+            .await;
+        client.expect_no_response().await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn transaction_status_transitions_are_pushed() {
+        use pathfinder_common::macro_prelude::*;
+
+        let mut client = Client::new().await;
+        // This transaction is in block 1 which is not L1 accepted.
+        let transaction_hash = transaction_hash_bytes!(b"txn 1");
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": TRANSACTION_STATUS_TOPIC,
+                    "transaction_hash": transaction_hash,
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&successful_response(&0, req_id).unwrap())
+            .await;
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: TransactionStatusItem {
+                    transaction_hash,
+                    status: TransactionStatus::AcceptedOnL2,
+                },
+            })
+            .await;
+
+        // Updates for other transactions are ignored.
+        client
+            .status_sender
+            .send_if_receiving(TransactionStatusUpdate::Pending(vec![(
+                transaction_hash_bytes!(b"other"),
+                TransactionStatus::AcceptedOnL2,
+            )]));
+        client.expect_no_response().await;
+
+        let mut db = client.storage.connection().unwrap();
+        let db_tx = db.transaction().unwrap();
+        db_tx
+            .update_l1_l2_pointer(Some(BlockNumber::new_or_panic(1)))
+            .unwrap();
+        db_tx.commit().unwrap();
+
+        client
+            .status_sender
+            .send_if_receiving(TransactionStatusUpdate::L1Accepted(
+                BlockNumber::new_or_panic(1),
+            ));
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: TransactionStatusItem {
+                    transaction_hash,
+                    status: TransactionStatus::AcceptedOnL1,
+                },
+            })
+            .await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn transaction_status_is_polled_again_after_reorg() {
+        use pathfinder_common::macro_prelude::*;
+
+        let mut client = Client::new().await;
+        let transaction_hash = transaction_hash_bytes!(b"txn 1");
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": TRANSACTION_STATUS_TOPIC,
+                    "transaction_hash": transaction_hash,
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&successful_response(&0, req_id).unwrap())
+            .await;
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: TransactionStatusItem {
+                    transaction_hash,
+                    status: TransactionStatus::AcceptedOnL2,
+                },
+            })
+            .await;
+
+        // Change the status in storage without an update announcing it.
+        let mut db = client.storage.connection().unwrap();
+        let db_tx = db.transaction().unwrap();
+        db_tx
+            .update_l1_l2_pointer(Some(BlockNumber::new_or_panic(1)))
+            .unwrap();
+        db_tx.commit().unwrap();
+
+        client
+            .status_sender
+            .send_if_receiving(TransactionStatusUpdate::Reorg(BlockNumber::new_or_panic(2)));
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: TransactionStatusItem {
+                    transaction_hash,
+                    status: TransactionStatus::AcceptedOnL1,
+                },
+            })
+            .await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn transaction_status_polls_are_shared() {
+        use pathfinder_common::macro_prelude::*;
+
+        let polls = TransactionStatusPolls::default();
+        let first = polls.get(transaction_hash_bytes!(b"txn"));
+        let second = polls.get(transaction_hash_bytes!(b"txn"));
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let trigger = PollTrigger::Block(BlockNumber::new_or_panic(1));
+        let status = poll_status(&first, trigger, async {
+            Ok::<_, ()>(TransactionStatus::Received)
+        })
+        .await;
+        assert_eq!(status, Ok(TransactionStatus::Received));
+
+        // The poll of the same update is reused rather than queried again.
+        let status = poll_status(&second, trigger, async { Err(()) }).await;
+        assert_eq!(status, Ok(TransactionStatus::Received));
+
+        let trigger = PollTrigger::Block(BlockNumber::new_or_panic(2));
+        let status = poll_status(&second, trigger, async { Err(()) }).await;
+        assert_eq!(status, Err(()));
+
+        // Polls are forgotten once the transaction has no subscriptions left.
+        drop(first);
+        drop(second);
+        let _other = polls.get(transaction_hash_bytes!(b"other"));
+        assert_eq!(polls.0.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn can_subscribe_to_pending_transactions_by_sender() {
+        use pathfinder_common::macro_prelude::*;
+        use starknet_gateway_types::reply::transaction::{
+            InvokeTransaction, InvokeTransactionV1, Transaction,
+        };
+
+        let mut client = Client::new().await;
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": PENDING_TRANSACTIONS_TOPIC,
+                    "sender_address": "0x1",
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&successful_response(&0, req_id).unwrap())
+            .await;
+
+        let invoke = |sender, hash| {
+            PendingTransaction(Transaction::Invoke(InvokeTransaction::V1(
+                InvokeTransactionV1 {
+                    calldata: vec![],
+                    sender_address: sender,
+                    max_fee: Default::default(),
+                    signature: vec![],
+                    nonce: Default::default(),
+                    transaction_hash: hash,
+                },
+            )))
+        };
+        let matching = invoke(contract_address!("0x1"), transaction_hash!("0x11"));
+        client
+            .pending_transactions_sender
+            .send_if_receiving(vec![
+                invoke(contract_address!("0x2"), transaction_hash!("0x22")),
+                matching.clone(),
+            ])
+            .unwrap();
+
+        client
+            .expect_response(&SubscriptionItem {
+                subscription_id: 0,
+                item: matching,
+            })
+            .await;
+        client.expect_no_response().await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn subscriptions_are_closed_if_an_item_fails_to_serialize() {
+        let mut client = Client::new().await;
+
+        let req_id = RequestId::Number(1);
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&json!({
+                    "kind": PENDING_TRANSACTIONS_TOPIC,
+                })))),
+                id: req_id.clone(),
+            })
+            .await;
+        client
+            .expect_response(&successful_response(&0, req_id).unwrap())
+            .await;
+
+        client
+            .pending_transactions_sender
+            .sender
+            .send(TopicMessage::SerializationFailed)
+            .unwrap();
+        client
+            .expect_response(&ResponseEvent::SubscriptionClosed {
+                subscription_id: 0,
+                reason: "Failed to serialize a transaction. Closing subscription.".to_owned(),
+            })
+            .await;
+        client.expect_no_response().await;
+
+        client.destroy().await;
+    }
+
+    #[tokio::test]
+    async fn unknown_topic_is_rejected() {
+        let mut client = Client::new().await;
+
+        client
+            .send_request(&RpcRequest {
+                method: Cow::from(SUBSCRIBE_METHOD),
+                params: RawParams(Some(&value(&Kind {
+                    kind: "unknown".into(),
+                }))),
+                id: RequestId::Number(1),
+            })
+            .await;
+        client
+            .expect_response(&RpcResponse {
+                output: Err(RpcError::InvalidParams(
+                    "Unknown subscription type".to_owned(),
+                )),
+                id: RequestId::Number(1),
+            })
+            .await;
+
+        client.destroy().await;
+    }
+
     // TODO Prevent duplicate subscriptions?
     // This is actually tolerated by Alchemy, you can subscribe multiple times
     // to the same topic and receive duplicated messages as a result.
@@ -495,16 +1829,25 @@ mod tests {
         receiver: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
         server_handle: JoinHandle<()>,
         head_sender: JsonBroadcaster<BlockHeader>,
+        event_sender: JsonBroadcaster<Vec<EmittedEvent>>,
+        status_sender: Broadcaster<TransactionStatusUpdate>,
+        pending_transactions_sender: JsonBroadcaster<Vec<PendingTransaction>>,
+        storage: pathfinder_storage::Storage,
     }
 
     impl Client {
         async fn new() -> Client {
             let context = WebsocketContext::default();
             let head_sender = context.broadcasters.new_head.clone();
+            let event_sender = context.broadcasters.events.clone();
+            let status_sender = context.broadcasters.transaction_status.clone();
+            let pending_transactions_sender = context.broadcasters.pending_transactions.clone();
+            let rpc_context = RpcContext::for_tests().with_websockets(context);
+            let storage = rpc_context.storage.clone();
 
             let router = axum::Router::new()
                 .route("/ws", get(websocket_handler))
-                .with_state(context)
+                .with_state(rpc_context)
                 .layer(tower::ServiceBuilder::new());
 
             let listener = std::net::TcpListener::bind("127.0.0.1:0")
@@ -528,6 +1871,10 @@ mod tests {
 
             Client {
                 head_sender,
+                event_sender,
+                status_sender,
+                pending_transactions_sender,
+                storage,
                 sender,
                 receiver,
                 server_handle,
@@ -21,7 +21,11 @@ pub use pending::PendingData;
 
 use crate::jsonrpc::rpc_handler;
 use crate::jsonrpc::websocket::websocket_handler;
-pub use crate::jsonrpc::websocket::{BlockHeader, TopicBroadcasters};
+pub use crate::jsonrpc::websocket::{
+    BlockHeader, EmittedEvent, L1Reorg, NewHead, PendingTransaction, Reorg, TopicBroadcasters,
+    TopicMessage, TransactionStatusUpdate,
+};
+pub use crate::pathfinder::methods::TransactionStatus;
 use crate::v02::types::syncing::Syncing;
 use anyhow::Context;
 use axum::error_handling::HandleErrorLayer;
@@ -175,13 +179,11 @@ impl RpcServer {
             router
         };
 
-        let router = router
-            .with_state(self.context.websocket.clone().unwrap_or_default())
-            .layer(middleware);
+        let router = router.with_state(self.context.clone()).layer(middleware);
 
         let server_handle = tokio::spawn(async move {
             server
-                .serve(router.into_make_service())
+                .serve(router.into_make_service_with_connect_info::<SocketAddr>())
                 .await
                 .map_err(Into::into)
         });
@@ -326,9 +328,9 @@ pub mod test_utils {
             .set(contract0_addr, contract_state_hash)
             .unwrap();
 
-        let (storage_commitment0, nodes) = storage_commitment_tree.commit().unwrap();
+        let (storage_commitment0, update) = storage_commitment_tree.commit().unwrap();
         let storage_root_idx = db_txn
-            .insert_storage_trie(storage_commitment0, &nodes)
+            .insert_storage_trie(storage_commitment0, &update.nodes)
             .unwrap();
         db_txn
             .insert_storage_root(BlockNumber::GENESIS, Some(storage_root_idx))
@@ -367,9 +369,9 @@ pub mod test_utils {
         storage_commitment_tree
             .set(contract1_addr, contract_state_hash)
             .unwrap();
-        let (storage_commitment1, nodes) = storage_commitment_tree.commit().unwrap();
+        let (storage_commitment1, update) = storage_commitment_tree.commit().unwrap();
         let storage_root_idx = db_txn
-            .insert_storage_trie(storage_commitment1, &nodes)
+            .insert_storage_trie(storage_commitment1, &update.nodes)
             .unwrap();
         db_txn
             .insert_storage_root(BlockNumber::GENESIS + 1, Some(storage_root_idx))
@@ -426,9 +428,9 @@ pub mod test_utils {
         storage_commitment_tree
             .set(contract2_addr, contract_state_hash)
             .unwrap();
-        let (storage_commitment2, nodes) = storage_commitment_tree.commit().unwrap();
+        let (storage_commitment2, update) = storage_commitment_tree.commit().unwrap();
         let storage_root_idx = db_txn
-            .insert_storage_trie(storage_commitment2, &nodes)
+            .insert_storage_trie(storage_commitment2, &update.nodes)
             .unwrap();
         db_txn
             .insert_storage_root(BlockNumber::GENESIS + 2, Some(storage_root_idx))
@@ -1,3 +1,4 @@
 pub mod cors;
+pub mod rate_limit;
 pub(crate) mod request_id;
 pub(crate) mod tracing;
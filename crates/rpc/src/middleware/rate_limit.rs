@@ -0,0 +1,371 @@
+//! Per-client request rate limits, with a separate budget for expensive methods.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use http::{HeaderMap, HeaderName};
+
+/// Methods which execute transactions or scan large ranges of the database.
+const EXPENSIVE_METHODS: &[&str] = &[
+    "starknet_call",
+    "starknet_estimateFee",
+    "starknet_estimateMessageFee",
+    "starknet_simulateTransactions",
+    "starknet_traceBlockTransactions",
+    "starknet_traceTransaction",
+    "starknet_getEvents",
+    "pathfinder_call",
+    "pathfinder_estimateFee",
+    "pathfinder_simulateTransactions",
+    "pathfinder_traceCall",
+    "pathfinder_traceTransaction",
+    "pathfinder_getBlockWitness",
+];
+
+/// How long a client must be idle before its buckets are forgotten.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long it takes an empty bucket to refill, after which forgetting it makes no difference.
+const REFILL_TIME: Duration = Duration::from_secs(1);
+
+/// The most clients tracked at once. Once reached, the clients whose buckets have refilled are
+/// forgotten early, and the least recently seen client if there are none.
+const MAX_CLIENTS: usize = 100_000;
+
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    /// Requests per second allowed for each client, excluding expensive methods.
+    pub requests_per_second: Option<NonZeroU32>,
+    /// Requests per second allowed for each client to expensive methods.
+    pub expensive_requests_per_second: Option<NonZeroU32>,
+    /// Identify clients by the value of this header instead of their IP address if present.
+    pub api_key_header: Option<HeaderName>,
+    /// Identify clients by the last IP address in the `X-Forwarded-For` header if present, which
+    /// is the one added by the proxy in front of the node.
+    pub trust_forwarded_for: bool,
+}
+
+impl RateLimitConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.requests_per_second.is_some() || self.expensive_requests_per_second.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Client {
+    ApiKey(String),
+    Ip(IpAddr),
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    clients: Arc<Mutex<Clients>>,
+}
+
+struct Clients {
+    buckets: HashMap<Client, Buckets>,
+    last_cleanup: Instant,
+}
+
+impl Clients {
+    /// Forgets the clients which made no requests for `idle`.
+    fn forget_idle(&mut self, now: Instant, idle: Duration) {
+        self.buckets.retain(|_, buckets| {
+            buckets
+                .last_used()
+                .is_some_and(|used| now.saturating_duration_since(used) < idle)
+        });
+    }
+
+    fn forget_least_recently_used(&mut self) {
+        let client = self
+            .buckets
+            .iter()
+            .min_by_key(|(_, buckets)| buckets.last_used())
+            .map(|(client, _)| client.clone());
+
+        if let Some(client) = client {
+            self.buckets.remove(&client);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Buckets {
+    cheap: Option<Bucket>,
+    expensive: Option<Bucket>,
+}
+
+impl Buckets {
+    fn last_used(&self) -> Option<Instant> {
+        [&self.cheap, &self.expensive]
+            .into_iter()
+            .flatten()
+            .map(|bucket| bucket.updated)
+            .max()
+    }
+}
+
+/// A token bucket holding up to one second worth of requests.
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(now: Instant, rate: NonZeroU32) -> Self {
+        Self {
+            tokens: rate.get() as f64,
+            updated: now,
+        }
+    }
+
+    /// Takes a token from the bucket, or returns how long until one becomes available.
+    fn take(&mut self, now: Instant, rate: NonZeroU32) -> Result<(), Duration> {
+        let rate = rate.get() as f64;
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            clients: Arc::new(Mutex::new(Clients {
+                buckets: Default::default(),
+                last_cleanup: Instant::now(),
+            })),
+        }
+    }
+
+    /// Identifies the client making a request. Returns [None] if the client is unknown, in which
+    /// case no limits apply.
+    pub fn client(&self, addr: Option<SocketAddr>, headers: &HeaderMap) -> Option<Client> {
+        let api_key = self
+            .config
+            .api_key_header
+            .as_ref()
+            .and_then(|header| headers.get(header))
+            .and_then(|value| value.to_str().ok());
+
+        let forwarded_for = self
+            .config
+            .trust_forwarded_for
+            .then(|| headers.get("x-forwarded-for"))
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok());
+
+        match api_key {
+            Some(key) => Some(Client::ApiKey(key.to_owned())),
+            None => forwarded_for.or(addr.map(|addr| addr.ip())).map(Client::Ip),
+        }
+    }
+
+    /// Records a call to `method` by `client`. Returns how long the client should wait before
+    /// retrying if it is over its limit.
+    pub fn check(&self, client: &Client, method: &str) -> Result<(), Duration> {
+        self.check_at(Instant::now(), client, method)
+    }
+
+    fn check_at(&self, now: Instant, client: &Client, method: &str) -> Result<(), Duration> {
+        let expensive = EXPENSIVE_METHODS.contains(&method);
+        let rate = if expensive {
+            self.config.expensive_requests_per_second
+        } else {
+            self.config.requests_per_second
+        };
+        let Some(rate) = rate else {
+            return Ok(());
+        };
+
+        let mut clients = self.clients.lock().unwrap();
+
+        if now.saturating_duration_since(clients.last_cleanup) > IDLE_TIMEOUT {
+            clients.forget_idle(now, IDLE_TIMEOUT);
+            clients.last_cleanup = now;
+        }
+
+        if clients.buckets.len() >= MAX_CLIENTS && !clients.buckets.contains_key(client) {
+            clients.forget_idle(now, REFILL_TIME);
+            if clients.buckets.len() >= MAX_CLIENTS {
+                clients.forget_least_recently_used();
+            }
+        }
+
+        let buckets = clients.buckets.entry(client.clone()).or_default();
+        let bucket = if expensive {
+            &mut buckets.expensive
+        } else {
+            &mut buckets.cheap
+        };
+
+        bucket
+            .get_or_insert_with(|| Bucket::new(now, rate))
+            .take(now, rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(cheap: u32, expensive: u32) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            requests_per_second: NonZeroU32::new(cheap),
+            expensive_requests_per_second: NonZeroU32::new(expensive),
+            api_key_header: Some(HeaderName::from_static("x-api-key")),
+            trust_forwarded_for: false,
+        })
+    }
+
+    #[test]
+    fn budgets_are_separate() {
+        let limiter = limiter(2, 1);
+        let client = Client::Ip([127, 0, 0, 1].into());
+        let now = Instant::now();
+
+        limiter.check_at(now, &client, "starknet_call").unwrap();
+        limiter.check_at(now, &client, "starknet_call").unwrap_err();
+
+        limiter.check_at(now, &client, "starknet_chainId").unwrap();
+        limiter.check_at(now, &client, "starknet_chainId").unwrap();
+        let retry_after = limiter
+            .check_at(now, &client, "starknet_chainId")
+            .unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+
+        // Tokens are replenished over time.
+        let later = now + Duration::from_millis(500);
+        limiter
+            .check_at(later, &client, "starknet_chainId")
+            .unwrap();
+        limiter
+            .check_at(later, &client, "starknet_chainId")
+            .unwrap_err();
+    }
+
+    #[test]
+    fn clients_are_separate() {
+        let limiter = limiter(1, 1);
+        let now = Instant::now();
+
+        limiter
+            .check_at(now, &Client::Ip([127, 0, 0, 1].into()), "starknet_chainId")
+            .unwrap();
+        limiter
+            .check_at(now, &Client::Ip([127, 0, 0, 2].into()), "starknet_chainId")
+            .unwrap();
+        limiter
+            .check_at(now, &Client::Ip([127, 0, 0, 1].into()), "starknet_chainId")
+            .unwrap_err();
+    }
+
+    #[test]
+    fn unlimited_budget() {
+        let limiter = limiter(0, 1);
+        let client = Client::Ip([127, 0, 0, 1].into());
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            limiter.check_at(now, &client, "starknet_chainId").unwrap();
+        }
+    }
+
+    #[test]
+    fn client_identification() {
+        let limiter = limiter(1, 1);
+        let addr = Some(SocketAddr::from(([127, 0, 0, 1], 9545)));
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            limiter.client(addr, &headers),
+            Some(Client::Ip([127, 0, 0, 1].into()))
+        );
+        assert_eq!(limiter.client(None, &headers), None);
+
+        headers.insert("x-api-key", "secret".parse().unwrap());
+        assert_eq!(
+            limiter.client(addr, &headers),
+            Some(Client::ApiKey("secret".to_owned()))
+        );
+    }
+
+    #[test]
+    fn forwarded_for_is_opt_in() {
+        let addr = Some(SocketAddr::from(([127, 0, 0, 1], 9545)));
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "10.0.0.1, 10.0.0.2".parse().unwrap());
+
+        let untrusting = limiter(1, 1);
+        assert_eq!(
+            untrusting.client(addr, &headers),
+            Some(Client::Ip([127, 0, 0, 1].into()))
+        );
+
+        // The last address is the one added by the trusted proxy, the others are up to the client.
+        let trusting = RateLimiter::new(RateLimitConfig {
+            requests_per_second: NonZeroU32::new(1),
+            trust_forwarded_for: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            trusting.client(addr, &headers),
+            Some(Client::Ip([10, 0, 0, 2].into()))
+        );
+
+        headers.insert("x-forwarded-for", "garbage".parse().unwrap());
+        assert_eq!(
+            trusting.client(addr, &headers),
+            Some(Client::Ip([127, 0, 0, 1].into()))
+        );
+    }
+
+    #[test]
+    fn clients_are_bounded() {
+        let limiter = limiter(1, 1);
+        let now = Instant::now();
+        let first = Client::ApiKey("first".to_owned());
+        let later = now + Duration::from_millis(500);
+
+        limiter.check_at(now, &first, "starknet_chainId").unwrap();
+        for i in 1..MAX_CLIENTS {
+            let client = Client::ApiKey(i.to_string());
+            limiter
+                .check_at(later, &client, "starknet_chainId")
+                .unwrap();
+        }
+        assert_eq!(limiter.clients.lock().unwrap().buckets.len(), MAX_CLIENTS);
+
+        // Without refilled buckets to forget, the least recently seen client is forgotten.
+        let client = Client::ApiKey("new".to_owned());
+        limiter
+            .check_at(later, &client, "starknet_chainId")
+            .unwrap();
+        let clients = limiter.clients.lock().unwrap();
+        assert_eq!(clients.buckets.len(), MAX_CLIENTS);
+        assert!(!clients.buckets.contains_key(&first));
+        drop(clients);
+
+        // Once refilled, all the buckets are forgotten.
+        let refilled = later + REFILL_TIME;
+        limiter
+            .check_at(refilled, &first, "starknet_chainId")
+            .unwrap();
+        assert_eq!(limiter.clients.lock().unwrap().buckets.len(), 1);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::jsonrpc::{RpcRouter, RpcRouterBuilder};
 
 pub(crate) mod methods;
+pub(crate) mod types;
 
 #[rustfmt::skip]
 pub fn register_routes() -> RpcRouterBuilder {
@@ -8,4 +9,12 @@ pub fn register_routes() -> RpcRouterBuilder {
         .register("pathfinder_version",              || { pathfinder_common::consts::VERGEN_GIT_DESCRIBE })
         .register("pathfinder_getProof",             methods::get_proof)
         .register("pathfinder_getTransactionStatus", methods::get_transaction_status)
+        .register("pathfinder_getMessagesStatus",    methods::get_messages_status)
+        .register("pathfinder_getL1Settlement",      methods::get_l1_settlement)
+        .register("pathfinder_call",                 methods::call)
+        .register("pathfinder_estimateFee",          methods::estimate_fee)
+        .register("pathfinder_simulateTransactions", methods::simulate_transactions)
+        .register("pathfinder_traceCall",            methods::trace_call)
+        .register("pathfinder_traceTransaction",     methods::trace_transaction)
+        .register("pathfinder_getBlockWitness",      methods::get_block_witness)
 }
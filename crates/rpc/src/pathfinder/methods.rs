@@ -1,5 +1,18 @@
+mod get_block_witness;
+mod get_l1_settlement;
+mod get_messages_status;
 mod get_proof;
 mod get_transaction_status;
+mod overrides;
+mod trace_call;
+mod trace_transaction;
 
+pub(crate) use get_block_witness::get_block_witness;
+pub(crate) use get_l1_settlement::get_l1_settlement;
+pub(crate) use get_messages_status::get_messages_status;
 pub(crate) use get_proof::get_proof;
-pub(crate) use get_transaction_status::get_transaction_status;
+pub use get_transaction_status::TransactionStatus;
+pub(crate) use get_transaction_status::{get_transaction_status, GetGatewayTransactionInput};
+pub(crate) use overrides::{call, call_v04, estimate_fee, simulate_transactions};
+pub(crate) use trace_call::trace_call;
+pub(crate) use trace_transaction::trace_transaction;
@@ -0,0 +1,647 @@
+use std::collections::BTreeSet;
+
+use anyhow::{anyhow, Context};
+use pathfinder_common::{prelude::*, BlockId};
+use pathfinder_executor::{ExecutionState, TransactionExecutionError};
+use pathfinder_merkle_tree::ClassCommitmentTree;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use serde_with::skip_serializing_none;
+
+use super::get_proof::{contract_proof, ContractData, ProofNodes};
+use crate::compose_executor_transaction;
+use crate::context::RpcContext;
+use crate::executor::VERSIONS_LOWER_THAN_THIS_SHOULD_FALL_BACK_TO_FETCHING_TRACE_FROM_GATEWAY;
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockWitnessInput {
+    pub block_id: BlockId,
+    /// Limits the witness to the block's transactions up to and including this one.
+    #[serde(default)]
+    pub transaction_hash: Option<TransactionHash>,
+}
+
+#[derive(Debug)]
+pub enum GetBlockWitnessError {
+    Internal(anyhow::Error),
+    Custom(anyhow::Error),
+    BlockNotFound,
+    TxnHashNotFound,
+    BlockPruned { oldest_block: BlockNumber },
+    DataPruned { oldest_block: BlockNumber },
+}
+
+impl From<anyhow::Error> for GetBlockWitnessError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<pathfinder_storage::DataPruned>() {
+            Ok(pruned) => Self::DataPruned {
+                oldest_block: pruned.oldest_block,
+            },
+            Err(e) => Self::Internal(e),
+        }
+    }
+}
+
+impl From<TransactionExecutionError> for GetBlockWitnessError {
+    fn from(value: TransactionExecutionError) -> Self {
+        use TransactionExecutionError::*;
+        match value {
+            ExecutionError {
+                transaction_index,
+                error,
+            } => Self::Custom(anyhow!(
+                "Transaction execution failed at index {}: {}",
+                transaction_index,
+                error
+            )),
+            Internal(e) => Self::Internal(e),
+            Custom(e) => Self::Custom(e),
+        }
+    }
+}
+
+impl From<GetBlockWitnessError> for crate::error::ApplicationError {
+    fn from(x: GetBlockWitnessError) -> Self {
+        match x {
+            GetBlockWitnessError::BlockNotFound => Self::BlockNotFound,
+            GetBlockWitnessError::TxnHashNotFound => Self::TxnHashNotFound,
+            GetBlockWitnessError::BlockPruned { oldest_block } => Self::BlockPruned {
+                oldest_block: oldest_block.get(),
+            },
+            GetBlockWitnessError::DataPruned { oldest_block } => Self::DataPruned {
+                oldest_block: oldest_block.get(),
+            },
+            GetBlockWitnessError::Internal(internal) => Self::Internal(internal),
+            GetBlockWitnessError::Custom(custom) => Self::Custom(custom),
+        }
+    }
+}
+
+/// Everything needed to re-execute a block without access to the rest of the state.
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+pub struct GetBlockWitnessOutput {
+    /// The global state commitment of the parent block, which the proofs are against. Absent for
+    /// the genesis block, and like in `pathfinder_getProof` for parent blocks before Starknet
+    /// 0.11.0.
+    state_commitment: Option<StateCommitment>,
+    /// The class commitment of the parent block.
+    class_commitment: Option<ClassCommitment>,
+    /// The hash of the block ten blocks earlier, which is written to the system contract before
+    /// the block's transactions execute. Reads of it are not part of
+    /// [contracts](GetBlockWitnessOutput::contracts).
+    system_contract_update: Option<StorageWrite>,
+    /// The contracts accessed by the block's transactions.
+    contracts: Vec<ContractWitness>,
+    /// The classes loaded by the block's transactions from the parent state. Classes declared by
+    /// the block itself are not included.
+    classes: Vec<ClassWitness>,
+    /// The Sierra classes loaded by the block's transactions, and the classes whose compiled
+    /// class hashes they read.
+    class_proofs: Vec<ClassProof>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageWrite {
+    contract_address: ContractAddress,
+    key: StorageAddress,
+    value: StorageValue,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+pub struct ContractWitness {
+    contract_address: ContractAddress,
+    /// Membership / Non-membership proof for the contract in the parent state.
+    contract_proof: ProofNodes,
+    /// Present only if the contract exists in the parent state. Its storage proofs are in the
+    /// order of [storage](ContractWitness::storage).
+    contract_data: Option<ContractData>,
+    /// The storage slots read by the block's transactions and their values in the parent state.
+    storage: Vec<StorageEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageEntry {
+    key: StorageAddress,
+    value: StorageValue,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+pub struct ClassWitness {
+    class_hash: ClassHash,
+    definition: Box<RawValue>,
+    /// Present only for Sierra classes.
+    casm: Option<Box<RawValue>>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+pub struct ClassProof {
+    class_hash: ClassHash,
+    /// Absent if the class is not declared in the parent state.
+    compiled_class_hash: Option<CasmHash>,
+    /// Membership / Non-membership proof for the class in the parent class commitment tree.
+    class_proof: ProofNodes,
+}
+
+/// Executes the block and returns the values, proofs and classes its transactions read from the
+/// parent state.
+pub async fn get_block_witness(
+    context: RpcContext,
+    input: GetBlockWitnessInput,
+) -> Result<GetBlockWitnessOutput, GetBlockWitnessError> {
+    let block_id = match input.block_id {
+        BlockId::Pending => {
+            return Err(GetBlockWitnessError::Internal(anyhow!(
+                "'pending' is not currently supported by this method!"
+            )))
+        }
+        other => other.try_into().expect("Only pending cast should fail"),
+    };
+    let transaction_hash = input.transaction_hash;
+
+    let storage = context.storage.clone();
+    let span = tracing::Span::current();
+
+    let jh = tokio::task::spawn_blocking(move || {
+        let _g = span.enter();
+        let mut db = storage
+            .connection()
+            .context("Opening database connection")?;
+
+        let tx = db.transaction().context("Creating database transaction")?;
+
+        let header = tx
+            .block_header(block_id)
+            .context("Fetching block header")?
+            .ok_or(GetBlockWitnessError::BlockNotFound)?;
+
+        let starknet_version = header
+            .starknet_version
+            .parse_as_semver()
+            .context("Parsing starknet version")?
+            .unwrap_or(semver::Version::new(0, 0, 0));
+        if starknet_version
+            < VERSIONS_LOWER_THAN_THIS_SHOULD_FALL_BACK_TO_FETCHING_TRACE_FROM_GATEWAY
+        {
+            return Err(GetBlockWitnessError::Custom(anyhow!(
+                "Witnesses are not available for blocks before Starknet {}",
+                VERSIONS_LOWER_THAN_THIS_SHOULD_FALL_BACK_TO_FETCHING_TRACE_FROM_GATEWAY
+            )));
+        }
+
+        let parent = header.number.parent();
+        if let (Some(parent), Some(oldest_block)) = (
+            parent,
+            tx.trie_prune_horizon()
+                .context("Querying trie prune horizon")?,
+        ) {
+            if parent < oldest_block {
+                return Err(GetBlockWitnessError::BlockPruned { oldest_block });
+            }
+        }
+
+        let mut transactions = tx
+            .transactions_for_block(block_id)
+            .context("Fetching transactions")?
+            .context("Transaction data missing")?;
+        if let Some(transaction_hash) = transaction_hash {
+            let index = transactions
+                .iter()
+                .position(|transaction| transaction.hash() == transaction_hash)
+                .ok_or(GetBlockWitnessError::TxnHashNotFound)?;
+            transactions.truncate(index + 1);
+        }
+        let transactions = transactions
+            .iter()
+            .map(|transaction| compose_executor_transaction(transaction, &tx))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let state = ExecutionState::trace(&tx, context.chain_id, header, None).with_access_lists();
+        let system_contract_update = state
+            .system_contract_update()
+            .context("Querying system contract update")?;
+        let traces = pathfinder_executor::trace_all(state, transactions, true, true)?;
+
+        let mut access_list = pathfinder_executor::types::AccessList::default();
+        for (_, trace) in traces {
+            let trace_access_list = trace
+                .access_list()
+                .context("Access list missing from trace")?;
+            access_list.extend(trace_access_list.clone());
+        }
+
+        // The slot holds the written value rather than the one in the parent state.
+        if let Some((contract_address, key, _)) = system_contract_update {
+            if let Some(keys) = access_list.storage_reads.get_mut(&contract_address) {
+                keys.remove(&key);
+                if keys.is_empty() {
+                    access_list.storage_reads.remove(&contract_address);
+                }
+            }
+        }
+        let system_contract_update =
+            system_contract_update.map(|(contract_address, key, value)| StorageWrite {
+                contract_address,
+                key,
+                value,
+            });
+
+        let (state_commitment, class_commitment) = match parent {
+            Some(parent) => {
+                let parent = tx
+                    .block_header(parent.into())
+                    .context("Fetching parent block header")?
+                    .context("Parent block header missing")?;
+
+                let state_commitment = match parent.state_commitment {
+                    StateCommitment::ZERO => None,
+                    other => Some(other),
+                };
+                let class_commitment = match parent.class_commitment {
+                    ClassCommitment::ZERO => None,
+                    other => Some(other),
+                };
+
+                (state_commitment, class_commitment)
+            }
+            None => (None, None),
+        };
+
+        let contract_addresses = access_list
+            .storage_reads
+            .keys()
+            .chain(&access_list.nonces)
+            .chain(&access_list.contracts)
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        let contracts = contract_addresses
+            .into_iter()
+            .map(|contract_address| {
+                let keys = access_list
+                    .storage_reads
+                    .get(&contract_address)
+                    .map(|keys| keys.iter().copied().collect::<Vec<_>>())
+                    .unwrap_or_default();
+
+                // The genesis block executes on an empty state.
+                let Some(parent) = parent else {
+                    return Ok(ContractWitness {
+                        contract_address,
+                        contract_proof: ProofNodes::default(),
+                        contract_data: None,
+                        storage: keys
+                            .into_iter()
+                            .map(|key| StorageEntry {
+                                key,
+                                value: StorageValue::ZERO,
+                            })
+                            .collect(),
+                    });
+                };
+
+                let storage = keys
+                    .iter()
+                    .map(|key| {
+                        let value = tx
+                            .storage_value(parent.into(), contract_address, *key)
+                            .context("Querying storage value")?
+                            .unwrap_or_default();
+
+                        Ok(StorageEntry { key: *key, value })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                let (contract_proof, contract_data) =
+                    contract_proof(&tx, parent, contract_address, &keys)?;
+
+                Ok(ContractWitness {
+                    contract_address,
+                    contract_proof,
+                    contract_data,
+                    storage,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut classes = Vec::new();
+        for class_hash in &access_list.classes {
+            // The genesis block executes on an empty state.
+            let Some(parent) = parent else {
+                break;
+            };
+            // Classes which are not declared in the parent state were looked up but not loaded,
+            // or are declared by the block itself, whose declare transactions contain them.
+            let Some(definition) = tx
+                .class_definition_at(parent.into(), *class_hash)
+                .context("Fetching class definition")?
+            else {
+                continue;
+            };
+            let definition = raw_json(definition).context("Parsing class definition")?;
+
+            let casm = tx
+                .casm_definition_at(parent.into(), *class_hash)
+                .context("Fetching compiled class definition")?
+                .map(raw_json)
+                .transpose()
+                .context("Parsing compiled class definition")?;
+
+            classes.push(ClassWitness {
+                class_hash: *class_hash,
+                definition,
+                casm,
+            });
+        }
+
+        let class_proofs = classes
+            .iter()
+            .filter(|class| class.casm.is_some())
+            .map(|class| class.class_hash)
+            .chain(access_list.compiled_class_hashes.iter().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|class_hash| {
+                let Some(parent) = parent else {
+                    return Ok(ClassProof {
+                        class_hash,
+                        compiled_class_hash: None,
+                        class_proof: ProofNodes::default(),
+                    });
+                };
+
+                let compiled_class_hash = tx
+                    .casm_hash_at(parent.into(), class_hash)
+                    .context("Querying compiled class hash")?;
+                let class_proof =
+                    ClassCommitmentTree::get_proof(&tx, parent, SierraHash(class_hash.0))
+                        .context("Creating class proof")?;
+
+                Ok(ClassProof {
+                    class_hash,
+                    compiled_class_hash,
+                    class_proof: ProofNodes(class_proof),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(GetBlockWitnessOutput {
+            state_commitment,
+            class_commitment,
+            system_contract_update,
+            contracts,
+            classes,
+            class_proofs,
+        })
+    });
+
+    jh.await.context("Database read panic or shutting down")?
+}
+
+fn raw_json(definition: Vec<u8>) -> anyhow::Result<Box<RawValue>> {
+    let definition = String::from_utf8(definition)?;
+    Ok(RawValue::from_string(definition)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::hash::{FeltHash, PedersenHash};
+    use pathfinder_common::trie::TrieNode;
+    use pathfinder_common::{macro_prelude::*, StarknetVersion};
+    use pathfinder_crypto::Felt;
+    use pathfinder_merkle_tree::contract_state::calculate_contract_state_hash;
+    use pathfinder_storage::Storage;
+
+    use super::*;
+    use crate::v06::method::simulate_transactions::tests::fixtures;
+    use crate::v06::method::trace_block_transactions::tests::setup_multi_tx_trace_test;
+
+    /// Follows `proof` from `root` towards `key`. Returns the value of the leaf, or `None` if
+    /// the proof shows that the key is not in the tree. Panics if the proof is invalid.
+    fn verify_proof<H: FeltHash>(root: Felt, key: Felt, proof: &ProofNodes) -> Option<Felt> {
+        let mut expected_hash = root;
+        let mut remaining_path = key.view_bits();
+
+        for node in &proof.0 {
+            assert_eq!(node.hash::<H>(), expected_hash, "Proof node hash mismatch");
+
+            match node {
+                TrieNode::Binary { left, right } => {
+                    expected_hash = if remaining_path[0] { *right } else { *left };
+                    remaining_path = &remaining_path[1..];
+                }
+                TrieNode::Edge { child, path } => {
+                    if path != &remaining_path[..path.len()] {
+                        return None;
+                    }
+                    expected_hash = *child;
+                    remaining_path = &remaining_path[path.len()..];
+                }
+            }
+        }
+
+        assert!(remaining_path.is_empty(), "Proof ends before the leaf");
+        Some(expected_hash)
+    }
+
+    #[tokio::test]
+    async fn witness() {
+        let (context, next_block_header, _) = setup_multi_tx_trace_test().await.unwrap();
+
+        let input = GetBlockWitnessInput {
+            block_id: next_block_header.hash.into(),
+            transaction_hash: None,
+        };
+        let output = get_block_witness(context.clone(), input).await.unwrap();
+
+        // The fee token balances are read.
+        let fee_token = output
+            .contracts
+            .iter()
+            .find(|contract| {
+                contract.contract_address == pathfinder_executor::ETH_FEE_TOKEN_ADDRESS
+            })
+            .unwrap();
+        assert!(!fee_token.storage.is_empty());
+
+        // The proofs are against the parent state commitment.
+        let state_commitment = output.state_commitment.unwrap();
+        let class_commitment = output.class_commitment.unwrap_or_default();
+        let storage_commitment =
+            StorageCommitment(output.contracts[0].contract_proof.0[0].hash::<PedersenHash>());
+        assert_eq!(
+            StateCommitment::calculate(storage_commitment, class_commitment),
+            state_commitment
+        );
+
+        let mut existing_contracts = 0;
+        for contract in &output.contracts {
+            let contract_state_hash = verify_proof::<PedersenHash>(
+                storage_commitment.0,
+                contract.contract_address.0,
+                &contract.contract_proof,
+            );
+
+            let Some(contract_data) = &contract.contract_data else {
+                assert_eq!(contract_state_hash, None);
+                assert!(contract
+                    .storage
+                    .iter()
+                    .all(|entry| entry.value == StorageValue::ZERO));
+                continue;
+            };
+            existing_contracts += 1;
+            assert_eq!(
+                contract_state_hash,
+                Some(
+                    calculate_contract_state_hash(
+                        contract_data.class_hash,
+                        contract_data.root,
+                        contract_data.nonce
+                    )
+                    .0
+                )
+            );
+
+            assert_eq!(contract.storage.len(), contract_data.storage_proofs.len());
+            for (entry, proof) in contract.storage.iter().zip(&contract_data.storage_proofs) {
+                let value = verify_proof::<PedersenHash>(contract_data.root.0, entry.key.0, proof)
+                    .unwrap_or_default();
+                assert_eq!(value, entry.value.0);
+            }
+        }
+        assert!(existing_contracts > 0);
+
+        // The declared class is not declared in the parent state.
+        let mut db = context.storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        let parent = next_block_header.number.parent().unwrap();
+        assert!(!output.classes.is_empty());
+        for class in &output.classes {
+            assert!(tx
+                .class_definition_at(parent.into(), class.class_hash)
+                .unwrap()
+                .is_some());
+        }
+        let declared_class = ClassHash(fixtures::SIERRA_HASH.0);
+        assert!(!output
+            .classes
+            .iter()
+            .any(|class| class.class_hash == declared_class));
+        let class_proof = output
+            .class_proofs
+            .iter()
+            .find(|class| class.class_hash == declared_class)
+            .unwrap();
+        assert_eq!(class_proof.compiled_class_hash, None);
+        assert!(class_proof.class_proof.0.is_empty());
+
+        // The block is not ten blocks deep.
+        assert!(output.system_contract_update.is_none());
+    }
+
+    #[tokio::test]
+    async fn transaction_scope() {
+        let (context, next_block_header, _) = setup_multi_tx_trace_test().await.unwrap();
+
+        let transaction_hash = {
+            let mut db = context.storage.connection().unwrap();
+            let tx = db.transaction().unwrap();
+            tx.transactions_for_block(next_block_header.hash.into())
+                .unwrap()
+                .unwrap()[0]
+                .hash()
+        };
+
+        // Only the declare transaction is executed, which checks that the class is not declared
+        // yet and does not touch the contract deployed by the following transactions.
+        let input = GetBlockWitnessInput {
+            block_id: next_block_header.hash.into(),
+            transaction_hash: Some(transaction_hash),
+        };
+        let output = get_block_witness(context.clone(), input).await.unwrap();
+
+        let declared_class = ClassHash(fixtures::SIERRA_HASH.0);
+        assert!(output
+            .class_proofs
+            .iter()
+            .any(|class| class.class_hash == declared_class));
+        assert!(!output
+            .contracts
+            .iter()
+            .any(|contract| { contract.contract_address == fixtures::DEPLOYED_CONTRACT_ADDRESS }));
+
+        let input = GetBlockWitnessInput {
+            block_id: next_block_header.hash.into(),
+            transaction_hash: Some(transaction_hash_bytes!(b"non-existent")),
+        };
+        let err = get_block_witness(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(err, GetBlockWitnessError::TxnHashNotFound);
+    }
+
+    #[tokio::test]
+    async fn genesis() {
+        let storage = Storage::in_memory().unwrap();
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        let header = BlockHeader::builder()
+            .with_number(BlockNumber::GENESIS)
+            .with_starknet_version(StarknetVersion::new(0, 13, 0))
+            .finalize_with_hash(block_hash!("0xb00"));
+        tx.insert_block_header(&header).unwrap();
+        tx.insert_transaction_data(header.hash, header.number, &[])
+            .unwrap();
+        tx.commit().unwrap();
+
+        let context = RpcContext::for_tests().with_storage(storage);
+        let input = GetBlockWitnessInput {
+            block_id: BlockId::Number(BlockNumber::GENESIS),
+            transaction_hash: None,
+        };
+        let output = get_block_witness(context, input).await.unwrap();
+
+        // There is no parent state to prove against.
+        assert_eq!(output.state_commitment, None);
+        assert_eq!(output.class_commitment, None);
+        assert!(output.system_contract_update.is_none());
+        assert!(output.contracts.is_empty());
+        assert!(output.classes.is_empty());
+        assert!(output.class_proofs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn block_pruned() {
+        let (context, next_block_header, _) = setup_multi_tx_trace_test().await.unwrap();
+
+        let mut db = context.storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        tx.prune_tries(next_block_header.number).unwrap();
+        tx.commit().unwrap();
+
+        let input = GetBlockWitnessInput {
+            block_id: next_block_header.hash.into(),
+            transaction_hash: None,
+        };
+        let err = get_block_witness(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            err,
+            GetBlockWitnessError::BlockPruned { oldest_block } if oldest_block == next_block_header.number
+        );
+    }
+
+    #[tokio::test]
+    async fn block_not_found() {
+        let context = RpcContext::for_tests();
+        let input = GetBlockWitnessInput {
+            block_id: BlockId::Number(BlockNumber::new_or_panic(1000)),
+            transaction_hash: None,
+        };
+
+        let err = get_block_witness(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(err, GetBlockWitnessError::BlockNotFound);
+    }
+}
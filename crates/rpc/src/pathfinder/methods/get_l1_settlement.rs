@@ -0,0 +1,128 @@
+use anyhow::Context;
+use pathfinder_common::{BlockHash, BlockId, BlockNumber, StateCommitment};
+use pathfinder_ethereum::StateUpdateLog;
+use primitive_types::H256;
+
+use crate::context::RpcContext;
+
+#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+pub struct GetL1SettlementInput {
+    block_id: BlockId,
+}
+
+/// The L1 state update which settled a block.
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct L1Settlement {
+    l1_block_number: u64,
+    l1_block_hash: H256,
+    l1_transaction_hash: H256,
+    /// The latest block included in the state update.
+    block_number: BlockNumber,
+    block_hash: BlockHash,
+    state_root: StateCommitment,
+}
+
+impl From<StateUpdateLog> for L1Settlement {
+    fn from(log: StateUpdateLog) -> Self {
+        Self {
+            l1_block_number: log.l1_block_number,
+            l1_block_hash: log.l1_block_hash,
+            l1_transaction_hash: log.l1_transaction_hash,
+            block_number: log.update.block_number,
+            block_hash: log.update.block_hash,
+            state_root: log.update.state_root,
+        }
+    }
+}
+
+crate::error::generate_rpc_error_subset!(GetL1SettlementError: BlockNotFound);
+
+/// Returns the first L1 state update which includes the block, or [None] if the block has not been
+/// settled on L1 yet, or the L1 transaction which settled it is unknown.
+pub async fn get_l1_settlement(
+    context: RpcContext,
+    input: GetL1SettlementInput,
+) -> Result<Option<L1Settlement>, GetL1SettlementError> {
+    let block_id = match input.block_id {
+        BlockId::Pending => return Ok(None),
+        other => other.try_into().expect("Only pending cast should fail"),
+    };
+
+    let span = tracing::Span::current();
+
+    tokio::task::spawn_blocking(move || {
+        let _g = span.enter();
+
+        let mut db = context
+            .storage
+            .connection()
+            .context("Opening database connection")?;
+        let db_tx = db.transaction().context("Creating database transaction")?;
+
+        let (number, _) = db_tx
+            .block_id(block_id)
+            .context("Querying block")?
+            .ok_or(GetL1SettlementError::BlockNotFound)?;
+
+        let settlement = db_tx
+            .l1_state_log_settling(number)
+            .context("Querying L1 state update")?
+            .map(L1Settlement::from);
+
+        Ok(settlement)
+    })
+    .await
+    .context("Joining database task")?
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_ethereum::EthereumStateUpdate;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn settled_block() {
+        let context = RpcContext::for_tests();
+        let log = StateUpdateLog {
+            update: EthereumStateUpdate {
+                state_root: state_commitment_bytes!(b"state commitment 1"),
+                block_number: BlockNumber::new_or_panic(1),
+                block_hash: block_hash_bytes!(b"block 1"),
+            },
+            l1_block_number: 100,
+            l1_block_hash: H256::from_low_u64_be(100),
+            l1_transaction_hash: H256::from_low_u64_be(1),
+        };
+        let mut db = context.storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        tx.insert_l1_state_log(&log).unwrap();
+        tx.commit().unwrap();
+
+        for block in [BlockNumber::GENESIS, BlockNumber::new_or_panic(1)] {
+            let input = GetL1SettlementInput {
+                block_id: block.into(),
+            };
+            let settlement = get_l1_settlement(context.clone(), input).await.unwrap();
+            assert_eq!(settlement, Some(log.clone().into()));
+        }
+
+        let input = GetL1SettlementInput {
+            block_id: BlockId::Latest,
+        };
+        let settlement = get_l1_settlement(context, input).await.unwrap();
+        assert_eq!(settlement, None);
+    }
+
+    #[tokio::test]
+    async fn block_not_found() {
+        let context = RpcContext::for_tests();
+        let input = GetL1SettlementInput {
+            block_id: BlockNumber::new_or_panic(100).into(),
+        };
+        let result = get_l1_settlement(context, input).await;
+
+        assert_matches::assert_matches!(result, Err(GetL1SettlementError::BlockNotFound));
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::Context;
+use pathfinder_common::TransactionHash;
+use pathfinder_storage::L1MessageDirection;
+use primitive_types::H256;
+
+use super::get_transaction_status::database_status;
+use super::TransactionStatus;
+use crate::context::RpcContext;
+
+#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+pub struct GetMessagesStatusInput {
+    /// Hash of the L1 transaction which sent the messages.
+    transaction_hash: H256,
+}
+
+#[derive(serde::Serialize, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum MessageStatus {
+    /// A message sent from L1 to L2.
+    #[serde(rename = "L1_TO_L2")]
+    L1ToL2 {
+        message_hash: H256,
+        /// Hash of the L2 `L1_HANDLER` transaction which handles the message.
+        transaction_hash: TransactionHash,
+        /// Status of the L2 transaction, which is `NOT_RECEIVED` until it has been synced.
+        status: TransactionStatus,
+    },
+    /// A message sent from L2 to L1, which the L1 transaction consumed.
+    #[serde(rename = "CONSUMED_L2_TO_L1")]
+    ConsumedL2ToL1 { message_hash: H256 },
+}
+
+crate::error::generate_rpc_error_subset!(GetMessagesStatusError: TxnHashNotFound; DataPruned);
+
+/// Returns the messages sent or consumed by an L1 transaction, along with the L2 transactions
+/// resulting from the L1 to L2 messages.
+///
+/// Only the L1 blocks indexed by L1 sync are known, which are the finalized blocks from the node's
+/// first run onwards, and earlier blocks backfilled down to `ethereum.index-messages-from`.
+pub async fn get_messages_status(
+    context: RpcContext,
+    input: GetMessagesStatusInput,
+) -> Result<Vec<MessageStatus>, GetMessagesStatusError> {
+    let span = tracing::Span::current();
+
+    tokio::task::spawn_blocking(move || {
+        let _g = span.enter();
+
+        let mut db = context
+            .storage
+            .connection()
+            .context("Opening database connection")?;
+        let db_tx = db.transaction().context("Creating database transaction")?;
+
+        let pending = context
+            .pending_data
+            .get(&db_tx)
+            .context("Querying pending data")?;
+
+        let messages = db_tx
+            .l1_messages(input.transaction_hash)
+            .context("Querying L1 messages")?;
+
+        let statuses = messages
+            .into_iter()
+            .map(|message| {
+                let message_hash = message.message_hash;
+                match message.direction {
+                    L1MessageDirection::L1ToL2 => {
+                        let transaction_hash = message
+                            .l2_transaction_hash
+                            .context("L1 to L2 message without L2 transaction")?;
+                        let status = database_status(&db_tx, &pending.block, transaction_hash)?
+                            .unwrap_or(TransactionStatus::NotReceived);
+
+                        anyhow::Ok(MessageStatus::L1ToL2 {
+                            message_hash,
+                            transaction_hash,
+                            status,
+                        })
+                    }
+                    L1MessageDirection::ConsumedL2ToL1 => {
+                        Ok(MessageStatus::ConsumedL2ToL1 { message_hash })
+                    }
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if statuses.is_empty() {
+            return Err(GetMessagesStatusError::TxnHashNotFound);
+        }
+
+        Ok(statuses)
+    })
+    .await
+    .context("Joining database task")?
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_storage::L1Message;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn l1_to_l2_messages() {
+        let context = RpcContext::for_tests();
+        let l1_tx_hash = H256::from_low_u64_be(1);
+
+        let messages = vec![
+            L1Message {
+                l1_transaction_hash: l1_tx_hash,
+                message_hash: H256::from_low_u64_be(10),
+                direction: L1MessageDirection::L1ToL2,
+                l1_block_number: 100,
+                // This transaction is in block 0 which is L1 accepted.
+                l2_transaction_hash: Some(transaction_hash_bytes!(b"txn 0")),
+            },
+            L1Message {
+                l1_transaction_hash: l1_tx_hash,
+                message_hash: H256::from_low_u64_be(11),
+                direction: L1MessageDirection::L1ToL2,
+                l1_block_number: 100,
+                l2_transaction_hash: Some(transaction_hash_bytes!(b"not synced yet")),
+            },
+            L1Message {
+                l1_transaction_hash: l1_tx_hash,
+                message_hash: H256::from_low_u64_be(12),
+                direction: L1MessageDirection::ConsumedL2ToL1,
+                l1_block_number: 100,
+                l2_transaction_hash: None,
+            },
+        ];
+        let mut db = context.storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        tx.insert_l1_messages(&messages).unwrap();
+        tx.commit().unwrap();
+
+        let input = GetMessagesStatusInput {
+            transaction_hash: l1_tx_hash,
+        };
+        let statuses = get_messages_status(context, input).await.unwrap();
+
+        assert_eq!(
+            statuses,
+            vec![
+                MessageStatus::L1ToL2 {
+                    message_hash: H256::from_low_u64_be(10),
+                    transaction_hash: transaction_hash_bytes!(b"txn 0"),
+                    status: TransactionStatus::AcceptedOnL1,
+                },
+                MessageStatus::L1ToL2 {
+                    message_hash: H256::from_low_u64_be(11),
+                    transaction_hash: transaction_hash_bytes!(b"not synced yet"),
+                    status: TransactionStatus::NotReceived,
+                },
+                MessageStatus::ConsumedL2ToL1 {
+                    message_hash: H256::from_low_u64_be(12),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn only_consumed_l2_to_l1_messages() {
+        let context = RpcContext::for_tests();
+        let l1_tx_hash = H256::from_low_u64_be(1);
+
+        let messages = vec![L1Message {
+            l1_transaction_hash: l1_tx_hash,
+            message_hash: H256::from_low_u64_be(12),
+            direction: L1MessageDirection::ConsumedL2ToL1,
+            l1_block_number: 100,
+            l2_transaction_hash: None,
+        }];
+        let mut db = context.storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        tx.insert_l1_messages(&messages).unwrap();
+        tx.commit().unwrap();
+
+        let input = GetMessagesStatusInput {
+            transaction_hash: l1_tx_hash,
+        };
+        let statuses = get_messages_status(context, input).await.unwrap();
+
+        assert_eq!(
+            statuses,
+            vec![MessageStatus::ConsumedL2ToL1 {
+                message_hash: H256::from_low_u64_be(12),
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(&statuses[0]).unwrap(),
+            serde_json::json!({
+                "type": "CONSUMED_L2_TO_L1",
+                "message_hash": "0x000000000000000000000000000000000000000000000000000000000000000c",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_l1_transaction() {
+        let context = RpcContext::for_tests();
+        let input = GetMessagesStatusInput {
+            transaction_hash: H256::from_low_u64_be(1),
+        };
+        let result = get_messages_status(context, input).await;
+
+        assert_matches::assert_matches!(result, Err(GetMessagesStatusError::TxnHashNotFound));
+    }
+}
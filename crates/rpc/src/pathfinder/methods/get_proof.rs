@@ -21,6 +21,7 @@ pub enum GetProofError {
     Internal(anyhow::Error),
     BlockNotFound,
     ProofLimitExceeded { limit: u32, requested: u32 },
+    BlockPruned { oldest_block: BlockNumber },
 }
 
 impl From<anyhow::Error> for GetProofError {
@@ -36,6 +37,9 @@ impl From<GetProofError> for crate::error::ApplicationError {
                 Self::ProofLimitExceeded { limit, requested }
             }
             GetProofError::BlockNotFound => Self::BlockNotFound,
+            GetProofError::BlockPruned { oldest_block } => Self::BlockPruned {
+                oldest_block: oldest_block.get(),
+            },
             GetProofError::Internal(internal) => Self::Internal(internal),
         }
     }
@@ -49,8 +53,8 @@ struct PathWrapper {
 }
 
 /// Wrapper around [`Vec<TrieNode>`] as we don't control [TrieNode] in this crate.
-#[derive(Debug)]
-pub struct ProofNodes(Vec<TrieNode>);
+#[derive(Debug, Default)]
+pub struct ProofNodes(pub(super) Vec<TrieNode>);
 
 impl Serialize for ProofNodes {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -108,18 +112,18 @@ impl Serialize for ProofNodes {
 #[derive(Debug, Serialize)]
 pub struct ContractData {
     /// Required to verify the contract state hash to contract root calculation.
-    class_hash: ClassHash,
+    pub(super) class_hash: ClassHash,
     /// Required to verify the contract state hash to contract root calculation.
-    nonce: ContractNonce,
+    pub(super) nonce: ContractNonce,
 
     /// Root of the Contract state tree
-    root: ContractRoot,
+    pub(super) root: ContractRoot,
 
     /// This is currently just a constant = 0, however it might change in the future.
     contract_state_hash_version: Felt,
 
     /// The proofs associated with the queried storage values
-    storage_proofs: Vec<ProofNodes>,
+    pub(super) storage_proofs: Vec<ProofNodes>,
 }
 
 /// Holds the membership/non-membership of a contract and its associated contract contract if the contract exists.
@@ -180,6 +184,15 @@ pub async fn get_proof(
             .context("Fetching block header")?
             .ok_or(GetProofError::BlockNotFound)?;
 
+        if let Some(oldest_block) = tx
+            .trie_prune_horizon()
+            .context("Querying trie prune horizon")?
+        {
+            if header.number < oldest_block {
+                return Err(GetProofError::BlockPruned { oldest_block });
+            }
+        }
+
         let state_commitment = match header.state_commitment {
             StateCommitment::ZERO => None,
             other => Some(other),
@@ -189,75 +202,77 @@ pub async fn get_proof(
             other => Some(other),
         };
 
-        // Generate a proof for this contract. If the contract does not exist, this will
-        // be a "non membership" proof.
-        let contract_proof =
-            StorageCommitmentTree::get_proof(&tx, header.number, &input.contract_address)
-                .context("Creating contract proof")?;
-        let contract_proof = ProofNodes(contract_proof);
-
-        let contract_state_hash = tx
-            .contract_state_hash(header.number, input.contract_address)
-            .context("Fetching contract's state hash")?;
-
-        if contract_state_hash.is_none() {
-            return Ok(GetProofOutput {
-                state_commitment,
-                class_commitment,
-                contract_proof,
-                contract_data: None,
-            });
-        };
-
-        let contract_root = tx
-            .contract_root(header.number, input.contract_address)
-            .context("Querying contract's root")?
-            .unwrap_or_default();
-
-        let class_hash = tx
-            .contract_class_hash(header.number.into(), input.contract_address)
-            .context("Querying contract's class hash")?
-            .unwrap_or_default();
-
-        let nonce = tx
-            .contract_nonce(input.contract_address, header.number.into())
-            .context("Querying contract's nonce")?
-            .unwrap_or_default();
-
-        let storage_proofs = input
-            .keys
-            .iter()
-            .map(|k| {
-                ContractsStorageTree::get_proof(
-                    &tx,
-                    input.contract_address,
-                    header.number,
-                    k.view_bits(),
-                )
-                .map(ProofNodes)
-            })
-            .collect::<anyhow::Result<Vec<_>>>()
-            .context("Get proof from contract state treee")?;
-
-        let contract_data = ContractData {
-            class_hash,
-            nonce,
-            root: contract_root,
-            contract_state_hash_version: Felt::ZERO, // Currently, this is defined as 0. Might change in the future.
-            storage_proofs,
-        };
+        let (contract_proof, contract_data) =
+            contract_proof(&tx, header.number, input.contract_address, &input.keys)?;
 
         Ok(GetProofOutput {
             state_commitment,
             class_commitment,
             contract_proof,
-            contract_data: Some(contract_data),
+            contract_data,
         })
     });
 
     jh.await.context("Database read panic or shutting down")?
 }
 
+/// Generates the proof for a contract and, if the contract exists, its data and the proofs for
+/// its storage `keys` at `block`.
+pub(super) fn contract_proof<'tx>(
+    tx: &'tx pathfinder_storage::Transaction<'tx>,
+    block: BlockNumber,
+    contract_address: ContractAddress,
+    keys: &[StorageAddress],
+) -> anyhow::Result<(ProofNodes, Option<ContractData>)> {
+    // Generate a proof for this contract. If the contract does not exist, this will
+    // be a "non membership" proof.
+    let contract_proof = StorageCommitmentTree::get_proof(tx, block, &contract_address)
+        .context("Creating contract proof")?;
+    let contract_proof = ProofNodes(contract_proof);
+
+    let contract_state_hash = tx
+        .contract_state_hash(block, contract_address)
+        .context("Fetching contract's state hash")?;
+
+    if contract_state_hash.is_none() {
+        return Ok((contract_proof, None));
+    };
+
+    let contract_root = tx
+        .contract_root(block, contract_address)
+        .context("Querying contract's root")?
+        .unwrap_or_default();
+
+    let class_hash = tx
+        .contract_class_hash(block.into(), contract_address)
+        .context("Querying contract's class hash")?
+        .unwrap_or_default();
+
+    let nonce = tx
+        .contract_nonce(contract_address, block.into())
+        .context("Querying contract's nonce")?
+        .unwrap_or_default();
+
+    let storage_proofs = keys
+        .iter()
+        .map(|k| {
+            ContractsStorageTree::get_proof(tx, contract_address, block, k.view_bits())
+                .map(ProofNodes)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .context("Get proof from contract state treee")?;
+
+    let contract_data = ContractData {
+        class_hash,
+        nonce,
+        root: contract_root,
+        contract_state_hash_version: Felt::ZERO, // Currently, this is defined as 0. Might change in the future.
+        storage_proofs,
+    };
+
+    Ok((contract_proof, Some(contract_data)))
+}
+
 #[cfg(test)]
 mod tests {
     use pathfinder_common::macro_prelude::*;
@@ -278,4 +293,33 @@ mod tests {
         let err = get_proof(context, input).await.unwrap_err();
         assert_matches::assert_matches!(err, GetProofError::ProofLimitExceeded { .. });
     }
+
+    #[tokio::test]
+    async fn block_pruned() {
+        let context = RpcContext::for_tests();
+
+        let mut db = context.storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        tx.prune_tries(BlockNumber::new_or_panic(1)).unwrap();
+        tx.commit().unwrap();
+
+        let input = GetProofInput {
+            block_id: BlockId::Number(BlockNumber::GENESIS),
+            contract_address: contract_address!("0xdeadbeef"),
+            keys: vec![],
+        };
+
+        let err = get_proof(context.clone(), input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            err,
+            GetProofError::BlockPruned { oldest_block } if oldest_block == BlockNumber::new_or_panic(1)
+        );
+
+        let input = GetProofInput {
+            block_id: BlockId::Number(BlockNumber::new_or_panic(1)),
+            contract_address: contract_address!("0xdeadbeef"),
+            keys: vec![],
+        };
+        get_proof(context, input).await.unwrap();
+    }
 }
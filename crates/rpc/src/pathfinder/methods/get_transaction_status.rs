@@ -7,10 +7,10 @@ use crate::context::RpcContext;
 
 #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
 pub struct GetGatewayTransactionInput {
-    transaction_hash: TransactionHash,
+    pub(crate) transaction_hash: TransactionHash,
 }
 
-crate::error::generate_rpc_error_subset!(GetGatewayTransactionError:);
+crate::error::generate_rpc_error_subset!(GetGatewayTransactionError: ; DataPruned);
 
 pub async fn get_transaction_status(
     context: RpcContext,
@@ -27,35 +27,12 @@ pub async fn get_transaction_status(
             .context("Opening database connection")?;
         let db_tx = db.transaction().context("Creating database transaction")?;
 
-        // Check pending transactions first.
         let pending = context
             .pending_data
             .get(&db_tx)
             .context("Querying pending data")?;
-        if let Some(status) = pending_status(&pending.block, &input.transaction_hash) {
-            return Ok(Some(status));
-        }
-
-        let Some((_, receipt, block_hash)) = db_tx
-            .transaction_with_receipt(input.transaction_hash)
-            .context("Fetching receipt from database")?
-        else {
-            return anyhow::Ok(None);
-        };
-
-        if receipt.execution_status == ExecutionStatus::Reverted {
-            return Ok(Some(TransactionStatus::Reverted));
-        }
-
-        let l1_accepted = db_tx
-            .block_is_l1_accepted(block_hash.into())
-            .context("Querying block's status")?;
 
-        if l1_accepted {
-            Ok(Some(TransactionStatus::AcceptedOnL1))
-        } else {
-            Ok(Some(TransactionStatus::AcceptedOnL2))
-        }
+        database_status(&db_tx, &pending.block, input.transaction_hash)
     })
     .await
     .context("Joining database task")??;
@@ -75,6 +52,39 @@ pub async fn get_transaction_status(
         .map_err(GetGatewayTransactionError::Internal)
 }
 
+/// The status of a transaction which is either pending or stored in the database.
+pub(super) fn database_status(
+    db_tx: &pathfinder_storage::Transaction<'_>,
+    pending: &PendingBlock,
+    transaction_hash: TransactionHash,
+) -> anyhow::Result<Option<TransactionStatus>> {
+    // Check pending transactions first.
+    if let Some(status) = pending_status(pending, &transaction_hash) {
+        return Ok(Some(status));
+    }
+
+    let Some((_, receipt, block_hash)) = db_tx
+        .transaction_with_receipt(transaction_hash)
+        .context("Fetching receipt from database")?
+    else {
+        return Ok(None);
+    };
+
+    if receipt.execution_status == ExecutionStatus::Reverted {
+        return Ok(Some(TransactionStatus::Reverted));
+    }
+
+    let l1_accepted = db_tx
+        .block_is_l1_accepted(block_hash.into())
+        .context("Querying block's status")?;
+
+    if l1_accepted {
+        Ok(Some(TransactionStatus::AcceptedOnL1))
+    } else {
+        Ok(Some(TransactionStatus::AcceptedOnL2))
+    }
+}
+
 fn pending_status(pending: &PendingBlock, tx_hash: &TransactionHash) -> Option<TransactionStatus> {
     pending.transaction_receipts.iter().find_map(|rx| {
         if &rx.transaction_hash == tx_hash {
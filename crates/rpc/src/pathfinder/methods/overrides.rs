@@ -0,0 +1,562 @@
+//! Variants of `starknet_call`, `starknet_estimateFee` and `starknet_simulateTransactions` which
+//! execute on top of caller provided state and block properties.
+
+use anyhow::Context;
+use pathfinder_common::{
+    BlockId, BlockNumber, BlockTimestamp, ClassHash, ContractAddress, ContractNonce, GasPrice,
+    SequencerAddress, StorageAddress, StorageValue,
+};
+use pathfinder_executor::StateOverride;
+use serde::{Deserialize, Serialize};
+
+use crate::context::RpcContext;
+use crate::pathfinder::types::AccessList;
+use crate::v02::types::request::BroadcastedTransaction;
+use crate::v02::types::ContractClass;
+use crate::v05::method::call::{CallError, CallOutput, FunctionCall};
+use crate::v06::method::estimate_fee::{EstimateFeeError, FeeEstimate, SimulationFlags};
+use crate::v06::method::simulate_transactions::{dto, SimulateTransactionError};
+
+/// Overrides the state of a single contract.
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ContractOverride {
+    contract_address: ContractAddress,
+    #[serde(default)]
+    storage: Vec<StorageOverride>,
+    #[serde(default)]
+    nonce: Option<ContractNonce>,
+    #[serde(default)]
+    class_hash: Option<ClassHash>,
+    /// Replaces the contract's class with this definition, which need not be declared.
+    #[serde(default)]
+    contract_class: Option<ContractClass>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct StorageOverride {
+    key: StorageAddress,
+    value: StorageValue,
+}
+
+/// Overrides the properties of the block the transactions are executed in. The state is still read
+/// from the requested block.
+#[serde_with::serde_as]
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockOverride {
+    #[serde(default)]
+    block_number: Option<BlockNumber>,
+    #[serde(default)]
+    timestamp: Option<BlockTimestamp>,
+    #[serde_as(as = "Option<pathfinder_serde::GasPriceAsHexStr>")]
+    #[serde(default)]
+    l1_gas_price_in_wei: Option<GasPrice>,
+    #[serde_as(as = "Option<pathfinder_serde::GasPriceAsHexStr>")]
+    #[serde(default)]
+    l1_gas_price_in_fri: Option<GasPrice>,
+    #[serde(default)]
+    sequencer_address: Option<SequencerAddress>,
+}
+
+impl From<BlockOverride> for pathfinder_executor::BlockOverride {
+    fn from(value: BlockOverride) -> Self {
+        Self {
+            number: value.block_number,
+            timestamp: value.timestamp,
+            eth_l1_gas_price: value.l1_gas_price_in_wei,
+            strk_l1_gas_price: value.l1_gas_price_in_fri,
+            sequencer_address: value.sequencer_address,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CallInput {
+    request: FunctionCall,
+    block_id: BlockId,
+    #[serde(default)]
+    state_override: Vec<ContractOverride>,
+    #[serde(default)]
+    block_override: BlockOverride,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct EstimateFeeInput {
+    request: Vec<BroadcastedTransaction>,
+    simulation_flags: SimulationFlags,
+    block_id: BlockId,
+    #[serde(default)]
+    state_override: Vec<ContractOverride>,
+    #[serde(default)]
+    block_override: BlockOverride,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SimulateTransactionsInput {
+    block_id: BlockId,
+    transactions: Vec<BroadcastedTransaction>,
+    simulation_flags: dto::SimulationFlags,
+    #[serde(default)]
+    state_override: Vec<ContractOverride>,
+    #[serde(default)]
+    block_override: BlockOverride,
+    /// Include the parts of the state each transaction accessed in the output.
+    #[serde(default)]
+    access_list: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct SimulateTransactionsOutput(Vec<SimulatedTransaction>);
+
+/// The output of `starknet_simulateTransactions`, optionally with an access list.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct SimulatedTransaction {
+    #[serde(flatten)]
+    simulation: dto::SimulatedTransaction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    access_list: Option<AccessList>,
+}
+
+pub async fn call(context: RpcContext, input: CallInput) -> Result<CallOutput, CallError> {
+    let state_override = state_override(input.state_override)
+        .await?
+        .map_err(CallError::Custom)?;
+    let block_override = input.block_override.into();
+
+    let input = crate::v05::method::call::CallInput {
+        request: input.request,
+        block_id: input.block_id,
+    };
+
+    crate::v05::method::call::call_with_overrides(context, input, state_override, block_override)
+        .await
+}
+
+/// [call] with the errors of the v0.4 API, which has no contract error.
+pub async fn call_v04(
+    context: RpcContext,
+    input: CallInput,
+) -> Result<CallOutput, crate::v02::method::call::CallError> {
+    call(context, input).await.map_err(Into::into)
+}
+
+pub async fn estimate_fee(
+    context: RpcContext,
+    input: EstimateFeeInput,
+) -> Result<Vec<FeeEstimate>, EstimateFeeError> {
+    let state_override = state_override(input.state_override)
+        .await?
+        .map_err(EstimateFeeError::Custom)?;
+    let block_override = input.block_override.into();
+
+    let input = crate::v06::method::estimate_fee::EstimateFeeInput {
+        request: input.request,
+        simulation_flags: input.simulation_flags,
+        block_id: input.block_id,
+    };
+
+    crate::v06::method::estimate_fee::estimate_fee_with_overrides(
+        context,
+        input,
+        state_override,
+        block_override,
+    )
+    .await
+}
+
+pub async fn simulate_transactions(
+    context: RpcContext,
+    input: SimulateTransactionsInput,
+) -> Result<SimulateTransactionsOutput, SimulateTransactionError> {
+    let state_override = state_override(input.state_override)
+        .await?
+        .map_err(SimulateTransactionError::Custom)?;
+    let block_override = input.block_override.into();
+    let access_list = input.access_list;
+
+    let input = crate::v06::method::simulate_transactions::SimulateTransactionInput {
+        block_id: input.block_id,
+        transactions: input.transactions,
+        simulation_flags: input.simulation_flags,
+    };
+
+    let simulations =
+        crate::v06::method::simulate_transactions::simulate_transactions_with_overrides(
+            context,
+            input,
+            state_override,
+            block_override,
+            access_list,
+        )
+        .await?;
+
+    let simulations = simulations
+        .into_iter()
+        .map(|simulation| {
+            let access_list = simulation.trace.access_list().cloned().map(Into::into);
+            Ok(SimulatedTransaction {
+                simulation: simulation.try_into()?,
+                access_list,
+            })
+        })
+        .collect::<Result<Vec<_>, pathfinder_executor::TransactionExecutionError>>()?;
+
+    Ok(SimulateTransactionsOutput(simulations))
+}
+
+/// Computes the class hashes of and compiles the injected classes. The inner result is an error if
+/// the overrides are invalid.
+async fn state_override(
+    overrides: Vec<ContractOverride>,
+) -> anyhow::Result<anyhow::Result<StateOverride>> {
+    let span = tracing::Span::current();
+
+    tokio::task::spawn_blocking(move || {
+        let _g = span.enter();
+
+        let mut state_override = StateOverride::default();
+
+        for contract in overrides {
+            let mut class_hash = contract.class_hash;
+
+            if let Some(class) = contract.contract_class {
+                let hash = compile(class, &mut state_override)?;
+                anyhow::ensure!(
+                    class_hash.map_or(true, |class_hash| class_hash == hash),
+                    "The class hash of contract {} does not match its class definition",
+                    contract.contract_address.0
+                );
+
+                class_hash = Some(hash);
+            }
+
+            let entry = state_override
+                .contracts
+                .entry(contract.contract_address)
+                .or_default();
+            entry.storage.extend(
+                contract
+                    .storage
+                    .into_iter()
+                    .map(|storage| (storage.key, storage.value)),
+            );
+            entry.nonce = contract.nonce.or(entry.nonce);
+            entry.class_hash = class_hash.or(entry.class_hash);
+        }
+
+        Ok(state_override)
+    })
+    .await
+    .context("Compiling state overrides")
+}
+
+/// Adds the compiled class to the overridden classes, returning its class hash.
+fn compile(class: ContractClass, state_override: &mut StateOverride) -> anyhow::Result<ClassHash> {
+    let class_hash = class.class_hash().context("Computing class hash")?.hash();
+
+    let definition = match class {
+        ContractClass::Cairo(class) => {
+            let definition = class
+                .serialize_to_json()
+                .context("Serializing Cairo class definition")?;
+            pathfinder_executor::parse_deprecated_class_definition(definition)
+                .context("Parsing Cairo class definition")?
+        }
+        ContractClass::Sierra(class) => {
+            let casm = pathfinder_compiler::compile_to_casm_with_latest_compiler(
+                &class
+                    .serialize_to_json()
+                    .context("Serializing Sierra class definition")?,
+            )
+            .context("Compiling Sierra class definition to CASM")?;
+            let casm_hash = pathfinder_compiler::casm_class_hash(&casm)
+                .context("Computing compiled class hash")?;
+            state_override
+                .compiled_class_hashes
+                .insert(class_hash, casm_hash);
+
+            pathfinder_executor::parse_casm_definition(casm)
+                .context("Parsing CASM class definition")?
+        }
+    };
+
+    state_override.classes.insert(class_hash, definition);
+
+    Ok(class_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_common::{
+        BlockHash, BlockHeader, BlockNumber, BlockTimestamp, CallParam, CallResultValue,
+        EntryPoint, StateUpdate,
+    };
+    use pathfinder_crypto::Felt;
+    use pathfinder_storage::Storage;
+    use starknet_gateway_test_fixtures::class_definitions::{
+        CONTRACT_DEFINITION, CONTRACT_DEFINITION_CLASS_HASH,
+    };
+
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let input = serde_json::json!({
+            "request": {
+                "contract_address": "0x1",
+                "entry_point_selector": "0x2",
+                "calldata": [],
+            },
+            "block_id": "latest",
+            "state_override": [{
+                "contract_address": "0x3",
+                "storage": [{"key": "0x4", "value": "0x5"}],
+                "nonce": "0x6",
+            }],
+            "block_override": {
+                "timestamp": 1234,
+                "l1_gas_price_in_wei": "0x7",
+            },
+        });
+
+        let input = serde_json::from_value::<CallInput>(input).unwrap();
+        assert_eq!(
+            input.state_override,
+            vec![ContractOverride {
+                contract_address: contract_address!("0x3"),
+                storage: vec![StorageOverride {
+                    key: storage_address!("0x4"),
+                    value: storage_value!("0x5"),
+                }],
+                nonce: Some(contract_nonce!("0x6")),
+                class_hash: None,
+                contract_class: None,
+            }]
+        );
+        assert_eq!(
+            input.block_override,
+            BlockOverride {
+                timestamp: Some(BlockTimestamp::new_or_panic(1234)),
+                l1_gas_price_in_wei: Some(GasPrice(7)),
+                ..Default::default()
+            }
+        );
+
+        // The overrides are optional.
+        let input = serde_json::json!([
+            {
+                "contract_address": "0x1",
+                "entry_point_selector": "0x2",
+                "calldata": [],
+            },
+            "latest",
+        ]);
+        let input = serde_json::from_value::<CallInput>(input).unwrap();
+        assert!(input.state_override.is_empty());
+        assert_eq!(input.block_override, BlockOverride::default());
+    }
+
+    /// A context with a single block in which the test class is declared, but not deployed.
+    fn test_context() -> RpcContext {
+        let storage = Storage::in_memory().unwrap();
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+
+        tx.insert_cairo_class(CONTRACT_DEFINITION_CLASS_HASH, CONTRACT_DEFINITION)
+            .unwrap();
+
+        let header = BlockHeader::builder()
+            .with_number(BlockNumber::GENESIS)
+            .with_timestamp(BlockTimestamp::new_or_panic(0))
+            .finalize_with_hash(BlockHash(felt!("0xb00")));
+        tx.insert_block_header(&header).unwrap();
+        tx.insert_state_update(
+            BlockNumber::GENESIS,
+            &StateUpdate::default()
+                .with_block_hash(header.hash)
+                .with_declared_cairo_class(CONTRACT_DEFINITION_CLASS_HASH),
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        RpcContext::for_tests_on(pathfinder_common::Chain::Mainnet).with_storage(storage)
+    }
+
+    fn get_value(key: StorageAddress, state_override: Vec<ContractOverride>) -> CallInput {
+        CallInput {
+            request: FunctionCall {
+                contract_address: contract_address!("0xc01"),
+                entry_point_selector: EntryPoint::hashed(b"get_value"),
+                calldata: vec![CallParam(*key.get())],
+            },
+            block_id: BlockId::Latest,
+            state_override,
+            block_override: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn call_undeployed_contract() {
+        let context = test_context();
+        let key = storage_address!("0x123");
+
+        let input = get_value(
+            key,
+            vec![ContractOverride {
+                contract_address: contract_address!("0xc01"),
+                storage: vec![StorageOverride {
+                    key,
+                    value: storage_value!("0x3"),
+                }],
+                nonce: None,
+                class_hash: Some(CONTRACT_DEFINITION_CLASS_HASH),
+                contract_class: None,
+            }],
+        );
+        let result = call(context.clone(), input).await.unwrap();
+        assert_eq!(result, CallOutput(vec![CallResultValue(felt!("0x3"))]));
+
+        // Without the overrides there is no contract to call.
+        let input = get_value(key, vec![]);
+        let error = call(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(error, CallError::ContractNotFound);
+    }
+
+    #[tokio::test]
+    async fn call_injected_class() {
+        let context = test_context();
+        let key = storage_address!("0x123");
+
+        let class = ContractClass::from_definition_bytes(CONTRACT_DEFINITION).unwrap();
+        let input = get_value(
+            key,
+            vec![ContractOverride {
+                contract_address: contract_address!("0xc01"),
+                storage: vec![StorageOverride {
+                    key,
+                    value: storage_value!("0x4"),
+                }],
+                nonce: None,
+                class_hash: None,
+                contract_class: Some(class),
+            }],
+        );
+        let result = call(context, input).await.unwrap();
+        assert_eq!(result, CallOutput(vec![CallResultValue(felt!("0x4"))]));
+    }
+
+    #[tokio::test]
+    async fn injected_sierra_class_has_compiled_class_hash() {
+        use crate::v04::method::simulate_transactions::tests::fixtures;
+
+        let class = ContractClass::from_definition_bytes(fixtures::SIERRA_DEFINITION).unwrap();
+        let overrides = vec![ContractOverride {
+            contract_address: contract_address!("0xc01"),
+            storage: vec![],
+            nonce: None,
+            class_hash: None,
+            contract_class: Some(class),
+        }];
+
+        let state_override = state_override(overrides).await.unwrap().unwrap();
+        assert!(state_override.classes.contains_key(&fixtures::SIERRA_HASH));
+        assert_eq!(
+            state_override.compiled_class_hashes[&fixtures::SIERRA_HASH],
+            fixtures::CASM_HASH
+        );
+    }
+
+    #[tokio::test]
+    async fn v04_call_has_no_contract_error() {
+        let context = test_context();
+        let class = ContractClass::from_definition_bytes(CONTRACT_DEFINITION).unwrap();
+        let mut input = get_value(
+            storage_address!("0x123"),
+            vec![ContractOverride {
+                contract_address: contract_address!("0xc01"),
+                storage: vec![],
+                nonce: None,
+                class_hash: None,
+                contract_class: Some(class),
+            }],
+        );
+        input.request.entry_point_selector = EntryPoint::hashed(b"no_such_entry_point");
+
+        let error = call_v04(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(error, crate::v02::method::call::CallError::Custom(_));
+    }
+
+    #[tokio::test]
+    async fn block_override_changes_call_result() {
+        use starknet_gateway_test_fixtures::class_definitions::INTEGRATION_TEST;
+
+        let context = test_context();
+        let class = ContractClass::from_definition_bytes(INTEGRATION_TEST).unwrap();
+
+        // These entry points fail unless the block has the expected properties.
+        let input = |entry_point: &[u8], expected: u64, block_override: BlockOverride| CallInput {
+            request: FunctionCall {
+                contract_address: contract_address!("0xc01"),
+                entry_point_selector: EntryPoint::hashed(entry_point),
+                calldata: vec![CallParam(Felt::from_u64(expected))],
+            },
+            block_id: BlockId::Latest,
+            state_override: vec![ContractOverride {
+                contract_address: contract_address!("0xc01"),
+                storage: vec![],
+                nonce: None,
+                class_hash: None,
+                contract_class: Some(class.clone()),
+            }],
+            block_override,
+        };
+
+        let timestamp = input(b"test_get_block_timestamp", 1234, Default::default());
+        let error = call(context.clone(), timestamp).await.unwrap_err();
+        assert_matches::assert_matches!(error, CallError::ContractErrorV05 { .. });
+
+        let timestamp = input(
+            b"test_get_block_timestamp",
+            1234,
+            BlockOverride {
+                timestamp: Some(BlockTimestamp::new_or_panic(1234)),
+                ..Default::default()
+            },
+        );
+        call(context.clone(), timestamp).await.unwrap();
+
+        let number = input(b"test_get_block_number", 5, Default::default());
+        let error = call(context.clone(), number).await.unwrap_err();
+        assert_matches::assert_matches!(error, CallError::ContractErrorV05 { .. });
+
+        let number = input(
+            b"test_get_block_number",
+            5,
+            BlockOverride {
+                block_number: Some(BlockNumber::new_or_panic(5)),
+                ..Default::default()
+            },
+        );
+        call(context, number).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn mismatched_class_hash() {
+        let class = ContractClass::from_definition_bytes(CONTRACT_DEFINITION).unwrap();
+        let overrides = vec![ContractOverride {
+            contract_address: contract_address!("0xc01"),
+            storage: vec![],
+            nonce: None,
+            class_hash: Some(class_hash!("0x1")),
+            contract_class: Some(class),
+        }];
+
+        state_override(overrides).await.unwrap().unwrap_err();
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Serialize;
+
+use crate::context::RpcContext;
+use crate::v05::method::call::{CallError, CallInput};
+use crate::v06::method::simulate_transactions::dto::FunctionInvocation;
+
+/// The invocation tree of a call. If the call failed, the tree ends where it failed.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct TraceCallOutput {
+    #[serde(flatten)]
+    invocation: FunctionInvocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revert_reason: Option<String>,
+}
+
+/// Executes the call like `starknet_call`, but returns its whole invocation tree including inner
+/// calls, events, messages and execution resources.
+pub async fn trace_call(
+    context: RpcContext,
+    input: CallInput,
+) -> Result<TraceCallOutput, CallError> {
+    let trace = crate::v05::method::call::execute(
+        context,
+        input,
+        Default::default(),
+        Default::default(),
+        |state, request| {
+            pathfinder_executor::trace_call(
+                state,
+                request.contract_address,
+                request.entry_point_selector,
+                request.calldata,
+            )
+        },
+    )
+    .await?;
+
+    Ok(TraceCallOutput {
+        invocation: trace.invocation.into(),
+        revert_reason: trace.revert_reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_common::{
+        BlockHash, BlockHeader, BlockId, BlockNumber, BlockTimestamp, CallParam, EntryPoint,
+        StateUpdate,
+    };
+    use pathfinder_storage::Storage;
+    use starknet_gateway_test_fixtures::class_definitions::{
+        CONTRACT_DEFINITION, CONTRACT_DEFINITION_CLASS_HASH,
+    };
+
+    use super::*;
+    use crate::v05::method::call::FunctionCall;
+
+    /// A context with the test contract deployed at 0xc01, with 0x3 stored at 0x123.
+    fn test_context() -> RpcContext {
+        let storage = Storage::in_memory().unwrap();
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+
+        tx.insert_cairo_class(CONTRACT_DEFINITION_CLASS_HASH, CONTRACT_DEFINITION)
+            .unwrap();
+
+        let header = BlockHeader::builder()
+            .with_number(BlockNumber::GENESIS)
+            .with_timestamp(BlockTimestamp::new_or_panic(0))
+            .finalize_with_hash(BlockHash(felt!("0xb00")));
+        tx.insert_block_header(&header).unwrap();
+        tx.insert_state_update(
+            BlockNumber::GENESIS,
+            &StateUpdate::default()
+                .with_block_hash(header.hash)
+                .with_declared_cairo_class(CONTRACT_DEFINITION_CLASS_HASH)
+                .with_deployed_contract(contract_address!("0xc01"), CONTRACT_DEFINITION_CLASS_HASH)
+                .with_storage_update(
+                    contract_address!("0xc01"),
+                    storage_address!("0x123"),
+                    storage_value!("0x3"),
+                ),
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        RpcContext::for_tests_on(pathfinder_common::Chain::Mainnet).with_storage(storage)
+    }
+
+    fn input(request: FunctionCall) -> CallInput {
+        CallInput {
+            request,
+            block_id: BlockId::Latest,
+        }
+    }
+
+    #[tokio::test]
+    async fn invocation_tree() {
+        let request = FunctionCall {
+            contract_address: contract_address!("0xc01"),
+            entry_point_selector: EntryPoint::hashed(b"get_value"),
+            calldata: vec![CallParam(felt!("0x123"))],
+        };
+
+        let trace = trace_call(test_context(), input(request.clone()))
+            .await
+            .unwrap();
+        assert_eq!(trace.invocation.function_call, request);
+        assert_eq!(
+            trace.invocation.class_hash,
+            Some(CONTRACT_DEFINITION_CLASS_HASH.0)
+        );
+        assert_eq!(trace.invocation.result, vec![felt!("0x3")]);
+        assert!(trace.invocation.calls.is_empty());
+        assert_eq!(trace.revert_reason, None);
+    }
+
+    #[tokio::test]
+    async fn inner_calls() {
+        // Calls increase_value on itself, which adds 0x2 to the value at 0x123.
+        let request = FunctionCall {
+            contract_address: contract_address!("0xc01"),
+            entry_point_selector: EntryPoint::hashed(b"call_increase_value"),
+            calldata: vec![
+                CallParam(felt!("0xc01")),
+                CallParam(felt!("0x123")),
+                CallParam(felt!("0x2")),
+            ],
+        };
+
+        let trace = trace_call(test_context(), input(request.clone()))
+            .await
+            .unwrap();
+        assert_eq!(trace.invocation.function_call, request);
+        assert_eq!(trace.invocation.calls.len(), 1);
+
+        let inner = &trace.invocation.calls[0];
+        assert_eq!(
+            inner.function_call,
+            FunctionCall {
+                contract_address: contract_address!("0xc01"),
+                entry_point_selector: EntryPoint::hashed(b"increase_value"),
+                calldata: vec![CallParam(felt!("0x123")), CallParam(felt!("0x2"))],
+            }
+        );
+        assert_eq!(inner.caller_address, felt!("0xc01"));
+        assert!(inner.calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_call() {
+        // The inner call is made to a contract which is not deployed.
+        let request = FunctionCall {
+            contract_address: contract_address!("0xc01"),
+            entry_point_selector: EntryPoint::hashed(b"call_increase_value"),
+            calldata: vec![
+                CallParam(felt!("0xdead")),
+                CallParam(felt!("0x123")),
+                CallParam(felt!("0x2")),
+            ],
+        };
+
+        let error = trace_call(test_context(), input(request))
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(error, CallError::ContractErrorV05 { revert_error } => {
+            assert!(revert_error.starts_with("Execution error"), "{revert_error}");
+        });
+    }
+}
@@ -0,0 +1,82 @@
+use serde::Serialize;
+
+use crate::context::RpcContext;
+use crate::pathfinder::types::AccessList;
+use crate::v06::method::simulate_transactions::dto::TransactionTrace;
+use crate::v06::method::trace_transaction::{TraceTransactionError, TraceTransactionInput};
+
+/// The output of `starknet_traceTransaction` with the access list of the transaction.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct TraceTransactionOutput {
+    #[serde(flatten)]
+    trace: TransactionTrace,
+    /// Missing for old transactions, whose traces are fetched from the gateway.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    access_list: Option<AccessList>,
+}
+
+pub async fn trace_transaction(
+    context: RpcContext,
+    input: TraceTransactionInput,
+) -> Result<TraceTransactionOutput, TraceTransactionError> {
+    let (output, access_list) =
+        crate::v06::method::trace_transaction::trace_transaction_impl(context, input, true).await?;
+
+    Ok(TraceTransactionOutput {
+        trace: output.0,
+        access_list: access_list.map(Into::into),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v06::method::trace_block_transactions::tests::setup_multi_tx_trace_test;
+
+    #[tokio::test]
+    async fn access_lists() {
+        let (context, _, traces) = setup_multi_tx_trace_test().await.unwrap();
+
+        for trace in traces {
+            let input = TraceTransactionInput {
+                transaction_hash: trace.transaction_hash,
+            };
+            let output = trace_transaction(context.clone(), input).await.unwrap();
+
+            // The trace itself is unaffected.
+            pretty_assertions_sorted::assert_eq!(output.trace, trace.trace_root);
+
+            let access_list = output.access_list.unwrap();
+            let state_diff = match output.trace {
+                TransactionTrace::Declare(trace) => trace.state_diff,
+                TransactionTrace::DeployAccount(trace) => trace.state_diff,
+                TransactionTrace::Invoke(trace) => trace.state_diff,
+                TransactionTrace::L1Handler(trace) => trace.state_diff,
+            }
+            .unwrap();
+
+            // Every write must show up in the access list.
+            for diff in state_diff.storage_diffs {
+                let keys = access_list
+                    .storage_writes
+                    .iter()
+                    .find(|access| access.contract_address == diff.address)
+                    .map(|access| &access.keys)
+                    .unwrap();
+                for entry in diff.storage_entries {
+                    assert!(keys.contains(&entry.key));
+                }
+            }
+            for nonce in state_diff.nonces {
+                assert!(access_list.nonces.contains(&nonce.contract_address));
+            }
+
+            // The sender's nonce and the fee token balances are read.
+            assert!(!access_list.nonces.is_empty());
+            assert!(access_list.storage_reads.iter().any(
+                |access| access.contract_address == pathfinder_executor::ETH_FEE_TOKEN_ADDRESS
+            ));
+            assert!(!access_list.classes.is_empty());
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use pathfinder_common::{ClassHash, ContractAddress, StorageAddress};
+use serde::Serialize;
+
+/// The parts of the state read or written by a transaction.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct AccessList {
+    pub storage_reads: Vec<StorageAccess>,
+    pub storage_writes: Vec<StorageAccess>,
+    /// Classes whose definitions were loaded.
+    pub classes: Vec<ClassHash>,
+    /// Classes whose compiled class hashes were read.
+    pub compiled_class_hashes: Vec<ClassHash>,
+    /// Contracts whose nonces were read or written.
+    pub nonces: Vec<ContractAddress>,
+    /// Contracts whose class hashes were read.
+    pub contracts: Vec<ContractAddress>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct StorageAccess {
+    pub contract_address: ContractAddress,
+    pub keys: Vec<StorageAddress>,
+}
+
+impl From<pathfinder_executor::types::AccessList> for AccessList {
+    fn from(value: pathfinder_executor::types::AccessList) -> Self {
+        let storage = |accesses: std::collections::BTreeMap<_, std::collections::BTreeSet<_>>| {
+            accesses
+                .into_iter()
+                .map(|(contract_address, keys)| StorageAccess {
+                    contract_address,
+                    keys: keys.into_iter().collect(),
+                })
+                .collect()
+        };
+
+        Self {
+            storage_reads: storage(value.storage_reads),
+            storage_writes: storage(value.storage_writes),
+            classes: value.classes.into_iter().collect(),
+            compiled_class_hashes: value.compiled_class_hashes.into_iter().collect(),
+            nonces: value.nonces.into_iter().collect(),
+            contracts: value.contracts.into_iter().collect(),
+        }
+    }
+}
@@ -2,8 +2,10 @@ use pathfinder_common::{macro_prelude::*, StarknetVersion, StorageAddress};
 
 use pathfinder_common::{
     felt, BlockHash, BlockHeader, BlockNumber, BlockTimestamp, ContractAddress, GasPrice,
-    StateUpdate,
+    StateUpdate, StorageCommitment,
 };
+use pathfinder_merkle_tree::contract_state::update_contract_state;
+use pathfinder_merkle_tree::StorageCommitmentTree;
 use pathfinder_storage::Storage;
 use starknet_gateway_test_fixtures::class_definitions::{DUMMY_ACCOUNT, DUMMY_ACCOUNT_CLASS_HASH};
 
@@ -46,18 +48,6 @@ pub async fn test_storage<F: FnOnce(StateUpdate) -> StateUpdate>(
     tx.insert_cairo_class(erc20_class_hash, erc20_class_definition)
         .unwrap();
 
-    let header = BlockHeader::builder()
-        .with_number(block1_number)
-        .with_timestamp(BlockTimestamp::new_or_panic(1))
-        .with_eth_l1_gas_price(GasPrice(1))
-        .with_strk_l1_gas_price(GasPrice(2))
-        .with_sequencer_address(sequencer_address!(
-            "0x1176a1bd84444c89232ec27754698e5d2e7e1a7f1539f12027f28b23ec9f3d8"
-        ))
-        .with_starknet_version(StarknetVersion::new(0, 13, 0))
-        .finalize_with_hash(block1_hash);
-    tx.insert_block_header(&header).unwrap();
-
     let account_contract_address = contract_address!("0xc01");
     let universal_deployer_address = contract_address!("0xc02");
 
@@ -78,6 +68,21 @@ pub async fn test_storage<F: FnOnce(StateUpdate) -> StateUpdate>(
             storage_value!("0x10000000000000000000000000000"),
         );
     let state_update = customize_state_update(state_update);
+    let storage_commitment = insert_tries(&tx, block1_number, &state_update);
+
+    let header = BlockHeader::builder()
+        .with_number(block1_number)
+        .with_timestamp(BlockTimestamp::new_or_panic(1))
+        .with_eth_l1_gas_price(GasPrice(1))
+        .with_strk_l1_gas_price(GasPrice(2))
+        .with_sequencer_address(sequencer_address!(
+            "0x1176a1bd84444c89232ec27754698e5d2e7e1a7f1539f12027f28b23ec9f3d8"
+        ))
+        .with_starknet_version(StarknetVersion::new(0, 13, 0))
+        .with_storage_commitment(storage_commitment)
+        .with_calculated_state_commitment()
+        .finalize_with_hash(block1_hash);
+    tx.insert_block_header(&header).unwrap();
     tx.insert_state_update(block1_number, &state_update)
         .unwrap();
 
@@ -91,6 +96,46 @@ pub async fn test_storage<F: FnOnce(StateUpdate) -> StateUpdate>(
     )
 }
 
+/// Inserts the tries of the state of `block`, the first one with state, and returns its storage
+/// commitment. Contracts which only have storage get a zero class hash.
+fn insert_tries(
+    tx: &pathfinder_storage::Transaction<'_>,
+    block: BlockNumber,
+    state_update: &StateUpdate,
+) -> StorageCommitment {
+    let mut storage_commitment_tree = StorageCommitmentTree::empty(tx);
+    for (contract_address, update) in &state_update.contract_updates {
+        let class_hash = update
+            .class
+            .as_ref()
+            .map(|class| class.class_hash())
+            .unwrap_or_default();
+        let update_results = update_contract_state(
+            *contract_address,
+            &update.storage,
+            update.nonce,
+            Some(class_hash),
+            tx,
+            false,
+            block,
+        )
+        .unwrap();
+        storage_commitment_tree
+            .set(*contract_address, update_results.state_hash)
+            .unwrap();
+        update_results.insert(block, tx).unwrap();
+    }
+
+    let (storage_commitment, update) = storage_commitment_tree.commit().unwrap();
+    let storage_root_idx = tx
+        .insert_storage_trie(storage_commitment, &update.nodes)
+        .unwrap();
+    tx.insert_storage_root(block, Some(storage_root_idx))
+        .unwrap();
+
+    storage_commitment
+}
+
 pub async fn test_context() -> (RpcContext, BlockHeader, ContractAddress, ContractAddress) {
     let (storage, header, account_contract_address, universal_deployer_address) =
         test_storage(|state_update| state_update).await;
@@ -12,7 +12,8 @@ pub struct GetTransactionByBlockIdAndIndexInput {
 
 crate::error::generate_rpc_error_subset!(
     GetTransactionByBlockIdAndIndexError: BlockNotFound,
-    InvalidTxnIndex
+    InvalidTxnIndex;
+    DataPruned
 );
 
 pub async fn get_transaction_by_block_id_and_index_impl(
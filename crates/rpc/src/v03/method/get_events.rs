@@ -17,6 +17,7 @@ pub enum GetEventsError {
     PageSizeTooBig,
     InvalidContinuationToken,
     TooManyKeysInFilter { limit: usize, requested: usize },
+    DataPruned { oldest_block: BlockNumber },
 }
 
 impl From<anyhow::Error> for GetEventsError {
@@ -36,6 +37,9 @@ impl From<GetEventsError> for crate::error::ApplicationError {
             GetEventsError::TooManyKeysInFilter { limit, requested } => {
                 Self::TooManyKeysInFilter { limit, requested }
             }
+            GetEventsError::DataPruned { oldest_block } => Self::DataPruned {
+                oldest_block: oldest_block.get(),
+            },
         }
     }
 }
@@ -169,6 +173,9 @@ pub async fn get_events(
             EventFilterError::TooManyMatches => GetEventsError::Custom(e.into()),
             EventFilterError::Internal(e) => GetEventsError::Internal(e),
             EventFilterError::PageSizeTooSmall => GetEventsError::Custom(e.into()),
+            EventFilterError::DataPruned(pruned) => GetEventsError::DataPruned {
+                oldest_block: pruned.oldest_block,
+            },
         })?;
 
         let new_continuation_token = match page.is_last_page {
@@ -749,6 +756,47 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn get_events_of_pruned_blocks() {
+        let (context, events) = setup();
+
+        const OLDEST_BLOCK: usize = 2;
+        let oldest_block = BlockNumber::new_or_panic(OLDEST_BLOCK as u64);
+        let mut db = context.storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        tx.prune_transactions(oldest_block).unwrap();
+        tx.commit().unwrap();
+
+        let input = GetEventsInput {
+            filter: EventFilter {
+                from_block: Some(BlockNumber::GENESIS.into()),
+                chunk_size: test_utils::NUM_EVENTS,
+                ..Default::default()
+            },
+        };
+        let error = get_events(context.clone(), input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            GetEventsError::DataPruned { oldest_block: block } if block == oldest_block
+        );
+
+        // Without a lower bound, only the events which were not pruned are returned.
+        let input = GetEventsInput {
+            filter: EventFilter {
+                chunk_size: test_utils::NUM_EVENTS,
+                ..Default::default()
+            },
+        };
+        let result = get_events(context, input).await.unwrap();
+        assert_eq!(
+            result,
+            GetEventsResult {
+                events: events[test_utils::EVENTS_PER_BLOCK * OLDEST_BLOCK..].to_vec(),
+                continuation_token: None,
+            }
+        );
+    }
+
     #[tokio::test]
     async fn get_events_from_latest_block() {
         let (context, events) = setup();
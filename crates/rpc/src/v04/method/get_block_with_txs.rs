@@ -13,7 +13,7 @@ pub struct GetBlockInput {
     block_id: BlockId,
 }
 
-crate::error::generate_rpc_error_subset!(GetBlockError: BlockNotFound);
+crate::error::generate_rpc_error_subset!(GetBlockError: BlockNotFound; DataPruned);
 
 /// Get block information with full transactions given the block id
 pub async fn get_block_with_txs(
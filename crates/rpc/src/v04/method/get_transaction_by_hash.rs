@@ -3,7 +3,7 @@ use crate::v04::types::TransactionWithHash;
 
 use crate::v02::method::get_transaction_by_hash as v02_get_transaction_by_hash;
 
-crate::error::generate_rpc_error_subset!(GetTransactionByHashError: TxnHashNotFound);
+crate::error::generate_rpc_error_subset!(GetTransactionByHashError: TxnHashNotFound; DataPruned);
 
 pub async fn get_transaction_by_hash(
     context: RpcContext,
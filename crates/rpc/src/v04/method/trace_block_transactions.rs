@@ -27,7 +27,7 @@ pub struct Trace {
 pub struct TraceBlockTransactionsOutput(pub Vec<Trace>);
 
 crate::error::generate_rpc_error_subset!(
-    TraceBlockTransactionsError: InvalidBlockHash
+    TraceBlockTransactionsError: InvalidBlockHash; DataPruned
 );
 
 impl From<ExecutionStateError> for TraceBlockTransactionsError {
@@ -1,5 +1,5 @@
 use anyhow::Context;
-use pathfinder_common::TransactionHash;
+use pathfinder_common::{BlockNumber, TransactionHash};
 use pathfinder_executor::{ExecutionState, TransactionExecutionError};
 use serde::{Deserialize, Serialize};
 use starknet_gateway_client::GatewayApi;
@@ -28,6 +28,7 @@ pub struct TraceTransactionOutput(pub TransactionTrace);
 pub enum TraceTransactionError {
     InvalidTxnHash,
     NoTraceAvailable(TraceError),
+    DataPruned { oldest_block: BlockNumber },
     Internal(anyhow::Error),
     Custom(anyhow::Error),
 }
@@ -61,7 +62,12 @@ impl From<TransactionExecutionError> for TraceTransactionError {
 
 impl From<anyhow::Error> for TraceTransactionError {
     fn from(e: anyhow::Error) -> Self {
-        Self::Internal(e)
+        match e.downcast::<pathfinder_storage::DataPruned>() {
+            Ok(pruned) => Self::DataPruned {
+                oldest_block: pruned.oldest_block,
+            },
+            Err(e) => Self::Internal(e),
+        }
     }
 }
 
@@ -72,6 +78,9 @@ impl From<TraceTransactionError> for ApplicationError {
             TraceTransactionError::NoTraceAvailable(status) => {
                 ApplicationError::NoTraceAvailable(status)
             }
+            TraceTransactionError::DataPruned { oldest_block } => ApplicationError::DataPruned {
+                oldest_block: oldest_block.get(),
+            },
             TraceTransactionError::Internal(e) => ApplicationError::Internal(e),
             TraceTransactionError::Custom(e) => ApplicationError::Custom(e),
         }
@@ -3,7 +3,7 @@ use crate::error::ApplicationError;
 use crate::felt::RpcFelt;
 use anyhow::Context;
 use pathfinder_common::{BlockId, CallParam, CallResultValue, ContractAddress, EntryPoint};
-use pathfinder_executor::ExecutionState;
+use pathfinder_executor::{BlockOverride, ExecutionState, StateOverride};
 
 #[derive(Debug)]
 pub enum CallError {
@@ -79,6 +79,47 @@ pub struct FunctionCall {
 pub struct CallOutput(#[serde_as(as = "Vec<RpcFelt>")] pub Vec<CallResultValue>);
 
 pub async fn call(context: RpcContext, input: CallInput) -> Result<CallOutput, CallError> {
+    call_with_overrides(context, input, Default::default(), Default::default()).await
+}
+
+pub(crate) async fn call_with_overrides(
+    context: RpcContext,
+    input: CallInput,
+    state_override: StateOverride,
+    block_override: BlockOverride,
+) -> Result<CallOutput, CallError> {
+    execute(
+        context,
+        input,
+        state_override,
+        block_override,
+        |state, request| {
+            pathfinder_executor::call(
+                state,
+                request.contract_address,
+                request.entry_point_selector,
+                request.calldata,
+            )
+        },
+    )
+    .await
+    .map(CallOutput)
+}
+
+/// Executes `f` on the state of the requested block.
+pub(crate) async fn execute<T, F>(
+    context: RpcContext,
+    input: CallInput,
+    state_override: StateOverride,
+    block_override: BlockOverride,
+    f: F,
+) -> Result<T, CallError>
+where
+    T: Send + 'static,
+    F: FnOnce(ExecutionState<'_>, FunctionCall) -> Result<T, pathfinder_executor::CallError>
+        + Send
+        + 'static,
+{
     let span = tracing::Span::current();
     let result = tokio::task::spawn_blocking(move || {
         let _g = span.enter();
@@ -109,21 +150,18 @@ pub async fn call(context: RpcContext, input: CallInput) -> Result<CallOutput, C
             }
         };
 
-        let state = ExecutionState::simulation(&db, context.chain_id, header, pending);
+        let state = ExecutionState::simulation(&db, context.chain_id, header, pending)
+            .with_state_override(state_override)
+            .with_block_override(block_override);
 
-        let result = pathfinder_executor::call(
-            state,
-            input.request.contract_address,
-            input.request.entry_point_selector,
-            input.request.calldata,
-        )?;
+        let result = f(state, input.request)?;
 
         Ok(result)
     })
     .await
     .context("Executing call")?;
 
-    result.map(CallOutput)
+    result
 }
 
 #[cfg(test)]
@@ -70,7 +70,7 @@ impl From<starknet_gateway_types::reply::transaction::ExecutionStatus> for Execu
     }
 }
 
-crate::error::generate_rpc_error_subset!(GetTransactionStatusError: TxnHashNotFound);
+crate::error::generate_rpc_error_subset!(GetTransactionStatusError: TxnHashNotFound; DataPruned);
 
 pub async fn get_transaction_status(
     context: RpcContext,
@@ -1,5 +1,5 @@
 use anyhow::Context;
-use pathfinder_common::{BlockId, TransactionHash};
+use pathfinder_common::{BlockId, BlockNumber, TransactionHash};
 use pathfinder_executor::{ExecutionState, TransactionExecutionError};
 use serde::{Deserialize, Serialize};
 use starknet_gateway_client::GatewayApi;
@@ -36,11 +36,17 @@ pub enum TraceBlockTransactionsError {
     Custom(anyhow::Error),
     BlockNotFound,
     ContractErrorV05 { revert_error: String },
+    DataPruned { oldest_block: BlockNumber },
 }
 
 impl From<anyhow::Error> for TraceBlockTransactionsError {
     fn from(value: anyhow::Error) -> Self {
-        Self::Internal(value)
+        match value.downcast::<pathfinder_storage::DataPruned>() {
+            Ok(pruned) => Self::DataPruned {
+                oldest_block: pruned.oldest_block,
+            },
+            Err(value) => Self::Internal(value),
+        }
     }
 }
 
@@ -53,6 +59,9 @@ impl From<TraceBlockTransactionsError> for crate::error::ApplicationError {
                 Self::ContractErrorV05 { revert_error }
             }
             TraceBlockTransactionsError::Custom(e) => Self::Custom(e),
+            TraceBlockTransactionsError::DataPruned { oldest_block } => Self::DataPruned {
+                oldest_block: oldest_block.get(),
+            },
         }
     }
 }
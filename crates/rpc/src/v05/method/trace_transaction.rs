@@ -1,5 +1,5 @@
 use anyhow::Context;
-use pathfinder_common::TransactionHash;
+use pathfinder_common::{BlockNumber, TransactionHash};
 use pathfinder_executor::{ExecutionState, TransactionExecutionError};
 use serde::{Deserialize, Serialize};
 use starknet_gateway_client::GatewayApi;
@@ -31,6 +31,7 @@ pub enum TraceTransactionError {
     InvalidTxnHash,
     NoTraceAvailable(TraceError),
     ContractErrorV05 { revert_error: String },
+    DataPruned { oldest_block: BlockNumber },
 }
 
 impl From<ExecutionStateError> for TraceTransactionError {
@@ -62,7 +63,12 @@ impl From<TransactionExecutionError> for TraceTransactionError {
 
 impl From<anyhow::Error> for TraceTransactionError {
     fn from(e: anyhow::Error) -> Self {
-        Self::Internal(e)
+        match e.downcast::<pathfinder_storage::DataPruned>() {
+            Ok(pruned) => Self::DataPruned {
+                oldest_block: pruned.oldest_block,
+            },
+            Err(e) => Self::Internal(e),
+        }
     }
 }
 
@@ -74,6 +80,7 @@ impl From<super::trace_block_transactions::TraceBlockTransactionsError> for Trac
             BlockNotFound => Self::Custom(anyhow::anyhow!("Block not found")),
             ContractErrorV05 { revert_error } => Self::ContractErrorV05 { revert_error },
             Custom(e) => Self::Custom(e),
+            DataPruned { oldest_block } => Self::DataPruned { oldest_block },
         }
     }
 }
@@ -88,6 +95,9 @@ impl From<TraceTransactionError> for ApplicationError {
             TraceTransactionError::ContractErrorV05 { revert_error } => {
                 ApplicationError::ContractErrorV05 { revert_error }
             }
+            TraceTransactionError::DataPruned { oldest_block } => ApplicationError::DataPruned {
+                oldest_block: oldest_block.get(),
+            },
             TraceTransactionError::Internal(e) => ApplicationError::Internal(e),
             TraceTransactionError::Custom(e) => ApplicationError::Custom(e),
         }
@@ -45,4 +45,12 @@ pub fn register_routes() -> RpcRouterBuilder {
         .register("starknet_traceTransaction"                , method::trace_transaction)
 
         .register("pathfinder_getProof"                      , crate::pathfinder::methods::get_proof)
+        .register("pathfinder_getMessagesStatus"             , crate::pathfinder::methods::get_messages_status)
+        .register("pathfinder_getL1Settlement"               , crate::pathfinder::methods::get_l1_settlement)
+        .register("pathfinder_call"                          , crate::pathfinder::methods::call)
+        .register("pathfinder_estimateFee"                   , crate::pathfinder::methods::estimate_fee)
+        .register("pathfinder_simulateTransactions"          , crate::pathfinder::methods::simulate_transactions)
+        .register("pathfinder_traceCall"                     , crate::pathfinder::methods::trace_call)
+        .register("pathfinder_traceTransaction"              , crate::pathfinder::methods::trace_transaction)
+        .register("pathfinder_getBlockWitness"               , crate::pathfinder::methods::get_block_witness)
 }
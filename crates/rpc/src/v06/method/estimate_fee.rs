@@ -1,5 +1,5 @@
 use anyhow::Context;
-use pathfinder_executor::ExecutionState;
+use pathfinder_executor::{BlockOverride, ExecutionState, StateOverride};
 use serde_with::serde_as;
 
 use crate::{
@@ -112,6 +112,15 @@ impl From<pathfinder_executor::types::FeeEstimate> for FeeEstimate {
 pub async fn estimate_fee(
     context: RpcContext,
     input: EstimateFeeInput,
+) -> Result<Vec<FeeEstimate>, EstimateFeeError> {
+    estimate_fee_with_overrides(context, input, Default::default(), Default::default()).await
+}
+
+pub(crate) async fn estimate_fee_with_overrides(
+    context: RpcContext,
+    input: EstimateFeeInput,
+    state_override: StateOverride,
+    block_override: BlockOverride,
 ) -> Result<Vec<FeeEstimate>, EstimateFeeError> {
     let span = tracing::Span::current();
 
@@ -143,7 +152,9 @@ pub async fn estimate_fee(
             }
         };
 
-        let state = ExecutionState::simulation(&db, context.chain_id, header, pending);
+        let state = ExecutionState::simulation(&db, context.chain_id, header, pending)
+            .with_state_override(state_override)
+            .with_block_override(block_override);
 
         let skip_validate = input
             .simulation_flags
@@ -8,7 +8,7 @@ pub struct GetTransactionReceiptInput {
     pub transaction_hash: TransactionHash,
 }
 
-crate::error::generate_rpc_error_subset!(GetTransactionReceiptError: TxnHashNotFound);
+crate::error::generate_rpc_error_subset!(GetTransactionReceiptError: TxnHashNotFound; DataPruned);
 
 pub async fn get_transaction_receipt(
     context: RpcContext,
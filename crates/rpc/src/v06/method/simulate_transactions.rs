@@ -5,15 +5,17 @@ use crate::{
 use anyhow::Context;
 use pathfinder_common::{BlockId, CallParam, EntryPoint};
 use pathfinder_crypto::Felt;
-use pathfinder_executor::{types::TransactionSimulation, TransactionExecutionError};
+use pathfinder_executor::{
+    types::TransactionSimulation, BlockOverride, StateOverride, TransactionExecutionError,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct SimulateTransactionInput {
-    block_id: BlockId,
-    transactions: Vec<BroadcastedTransaction>,
-    simulation_flags: dto::SimulationFlags,
+    pub(crate) block_id: BlockId,
+    pub(crate) transactions: Vec<BroadcastedTransaction>,
+    pub(crate) simulation_flags: dto::SimulationFlags,
 }
 
 #[derive(Debug, Serialize, Eq, PartialEq)]
@@ -83,6 +85,31 @@ pub async fn simulate_transactions(
     context: RpcContext,
     input: SimulateTransactionInput,
 ) -> Result<SimulateTransactionOutput, SimulateTransactionError> {
+    let simulations = simulate_transactions_with_overrides(
+        context,
+        input,
+        Default::default(),
+        Default::default(),
+        false,
+    )
+    .await?;
+
+    let simulations = simulations
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(SimulateTransactionOutput(simulations))
+}
+
+/// Simulates the transactions on top of the overrides, recording the access list of each
+/// transaction if `access_lists` is set.
+pub(crate) async fn simulate_transactions_with_overrides(
+    context: RpcContext,
+    input: SimulateTransactionInput,
+    state_override: StateOverride,
+    block_override: BlockOverride,
+    access_lists: bool,
+) -> Result<Vec<TransactionSimulation>, SimulateTransactionError> {
     let span = tracing::Span::current();
     tokio::task::spawn_blocking(move || {
         let _g = span.enter();
@@ -126,8 +153,13 @@ pub async fn simulate_transactions(
             }
         };
 
-        let state =
-            pathfinder_executor::ExecutionState::simulation(&db, context.chain_id, header, pending);
+        let mut state =
+            pathfinder_executor::ExecutionState::simulation(&db, context.chain_id, header, pending)
+                .with_state_override(state_override)
+                .with_block_override(block_override);
+        if access_lists {
+            state = state.with_access_lists();
+        }
 
         let transactions = input
             .transactions
@@ -137,11 +169,7 @@ pub async fn simulate_transactions(
 
         let txs =
             pathfinder_executor::simulate(state, transactions, skip_validate, skip_fee_charge)?;
-        let txs = txs
-            .into_iter()
-            .map(TryInto::try_into)
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(SimulateTransactionOutput(txs))
+        Ok(txs)
     })
     .await
     .context("Simulating transaction")?
@@ -1,5 +1,6 @@
 use anyhow::Context;
-use pathfinder_common::TransactionHash;
+use pathfinder_common::{BlockNumber, TransactionHash};
+use pathfinder_executor::types::AccessList;
 use pathfinder_executor::{ExecutionState, TransactionExecutionError};
 use serde::{Deserialize, Serialize};
 use starknet_gateway_client::GatewayApi;
@@ -31,6 +32,7 @@ pub enum TraceTransactionError {
     TxnHashNotFound,
     NoTraceAvailable(TraceError),
     ContractErrorV05 { revert_error: String },
+    DataPruned { oldest_block: BlockNumber },
 }
 
 impl From<ExecutionStateError> for TraceTransactionError {
@@ -62,7 +64,12 @@ impl From<TransactionExecutionError> for TraceTransactionError {
 
 impl From<anyhow::Error> for TraceTransactionError {
     fn from(e: anyhow::Error) -> Self {
-        Self::Internal(e)
+        match e.downcast::<pathfinder_storage::DataPruned>() {
+            Ok(pruned) => Self::DataPruned {
+                oldest_block: pruned.oldest_block,
+            },
+            Err(e) => Self::Internal(e),
+        }
     }
 }
 
@@ -73,6 +80,7 @@ impl From<super::trace_block_transactions::TraceBlockTransactionsError> for Trac
             Internal(e) => Self::Internal(e),
             BlockNotFound => Self::Custom(anyhow::anyhow!("Block not found")),
             Custom(e) => Self::Custom(e),
+            DataPruned { oldest_block } => Self::DataPruned { oldest_block },
         }
     }
 }
@@ -93,6 +101,9 @@ impl From<TraceTransactionError> for ApplicationError {
             TraceTransactionError::ContractErrorV05 { revert_error } => {
                 ApplicationError::ContractErrorV05 { revert_error }
             }
+            TraceTransactionError::DataPruned { oldest_block } => ApplicationError::DataPruned {
+                oldest_block: oldest_block.get(),
+            },
             TraceTransactionError::Internal(e) => ApplicationError::Internal(e),
             TraceTransactionError::Custom(e) => ApplicationError::Custom(e),
         }
@@ -103,9 +114,21 @@ pub async fn trace_transaction(
     context: RpcContext,
     input: TraceTransactionInput,
 ) -> Result<TraceTransactionOutput, TraceTransactionError> {
+    trace_transaction_impl(context, input, false)
+        .await
+        .map(|(output, _)| output)
+}
+
+/// Also returns the access list of the transaction if `access_list` is set. There is none for
+/// traces fetched from the gateway.
+pub(crate) async fn trace_transaction_impl(
+    context: RpcContext,
+    input: TraceTransactionInput,
+    access_list: bool,
+) -> Result<(TraceTransactionOutput, Option<AccessList>), TraceTransactionError> {
     #[allow(clippy::large_enum_variant)]
     enum LocalExecution {
-        Success(TransactionTrace),
+        Success(TransactionTrace, Option<AccessList>),
         Unsupported(starknet_gateway_types::reply::transaction::Transaction),
     }
 
@@ -198,22 +221,33 @@ pub async fn trace_transaction(
             )
         };
 
-        let state = ExecutionState::trace(&db, context.chain_id, header, None);
+        let mut state = ExecutionState::trace(&db, context.chain_id, header, None);
+        if access_list {
+            state = state.with_access_lists();
+        }
 
         let transactions = transactions
             .iter()
             .map(|transaction| compose_executor_transaction(transaction, &db))
             .collect::<Result<Vec<_>, _>>()?;
 
-        pathfinder_executor::trace_one(state, transactions, input.transaction_hash, true, true)
-            .map_err(TraceTransactionError::from)
-            .and_then(|x| Ok(LocalExecution::Success(x.try_into()?)))
+        let trace = pathfinder_executor::trace_one(
+            state,
+            transactions,
+            input.transaction_hash,
+            true,
+            true,
+        )?;
+        let access_list = trace.access_list().cloned();
+        Ok::<_, TraceTransactionError>(LocalExecution::Success(trace.try_into()?, access_list))
     })
     .await
     .context("trace_transaction: execution")??;
 
     let transaction = match local {
-        LocalExecution::Success(trace) => return Ok(TraceTransactionOutput(trace)),
+        LocalExecution::Success(trace, access_list) => {
+            return Ok((TraceTransactionOutput(trace), access_list))
+        }
         LocalExecution::Unsupported(x) => x,
     };
 
@@ -225,7 +259,7 @@ pub async fn trace_transaction(
 
     let trace = map_gateway_trace(transaction, trace)?;
 
-    Ok(TraceTransactionOutput(trace))
+    Ok((TraceTransactionOutput(trace), None))
 }
 
 #[cfg(test)]
@@ -16,46 +16,63 @@ pub use rusqlite::TransactionBehavior;
 pub use event::KEY_FILTER_LIMIT as EVENT_KEY_FILTER_LIMIT;
 pub use event::*;
 
-pub use transaction::TransactionStatus;
+pub use ethereum::{L1Message, L1MessageDirection};
+pub use transaction::{DataPruned, TransactionStatus};
 
 pub use trie::{Child, Node, StoredNode};
 
 use pathfinder_common::{
     BlockCommitmentSignature, BlockHash, BlockHeader, BlockNumber, CasmHash, ClassCommitment,
     ClassCommitmentLeafHash, ClassHash, ContractAddress, ContractNonce, ContractRoot,
-    ContractStateHash, SierraHash, StateUpdate, StorageAddress, StorageCommitment, StorageValue,
-    TransactionHash,
+    ContractStateHash, SierraHash, StateCommitment, StateUpdate, StorageAddress, StorageCommitment,
+    StorageValue, TransactionHash,
 };
 use pathfinder_crypto::Felt;
-use pathfinder_ethereum::EthereumStateUpdate;
+use pathfinder_ethereum::{EthereumStateUpdate, L1BlockId, StateUpdateLog};
+use primitive_types::H256;
 use starknet_gateway_types::reply::transaction as gateway;
 
-use crate::BlockId;
+use crate::{BlockId, TriePruneMode};
 
 type PooledConnection = r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>;
 
-pub struct Connection(PooledConnection);
+pub struct Connection {
+    connection: PooledConnection,
+    trie_prune_mode: TriePruneMode,
+}
 
 impl Connection {
-    pub(crate) fn from_inner(inner: PooledConnection) -> Self {
-        Self(inner)
+    pub(crate) fn from_inner(connection: PooledConnection, trie_prune_mode: TriePruneMode) -> Self {
+        Self {
+            connection,
+            trie_prune_mode,
+        }
     }
 
     pub fn transaction(&mut self) -> anyhow::Result<Transaction<'_>> {
-        let tx = self.0.transaction()?;
-        Ok(Transaction(tx))
+        let tx = self.connection.transaction()?;
+        Ok(Transaction {
+            transaction: tx,
+            trie_prune_mode: self.trie_prune_mode,
+        })
     }
 
     pub fn transaction_with_behavior(
         &mut self,
         behavior: TransactionBehavior,
     ) -> anyhow::Result<Transaction<'_>> {
-        let tx = self.0.transaction_with_behavior(behavior)?;
-        Ok(Transaction(tx))
+        let tx = self.connection.transaction_with_behavior(behavior)?;
+        Ok(Transaction {
+            transaction: tx,
+            trie_prune_mode: self.trie_prune_mode,
+        })
     }
 }
 
-pub struct Transaction<'inner>(rusqlite::Transaction<'inner>);
+pub struct Transaction<'inner> {
+    transaction: rusqlite::Transaction<'inner>,
+    trie_prune_mode: TriePruneMode,
+}
 
 impl<'inner> Transaction<'inner> {
     // The implementations here are intentionally kept as simple wrappers. This lets the real implementations
@@ -63,7 +80,10 @@ impl<'inner> Transaction<'inner> {
 
     #[cfg(test)]
     pub(crate) fn from_inner(tx: rusqlite::Transaction<'inner>) -> Self {
-        Self(tx)
+        Self {
+            transaction: tx,
+            trie_prune_mode: TriePruneMode::Archive,
+        }
     }
 
     pub fn insert_contract_state_hash(
@@ -98,6 +118,14 @@ impl<'inner> Transaction<'inner> {
         block::purge_block(self, block)
     }
 
+    /// Removes all blocks from `first` up to and including the latest block, and moves the
+    /// L1-L2 head back to before `first` if it is newer.
+    ///
+    /// Blocks are purged one at a time, newest first, using [Transaction::purge_block].
+    pub fn purge_blocks_from(&self, first: BlockNumber) -> anyhow::Result<()> {
+        block::purge_blocks_from(self, first)
+    }
+
     pub fn block_id(&self, block: BlockId) -> anyhow::Result<Option<(BlockNumber, BlockHash)>> {
         block::block_id(self, block)
     }
@@ -122,6 +150,33 @@ impl<'inner> Transaction<'inner> {
         ethereum::upsert_l1_state(self, update)
     }
 
+    pub fn insert_l1_messages(&self, messages: &[L1Message]) -> anyhow::Result<()> {
+        ethereum::insert_l1_messages(self, messages)
+    }
+
+    /// The messages logged by the L1 transaction, in the order they were indexed.
+    pub fn l1_messages(&self, l1_transaction_hash: H256) -> anyhow::Result<Vec<L1Message>> {
+        ethereum::l1_messages(self, l1_transaction_hash)
+    }
+
+    /// Sets the latest L1 block whose message logs were indexed.
+    pub fn update_l1_messages_head(&self, head: u64) -> anyhow::Result<()> {
+        ethereum::update_l1_messages_head(self, head)
+    }
+
+    pub fn l1_messages_head(&self) -> anyhow::Result<Option<u64>> {
+        ethereum::l1_messages_head(self)
+    }
+
+    /// Sets the earliest L1 block whose message logs were indexed.
+    pub fn update_l1_messages_tail(&self, tail: u64) -> anyhow::Result<()> {
+        ethereum::update_l1_messages_tail(self, tail)
+    }
+
+    pub fn l1_messages_tail(&self) -> anyhow::Result<Option<u64>> {
+        ethereum::l1_messages_tail(self)
+    }
+
     pub fn l1_state_at_number(
         &self,
         block: BlockNumber,
@@ -133,6 +188,38 @@ impl<'inner> Transaction<'inner> {
         ethereum::latest_l1_state(self)
     }
 
+    /// Stores the L1 state update together with the L1 block and transaction which logged it.
+    pub fn insert_l1_state_log(&self, log: &StateUpdateLog) -> anyhow::Result<()> {
+        ethereum::insert_l1_state_log(self, log)
+    }
+
+    /// The L1 state update which settled `block`, if known.
+    pub fn l1_state_log_settling(
+        &self,
+        block: BlockNumber,
+    ) -> anyhow::Result<Option<StateUpdateLog>> {
+        ethereum::l1_state_log_settling(self, block)
+    }
+
+    /// The latest L1 block before `before` which logged a stored state update.
+    pub fn l1_state_block_before(&self, before: u64) -> anyhow::Result<Option<L1BlockId>> {
+        ethereum::l1_state_block_before(self, before)
+    }
+
+    /// Removes the L1 state updates logged after the given L1 block, e.g. due to an L1 reorg.
+    pub fn purge_l1_state_after(&self, l1_block_number: u64) -> anyhow::Result<()> {
+        ethereum::purge_l1_state_after(self, l1_block_number)
+    }
+
+    /// Sets the latest L1 block whose state update logs were processed.
+    pub fn update_l1_state_head(&self, head: Option<L1BlockId>) -> anyhow::Result<()> {
+        ethereum::update_l1_state_head(self, head)
+    }
+
+    pub fn l1_state_head(&self) -> anyhow::Result<Option<L1BlockId>> {
+        ethereum::l1_state_head(self)
+    }
+
     /// Inserts the transaction, receipt and event data.
     pub fn insert_transaction_data(
         &self,
@@ -382,6 +469,85 @@ impl<'inner> Transaction<'inner> {
         trie::trie_storage::insert(self, root.0, nodes)
     }
 
+    /// Records the class trie nodes which were replaced by this block, so that they can be
+    /// pruned once the block leaves the retained history.
+    ///
+    /// This is a no-op unless [TriePruneMode::Prune] is enabled.
+    pub fn insert_class_trie_removals(
+        &self,
+        block_number: BlockNumber,
+        removed: &[u64],
+    ) -> anyhow::Result<()> {
+        if self.trie_prune_mode == TriePruneMode::Archive {
+            return Ok(());
+        }
+        trie::trie_class::insert_removals(self, block_number, removed)
+    }
+
+    /// Records the contract storage trie nodes which were replaced by this block.
+    ///
+    /// See [Transaction::insert_class_trie_removals].
+    pub fn insert_contract_trie_removals(
+        &self,
+        block_number: BlockNumber,
+        removed: &[u64],
+    ) -> anyhow::Result<()> {
+        if self.trie_prune_mode == TriePruneMode::Archive {
+            return Ok(());
+        }
+        trie::trie_contracts::insert_removals(self, block_number, removed)
+    }
+
+    /// Records the global storage trie nodes which were replaced by this block.
+    ///
+    /// See [Transaction::insert_class_trie_removals].
+    pub fn insert_storage_trie_removals(
+        &self,
+        block_number: BlockNumber,
+        removed: &[u64],
+    ) -> anyhow::Result<()> {
+        if self.trie_prune_mode == TriePruneMode::Archive {
+            return Ok(());
+        }
+        trie::trie_storage::insert_removals(self, block_number, removed)
+    }
+
+    /// Deletes the trie nodes and root indices which are only required by blocks before
+    /// `keep_from`. The tries of `keep_from` and later blocks remain intact.
+    pub fn prune_tries(&self, keep_from: BlockNumber) -> anyhow::Result<()> {
+        trie::prune_tries(self, keep_from)
+    }
+
+    /// The oldest block whose tries are still stored, or [None] if no tries were pruned yet.
+    pub fn trie_prune_horizon(&self) -> anyhow::Result<Option<BlockNumber>> {
+        trie::trie_prune_horizon(self)
+    }
+
+    /// Deletes the transaction bodies, receipts and events of all blocks before `keep_from`.
+    ///
+    /// Reading pruned data fails with [DataPruned]. Block headers and state are not affected.
+    pub fn prune_transactions(&self, keep_from: BlockNumber) -> anyhow::Result<()> {
+        transaction::prune_transactions(self, keep_from)
+    }
+
+    /// The oldest block whose transactions, receipts and events are still stored, or [None] if
+    /// no transaction data was pruned yet.
+    pub fn transaction_prune_horizon(&self) -> anyhow::Result<Option<BlockNumber>> {
+        transaction::transaction_prune_horizon(self)
+    }
+
+    /// Calculates the block's state commitment from the roots of its stored state tries.
+    pub fn state_commitment_from_tries(
+        &self,
+        block: BlockNumber,
+    ) -> anyhow::Result<StateCommitment> {
+        trie::state_commitment(self, block)
+    }
+
+    pub fn trie_prune_mode(&self) -> TriePruneMode {
+        self.trie_prune_mode
+    }
+
     pub fn class_trie_node(&self, index: u64) -> anyhow::Result<Option<StoredNode>> {
         trie::trie_class::node(self, index)
     }
@@ -505,10 +671,10 @@ impl<'inner> Transaction<'inner> {
     }
 
     pub(self) fn inner(&self) -> &rusqlite::Transaction<'_> {
-        &self.0
+        &self.transaction
     }
 
     pub fn commit(self) -> anyhow::Result<()> {
-        Ok(self.0.commit()?)
+        Ok(self.transaction.commit()?)
     }
 }
@@ -137,6 +137,56 @@ pub(super) fn purge_block(tx: &Transaction<'_>, block: BlockNumber) -> anyhow::R
         )
         .context("Deleting block from storage_roots table")?;
 
+    // The nodes replaced by this block are part of the parent block's tries again.
+    for table in [
+        "trie_class_removals",
+        "trie_contracts_removals",
+        "trie_storage_removals",
+    ] {
+        tx.inner()
+            .execute(
+                &format!("DELETE FROM {table} WHERE block_number = ?"),
+                params![&block],
+            )
+            .with_context(|| format!("Deleting block from {table} table"))?;
+    }
+
+    Ok(())
+}
+
+pub(super) fn purge_blocks_from(tx: &Transaction<'_>, first: BlockNumber) -> anyhow::Result<()> {
+    let Some((mut head, _)) = block_id(tx, BlockId::Latest).context("Querying latest block")?
+    else {
+        return Ok(());
+    };
+
+    // Purge each block one at a time. This keeps the memory footprint constant,
+    // which matters more than speed since large purges are rare.
+    while head >= first {
+        purge_block(tx, head).with_context(|| format!("Purging block {head}"))?;
+
+        // No further blocks to purge if we just purged genesis.
+        if head == BlockNumber::GENESIS {
+            break;
+        }
+
+        head -= 1;
+    }
+
+    // Track combined L1 and L2 state.
+    let l1_l2_head = tx.l1_l2_pointer().context("Querying L1-L2 head")?;
+    if let Some(l1_l2_head) = l1_l2_head {
+        if first == BlockNumber::GENESIS {
+            // If we purged genesis then unset the L1 L2 pointer as well since there
+            // are now no blocks remaining.
+            tx.update_l1_l2_pointer(None)
+                .context("Unsetting L1-L2 head")?;
+        } else if l1_l2_head >= first {
+            tx.update_l1_l2_pointer(Some(first - 1))
+                .context("Updating L1-L2 head")?;
+        }
+    }
+
     Ok(())
 }
 
@@ -440,6 +490,28 @@ mod tests {
         assert_eq!(class_exists, None);
     }
 
+    #[test]
+    fn purge_blocks_from() {
+        let (mut connection, headers) = setup();
+        let tx = connection.transaction().unwrap();
+        tx.update_l1_l2_pointer(Some(headers[2].number)).unwrap();
+
+        tx.purge_blocks_from(headers[1].number).unwrap();
+
+        let latest = tx.block_id(BlockId::Latest).unwrap();
+        assert_eq!(latest, Some((headers[0].number, headers[0].hash)));
+        assert!(!tx.block_exists(headers[1].number.into()).unwrap());
+        assert_eq!(tx.l1_l2_pointer().unwrap(), Some(headers[0].number));
+
+        // Purging past the head is a no-op.
+        tx.purge_blocks_from(headers[1].number).unwrap();
+        assert_eq!(tx.l1_l2_pointer().unwrap(), Some(headers[0].number));
+
+        tx.purge_blocks_from(BlockNumber::GENESIS).unwrap();
+        assert_eq!(tx.block_id(BlockId::Latest).unwrap(), None);
+        assert_eq!(tx.l1_l2_pointer().unwrap(), None);
+    }
+
     #[test]
     fn block_id() {
         let (mut connection, headers) = setup();
@@ -1,8 +1,30 @@
-use pathfinder_common::BlockNumber;
-use pathfinder_ethereum::EthereumStateUpdate;
+use anyhow::Context;
+use pathfinder_common::{BlockNumber, TransactionHash};
+use pathfinder_ethereum::{EthereumStateUpdate, L1BlockId, StateUpdateLog};
+use primitive_types::H256;
 
 use crate::prelude::*;
 
+/// The direction of a message between L1 and L2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum L1MessageDirection {
+    /// A message sent from L1 to L2, which is handled by an `L1_HANDLER` transaction.
+    L1ToL2,
+    /// A message sent from L2 to L1, which was consumed by the L1 transaction.
+    ConsumedL2ToL1,
+}
+
+/// A message between L1 and L2 which was logged by an L1 transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L1Message {
+    pub l1_transaction_hash: H256,
+    pub message_hash: H256,
+    pub direction: L1MessageDirection,
+    pub l1_block_number: u64,
+    /// The hash of the `L1_HANDLER` transaction which handles a message from L1 to L2.
+    pub l2_transaction_hash: Option<TransactionHash>,
+}
+
 pub(super) fn upsert_l1_state(
     tx: &Transaction<'_>,
     update: &EthereumStateUpdate,
@@ -75,6 +97,262 @@ pub(super) fn latest_l1_state(tx: &Transaction<'_>) -> anyhow::Result<Option<Eth
         .map_err(|e| e.into())
 }
 
+pub(super) fn insert_l1_state_log(
+    tx: &Transaction<'_>,
+    log: &StateUpdateLog,
+) -> anyhow::Result<()> {
+    tx.inner().execute(
+        r"INSERT OR REPLACE INTO l1_state (
+                    starknet_block_number,
+                    starknet_block_hash,
+                    starknet_state_root,
+                    l1_block_number,
+                    l1_block_hash,
+                    l1_transaction_hash
+                ) VALUES (
+                    :starknet_block_number,
+                    :starknet_block_hash,
+                    :starknet_state_root,
+                    :l1_block_number,
+                    :l1_block_hash,
+                    :l1_transaction_hash
+                )",
+        named_params! {
+            ":starknet_block_number": &log.update.block_number,
+            ":starknet_block_hash": &log.update.block_hash,
+            ":starknet_state_root": &log.update.state_root,
+            ":l1_block_number": &log.l1_block_number.try_into_sql_int()?,
+            ":l1_block_hash": &log.l1_block_hash.as_bytes(),
+            ":l1_transaction_hash": &log.l1_transaction_hash.as_bytes(),
+        },
+    )?;
+
+    Ok(())
+}
+
+/// The first L1 state update which includes `block`, or [None] if the block has not been settled
+/// or the L1 block of its state update is unknown.
+pub(super) fn l1_state_log_settling(
+    tx: &Transaction<'_>,
+    block: BlockNumber,
+) -> anyhow::Result<Option<StateUpdateLog>> {
+    let log = tx
+        .inner()
+        .query_row(
+            r"SELECT starknet_block_number, starknet_block_hash, starknet_state_root,
+                l1_block_number, l1_block_hash, l1_transaction_hash FROM l1_state
+            WHERE starknet_block_number >= ?
+            ORDER BY starknet_block_number ASC
+            LIMIT 1",
+            params![&block],
+            |row| {
+                let update = EthereumStateUpdate {
+                    block_number: row.get_block_number(0)?,
+                    block_hash: row.get_block_hash(1)?,
+                    state_root: row.get_state_commitment(2)?,
+                };
+                let l1_block_number = row.get_optional_i64(3)?;
+                let l1_block_hash = row.get_optional_blob(4)?.map(H256::from_slice);
+                let l1_transaction_hash = row.get_optional_blob(5)?.map(H256::from_slice);
+
+                Ok(l1_block_number
+                    .zip(l1_block_hash)
+                    .zip(l1_transaction_hash)
+                    .map(|((l1_block_number, l1_block_hash), l1_transaction_hash)| {
+                        StateUpdateLog {
+                            update,
+                            l1_block_number: l1_block_number as u64,
+                            l1_block_hash,
+                            l1_transaction_hash,
+                        }
+                    }))
+            },
+        )
+        .optional()?;
+
+    Ok(log.flatten())
+}
+
+/// The latest L1 block before `before` which logged a state update.
+pub(super) fn l1_state_block_before(
+    tx: &Transaction<'_>,
+    before: u64,
+) -> anyhow::Result<Option<L1BlockId>> {
+    tx.inner()
+        .query_row(
+            r"SELECT l1_block_number, l1_block_hash FROM l1_state
+            WHERE l1_block_number < ?
+            ORDER BY l1_block_number DESC
+            LIMIT 1",
+            params![&before.try_into_sql_int()?],
+            |row| {
+                Ok(L1BlockId {
+                    number: row.get_i64(0)? as u64,
+                    hash: H256::from_slice(row.get_blob(1)?),
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| e.into())
+}
+
+/// Removes the state updates logged in L1 blocks after `l1_block_number`.
+pub(super) fn purge_l1_state_after(
+    tx: &Transaction<'_>,
+    l1_block_number: u64,
+) -> anyhow::Result<()> {
+    tx.inner().execute(
+        "DELETE FROM l1_state WHERE l1_block_number > ?",
+        params![&l1_block_number.try_into_sql_int()?],
+    )?;
+
+    Ok(())
+}
+
+pub(super) fn update_l1_state_head(
+    tx: &Transaction<'_>,
+    head: Option<L1BlockId>,
+) -> anyhow::Result<()> {
+    let number = head
+        .map(|head| head.number.try_into_sql_int())
+        .transpose()?;
+    tx.inner().execute(
+        "UPDATE refs SET l1_state_head_number = ?, l1_state_head_hash = ? WHERE idx = 1",
+        params![&number, &head.as_ref().map(|head| head.hash.as_bytes())],
+    )?;
+
+    Ok(())
+}
+
+pub(super) fn l1_state_head(tx: &Transaction<'_>) -> anyhow::Result<Option<L1BlockId>> {
+    // This table always contains exactly one row.
+    tx.inner()
+        .query_row(
+            "SELECT l1_state_head_number, l1_state_head_hash FROM refs WHERE idx = 1",
+            [],
+            |row| {
+                let number = row.get_optional_i64(0)?;
+                let hash = row.get_optional_blob(1)?.map(H256::from_slice);
+
+                Ok(number.zip(hash).map(|(number, hash)| L1BlockId {
+                    number: number as u64,
+                    hash,
+                }))
+            },
+        )
+        .map_err(|e| e.into())
+}
+
+pub(super) fn insert_l1_messages(
+    tx: &Transaction<'_>,
+    messages: &[L1Message],
+) -> anyhow::Result<()> {
+    let mut stmt = tx
+        .inner()
+        .prepare(
+            r"INSERT OR IGNORE INTO l1_messages
+                    ( l1_transaction_hash,  message_hash,  direction,  l1_block_number,  l2_transaction_hash)
+             VALUES (:l1_transaction_hash, :message_hash, :direction, :l1_block_number, :l2_transaction_hash)",
+        )
+        .context("Preparing statement")?;
+
+    for message in messages {
+        let direction: i64 = match message.direction {
+            L1MessageDirection::L1ToL2 => 0,
+            L1MessageDirection::ConsumedL2ToL1 => 1,
+        };
+        stmt.execute(named_params! {
+            ":l1_transaction_hash": &message.l1_transaction_hash.as_bytes(),
+            ":message_hash": &message.message_hash.as_bytes(),
+            ":direction": &direction,
+            ":l1_block_number": &message.l1_block_number.try_into_sql_int()?,
+            ":l2_transaction_hash": &message.l2_transaction_hash,
+        })
+        .context("Inserting L1 message")?;
+    }
+
+    Ok(())
+}
+
+pub(super) fn l1_messages(
+    tx: &Transaction<'_>,
+    l1_transaction_hash: H256,
+) -> anyhow::Result<Vec<L1Message>> {
+    let mut stmt = tx
+        .inner()
+        .prepare(
+            r"SELECT message_hash, direction, l1_block_number, l2_transaction_hash FROM l1_messages
+            WHERE l1_transaction_hash = ?
+            ORDER BY rowid",
+        )
+        .context("Preparing statement")?;
+
+    let messages = stmt
+        .query_map(params![&l1_transaction_hash.as_bytes()], |row| {
+            let message_hash = H256::from_slice(row.get_blob(0)?);
+            let direction = match row.get_i64(1)? {
+                0 => L1MessageDirection::L1ToL2,
+                _ => L1MessageDirection::ConsumedL2ToL1,
+            };
+            let l1_block_number = row.get_i64(2)? as u64;
+            let l2_transaction_hash = row.get_optional_felt(3)?.map(TransactionHash);
+
+            Ok(L1Message {
+                l1_transaction_hash,
+                message_hash,
+                direction,
+                l1_block_number,
+                l2_transaction_hash,
+            })
+        })
+        .context("Querying L1 messages")?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(messages)
+}
+
+pub(super) fn update_l1_messages_head(tx: &Transaction<'_>, head: u64) -> anyhow::Result<()> {
+    tx.inner().execute(
+        "UPDATE refs SET l1_messages_head = ? WHERE idx = 1",
+        params![&head.try_into_sql_int()?],
+    )?;
+
+    Ok(())
+}
+
+pub(super) fn l1_messages_head(tx: &Transaction<'_>) -> anyhow::Result<Option<u64>> {
+    // This table always contains exactly one row.
+    tx.inner()
+        .query_row(
+            "SELECT l1_messages_head FROM refs WHERE idx = 1",
+            [],
+            |row| row.get_optional_i64(0),
+        )
+        .map(|head| head.map(|head| head as u64))
+        .map_err(|e| e.into())
+}
+
+pub(super) fn update_l1_messages_tail(tx: &Transaction<'_>, tail: u64) -> anyhow::Result<()> {
+    tx.inner().execute(
+        "UPDATE refs SET l1_messages_tail = ? WHERE idx = 1",
+        params![&tail.try_into_sql_int()?],
+    )?;
+
+    Ok(())
+}
+
+pub(super) fn l1_messages_tail(tx: &Transaction<'_>) -> anyhow::Result<Option<u64>> {
+    // This table always contains exactly one row.
+    tx.inner()
+        .query_row(
+            "SELECT l1_messages_tail FROM refs WHERE idx = 1",
+            [],
+            |row| row.get_optional_i64(0),
+        )
+        .map(|tail| tail.map(|tail| tail as u64))
+        .map_err(|e| e.into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,4 +449,174 @@ mod tests {
             .unwrap();
         assert_eq!(result, new_value);
     }
+
+    fn state_update_log(starknet_block: u64, l1_block: u64) -> StateUpdateLog {
+        StateUpdateLog {
+            update: EthereumStateUpdate {
+                state_root: StateCommitment(Felt::from_u64(starknet_block)),
+                block_number: BlockNumber::new_or_panic(starknet_block),
+                block_hash: BlockHash(Felt::from_u64(starknet_block + 100)),
+            },
+            l1_block_number: l1_block,
+            l1_block_hash: H256::from_low_u64_be(l1_block),
+            l1_transaction_hash: H256::from_low_u64_be(l1_block + 1000),
+        }
+    }
+
+    #[test]
+    fn l1_state_log_settling_block() {
+        let storage = Storage::in_memory().unwrap();
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        let first = state_update_log(5, 100);
+        let second = state_update_log(10, 200);
+        insert_l1_state_log(&tx, &first).unwrap();
+        insert_l1_state_log(&tx, &second).unwrap();
+
+        for block in 0..=5 {
+            let result = l1_state_log_settling(&tx, BlockNumber::new_or_panic(block)).unwrap();
+            assert_eq!(result.as_ref(), Some(&first));
+        }
+        for block in 6..=10 {
+            let result = l1_state_log_settling(&tx, BlockNumber::new_or_panic(block)).unwrap();
+            assert_eq!(result.as_ref(), Some(&second));
+        }
+        let result = l1_state_log_settling(&tx, BlockNumber::new_or_panic(11)).unwrap();
+        assert_eq!(result, None);
+
+        // The state update is still usable as plain L1 state.
+        assert_eq!(latest_l1_state(&tx).unwrap(), Some(second.update));
+    }
+
+    #[test]
+    fn l1_state_settling_block_without_l1_data() {
+        let storage = Storage::in_memory().unwrap();
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        insert_l1_state_log(&tx, &state_update_log(10, 200)).unwrap();
+        upsert_l1_state(&tx, &state_update_log(5, 100).update).unwrap();
+
+        let result = l1_state_log_settling(&tx, BlockNumber::new_or_panic(3)).unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn l1_state_reorg() {
+        let storage = Storage::in_memory().unwrap();
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        let logs = [
+            state_update_log(5, 100),
+            state_update_log(10, 200),
+            state_update_log(15, 300),
+        ];
+        for log in &logs {
+            insert_l1_state_log(&tx, log).unwrap();
+        }
+
+        let block_id = |log: &StateUpdateLog| L1BlockId {
+            number: log.l1_block_number,
+            hash: log.l1_block_hash,
+        };
+        assert_eq!(
+            l1_state_block_before(&tx, 300).unwrap(),
+            Some(block_id(&logs[1]))
+        );
+        assert_eq!(
+            l1_state_block_before(&tx, 301).unwrap(),
+            Some(block_id(&logs[2]))
+        );
+        assert_eq!(l1_state_block_before(&tx, 100).unwrap(), None);
+
+        purge_l1_state_after(&tx, 200).unwrap();
+        assert_eq!(latest_l1_state(&tx).unwrap(), Some(logs[1].update.clone()));
+        assert_eq!(
+            l1_state_block_before(&tx, u64::MAX >> 1).unwrap(),
+            Some(block_id(&logs[1]))
+        );
+    }
+
+    #[test]
+    fn l1_state_head() {
+        let storage = Storage::in_memory().unwrap();
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        assert_eq!(super::l1_state_head(&tx).unwrap(), None);
+
+        let head = L1BlockId {
+            number: 1234,
+            hash: H256::from_low_u64_be(1),
+        };
+        update_l1_state_head(&tx, Some(head)).unwrap();
+        assert_eq!(super::l1_state_head(&tx).unwrap(), Some(head));
+
+        update_l1_state_head(&tx, None).unwrap();
+        assert_eq!(super::l1_state_head(&tx).unwrap(), None);
+    }
+
+    #[test]
+    fn l1_messages_by_l1_transaction() {
+        let storage = Storage::in_memory().unwrap();
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        let l1_transaction_hash = H256::from_low_u64_be(1);
+        let messages = vec![
+            L1Message {
+                l1_transaction_hash,
+                message_hash: H256::from_low_u64_be(10),
+                direction: L1MessageDirection::L1ToL2,
+                l1_block_number: 100,
+                l2_transaction_hash: Some(transaction_hash!("0x123")),
+            },
+            L1Message {
+                l1_transaction_hash,
+                message_hash: H256::from_low_u64_be(11),
+                direction: L1MessageDirection::ConsumedL2ToL1,
+                l1_block_number: 100,
+                l2_transaction_hash: None,
+            },
+        ];
+        let other = L1Message {
+            l1_transaction_hash: H256::from_low_u64_be(2),
+            ..messages[0].clone()
+        };
+
+        insert_l1_messages(&tx, &messages).unwrap();
+        insert_l1_messages(&tx, &[other]).unwrap();
+        // Inserting the same messages again is a no-op.
+        insert_l1_messages(&tx, &messages).unwrap();
+
+        let result = l1_messages(&tx, l1_transaction_hash).unwrap();
+        assert_eq!(result, messages);
+
+        let result = l1_messages(&tx, H256::from_low_u64_be(3)).unwrap();
+        assert_eq!(result, vec![]);
+    }
+
+    #[test]
+    fn l1_messages_head() {
+        let storage = Storage::in_memory().unwrap();
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        assert_eq!(super::l1_messages_head(&tx).unwrap(), None);
+        update_l1_messages_head(&tx, 1234).unwrap();
+        assert_eq!(super::l1_messages_head(&tx).unwrap(), Some(1234));
+    }
+
+    #[test]
+    fn l1_messages_tail() {
+        let storage = Storage::in_memory().unwrap();
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        assert_eq!(super::l1_messages_tail(&tx).unwrap(), None);
+        update_l1_messages_tail(&tx, 1000).unwrap();
+        assert_eq!(super::l1_messages_tail(&tx).unwrap(), Some(1000));
+    }
 }
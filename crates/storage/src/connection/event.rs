@@ -1,5 +1,5 @@
 use crate::params::ToSql;
-use crate::{prelude::*, BlockId};
+use crate::{prelude::*, BlockId, DataPruned};
 
 use anyhow::Context;
 use pathfinder_common::event::Event;
@@ -46,6 +46,8 @@ pub enum EventFilterError {
     PageSizeTooSmall,
     #[error("Event query too broad. Reduce the block range or add more keys.")]
     TooManyMatches,
+    #[error(transparent)]
+    DataPruned(#[from] DataPruned),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -133,6 +135,22 @@ pub(super) fn event_count_for_block(tx: &Transaction<'_>, block: BlockId) -> any
     }
 }
 
+/// Deletes the events of the blocks in the range `[from, to)`.
+pub(super) fn delete_events(
+    tx: &Transaction<'_>,
+    from: BlockNumber,
+    to: BlockNumber,
+) -> anyhow::Result<()> {
+    tx.inner()
+        .execute(
+            "DELETE FROM starknet_events WHERE block_number >= ? AND block_number < ?",
+            params![&from, &to],
+        )
+        .context("Deleting events")?;
+
+    Ok(())
+}
+
 pub(super) fn get_events<K: KeyFilter>(
     tx: &Transaction<'_>,
     filter: &EventFilter<K>,
@@ -145,9 +163,21 @@ pub(super) fn get_events<K: KeyFilter>(
         return Err(EventFilterError::PageSizeTooSmall);
     }
 
+    // Without a lower bound only the events which were not pruned are searched.
+    let from_block = match (
+        filter.from_block,
+        super::transaction::transaction_prune_horizon(tx)?,
+    ) {
+        (Some(from_block), Some(oldest_block)) if from_block < oldest_block => {
+            return Err(DataPruned { oldest_block }.into());
+        }
+        (None, oldest_block) => oldest_block,
+        (from_block, _) => from_block,
+    };
+
     let strategy = select_query_strategy(
         tx,
-        filter.from_block.as_ref(),
+        from_block.as_ref(),
         filter.to_block.as_ref(),
         filter.contract_address.as_ref(),
         &filter.keys,
@@ -167,7 +197,7 @@ pub(super) fn get_events<K: KeyFilter>(
 
     let (mut base_query, mut params) = event_query(
         base_query,
-        filter.from_block.as_ref(),
+        from_block.as_ref(),
         filter.to_block.as_ref(),
         filter.contract_address.as_ref(),
         &filter.keys,
@@ -828,6 +858,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_events_from_pruned_blocks() {
+        let (storage, test_data) = test_utils::setup_test_storage();
+        let emitted_events = test_data.events;
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        const BLOCK_NUMBER: usize = 2;
+        let oldest_block = BlockNumber::new_or_panic(BLOCK_NUMBER as u64);
+        tx.prune_transactions(oldest_block).unwrap();
+
+        let filter = EventFilter {
+            from_block: Some(oldest_block - 1),
+            to_block: Some(oldest_block),
+            contract_address: None,
+            keys: V03KeyFilter::new(vec![]),
+            page_size: test_utils::NUM_EVENTS,
+            offset: 0,
+        };
+        let error = get_events(&tx, &filter).unwrap_err();
+        assert_matches!(
+            error,
+            EventFilterError::DataPruned(pruned) => assert_eq!(pruned, DataPruned { oldest_block })
+        );
+
+        let expected_events = &emitted_events[test_utils::EVENTS_PER_BLOCK * BLOCK_NUMBER
+            ..test_utils::EVENTS_PER_BLOCK * (BLOCK_NUMBER + 1)];
+        for from_block in [Some(oldest_block), None] {
+            let filter = EventFilter {
+                from_block,
+                to_block: Some(oldest_block),
+                contract_address: None,
+                keys: V03KeyFilter::new(vec![]),
+                page_size: test_utils::NUM_EVENTS,
+                offset: 0,
+            };
+            let events = get_events(&tx, &filter).unwrap();
+            assert_eq!(events.events, expected_events);
+        }
+    }
+
     #[test]
     fn get_events_up_to_block() {
         let (storage, test_data) = test_utils::setup_test_storage();
@@ -11,6 +11,16 @@ pub enum TransactionStatus {
     L2Accepted,
 }
 
+/// The requested transaction data was deleted by [Transaction::prune_transactions].
+///
+/// This is returned wrapped in an [anyhow::Error] by the queries which read transaction bodies,
+/// receipts or events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Transaction data was pruned, the oldest block available is {oldest_block}")]
+pub struct DataPruned {
+    pub oldest_block: BlockNumber,
+}
+
 pub(super) fn insert_transactions(
     tx: &Transaction<'_>,
     block_hash: BlockHash,
@@ -76,7 +86,10 @@ pub(super) fn transaction(
         None => return Ok(None),
     };
 
-    let transaction = row.get_ref_unwrap(0).as_blob()?;
+    let transaction = match row.get_ref_unwrap(0).as_blob_or_null()? {
+        Some(data) => data,
+        None => return Err(pruned_error(tx)),
+    };
     let transaction = zstd::decode_all(transaction).context("Decompressing transaction")?;
     let transaction = serde_json::from_slice(&transaction).context("Deserializing transaction")?;
 
@@ -99,13 +112,16 @@ pub(super) fn transaction_with_receipt(
         None => return Ok(None),
     };
 
-    let transaction = row.get_ref_unwrap("tx").as_blob()?;
+    let transaction = match row.get_ref_unwrap("tx").as_blob_or_null()? {
+        Some(data) => data,
+        None => return Err(pruned_error(tx)),
+    };
     let transaction = zstd::decode_all(transaction).context("Decompressing transaction")?;
     let transaction = serde_json::from_slice(&transaction).context("Deserializing transaction")?;
 
     let receipt = match row.get_ref_unwrap("receipt").as_blob_or_null()? {
         Some(data) => data,
-        None => return Ok(None),
+        None => return Err(pruned_error(tx)),
     };
     let receipt = zstd::decode_all(receipt).context("Decompressing receipt")?;
     let receipt = serde_json::from_slice(&receipt).context("Deserializing receipt")?;
@@ -141,7 +157,7 @@ pub(super) fn transaction_at_block(
 
     let transaction = match row.get_ref_unwrap(0).as_blob_or_null()? {
         Some(data) => data,
-        None => return Ok(None),
+        None => return Err(pruned_error(tx)),
     };
 
     let transaction = zstd::decode_all(transaction).context("Decompressing transaction")?;
@@ -203,18 +219,16 @@ pub(super) fn transaction_data_for_block(
 
     let mut data = Vec::new();
     while let Some(row) = rows.next()? {
-        let receipt = row
-            .get_ref_unwrap("receipt")
-            .as_blob_or_null()?
-            .context("Receipt data missing")?;
+        let Some(receipt) = row.get_ref_unwrap("receipt").as_blob_or_null()? else {
+            return Err(pruned_error(tx));
+        };
         let receipt = zstd::decode_all(receipt).context("Decompressing transaction receipt")?;
         let receipt =
             serde_json::from_slice(&receipt).context("Deserializing transaction receipt")?;
 
-        let transaction = row
-            .get_ref_unwrap("tx")
-            .as_blob_or_null()?
-            .context("Transaction data missing")?;
+        let Some(transaction) = row.get_ref_unwrap("tx").as_blob_or_null()? else {
+            return Err(pruned_error(tx));
+        };
         let transaction = zstd::decode_all(transaction).context("Decompressing transaction")?;
         let transaction =
             serde_json::from_slice(&transaction).context("Deserializing transaction")?;
@@ -244,10 +258,9 @@ pub(super) fn transactions_for_block(
 
     let mut data = Vec::new();
     while let Some(row) = rows.next()? {
-        let transaction = row
-            .get_ref_unwrap("tx")
-            .as_blob_or_null()?
-            .context("Transaction data missing")?;
+        let Some(transaction) = row.get_ref_unwrap("tx").as_blob_or_null()? else {
+            return Err(pruned_error(tx));
+        };
         let transaction = zstd::decode_all(transaction).context("Decompressing transaction")?;
         let transaction =
             serde_json::from_slice(&transaction).context("Deserializing transaction")?;
@@ -293,6 +306,63 @@ pub(super) fn transaction_block_hash(
         .map_err(|e| e.into())
 }
 
+/// Deletes the transaction bodies, receipts and events of all blocks before `keep_from`.
+///
+/// The transaction hashes and their block and index are kept, so that lookups of pruned
+/// transactions fail with [DataPruned] instead of not finding them at all.
+pub(super) fn prune_transactions(
+    tx: &Transaction<'_>,
+    keep_from: BlockNumber,
+) -> anyhow::Result<()> {
+    let previous = transaction_prune_horizon(tx).context("Querying transaction prune horizon")?;
+    if previous.is_some_and(|previous| previous >= keep_from) {
+        return Ok(());
+    }
+    let from = previous.unwrap_or(BlockNumber::GENESIS);
+
+    tx.inner()
+        .execute(
+            "UPDATE starknet_transactions SET tx = NULL, receipt = NULL WHERE block_hash IN (
+                SELECT hash FROM canonical_blocks WHERE number >= ? AND number < ?
+            )",
+            params![&from, &keep_from],
+        )
+        .context("Deleting transaction data")?;
+
+    super::event::delete_events(tx, from, keep_from).context("Deleting events")?;
+
+    tx.inner()
+        .execute(
+            "UPDATE refs SET transaction_prune_horizon = ? WHERE idx = 1",
+            params![&keep_from],
+        )
+        .context("Updating transaction prune horizon")?;
+
+    Ok(())
+}
+
+pub(super) fn transaction_prune_horizon(
+    tx: &Transaction<'_>,
+) -> anyhow::Result<Option<BlockNumber>> {
+    // This table always contains exactly one row.
+    tx.inner()
+        .query_row(
+            "SELECT transaction_prune_horizon FROM refs WHERE idx = 1",
+            [],
+            |row| row.get_optional_block_number(0),
+        )
+        .map_err(Into::into)
+}
+
+/// The error for transaction data which is missing because it was pruned.
+fn pruned_error(tx: &Transaction<'_>) -> anyhow::Error {
+    match transaction_prune_horizon(tx) {
+        Ok(Some(oldest_block)) => DataPruned { oldest_block }.into(),
+        Ok(None) => anyhow::anyhow!("Transaction data missing"),
+        Err(e) => e.context("Querying transaction prune horizon"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pathfinder_common::macro_prelude::*;
@@ -604,4 +674,42 @@ mod tests {
             super::transaction_block_hash(&tx, transaction_hash_bytes!(b"invalid hash")).unwrap();
         assert_eq!(invalid, None);
     }
+
+    #[test]
+    fn prune_transactions() {
+        let (mut db, header, body) = setup();
+        let tx = db.transaction().unwrap();
+        assert_eq!(super::transaction_prune_horizon(&tx).unwrap(), None);
+
+        // The block is still retained.
+        super::prune_transactions(&tx, header.number).unwrap();
+        let target = body.first().unwrap().0.hash();
+        assert!(super::transaction(&tx, target).unwrap().is_some());
+
+        super::prune_transactions(&tx, header.number + 1).unwrap();
+        let expected = DataPruned {
+            oldest_block: header.number + 1,
+        };
+        assert_eq!(
+            super::transaction_prune_horizon(&tx).unwrap(),
+            Some(expected.oldest_block)
+        );
+
+        let error = super::transaction(&tx, target).unwrap_err();
+        assert_eq!(error.downcast_ref::<DataPruned>(), Some(&expected));
+        let error = super::transaction_with_receipt(&tx, target).unwrap_err();
+        assert_eq!(error.downcast_ref::<DataPruned>(), Some(&expected));
+        let error = super::transaction_data_for_block(&tx, header.number.into()).unwrap_err();
+        assert_eq!(error.downcast_ref::<DataPruned>(), Some(&expected));
+        let error = super::transaction_at_block(&tx, header.number.into(), 0).unwrap_err();
+        assert_eq!(error.downcast_ref::<DataPruned>(), Some(&expected));
+
+        // Headers and transaction hashes are kept.
+        let hashes = super::transaction_hashes_for_block(&tx, header.number.into())
+            .unwrap()
+            .unwrap();
+        assert_eq!(hashes.len(), body.len());
+        let block_hash = super::transaction_block_hash(&tx, target).unwrap();
+        assert_eq!(block_hash, Some(header.hash));
+    }
 }
@@ -139,9 +139,97 @@ pub(super) fn insert_contract_root(
     Ok(())
 }
 
+pub(super) fn trie_prune_horizon(tx: &Transaction<'_>) -> anyhow::Result<Option<BlockNumber>> {
+    // This table always contains exactly one row.
+    tx.inner()
+        .query_row(
+            "SELECT trie_prune_horizon FROM refs WHERE idx = 1",
+            [],
+            |row| row.get_optional_block_number(0),
+        )
+        .map_err(Into::into)
+}
+
+pub(super) fn state_commitment(
+    tx: &Transaction<'_>,
+    block_number: BlockNumber,
+) -> anyhow::Result<StateCommitment> {
+    let storage_commitment = match storage_root_index(tx, block_number)? {
+        Some(index) => StorageCommitment(
+            trie_storage::hash(tx, index)?.context("Storage trie root node is missing")?,
+        ),
+        None => StorageCommitment::ZERO,
+    };
+    let class_commitment = match class_root_index(tx, block_number)? {
+        Some(index) => ClassCommitment(
+            trie_class::hash(tx, index)?.context("Class trie root node is missing")?,
+        ),
+        None => ClassCommitment::ZERO,
+    };
+
+    Ok(StateCommitment::calculate(
+        storage_commitment,
+        class_commitment,
+    ))
+}
+
+pub(super) fn prune_tries(tx: &Transaction<'_>, keep_from: BlockNumber) -> anyhow::Result<()> {
+    let previous = trie_prune_horizon(tx).context("Querying trie prune horizon")?;
+    if previous.is_some_and(|previous| previous >= keep_from) {
+        return Ok(());
+    }
+
+    // Nodes removed from a trie by some block are no longer reachable from the roots of that
+    // block or any later block.
+    trie_class::prune(tx, keep_from).context("Pruning class trie")?;
+    trie_contracts::prune(tx, keep_from).context("Pruning contract tries")?;
+    trie_storage::prune(tx, keep_from).context("Pruning storage trie")?;
+
+    // Roots are looked up as the latest root at or before a block, so the latest root at
+    // `keep_from` must be kept.
+    tx.inner()
+        .execute(
+            "DELETE FROM class_roots WHERE block_number < (
+                SELECT MAX(block_number) FROM class_roots WHERE block_number <= ?
+            )",
+            params![&keep_from],
+        )
+        .context("Deleting class roots")?;
+    tx.inner()
+        .execute(
+            "DELETE FROM storage_roots WHERE block_number < (
+                SELECT MAX(block_number) FROM storage_roots WHERE block_number <= ?
+            )",
+            params![&keep_from],
+        )
+        .context("Deleting storage roots")?;
+    // Only contracts whose root changed since the previous pruning can have new superseded roots.
+    tx.inner()
+        .execute(
+            "DELETE FROM contract_roots WHERE rowid IN (
+                SELECT old.rowid FROM contract_roots AS new
+                JOIN contract_roots AS old
+                    ON old.contract_address = new.contract_address
+                    AND old.block_number < new.block_number
+                WHERE new.block_number >= ? AND new.block_number <= ?
+            )",
+            params![&previous.unwrap_or(BlockNumber::GENESIS), &keep_from],
+        )
+        .context("Deleting contract roots")?;
+
+    tx.inner()
+        .execute(
+            "UPDATE refs SET trie_prune_horizon = ? WHERE idx = 1",
+            params![&keep_from],
+        )
+        .context("Updating trie prune horizon")?;
+
+    Ok(())
+}
+
 mod macros {
-    /// Generates the `insert`, `node` and `hash` trie functions for the given table name, within
-    /// a module with the table name.
+    /// Generates the `insert`, `node`, `hash`, `insert_removals` and `prune` trie functions for
+    /// the given table name, within a module with the table name.
     macro_rules! create_trie_fns {
         ($table: ident) => {
             pub(super) mod $table {
@@ -266,6 +354,61 @@ mod macros {
                         .optional()
                         .map_err(Into::into)
                 }
+
+                /// Records the nodes which are no longer part of the trie as of the given block.
+                pub fn insert_removals(
+                    tx: &Transaction<'_>,
+                    block_number: BlockNumber,
+                    removed: &[u64],
+                ) -> anyhow::Result<()> {
+                    let mut stmt = tx
+                        .inner()
+                        .prepare_cached(concat!(
+                            "INSERT INTO ",
+                            stringify!($table),
+                            "_removals (block_number, idx) VALUES(?, ?)",
+                        ))
+                        .context("Creating insert removal statement")?;
+
+                    for index in removed {
+                        stmt.execute(params![&block_number, index])
+                            .context("Inserting removal")?;
+                    }
+
+                    Ok(())
+                }
+
+                /// Deletes the nodes which were removed from the trie at or before the given block.
+                pub fn prune(
+                    tx: &Transaction<'_>,
+                    block_number: BlockNumber,
+                ) -> anyhow::Result<()> {
+                    tx.inner()
+                        .execute(
+                            concat!(
+                                "DELETE FROM ",
+                                stringify!($table),
+                                " WHERE idx IN (SELECT idx FROM ",
+                                stringify!($table),
+                                "_removals WHERE block_number <= ?)",
+                            ),
+                            params![&block_number],
+                        )
+                        .context("Deleting removed nodes")?;
+
+                    tx.inner()
+                        .execute(
+                            concat!(
+                                "DELETE FROM ",
+                                stringify!($table),
+                                "_removals WHERE block_number <= ?",
+                            ),
+                            params![&block_number],
+                        )
+                        .context("Deleting removals")?;
+
+                    Ok(())
+                }
             }
         };
     }
@@ -649,6 +792,11 @@ mod tests {
                 [],
             )
             .unwrap();
+            db.execute(
+                "CREATE TABLE test_table_removals (block_number INTEGER NOT NULL, idx INTEGER NOT NULL)",
+                [],
+            )
+            .unwrap();
 
             db
         }
@@ -829,6 +977,81 @@ mod tests {
             assert_eq!(path, bitvec::bitvec![u8, Msb0; 1,0,1,1,1,0,0,0,0,0,1,1]);
             assert_eq!(child, 123);
         }
+
+        #[test]
+        fn removed_nodes_are_pruned() {
+            let mut db = setup_db();
+            let tx = db.transaction().unwrap();
+            let tx = crate::Transaction::from_inner(tx);
+
+            let insert_leaf = |hash| {
+                let nodes = HashMap::from([(hash, Node::LeafBinary)]);
+                test_table::insert(&tx, hash, &nodes).unwrap()
+            };
+            let old = insert_leaf(felt_bytes!(b"old"));
+            let newer = insert_leaf(felt_bytes!(b"newer"));
+            let current = insert_leaf(felt_bytes!(b"current"));
+
+            test_table::insert_removals(&tx, BlockNumber::new_or_panic(1), &[old]).unwrap();
+            test_table::insert_removals(&tx, BlockNumber::new_or_panic(2), &[newer]).unwrap();
+
+            test_table::prune(&tx, BlockNumber::new_or_panic(1)).unwrap();
+
+            assert!(test_table::node(&tx, old).unwrap().is_none());
+            assert!(test_table::node(&tx, newer).unwrap().is_some());
+            assert!(test_table::node(&tx, current).unwrap().is_some());
+
+            test_table::prune(&tx, BlockNumber::new_or_panic(5)).unwrap();
+
+            assert!(test_table::node(&tx, newer).unwrap().is_none());
+            assert!(test_table::node(&tx, current).unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn prune_tries_keeps_the_roots_of_retained_blocks() {
+        let mut db = crate::Storage::in_memory().unwrap().connection().unwrap();
+        let tx = db.transaction().unwrap();
+
+        let contract = contract_address_bytes!(b"address");
+        for block in 0..5 {
+            let block = BlockNumber::new_or_panic(block);
+            insert_class_root(&tx, block, Some(block.get())).unwrap();
+            insert_storage_root(&tx, block, Some(block.get())).unwrap();
+        }
+        insert_contract_root(&tx, BlockNumber::new_or_panic(0), contract, Some(10)).unwrap();
+        insert_contract_root(&tx, BlockNumber::new_or_panic(1), contract, Some(11)).unwrap();
+        insert_contract_root(&tx, BlockNumber::new_or_panic(4), contract, Some(14)).unwrap();
+
+        assert_eq!(trie_prune_horizon(&tx).unwrap(), None);
+
+        prune_tries(&tx, BlockNumber::new_or_panic(3)).unwrap();
+
+        assert_eq!(
+            trie_prune_horizon(&tx).unwrap(),
+            Some(BlockNumber::new_or_panic(3))
+        );
+        assert_eq!(
+            class_root_index(&tx, BlockNumber::new_or_panic(2)).unwrap(),
+            None
+        );
+        assert_eq!(
+            class_root_index(&tx, BlockNumber::new_or_panic(3)).unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            storage_root_index(&tx, BlockNumber::new_or_panic(4)).unwrap(),
+            Some(4)
+        );
+        // The contract root of block 1 is still the latest one at block 3.
+        assert_eq!(
+            contract_root_index(&tx, BlockNumber::new_or_panic(3), contract).unwrap(),
+            Some(11)
+        );
+        assert_eq!(
+            contract_root_index(&tx, BlockNumber::new_or_panic(0), contract).unwrap(),
+            None
+        );
     }
 
     #[test]
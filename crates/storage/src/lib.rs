@@ -9,6 +9,7 @@ mod connection;
 pub mod fake;
 mod params;
 mod schema;
+pub mod snapshot;
 pub mod test_utils;
 
 use std::num::NonZeroU32;
@@ -35,6 +36,19 @@ pub enum JournalMode {
     WAL,
 }
 
+/// Specifies how much history of the state tries is kept by the [Storage].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TriePruneMode {
+    /// Keep the tries of all blocks.
+    #[default]
+    Archive,
+    /// Keep only the tries of the latest `num_blocks_kept` blocks.
+    ///
+    /// Trie nodes which are replaced are tracked so that they can later be deleted by
+    /// [Transaction::prune_tries].
+    Prune { num_blocks_kept: u64 },
+}
+
 /// Identifies a specific starknet block stored in the database.
 ///
 /// Note that this excludes the `Pending` variant since we never store pending data
@@ -88,18 +102,29 @@ struct Inner {
     /// Uses [`Arc`] to allow _shallow_ [Storage] cloning
     database_path: Arc<PathBuf>,
     pool: Pool<SqliteConnectionManager>,
+    trie_prune_mode: TriePruneMode,
 }
 
 pub struct StorageManager {
     database_path: PathBuf,
     journal_mode: JournalMode,
+    trie_prune_mode: TriePruneMode,
+    read_only: bool,
 }
 
 impl StorageManager {
+    pub fn with_trie_prune_mode(mut self, trie_prune_mode: TriePruneMode) -> Self {
+        self.trie_prune_mode = trie_prune_mode;
+        self
+    }
+
     pub fn create_pool(&self, capacity: NonZeroU32) -> anyhow::Result<Storage> {
         let journal_mode = self.journal_mode;
-        let pool_manager = SqliteConnectionManager::file(&self.database_path)
+        let mut pool_manager = SqliteConnectionManager::file(&self.database_path)
             .with_init(move |connection| setup_connection(connection, journal_mode));
+        if self.read_only {
+            pool_manager = pool_manager.with_flags(read_only_flags());
+        }
         let pool = Pool::builder()
             .max_size(capacity.get())
             .build(pool_manager)?;
@@ -107,6 +132,7 @@ impl StorageManager {
         Ok(Storage(Inner {
             database_path: Arc::new(self.database_path.clone()),
             pool,
+            trie_prune_mode: self.trie_prune_mode,
         }))
     }
 }
@@ -136,19 +162,54 @@ impl Storage {
         Ok(StorageManager {
             database_path,
             journal_mode,
+            trie_prune_mode: TriePruneMode::Archive,
+            read_only: false,
+        })
+    }
+
+    /// Opens an existing database without modifying it, and returns a [storage manager](StorageManager)
+    /// whose connections are read-only.
+    ///
+    /// Fails if the database schema is not at the latest version, as it won't be migrated.
+    pub fn open_read_only(database_path: PathBuf) -> anyhow::Result<StorageManager> {
+        let connection = rusqlite::Connection::open_with_flags(&database_path, read_only_flags())
+            .context("Opening DB")?;
+        let current_revision = schema_version(&connection)?;
+        let latest_revision = schema::BASE_SCHEMA_REVISION + schema::migrations().len();
+        anyhow::ensure!(
+            current_revision == latest_revision,
+            "Database version {current_revision} is not the latest version {latest_revision}, \
+             run pathfinder to migrate it first"
+        );
+        connection
+            .close()
+            .map_err(|(_connection, error)| error)
+            .context("Closing DB after checking its version")?;
+
+        Ok(StorageManager {
+            database_path,
+            // Only determines the synchronous setting, which has no effect on reads.
+            journal_mode: JournalMode::WAL,
+            trie_prune_mode: TriePruneMode::Archive,
+            read_only: true,
         })
     }
 
     /// Returns a new Sqlite [Connection] to the database.
     pub fn connection(&self) -> anyhow::Result<Connection> {
         let conn = self.0.pool.get()?;
-        Ok(Connection::from_inner(conn))
+        Ok(Connection::from_inner(conn, self.0.trie_prune_mode))
     }
 
     /// Convenience function for tests to create an in-memory database.
     /// Equivalent to [Storage::migrate] with an in-memory backed database.
     // No longer cfg(test) because needed in benchmarks
     pub fn in_memory() -> anyhow::Result<Self> {
+        Self::in_memory_with_trie_prune_mode(TriePruneMode::Archive)
+    }
+
+    /// Same as [Storage::in_memory] but with the given [TriePruneMode].
+    pub fn in_memory_with_trie_prune_mode(trie_prune_mode: TriePruneMode) -> anyhow::Result<Self> {
         // Create a unique database name so that they are not shared between
         // concurrent tests. i.e. Make every in-mem Storage unique.
         lazy_static::lazy_static!(
@@ -168,7 +229,8 @@ impl Storage {
         // therefore holds the database in-place until the pool is established.
         let _conn = rusqlite::Connection::open(&database_path)?;
 
-        let storage = Self::migrate(database_path, JournalMode::Rollback)?;
+        let storage = Self::migrate(database_path, JournalMode::Rollback)?
+            .with_trie_prune_mode(trie_prune_mode);
 
         storage.create_pool(NonZeroU32::new(5).unwrap())
     }
@@ -176,6 +238,16 @@ impl Storage {
     pub fn path(&self) -> &Path {
         &self.0.database_path
     }
+
+    pub fn trie_prune_mode(&self) -> TriePruneMode {
+        self.0.trie_prune_mode
+    }
+}
+
+fn read_only_flags() -> rusqlite::OpenFlags {
+    rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY
+        | rusqlite::OpenFlags::SQLITE_OPEN_URI
+        | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX
 }
 
 fn setup_journal_mode(
@@ -392,6 +464,37 @@ mod tests {
         migrate_database(&mut conn).unwrap_err();
     }
 
+    #[test]
+    fn open_read_only_requires_the_latest_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let database_path = dir.path().join("pathfinder.sqlite");
+
+        Storage::migrate(database_path.clone(), JournalMode::WAL).unwrap();
+        let latest_version = schema::migrations().len() + schema::BASE_SCHEMA_REVISION;
+        let set_version = |version| {
+            rusqlite::Connection::open(&database_path)
+                .unwrap()
+                .pragma_update(None, VERSION_KEY, version)
+                .unwrap();
+        };
+
+        set_version(latest_version - 1);
+        Storage::open_read_only(database_path.clone()).unwrap_err();
+
+        set_version(latest_version);
+        let storage = Storage::open_read_only(database_path)
+            .unwrap()
+            .create_pool(NonZeroU32::new(1).unwrap())
+            .unwrap();
+
+        // Connections can read, but not write.
+        let mut conn = storage.connection().unwrap();
+        let tx = conn.transaction().unwrap();
+        assert_eq!(tx.block_id(BlockId::Latest).unwrap(), None);
+        tx.insert_block_header(&pathfinder_common::BlockHeader::default())
+            .unwrap_err();
+    }
+
     #[test]
     fn foreign_keys_are_enforced() {
         let conn = rusqlite::Connection::open_in_memory().unwrap();
@@ -5,6 +5,10 @@ mod revision_0042;
 mod revision_0043;
 mod revision_0044;
 mod revision_0045;
+mod revision_0046;
+mod revision_0047;
+mod revision_0048;
+mod revision_0049;
 
 pub(crate) use base::base_schema;
 
@@ -18,6 +22,10 @@ pub fn migrations() -> &'static [MigrationFn] {
         revision_0043::migrate,
         revision_0044::migrate,
         revision_0045::migrate,
+        revision_0046::migrate,
+        revision_0047::migrate,
+        revision_0048::migrate,
+        revision_0049::migrate,
     ]
 }
 
@@ -0,0 +1,34 @@
+use anyhow::Context;
+
+/// Adds the tables tracking which trie nodes were replaced by each block, the horizon
+/// below which the tries were pruned and an index for pruning contract roots.
+pub(crate) fn migrate(tx: &rusqlite::Transaction<'_>) -> anyhow::Result<()> {
+    for table in ["trie_class", "trie_contracts", "trie_storage"] {
+        tx.execute_batch(&format!(
+            r"
+CREATE TABLE {table}_removals (
+    block_number INTEGER NOT NULL,
+    idx          INTEGER NOT NULL
+);
+CREATE INDEX {table}_removals_block_number ON {table}_removals(block_number);"
+        ))
+        .with_context(|| format!("Creating {table}_removals table"))?;
+    }
+
+    tx.execute(
+        "ALTER TABLE refs ADD COLUMN trie_prune_horizon INTEGER DEFAULT NULL",
+        [],
+    )
+    .context("Adding trie_prune_horizon column")?;
+
+    // Covers the range of newer roots looked up when pruning superseded contract roots, and
+    // replaces the index on the block number alone.
+    tx.execute_batch(
+        r"
+DROP INDEX contract_roots_block_number;
+CREATE INDEX contract_roots_block_number_address ON contract_roots(block_number, contract_address);",
+    )
+    .context("Creating index on contract_roots(block_number, contract_address)")?;
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use anyhow::Context;
+
+/// Adds the horizon below which transaction bodies, receipts and events were pruned.
+pub(crate) fn migrate(tx: &rusqlite::Transaction<'_>) -> anyhow::Result<()> {
+    tx.execute(
+        "ALTER TABLE refs ADD COLUMN transaction_prune_horizon INTEGER DEFAULT NULL",
+        [],
+    )
+    .context("Adding transaction_prune_horizon column")?;
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anyhow::Context;
+
+/// Adds the table of messages between L1 and L2 logged by the core contract, and the range of L1
+/// blocks whose message logs were indexed.
+pub(crate) fn migrate(tx: &rusqlite::Transaction<'_>) -> anyhow::Result<()> {
+    tx.execute_batch(
+        r"
+CREATE TABLE l1_messages (
+    l1_transaction_hash BLOB    NOT NULL,
+    message_hash        BLOB    NOT NULL,
+    -- 0 for messages from L1 to L2, 1 for consumed messages from L2 to L1
+    direction           INTEGER NOT NULL,
+    l1_block_number     INTEGER NOT NULL,
+    -- The L1 handler transaction of a message from L1 to L2
+    l2_transaction_hash BLOB,
+    PRIMARY KEY (l1_transaction_hash, message_hash, direction)
+);
+CREATE INDEX l1_messages_message_hash ON l1_messages(message_hash);",
+    )
+    .context("Creating l1_messages table")?;
+
+    tx.execute(
+        "ALTER TABLE refs ADD COLUMN l1_messages_head INTEGER DEFAULT NULL",
+        [],
+    )
+    .context("Adding l1_messages_head column")?;
+
+    tx.execute(
+        "ALTER TABLE refs ADD COLUMN l1_messages_tail INTEGER DEFAULT NULL",
+        [],
+    )
+    .context("Adding l1_messages_tail column")?;
+
+    Ok(())
+}
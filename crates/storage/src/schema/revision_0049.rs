@@ -0,0 +1,23 @@
+use anyhow::Context;
+
+/// Records the L1 block and transaction of each L1 state update, and the latest L1 block whose
+/// state update logs were processed.
+pub(crate) fn migrate(tx: &rusqlite::Transaction<'_>) -> anyhow::Result<()> {
+    tx.execute_batch(
+        r"
+ALTER TABLE l1_state ADD COLUMN l1_block_number INTEGER DEFAULT NULL;
+ALTER TABLE l1_state ADD COLUMN l1_block_hash BLOB DEFAULT NULL;
+ALTER TABLE l1_state ADD COLUMN l1_transaction_hash BLOB DEFAULT NULL;
+CREATE INDEX l1_state_l1_block_number ON l1_state(l1_block_number);",
+    )
+    .context("Adding L1 columns to l1_state table")?;
+
+    tx.execute_batch(
+        r"
+ALTER TABLE refs ADD COLUMN l1_state_head_number INTEGER DEFAULT NULL;
+ALTER TABLE refs ADD COLUMN l1_state_head_hash BLOB DEFAULT NULL;",
+    )
+    .context("Adding L1 state head columns")?;
+
+    Ok(())
+}
@@ -0,0 +1,531 @@
+//! Offline database snapshots.
+//!
+//! A snapshot is a directory containing a [Manifest] and the database file split into
+//! zstd compressed chunks. Each chunk is checksummed individually so that a snapshot can be
+//! transferred, verified and imported piecewise, and the database as a whole is checksummed
+//! once reassembled.
+//!
+//! The exported database is verified before and after the transfer by comparing the head
+//! block's state commitment with the roots of the stored state tries.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::num::NonZeroU32;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Context;
+use pathfinder_common::{BlockHash, BlockHeader, BlockNumber, StateCommitment};
+use rusqlite::TransactionBehavior;
+use sha3::{Digest, Sha3_256};
+
+use crate::{BlockId, JournalMode, Storage, Transaction};
+
+/// The file name of the [Manifest] within a snapshot directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// The version of the snapshot format described by [Manifest].
+const FORMAT_VERSION: u32 = 1;
+
+/// zstd compression level used for the chunks.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Describes the contents of a snapshot directory.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    /// The schema revision of the database contained in the snapshot.
+    pub schema_revision: usize,
+    /// The head of the database contained in the snapshot.
+    pub block_number: u64,
+    pub block_hash: BlockHash,
+    pub state_commitment: StateCommitment,
+    /// The size of the uncompressed database file.
+    pub database_size: u64,
+    /// Hex encoded SHA3-256 checksum of the uncompressed database file.
+    pub database_checksum: String,
+    /// The uncompressed size of every chunk, except possibly the last one.
+    pub chunk_size: u64,
+    pub chunks: Vec<Chunk>,
+}
+
+/// A single compressed piece of the database file.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Chunk {
+    /// File name relative to the snapshot directory.
+    pub file: String,
+    /// The size of the uncompressed data.
+    pub size: u64,
+    /// Hex encoded SHA3-256 checksum of the uncompressed data.
+    pub checksum: String,
+    /// The size of the compressed chunk file.
+    pub compressed_size: u64,
+    /// Hex encoded SHA3-256 checksum of the compressed chunk file.
+    pub compressed_checksum: String,
+}
+
+impl Manifest {
+    pub fn read(snapshot: &Path) -> anyhow::Result<Self> {
+        let path = snapshot.join(MANIFEST_FILE);
+        let file = File::open(&path).with_context(|| format!("Opening {}", path.display()))?;
+        let manifest: Self =
+            serde_json::from_reader(BufReader::new(file)).context("Parsing snapshot manifest")?;
+
+        anyhow::ensure!(
+            manifest.format_version == FORMAT_VERSION,
+            "Unsupported snapshot format version {}, expected {FORMAT_VERSION}",
+            manifest.format_version
+        );
+
+        Ok(manifest)
+    }
+}
+
+/// Exports a snapshot of the `database` into the `output` directory.
+///
+/// The snapshot is taken at `block`, or at the current head of the database if [None]. The
+/// database may be in use while exporting as the copy is made within a single read transaction.
+pub fn export(
+    database: &Path,
+    output: &Path,
+    block: Option<BlockNumber>,
+    chunk_size: u64,
+) -> anyhow::Result<Manifest> {
+    anyhow::ensure!(chunk_size > 0, "Chunk size must be non-zero");
+    anyhow::ensure!(
+        !output.join(MANIFEST_FILE).exists(),
+        "Output directory {} already contains a snapshot",
+        output.display()
+    );
+    std::fs::create_dir_all(output).context("Creating output directory")?;
+
+    let copy = output.join("database.sqlite.tmp");
+    if copy.exists() {
+        std::fs::remove_file(&copy).context("Removing stale database copy")?;
+    }
+
+    tracing::info!(database=%database.display(), "Copying database");
+    {
+        let source = rusqlite::Connection::open_with_flags(
+            database,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )
+        .context("Opening source database")?;
+        let copy = copy.to_str().context("Output path is not valid UTF-8")?;
+        source
+            .execute("VACUUM INTO ?", [copy])
+            .context("Copying database")?;
+    }
+
+    // Migrating the copy ensures the snapshot has the schema revision of this application.
+    let storage = Storage::migrate(copy.clone(), JournalMode::Rollback)
+        .context("Migrating database copy")?
+        .create_pool(NonZeroU32::new(1).unwrap())?;
+    let head = {
+        let mut db = storage.connection()?;
+        let tx = db
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .context("Creating database transaction")?;
+
+        let (latest, _) = tx
+            .block_id(BlockId::Latest)
+            .context("Querying latest block")?
+            .context("Database contains no blocks")?;
+
+        if let Some(block) = block {
+            anyhow::ensure!(
+                block <= latest,
+                "Requested block {block} is newer than the database head {latest}"
+            );
+            if block < latest {
+                tracing::info!(from=%latest, to=%block, "Removing newer blocks from the copy");
+                tx.purge_blocks_from(block + 1)
+                    .context("Removing newer blocks")?;
+            }
+        }
+        let head = block.unwrap_or(latest);
+
+        let header = verify_state_commitment(&tx, head)?;
+        tx.commit().context("Committing database transaction")?;
+        header
+    };
+    drop(storage);
+
+    // Reclaim the space of any removed blocks.
+    rusqlite::Connection::open(&copy)
+        .context("Opening database copy")?
+        .execute("VACUUM", [])
+        .context("Vacuuming database copy")?;
+
+    let manifest = write_chunks(&copy, output, &head, chunk_size)?;
+    std::fs::remove_file(&copy).context("Removing database copy")?;
+
+    Ok(manifest)
+}
+
+/// Imports the snapshot from the `input` directory into a new database file at `database`.
+///
+/// Chunks are verified as they are imported. An interrupted import can be resumed as chunks
+/// which were already imported are verified and skipped.
+pub fn import(input: &Path, database: &Path) -> anyhow::Result<Manifest> {
+    let manifest = Manifest::read(input)?;
+
+    let latest_revision = crate::schema::BASE_SCHEMA_REVISION + crate::schema::migrations().len();
+    anyhow::ensure!(
+        manifest.schema_revision <= latest_revision,
+        "Snapshot schema revision {} is newer than this application supports {latest_revision}",
+        manifest.schema_revision
+    );
+    anyhow::ensure!(
+        !database.exists(),
+        "Database {} already exists",
+        database.display()
+    );
+
+    let mut partial = database.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&partial)
+        .with_context(|| format!("Opening {}", partial.display()))?;
+    let existing_size = file.metadata().context("Reading file metadata")?.len();
+
+    let mut offset = 0;
+    for (index, chunk) in manifest.chunks.iter().enumerate() {
+        let end = offset + chunk.size;
+
+        if end <= existing_size
+            && checksum(read_range(&mut file, offset, chunk.size)?) == chunk.checksum
+        {
+            tracing::debug!(%index, "Chunk already imported");
+        } else {
+            let data = read_chunk(input, chunk)
+                .with_context(|| format!("Reading chunk {index} ({})", chunk.file))?;
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&data).context("Writing chunk")?;
+            tracing::info!(%index, total=%manifest.chunks.len(), "Chunk imported");
+        }
+
+        offset = end;
+    }
+
+    anyhow::ensure!(
+        offset == manifest.database_size,
+        "Chunk sizes do not add up to the database size"
+    );
+    file.set_len(manifest.database_size)?;
+    file.sync_all().context("Syncing database file")?;
+
+    file.seek(SeekFrom::Start(0))?;
+    let mut hasher = Sha3_256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer).context("Reading database")?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    anyhow::ensure!(
+        hex::encode(hasher.finalize()) == manifest.database_checksum,
+        "Database checksum mismatch"
+    );
+    drop(file);
+
+    verify_database(&partial, &manifest).context("Verifying imported database")?;
+
+    std::fs::rename(&partial, database).context("Moving database into place")?;
+
+    Ok(manifest)
+}
+
+/// Verifies that the database matches the snapshot [Manifest].
+fn verify_database(database: &Path, manifest: &Manifest) -> anyhow::Result<()> {
+    let revision =
+        crate::schema_version(&rusqlite::Connection::open(database).context("Opening database")?)?;
+    anyhow::ensure!(
+        revision == manifest.schema_revision,
+        "Schema revision {revision} does not match the manifest's {}",
+        manifest.schema_revision
+    );
+
+    let storage = Storage::migrate(database.to_owned(), JournalMode::Rollback)
+        .context("Migrating database")?
+        .create_pool(NonZeroU32::new(1).unwrap())?;
+    let mut db = storage.connection()?;
+    let tx = db.transaction()?;
+
+    let (latest, hash) = tx
+        .block_id(BlockId::Latest)
+        .context("Querying latest block")?
+        .context("Database contains no blocks")?;
+    anyhow::ensure!(
+        latest.get() == manifest.block_number && hash == manifest.block_hash,
+        "Database head {latest} does not match the manifest's block {}",
+        manifest.block_number
+    );
+
+    let header = verify_state_commitment(&tx, latest)?;
+    anyhow::ensure!(
+        header.state_commitment == manifest.state_commitment,
+        "State commitment does not match the manifest"
+    );
+
+    Ok(())
+}
+
+/// Verifies that the block's state commitment matches the roots of the stored state tries.
+fn verify_state_commitment(
+    tx: &Transaction<'_>,
+    block: BlockNumber,
+) -> anyhow::Result<BlockHeader> {
+    if let Some(horizon) = tx.trie_prune_horizon()? {
+        anyhow::ensure!(
+            block >= horizon,
+            "The state tries of block {block} were pruned, the oldest available block is {horizon}"
+        );
+    }
+
+    let header = tx
+        .block_header(block.into())
+        .context("Querying block header")?
+        .context("Block header is missing")?;
+
+    let state_commitment = tx
+        .state_commitment_from_tries(block)
+        .context("Calculating state commitment")?;
+    anyhow::ensure!(
+        state_commitment == header.state_commitment,
+        "State commitment mismatch at block {block}: header has {}, state tries have {state_commitment}",
+        header.state_commitment
+    );
+
+    Ok(header)
+}
+
+fn write_chunks(
+    database: &Path,
+    output: &Path,
+    head: &BlockHeader,
+    chunk_size: u64,
+) -> anyhow::Result<Manifest> {
+    let mut file = File::open(database).context("Opening database copy")?;
+    let database_size = file.metadata()?.len();
+    let schema_revision = crate::schema_version(
+        &rusqlite::Connection::open(database).context("Opening database copy")?,
+    )?;
+
+    let mut hasher = Sha3_256::new();
+    let mut chunks = Vec::new();
+    let mut buffer = Vec::new();
+
+    loop {
+        buffer.clear();
+        (&mut file)
+            .take(chunk_size)
+            .read_to_end(&mut buffer)
+            .context("Reading database copy")?;
+        if buffer.is_empty() {
+            break;
+        }
+        hasher.update(&buffer);
+
+        let compressed =
+            zstd::bulk::compress(&buffer, COMPRESSION_LEVEL).context("Compressing chunk")?;
+        let name = format!("chunk-{:06}.zst", chunks.len());
+        std::fs::write(output.join(&name), &compressed)
+            .with_context(|| format!("Writing {name}"))?;
+
+        chunks.push(Chunk {
+            file: name,
+            size: buffer.len() as u64,
+            checksum: checksum(&buffer),
+            compressed_size: compressed.len() as u64,
+            compressed_checksum: checksum(&compressed),
+        });
+    }
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        schema_revision,
+        block_number: head.number.get(),
+        block_hash: head.hash,
+        state_commitment: head.state_commitment,
+        database_size,
+        database_checksum: hex::encode(hasher.finalize()),
+        chunk_size,
+        chunks,
+    };
+
+    // The manifest is written last so that its presence marks a complete snapshot.
+    let manifest_json = serde_json::to_vec_pretty(&manifest).context("Serializing manifest")?;
+    std::fs::write(output.join(MANIFEST_FILE), manifest_json).context("Writing manifest")?;
+
+    Ok(manifest)
+}
+
+/// Reads, verifies and decompresses a chunk.
+///
+/// The chunk file must be a regular file within the snapshot directory of the size given by the
+/// manifest, so that a tampered manifest cannot make the import read other files.
+fn read_chunk(snapshot: &Path, chunk: &Chunk) -> anyhow::Result<Vec<u8>> {
+    let file = Path::new(&chunk.file);
+    anyhow::ensure!(
+        file.components()
+            .all(|component| matches!(component, Component::Normal(_))),
+        "Chunk file is not within the snapshot directory"
+    );
+    let path = snapshot.join(file);
+
+    let metadata = std::fs::symlink_metadata(&path).context("Reading chunk file metadata")?;
+    anyhow::ensure!(metadata.is_file(), "Chunk file is not a regular file");
+    anyhow::ensure!(
+        metadata.len() == chunk.compressed_size,
+        "Compressed chunk size mismatch"
+    );
+
+    let mut compressed = Vec::new();
+    File::open(&path)?
+        .take(chunk.compressed_size)
+        .read_to_end(&mut compressed)?;
+    anyhow::ensure!(
+        checksum(&compressed) == chunk.compressed_checksum,
+        "Compressed chunk checksum mismatch"
+    );
+
+    let size = usize::try_from(chunk.size).context("Chunk size overflow")?;
+    let data = zstd::bulk::decompress(&compressed, size).context("Decompressing chunk")?;
+    anyhow::ensure!(
+        data.len() == size && checksum(&data) == chunk.checksum,
+        "Chunk checksum mismatch"
+    );
+
+    Ok(data)
+}
+
+fn read_range(file: &mut File, offset: u64, size: u64) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(size).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn checksum(data: impl AsRef<[u8]>) -> String {
+    hex::encode(Sha3_256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pathfinder_common::macro_prelude::*;
+
+    fn setup(dir: &Path) -> PathBuf {
+        let database = dir.join("source.sqlite");
+        let storage = Storage::migrate(database.clone(), JournalMode::WAL)
+            .unwrap()
+            .create_pool(NonZeroU32::new(1).unwrap())
+            .unwrap();
+
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        let mut header = BlockHeader::builder()
+            .with_number(BlockNumber::GENESIS)
+            .finalize_with_hash(block_hash!("0x0"));
+        tx.insert_block_header(&header).unwrap();
+        for i in 1..4 {
+            header = header
+                .child_builder()
+                .finalize_with_hash(BlockHash(pathfinder_crypto::Felt::from_u64(i)));
+            tx.insert_block_header(&header).unwrap();
+        }
+        tx.commit().unwrap();
+
+        database
+    }
+
+    #[test]
+    fn export_and_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = setup(dir.path());
+        let snapshot = dir.path().join("snapshot");
+
+        let exported = export(
+            &database,
+            &snapshot,
+            Some(BlockNumber::new_or_panic(2)),
+            4096,
+        )
+        .unwrap();
+        assert_eq!(exported.block_number, 2);
+        assert!(exported.chunks.len() > 1);
+
+        let imported_path = dir.path().join("imported.sqlite");
+        let imported = import(&snapshot, &imported_path).unwrap();
+        assert_eq!(imported, exported);
+
+        let storage = Storage::migrate(imported_path, JournalMode::Rollback)
+            .unwrap()
+            .create_pool(NonZeroU32::new(1).unwrap())
+            .unwrap();
+        let mut db = storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        let latest = tx.block_id(BlockId::Latest).unwrap().unwrap();
+        assert_eq!(
+            latest,
+            (
+                BlockNumber::new_or_panic(2),
+                BlockHash(pathfinder_crypto::Felt::from_u64(2))
+            )
+        );
+    }
+
+    #[test]
+    fn corrupt_chunk_is_rejected_and_import_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = setup(dir.path());
+        let snapshot = dir.path().join("snapshot");
+        let manifest = export(&database, &snapshot, None, 4096).unwrap();
+
+        let last = snapshot.join(&manifest.chunks.last().unwrap().file);
+        let original = std::fs::read(&last).unwrap();
+        std::fs::write(&last, vec![0u8; original.len()]).unwrap();
+
+        let imported_path = dir.path().join("imported.sqlite");
+        let error = import(&snapshot, &imported_path).unwrap_err();
+        assert!(format!("{error:#}").contains("checksum mismatch"));
+        assert!(!imported_path.exists());
+
+        std::fs::write(&last, original).unwrap();
+        import(&snapshot, &imported_path).unwrap();
+    }
+
+    #[test]
+    fn chunks_outside_the_snapshot_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = setup(dir.path());
+        let snapshot = dir.path().join("snapshot");
+        let manifest = export(&database, &snapshot, None, 4096).unwrap();
+
+        let outside = dir.path().join("outside.zst");
+        let first = snapshot.join(&manifest.chunks[0].file);
+        std::fs::copy(first, &outside).unwrap();
+
+        for file in ["../outside.zst".to_owned(), outside.display().to_string()] {
+            let mut tampered = manifest.clone();
+            tampered.chunks[0].file = file;
+            std::fs::write(
+                snapshot.join(MANIFEST_FILE),
+                serde_json::to_vec(&tampered).unwrap(),
+            )
+            .unwrap();
+
+            let imported_path = dir.path().join("imported.sqlite");
+            let error = import(&snapshot, &imported_path).unwrap_err();
+            assert!(format!("{error:#}").contains("not within the snapshot directory"));
+        }
+    }
+}